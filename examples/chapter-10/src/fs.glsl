@@ -0,0 +1,17 @@
+in vec2 v_uv;
+
+out vec3 frag_color;
+
+uniform float t;
+uniform vec2 resolution;
+
+// edit this file while the chapter runs: the program is rebuilt as soon as it’s saved
+void main() {
+  // centered coordinates, square pixels, from -1 to 1 vertically
+  vec2 p = (v_uv * 2. - 1.) * vec2(resolution.x / resolution.y, 1.);
+  float d = length(p);
+
+  // rings of color moving outwards, on a disk
+  vec3 color = .5 + .5 * cos(d * 8. - t * 2. + vec3(0., 2., 4.));
+  frag_color = color * smoothstep(.9, .88, d);
+}
@@ -0,0 +1,167 @@
+mod reload;
+
+use crate::reload::ShaderWatcher;
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::{Program, Uniform};
+use luminance_front::tess::Mode;
+use luminance_front::Backend;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::path::Path;
+use std::process::exit;
+use std::time::Instant;
+
+/// Shader sources, read from the source directory of the chapter.
+const VS_FILE: &str = "vs.glsl";
+const FS_FILE: &str = "fs.glsl";
+
+#[derive(Copy, Clone, Debug, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 2]", wrapper = "VertexPosition")]
+  Position,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  #[allow(dead_code)]
+  position: VertexPosition,
+}
+
+// both uniforms are unbound: a shader being edited may not use them, or not yet
+#[derive(Debug, UniformInterface)]
+struct ShaderInterface {
+  #[uniform(unbound)]
+  t: Uniform<f32>,
+  #[uniform(unbound)]
+  resolution: Uniform<[f32; 2]>,
+}
+
+const QUAD: [Vertex; 4] = [
+  Vertex::new(VertexPosition::new([-1., -1.])),
+  Vertex::new(VertexPosition::new([1., -1.])),
+  Vertex::new(VertexPosition::new([1., 1.])),
+  Vertex::new(VertexPosition::new([-1., 1.])),
+];
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  let quad = ctxt
+    .new_tess()
+    .set_vertices(&QUAD[..])
+    .set_mode(Mode::TriangleFan)
+    .build()
+    .unwrap();
+
+  let shader_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+  let watcher = ShaderWatcher::new(&shader_dir, &[VS_FILE, FS_FILE]).unwrap_or_else(|e| {
+    eprintln!("{}", e);
+    exit(1);
+  });
+  println!("watching the shaders in {}", shader_dir.display());
+
+  // there’s no previous program to fall back to yet
+  let mut program = load_program(&mut ctxt, &watcher).unwrap_or_else(|e| {
+    eprintln!("{}", e);
+    exit(1);
+  });
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        _ => (),
+      }
+    }
+
+    // a program that doesn’t build leaves the previous one in place, so that a typo doesn’t kill
+    // the chapter; saving the fixed shader is enough to get going again
+    if watcher.changed() {
+      match load_program(&mut ctxt, &watcher) {
+        Ok(new_program) => {
+          program = new_program;
+          println!("shaders reloaded");
+        }
+
+        Err(e) => eprintln!("{}\nkeeping the previous shaders", e),
+      }
+    }
+
+    // rendering code goes here
+    let t = start_t.elapsed().as_secs_f32();
+    let [width, height] = back_buffer.size();
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default(),
+        |_, mut shd_gate| {
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.t, t);
+            iface.set(&uni.resolution, [width as f32, height as f32]);
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&quad)
+            })
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+/// Build the program from the current shader sources on disk.
+fn load_program<C>(
+  ctxt: &mut C,
+  watcher: &ShaderWatcher,
+) -> Result<Program<VertexSemantics, (), ShaderInterface>, String>
+where
+  C: GraphicsContext<Backend = Backend>,
+{
+  let sources = watcher.sources()?;
+
+  ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(&sources[0], None, None, &sources[1])
+    .map(|built| built.ignore_warnings())
+    .map_err(|e| format!("cannot build shader program:\n{}", e))
+}
@@ -0,0 +1,85 @@
+//! Shader sources watched on disk.
+//!
+//! The sources are read from the source directory of the chapter at runtime rather than embedded in
+//! the binary, and the directory is watched for changes. The directory is watched instead of the
+//! files themselves because many editors save by writing a new file and renaming it over the old
+//! one, which a watch on the old file wouldn’t survive.
+
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+/// Time events are gathered for before being reported; saving a file often comes as several
+/// events.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+pub struct ShaderWatcher {
+  dir: PathBuf,
+  /// Names of the watched files in `dir`.
+  files: Vec<&'static str>,
+  events: Receiver<DebouncedEvent>,
+  // events stop being sent when the watcher is dropped
+  _watcher: RecommendedWatcher,
+}
+
+impl ShaderWatcher {
+  /// Watch `files` in `dir`.
+  pub fn new<P>(dir: P, files: &[&'static str]) -> Result<Self, String>
+  where
+    P: Into<PathBuf>,
+  {
+    let dir = dir.into();
+    let (tx, events) = channel();
+    let mut watcher =
+      watcher(tx, DEBOUNCE).map_err(|e| format!("cannot create the file watcher: {}", e))?;
+
+    watcher
+      .watch(&dir, RecursiveMode::NonRecursive)
+      .map_err(|e| format!("cannot watch {}: {}", dir.display(), e))?;
+
+    Ok(ShaderWatcher {
+      dir,
+      files: files.to_vec(),
+      events,
+      _watcher: watcher,
+    })
+  }
+
+  /// Whether one of the files changed since the last call.
+  pub fn changed(&self) -> bool {
+    let mut changed = false;
+
+    for event in self.events.try_iter() {
+      match event {
+        DebouncedEvent::Create(ref path)
+        | DebouncedEvent::Write(ref path)
+        | DebouncedEvent::Rename(_, ref path) => changed |= self.is_watched(path),
+        DebouncedEvent::Error(e, _) => eprintln!("file watcher error: {}", e),
+        _ => (),
+      }
+    }
+
+    changed
+  }
+
+  /// Read the current content of the files, in the order they were given.
+  pub fn sources(&self) -> Result<Vec<String>, String> {
+    self
+      .files
+      .iter()
+      .map(|file| {
+        let path = self.dir.join(file);
+        fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path.display(), e))
+      })
+      .collect()
+  }
+
+  fn is_watched(&self, path: &Path) -> bool {
+    path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .map_or(false, |name| self.files.contains(&name))
+  }
+}
@@ -0,0 +1,9 @@
+in vec2 position;
+
+out vec2 v_uv;
+
+void main() {
+  // the quad covers the screen; its corners map to the corners of the texture space
+  v_uv = position * .5 + .5;
+  gl_Position = vec4(position, 0., 1.);
+}
@@ -0,0 +1,29 @@
+in vec3 v_position;
+in vec3 v_normal;
+
+out vec3 frag_color;
+
+uniform vec3 light_pos;
+uniform vec3 light_color;
+uniform vec3 camera_pos;
+
+const vec3 OBJ_COLOR = vec3(.6, .6, .6);
+const float AMBIENT = .1;
+const float SHININESS = 64.;
+
+void main() {
+  // interpolation shortens the normals between vertices
+  vec3 n = normalize(v_normal);
+  vec3 l = normalize(light_pos - v_position);
+  vec3 v = normalize(camera_pos - v_position);
+
+  // Blinn-Phong: the closer the normal is to the halfway vector between the light and the eye, the
+  // stronger the highlight
+  vec3 h = normalize(l + v);
+
+  float kd = max(dot(n, l), 0.);
+  // faces turned away from the light get no highlight, even if the halfway vector says otherwise
+  float ks = kd > 0. ? pow(max(dot(n, h), 0.), SHININESS) : 0.;
+
+  frag_color = OBJ_COLOR * AMBIENT + (OBJ_COLOR * kd + ks) * light_color;
+}
@@ -0,0 +1,171 @@
+mod obj;
+
+use crate::obj::Obj;
+use cgmath::{perspective, EuclideanSpace, Matrix4, Point3, Rad, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::env;
+use std::process::exit;
+use std::time::Instant;
+
+const VS_STR: &str = include_str!("vs.glsl");
+const FS_STR: &str = include_str!("fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_2);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 10.;
+
+const CAMERA_POS: [f32; 3] = [2., 2., 2.];
+
+/// The light circles around the model at this distance and height.
+const LIGHT_RADIUS: f32 = 2.;
+const LIGHT_HEIGHT: f32 = 1.5;
+/// Angular speed of the light, in radians per second.
+const LIGHT_SPEED: f32 = 0.8;
+const LIGHT_COLOR: [f32; 3] = [1., 0.95, 0.85];
+
+#[derive(Debug, UniformInterface)]
+struct ShaderInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  light_pos: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  light_color: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  position: VertexPosition,
+  normal: VertexNormal,
+}
+
+pub type VertexIndex = u32;
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let path = match env::args().nth(1) {
+    Some(path) => path,
+    None => {
+      eprintln!("usage: chapter-11 <model.obj>");
+      exit(1);
+    }
+  };
+  println!("loading {}", path);
+
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  let mesh = match Obj::load(&path) {
+    Ok(obj) => obj.to_tess(&mut ctxt).unwrap(),
+    Err(e) => {
+      eprintln!("cannot load {}: {}", path, e);
+      exit(1);
+    }
+  };
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let [width, height] = back_buffer.size();
+  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+
+  let view = Matrix4::<f32>::look_at(
+    Point3::from(CAMERA_POS),
+    Point3::origin(),
+    Vector3::unit_y(),
+  );
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+        _ => (),
+      }
+    }
+
+    // rendering code goes here
+    // the light orbits the model, so that the highlights slide over it
+    let t = start_t.elapsed().as_secs_f32();
+    let angle = t * LIGHT_SPEED;
+    let light_pos = [
+      LIGHT_RADIUS * angle.cos(),
+      LIGHT_HEIGHT,
+      LIGHT_RADIUS * angle.sin(),
+    ];
+    let color = [0.1, 0.1, 0.1, 1.];
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color(color),
+        |_, mut shd_gate| {
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.light_pos, light_pos);
+            iface.set(&uni.light_color, LIGHT_COLOR);
+            iface.set(&uni.camera_pos, CAMERA_POS);
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&mesh)
+            })
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
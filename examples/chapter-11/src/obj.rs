@@ -0,0 +1,92 @@
+//! Wavefront OBJ loading, kept minimal.
+//!
+//! This is the loader of chapter 3 as it first appears there: a single object made of a single
+//! geometry, triangles only, with a normal for every vertex. Chapter 3 grows it into a complete
+//! one (generated normals, polygons, materials, several objects); the lighting here doesn’t need
+//! any of that, so it isn’t copied over.
+
+use crate::{Vertex, VertexIndex, VertexNormal, VertexPosition};
+use luminance_front::context::GraphicsContext;
+use luminance_front::tess::{Interleaved, Mode, Tess, TessError};
+use luminance_front::Backend;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use try_guard::verify;
+use wavefront_obj::obj;
+
+pub struct Obj {
+  vertices: Vec<Vertex>,
+  indices: Vec<VertexIndex>,
+}
+
+impl Obj {
+  pub fn to_tess<C>(
+    &self,
+    ctxt: &mut C,
+  ) -> Result<Tess<Vertex, VertexIndex, (), Interleaved>, TessError>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    ctxt
+      .new_tess()
+      .set_mode(Mode::Triangle)
+      .set_vertices(self.vertices.clone())
+      .set_indices(self.indices.clone())
+      .build()
+  }
+
+  pub fn load<P>(path: P) -> Result<Self, String>
+  where
+    P: AsRef<Path>,
+  {
+    let file_content = fs::read_to_string(path).map_err(|e| format!("cannot open file: {}", e))?;
+    let obj_set = obj::parse(file_content).map_err(|e| format!("cannot parse: {:?}", e))?;
+    let objects = obj_set.objects;
+
+    verify!(objects.len() == 1).ok_or_else(|| "expecting a single object".to_owned())?;
+
+    let object = objects.into_iter().next().unwrap();
+
+    verify!(object.geometry.len() == 1).ok_or_else(|| "expecting a single geometry".to_owned())?;
+
+    let geometry = object.geometry.into_iter().next().unwrap();
+
+    println!("loading {}", object.name);
+    println!("{} vertices", object.vertices.len());
+    println!("{} shapes", geometry.shapes.len());
+
+    // build up vertices; for this to work, we remove duplicated vertices by putting them in a
+    // map associating the vertex with its ID
+    let mut vertex_cache: HashMap<obj::VTNIndex, VertexIndex> = HashMap::new();
+    let mut vertices: Vec<Vertex> = Vec::new();
+    let mut indices: Vec<VertexIndex> = Vec::new();
+
+    for shape in geometry.shapes {
+      if let obj::Primitive::Triangle(a, b, c) = shape.primitive {
+        for key in &[a, b, c] {
+          if let Some(vertex_index) = vertex_cache.get(key) {
+            indices.push(*vertex_index);
+          } else {
+            let p = object.vertices[key.0];
+            let n = object.normals[key
+              .2
+              .ok_or_else(|| "missing normal for a vertex".to_owned())?];
+            let position = VertexPosition::new([p.x as f32, p.y as f32, p.z as f32]);
+            let normal = VertexNormal::new([n.x as f32, n.y as f32, n.z as f32]);
+            let vertex = Vertex { position, normal };
+            let vertex_index = vertices.len() as VertexIndex;
+
+            vertex_cache.insert(*key, vertex_index);
+            vertices.push(vertex);
+            indices.push(vertex_index);
+          }
+        }
+      } else {
+        return Err("unsupported non-triangle shape".to_owned());
+      }
+    }
+
+    Ok(Obj { vertices, indices })
+  }
+}
@@ -0,0 +1,15 @@
+in vec3 position;
+in vec3 normal;
+
+out vec3 v_position;
+out vec3 v_normal;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+void main() {
+  // the model is drawn where it stands, so model space is world space
+  v_position = position;
+  v_normal = normal;
+  gl_Position = projection * view * vec4(position, 1.);
+}
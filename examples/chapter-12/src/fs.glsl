@@ -0,0 +1,45 @@
+in vec3 v_position;
+in vec3 v_normal;
+
+out vec3 frag_color;
+
+uniform vec3 albedo;
+// direction towards the light
+uniform vec3 light_dir;
+uniform vec3 light_color;
+uniform vec3 camera_pos;
+uniform mat4 light_view_projection;
+uniform sampler2D shadow_map;
+
+const float AMBIENT = .15;
+const float SHININESS = 64.;
+
+// 1 if the light reaches a point, 0 if something closer to the light is in the way
+float light_visibility(vec3 p, float cos_theta) {
+  vec4 light = light_view_projection * vec4(p, 1.);
+  vec3 coords = light.xyz / light.w * .5 + .5;
+
+  // outside of the shadow map, nothing casts shadows
+  if (any(lessThan(coords, vec3(0.))) || any(greaterThan(coords, vec3(1.)))) {
+    return 1.;
+  }
+
+  // a surface would shadow itself where its depth is rounded up in the map (shadow acne); the bias
+  // pushes it away, more so on surfaces grazed by the light, which cover more depth per texel
+  float bias = max(.005 * (1. - cos_theta), .0005);
+
+  return coords.z - bias <= texture(shadow_map, coords.xy).r ? 1. : 0.;
+}
+
+void main() {
+  vec3 n = normalize(v_normal);
+  vec3 l = normalize(light_dir);
+  vec3 v = normalize(camera_pos - v_position);
+  vec3 h = normalize(l + v);
+
+  float kd = max(dot(n, l), 0.);
+  float ks = kd > 0. ? pow(max(dot(n, h), 0.), SHININESS) : 0.;
+  float visibility = light_visibility(v_position, kd);
+
+  frag_color = albedo * AMBIENT + (albedo * kd + ks) * light_color * visibility;
+}
@@ -0,0 +1,268 @@
+mod obj;
+
+use crate::obj::Obj;
+use cgmath::{
+  ortho, perspective, EuclideanSpace, InnerSpace, Matrix4, Point3, Rad, SquareMatrix, Vector3,
+};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance::pipeline::TextureBinding;
+use luminance::pixel::{Depth32F, Floating};
+use luminance::texture::{Dim2, MagFilter, MinFilter, Sampler};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_front::tess::Mode;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::env;
+use std::process::exit;
+use std::time::Instant;
+
+const VS_STR: &str = include_str!("vs.glsl");
+const FS_STR: &str = include_str!("fs.glsl");
+const SHADOW_FS_STR: &str = include_str!("shadow_fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_2);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 10.;
+
+const CAMERA_POS: [f32; 3] = [2., 2., 2.];
+
+/// Resolution of the shadow map; the higher, the less jagged the edges of the shadows.
+const SHADOW_MAP_SIZE: u32 = 2048;
+
+/// Half the side of the ground, centered under the model.
+const GROUND_HALF_SIZE: f32 = 2.;
+
+/// The light is a sun circling the scene at this height, for a distance of 1.
+const LIGHT_HEIGHT: f32 = 1.2;
+/// Angular speed of the light, in radians per second.
+const LIGHT_SPEED: f32 = 0.4;
+const LIGHT_COLOR: [f32; 3] = [1., 0.95, 0.85];
+
+const MODEL_ALBEDO: [f32; 3] = [0.6, 0.6, 0.6];
+const GROUND_ALBEDO: [f32; 3] = [0.45, 0.5, 0.4];
+
+#[derive(Debug, UniformInterface)]
+struct ShaderInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  albedo: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  light_dir: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  light_color: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  light_view_projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  shadow_map: Uniform<TextureBinding<Dim2, Floating>>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  position: VertexPosition,
+  normal: VertexNormal,
+}
+
+pub type VertexIndex = u32;
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let path = match env::args().nth(1) {
+    Some(path) => path,
+    None => {
+      eprintln!("usage: chapter-12 <model.obj>");
+      exit(1);
+    }
+  };
+  println!("loading {}", path);
+
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  let obj = Obj::load(&path).unwrap_or_else(|e| {
+    eprintln!("cannot load {}: {}", path, e);
+    exit(1);
+  });
+  let mesh = obj.to_tess(&mut ctxt).unwrap();
+
+  // the ground lies right under the model, so that the model stands on it
+  let (min, _) = obj.bounds();
+  let ground = ctxt
+    .new_tess()
+    .set_vertices(&ground_vertices(min[1])[..])
+    .set_mode(Mode::TriangleFan)
+    .build()
+    .unwrap();
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  // only the depth is needed from the light’s point of view, so the shadow pass writes no color
+  let mut shadow_program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, SHADOW_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  // texels are looked up as they are: filtering depths would blend occluders with what’s behind
+  let sampler = Sampler {
+    min_filter: MinFilter::Nearest,
+    mag_filter: MagFilter::Nearest,
+    ..Sampler::default()
+  };
+  let mut shadow_fb = ctxt
+    .new_framebuffer::<Dim2, (), Depth32F>([SHADOW_MAP_SIZE, SHADOW_MAP_SIZE], 0, sampler)
+    .expect("shadow framebuffer");
+
+  let [width, height] = back_buffer.size();
+  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+
+  let view = Matrix4::<f32>::look_at(
+    Point3::from(CAMERA_POS),
+    Point3::origin(),
+    Vector3::unit_y(),
+  );
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+        _ => (),
+      }
+    }
+
+    // rendering code goes here
+    let t = start_t.elapsed().as_secs_f32();
+    let angle = t * LIGHT_SPEED;
+    let light_dir = Vector3::new(angle.cos(), LIGHT_HEIGHT, angle.sin()).normalize();
+    let light_view_projection = light_view_projection(light_dir);
+
+    // render the depth of the model as seen from the light…
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(&shadow_fb, &PipelineState::default(), |_, mut shd_gate| {
+        shd_gate.shade(&mut shadow_program, |mut iface, uni, mut rdr_gate| {
+          iface.set(&uni.projection, light_view_projection.into());
+          iface.set(&uni.view, Matrix4::<f32>::identity().into());
+
+          // the ground casts no shadow on anything, so it’s left out
+          rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+            tess_gate.render(&mesh)
+          })
+        })
+      })
+      .assume();
+
+    if render.is_err() {
+      break 'app;
+    }
+
+    // … then the scene, lit where the light reaches it
+    let color = [0.1, 0.1, 0.1, 1.];
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color(color),
+        |pipeline, mut shd_gate| {
+          let shadow_map = pipeline.bind_texture(shadow_fb.depth_stencil_slot())?;
+
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.light_dir, light_dir.into());
+            iface.set(&uni.light_color, LIGHT_COLOR);
+            iface.set(&uni.camera_pos, CAMERA_POS);
+            iface.set(&uni.light_view_projection, light_view_projection.into());
+            iface.set(&uni.shadow_map, shadow_map.binding());
+
+            iface.set(&uni.albedo, MODEL_ALBEDO);
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&mesh)
+            })?;
+
+            iface.set(&uni.albedo, GROUND_ALBEDO);
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&ground)
+            })
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+/// Corners of the ground at height `y`, counter-clockwise seen from above.
+fn ground_vertices(y: f32) -> [Vertex; 4] {
+  let s = GROUND_HALF_SIZE;
+  let corner = |x, z| Vertex {
+    position: VertexPosition::new([x, y, z]),
+    normal: VertexNormal::new([0., 1., 0.]),
+  };
+
+  [corner(-s, s), corner(s, s), corner(s, -s), corner(-s, -s)]
+}
+
+/// Orthographic projection of the scene as seen from the light, for the shadow map.
+///
+/// The light is a sun, infinitely far away: its rays are parallel, hence the orthographic
+/// projection rather than a perspective one.
+fn light_view_projection(light_dir: Vector3<f32>) -> Matrix4<f32> {
+  // the box must hold the whole ground whatever the direction of the light
+  let radius = GROUND_HALF_SIZE * std::f32::consts::SQRT_2;
+  let eye = Point3::origin() + light_dir * 2. * radius;
+  let view = Matrix4::look_at(eye, Point3::origin(), Vector3::unit_y());
+
+  ortho(-radius, radius, -radius, radius, 0., 4. * radius) * view
+}
@@ -0,0 +1,25 @@
+in vec3 v_position;
+in vec3 v_normal;
+in vec3 v_color;
+
+out vec3 frag_color;
+
+// direction towards the light
+uniform vec3 light_dir;
+uniform vec3 light_color;
+uniform vec3 camera_pos;
+
+const float AMBIENT = .15;
+const float SHININESS = 32.;
+
+void main() {
+  vec3 n = normalize(v_normal);
+  vec3 l = normalize(light_dir);
+  vec3 v = normalize(camera_pos - v_position);
+  vec3 h = normalize(l + v);
+
+  float kd = max(dot(n, l), 0.);
+  float ks = kd > 0. ? pow(max(dot(n, h), 0.), SHININESS) : 0.;
+
+  frag_color = v_color * AMBIENT + (v_color * kd + ks * .5) * light_color;
+}
@@ -0,0 +1,338 @@
+mod physics;
+mod shapes;
+
+use crate::physics::{Physics, STEP};
+use crate::shapes::Shape;
+use cgmath::{perspective, Matrix4, Point3, Rad, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_front::tess::{Interleaved, Mode, Tess};
+use luminance_front::Backend;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use rapier3d::prelude::RigidBodyHandle;
+use std::process::exit;
+use std::time::Instant;
+
+const VS_STR: &str = include_str!("vs.glsl");
+const FS_STR: &str = include_str!("fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 30.;
+
+const CAMERA_POS: [f32; 3] = [5., 3.5, 5.];
+const LIGHT_DIR: [f32; 3] = [0.4, 1., 0.6];
+const LIGHT_COLOR: [f32; 3] = [1., 0.95, 0.85];
+
+const GROUND_HALF_SIZE: f32 = 4.;
+const GROUND_COLOR: [f32; 3] = [0.45, 0.5, 0.4];
+
+/// The stack is made of layers of bodies laid out on a grid, each layer higher than the previous
+/// one.
+const LAYERS: usize = 8;
+const GRID_SIZE: usize = 3;
+const GRID_SPACING: f32 = 0.7;
+const FIRST_LAYER_HEIGHT: f32 = 1.;
+const LAYER_SPACING: f32 = 0.8;
+
+const PALETTE: [[f32; 3]; 5] = [
+  [0.85, 0.35, 0.3],
+  [0.95, 0.7, 0.3],
+  [0.4, 0.7, 0.45],
+  [0.3, 0.55, 0.85],
+  [0.6, 0.45, 0.8],
+];
+
+/// Longest time simulated per frame; past that, the simulation slows down rather than taking ever
+/// longer to catch up.
+const MAX_FRAME_TIME: f32 = 0.25;
+
+#[derive(Debug, UniformInterface)]
+struct ShaderInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  light_dir: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  light_color: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+  #[sem(
+    name = "translation",
+    repr = "[f32; 3]",
+    wrapper = "InstanceTranslation"
+  )]
+  Translation,
+  #[sem(name = "rotation", repr = "[f32; 4]", wrapper = "InstanceRotation")]
+  Rotation,
+  #[sem(name = "color", repr = "[f32; 3]", wrapper = "InstanceColor")]
+  Color,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  position: VertexPosition,
+  normal: VertexNormal,
+}
+
+pub type VertexIndex = u32;
+
+/// Where a body stands, as a translation and a rotation quaternion, and its color.
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics", instanced = "true")]
+pub struct Instance {
+  translation: InstanceTranslation,
+  rotation: InstanceRotation,
+  color: InstanceColor,
+}
+
+/// Body of the simulation, along with how it’s drawn.
+struct Body {
+  handle: RigidBodyHandle,
+  shape: Shape,
+  color: [f32; 3],
+}
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  let (mut physics, mut bodies) = drop_stack();
+  println!(
+    "{} bodies dropped; press R to drop them again",
+    bodies.len()
+  );
+
+  // one instanced tessellation per shape, holding all the bodies of that shape
+  let mut shape_meshes = Shape::ALL
+    .iter()
+    .map(|&shape| {
+      let (vertices, indices) = shape.mesh();
+      let tess = ctxt
+        .new_tess()
+        .set_mode(Mode::Triangle)
+        .set_vertices(vertices)
+        .set_indices(indices)
+        .set_instances(instances(&physics, &bodies, shape))
+        .build()
+        .unwrap();
+
+      (shape, tess)
+    })
+    .collect::<Vec<_>>();
+
+  let ground = ground(&mut ctxt);
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let [width, height] = back_buffer.size();
+  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+
+  let view = Matrix4::<f32>::look_at(
+    Point3::from(CAMERA_POS),
+    Point3::new(0., 1., 0.),
+    Vector3::unit_y(),
+  );
+
+  let mut last_t = 0.;
+  // simulated time lagging behind the real one
+  let mut lag = 0.;
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(Key::R, _, Action::Press, _) => {
+          let (new_physics, new_bodies) = drop_stack();
+          physics = new_physics;
+          bodies = new_bodies;
+          lag = 0.;
+        }
+
+        _ => (),
+      }
+    }
+
+    // step the simulation until it catches up with the time that passed since the last frame
+    let t = start_t.elapsed().as_secs_f32();
+    lag += (t - last_t).min(MAX_FRAME_TIME);
+    last_t = t;
+
+    while lag >= STEP {
+      physics.step();
+      lag -= STEP;
+    }
+
+    // the tessellations are kept, only the poses of the instances change
+    for (shape, tess) in &mut shape_meshes {
+      let poses = instances(&physics, &bodies, *shape);
+
+      match tess.instances_mut() {
+        Ok(mut instances) => instances.copy_from_slice(&poses),
+        Err(e) => eprintln!("cannot update instances: {}", e),
+      }
+    }
+
+    // rendering code goes here
+    let color = [0.1, 0.1, 0.1, 1.];
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color(color),
+        |_, mut shd_gate| {
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.light_dir, LIGHT_DIR);
+            iface.set(&uni.light_color, LIGHT_COLOR);
+            iface.set(&uni.camera_pos, CAMERA_POS);
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&ground)?;
+
+              shape_meshes
+                .iter()
+                .try_for_each(|(_, tess)| tess_gate.render(tess))
+            })
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+/// Set up a new simulation, with the bodies of the stack about to fall on the ground.
+fn drop_stack() -> (Physics, Vec<Body>) {
+  let mut physics = Physics::new();
+  let mut bodies = Vec::new();
+
+  physics.add_ground(GROUND_HALF_SIZE);
+
+  for layer in 0..LAYERS {
+    for i in 0..GRID_SIZE {
+      for j in 0..GRID_SIZE {
+        let k = bodies.len();
+        let shape = Shape::ALL[(layer + i + j) % Shape::ALL.len()];
+        let offset = (GRID_SIZE - 1) as f32 * 0.5;
+        let translation = [
+          (i as f32 - offset) * GRID_SPACING,
+          FIRST_LAYER_HEIGHT + layer as f32 * LAYER_SPACING,
+          (j as f32 - offset) * GRID_SPACING,
+        ];
+        // bodies are turned a bit, differently from each other, so that the stack tumbles rather
+        // than settling in neat columns
+        let rotation = [
+          0.4 * (k as f32 * 1.3).sin(),
+          k as f32 * 0.7,
+          0.4 * (k as f32 * 0.9).cos(),
+        ];
+        let handle = physics.add_body(shape.collider(), translation, rotation);
+
+        bodies.push(Body {
+          handle,
+          shape,
+          color: PALETTE[k % PALETTE.len()],
+        });
+      }
+    }
+  }
+
+  (physics, bodies)
+}
+
+/// Instances of the bodies of a given shape, where the simulation has them.
+fn instances(physics: &Physics, bodies: &[Body], shape: Shape) -> Vec<Instance> {
+  bodies
+    .iter()
+    .filter(|body| body.shape == shape)
+    .map(|body| {
+      let (translation, rotation) = physics.pose(body.handle);
+
+      Instance {
+        translation: InstanceTranslation::new(translation),
+        rotation: InstanceRotation::new(rotation),
+        color: InstanceColor::new(body.color),
+      }
+    })
+    .collect()
+}
+
+/// The ground, drawn as a single instance standing still.
+fn ground<C>(ctxt: &mut C) -> Tess<Vertex, (), Instance, Interleaved>
+where
+  C: GraphicsContext<Backend = Backend>,
+{
+  let s = GROUND_HALF_SIZE;
+  let corner = |x, z| Vertex {
+    position: VertexPosition::new([x, 0., z]),
+    normal: VertexNormal::new([0., 1., 0.]),
+  };
+  let vertices = vec![corner(-s, s), corner(s, s), corner(s, -s), corner(-s, -s)];
+  let instance = Instance {
+    translation: InstanceTranslation::new([0., 0., 0.]),
+    rotation: InstanceRotation::new([0., 0., 0., 1.]),
+    color: InstanceColor::new(GROUND_COLOR),
+  };
+
+  ctxt
+    .new_tess()
+    .set_mode(Mode::TriangleFan)
+    .set_vertices(vertices)
+    .set_instances(vec![instance])
+    .build()
+    .unwrap()
+}
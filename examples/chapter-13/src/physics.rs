@@ -0,0 +1,104 @@
+//! Rigid-body simulation.
+//!
+//! rapier keeps its state in a handful of sets and structures that all have to be handed to each
+//! step; they’re gathered here. The simulation runs at a fixed time step, whatever the frame rate:
+//! the main loop steps it as many times as needed to catch up with the time that passed.
+
+use rapier3d::prelude::*;
+
+/// Simulated time per step, in seconds.
+pub const STEP: f32 = 1. / 60.;
+
+pub struct Physics {
+  gravity: Vector<Real>,
+  params: IntegrationParameters,
+  pipeline: PhysicsPipeline,
+  islands: IslandManager,
+  broad_phase: BroadPhase,
+  narrow_phase: NarrowPhase,
+  bodies: RigidBodySet,
+  colliders: ColliderSet,
+  impulse_joints: ImpulseJointSet,
+  multibody_joints: MultibodyJointSet,
+  ccd_solver: CCDSolver,
+}
+
+impl Physics {
+  pub fn new() -> Self {
+    let params = IntegrationParameters {
+      dt: STEP,
+      ..IntegrationParameters::default()
+    };
+
+    Physics {
+      gravity: vector![0., -9.81, 0.],
+      params,
+      pipeline: PhysicsPipeline::new(),
+      islands: IslandManager::new(),
+      broad_phase: BroadPhase::new(),
+      narrow_phase: NarrowPhase::new(),
+      bodies: RigidBodySet::new(),
+      colliders: ColliderSet::new(),
+      impulse_joints: ImpulseJointSet::new(),
+      multibody_joints: MultibodyJointSet::new(),
+      ccd_solver: CCDSolver::new(),
+    }
+  }
+
+  /// Add a fixed slab whose top face lies at y = 0, of the given half size along X and Z.
+  pub fn add_ground(&mut self, half_size: f32) {
+    let ground = ColliderBuilder::cuboid(half_size, 0.1, half_size)
+      .translation(vector![0., -0.1, 0.])
+      .build();
+
+    self.colliders.insert(ground);
+  }
+
+  /// Add a body falling freely, at `translation` and turned by the axis-angle `rotation`.
+  pub fn add_body(
+    &mut self,
+    collider: Collider,
+    translation: [f32; 3],
+    rotation: [f32; 3],
+  ) -> RigidBodyHandle {
+    let body = RigidBodyBuilder::dynamic()
+      .translation(translation.into())
+      .rotation(rotation.into())
+      .build();
+    let handle = self.bodies.insert(body);
+
+    self
+      .colliders
+      .insert_with_parent(collider, handle, &mut self.bodies);
+
+    handle
+  }
+
+  /// Advance the simulation by STEP.
+  pub fn step(&mut self) {
+    self.pipeline.step(
+      &self.gravity,
+      &self.params,
+      &mut self.islands,
+      &mut self.broad_phase,
+      &mut self.narrow_phase,
+      &mut self.bodies,
+      &mut self.colliders,
+      &mut self.impulse_joints,
+      &mut self.multibody_joints,
+      &mut self.ccd_solver,
+      None,
+      &(),
+      &(),
+    );
+  }
+
+  /// Where a body stands, as its translation and its rotation, a quaternion as X, Y, Z and W.
+  pub fn pose(&self, handle: RigidBodyHandle) -> ([f32; 3], [f32; 4]) {
+    let body = &self.bodies[handle];
+    let t = body.translation();
+    let q = body.rotation();
+
+    ([t.x, t.y, t.z], [q.i, q.j, q.k, q.w])
+  }
+}
@@ -0,0 +1,129 @@
+//! Shapes of the bodies, both as meshes to draw and as colliders to simulate.
+//!
+//! The meshes are flat-shaded: every face has its own vertices, with the normal of the face.
+
+use crate::{Vertex, VertexIndex, VertexNormal, VertexPosition};
+use cgmath::{InnerSpace, Vector3};
+use rapier3d::prelude::*;
+
+/// Half the side of cubes.
+const CUBE_HALF_SIZE: f32 = 0.25;
+/// Distance from the center to the corners of octahedra.
+const OCTAHEDRON_RADIUS: f32 = 0.32;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Shape {
+  Cube,
+  Octahedron,
+}
+
+impl Shape {
+  pub const ALL: [Shape; 2] = [Shape::Cube, Shape::Octahedron];
+
+  pub fn mesh(self) -> (Vec<Vertex>, Vec<VertexIndex>) {
+    match self {
+      Shape::Cube => {
+        let s = CUBE_HALF_SIZE;
+        let corners = [
+          [-s, -s, -s],
+          [s, -s, -s],
+          [s, s, -s],
+          [-s, s, -s],
+          [-s, -s, s],
+          [s, -s, s],
+          [s, s, s],
+          [-s, s, s],
+        ];
+        // two triangles per face, counter-clockwise seen from outside
+        let triangles = [
+          [0, 3, 2],
+          [0, 2, 1],
+          [4, 5, 6],
+          [4, 6, 7],
+          [0, 1, 5],
+          [0, 5, 4],
+          [3, 7, 6],
+          [3, 6, 2],
+          [0, 4, 7],
+          [0, 7, 3],
+          [1, 2, 6],
+          [1, 6, 5],
+        ];
+
+        flat_mesh(&corners, &triangles)
+      }
+
+      Shape::Octahedron => {
+        let corners = octahedron_corners();
+        let triangles = [
+          [0, 4, 2],
+          [2, 4, 1],
+          [1, 4, 3],
+          [3, 4, 0],
+          [2, 5, 0],
+          [1, 5, 2],
+          [3, 5, 1],
+          [0, 5, 3],
+        ];
+
+        flat_mesh(&corners, &triangles)
+      }
+    }
+  }
+
+  pub fn collider(self) -> Collider {
+    let builder = match self {
+      Shape::Cube => ColliderBuilder::cuboid(CUBE_HALF_SIZE, CUBE_HALF_SIZE, CUBE_HALF_SIZE),
+
+      // any convex shape can be simulated from the points it wraps
+      Shape::Octahedron => {
+        let points = octahedron_corners()
+          .iter()
+          .map(|&[x, y, z]| point![x, y, z])
+          .collect::<Vec<_>>();
+
+        ColliderBuilder::convex_hull(&points).expect("octahedron hull")
+      }
+    };
+
+    builder.restitution(0.2).friction(0.8).build()
+  }
+}
+
+/// Corners of an octahedron: +X, -X, +Z, -Z, +Y, -Y.
+fn octahedron_corners() -> [[f32; 3]; 6] {
+  let r = OCTAHEDRON_RADIUS;
+
+  [
+    [r, 0., 0.],
+    [-r, 0., 0.],
+    [0., 0., r],
+    [0., 0., -r],
+    [0., r, 0.],
+    [0., -r, 0.],
+  ]
+}
+
+fn flat_mesh(corners: &[[f32; 3]], triangles: &[[usize; 3]]) -> (Vec<Vertex>, Vec<VertexIndex>) {
+  let mut vertices = Vec::with_capacity(triangles.len() * 3);
+
+  for triangle in triangles {
+    let [a, b, c] = [
+      Vector3::from(corners[triangle[0]]),
+      Vector3::from(corners[triangle[1]]),
+      Vector3::from(corners[triangle[2]]),
+    ];
+    let normal = (b - a).cross(c - a).normalize();
+
+    for &p in &[a, b, c] {
+      vertices.push(Vertex {
+        position: VertexPosition::new(p.into()),
+        normal: VertexNormal::new(normal.into()),
+      });
+    }
+  }
+
+  let indices = (0..vertices.len() as VertexIndex).collect();
+
+  (vertices, indices)
+}
@@ -0,0 +1,24 @@
+in vec3 position;
+in vec3 normal;
+in vec3 translation;
+in vec4 rotation;
+in vec3 color;
+
+out vec3 v_position;
+out vec3 v_normal;
+out vec3 v_color;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+// rotate v by the unit quaternion q
+vec3 rotate(vec4 q, vec3 v) {
+  return v + 2. * cross(q.xyz, cross(q.xyz, v) + q.w * v);
+}
+
+void main() {
+  v_position = translation + rotate(rotation, position);
+  v_normal = rotate(rotation, normal);
+  v_color = color;
+  gl_Position = projection * view * vec4(v_position, 1.);
+}
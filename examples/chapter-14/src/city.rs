@@ -0,0 +1,205 @@
+//! The city: towers on a grid, around a ring road.
+//!
+//! Towers are detailed cylinders, costly to draw, and seen from the ring road most of them hide
+//! behind the ones closer to the camera: that’s where occlusion culling pays off. Each tower is
+//! queried with its bounding box, a unit cube scaled like the tower, much cheaper to draw.
+
+use crate::{Vertex, VertexIndex, VertexNormal, VertexPosition};
+use cgmath::{EuclideanSpace, Matrix4, Point3, Vector3};
+use std::f32::consts::PI;
+
+/// Number of towers along each side of the grid.
+pub const GRID_SIZE: usize = 32;
+/// Distance between the centers of neighbouring towers.
+pub const SPACING: f32 = 3.;
+/// Radius of the ring road the camera drives along.
+pub const RING_RADIUS: f32 = 30.;
+
+/// Segments around the towers and rings along their height.
+const SEGMENTS: usize = 64;
+const RINGS: usize = 32;
+
+/// Footprint and height of the towers.
+const MIN_WIDTH: f32 = 1.2;
+const MAX_WIDTH: f32 = 2.6;
+const MIN_HEIGHT: f32 = 2.;
+const MAX_HEIGHT: f32 = 14.;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tower {
+  /// Center of the base.
+  pub position: Point3<f32>,
+  pub width: f32,
+  pub height: f32,
+  pub color: [f32; 3],
+}
+
+impl Tower {
+  /// Transform of the tower mesh and of its bounding box, both unit-sized.
+  pub fn model(&self) -> Matrix4<f32> {
+    Matrix4::from_translation(self.position.to_vec())
+      * Matrix4::from_nonuniform_scale(self.width, self.height, self.width)
+  }
+
+  /// Whether `point` is in the bounding box of the tower, grown by `margin`.
+  ///
+  /// Boxes the camera is in get clipped by the near plane and can’t be seen from the inside, so
+  /// they’d be reported hidden whatever they hold.
+  pub fn contains(&self, point: Point3<f32>, margin: f32) -> bool {
+    let half = self.width * 0.5 + margin;
+    let offset = point - self.position;
+
+    offset.x.abs() <= half
+      && offset.z.abs() <= half
+      && offset.y >= -margin
+      && offset.y <= self.height + margin
+  }
+}
+
+/// Towers of the grid, leaving the ring road free.
+pub fn towers() -> Vec<Tower> {
+  let mut towers = Vec::new();
+  let offset = (GRID_SIZE - 1) as f32 * 0.5;
+
+  for i in 0..GRID_SIZE {
+    for j in 0..GRID_SIZE {
+      let k = i * GRID_SIZE + j;
+      let position = Point3::new(
+        (i as f32 - offset) * SPACING,
+        0.,
+        (j as f32 - offset) * SPACING,
+      );
+
+      let distance = (position.x * position.x + position.z * position.z).sqrt();
+      if (distance - RING_RADIUS).abs() < SPACING {
+        continue;
+      }
+
+      let gray = 0.45 + 0.35 * noise(k, 3);
+      towers.push(Tower {
+        position,
+        width: MIN_WIDTH + (MAX_WIDTH - MIN_WIDTH) * noise(k, 1),
+        height: MIN_HEIGHT + (MAX_HEIGHT - MIN_HEIGHT) * noise(k, 2).powi(2),
+        color: [gray, gray * 0.95, gray * 0.9],
+      });
+    }
+  }
+
+  towers
+}
+
+/// Unit tower: a cylinder of diameter 1 standing on the origin, 1 high, with a flat roof.
+pub fn tower_mesh() -> (Vec<Vertex>, Vec<VertexIndex>) {
+  let mut vertices = Vec::new();
+  let mut indices = Vec::new();
+
+  for ring in 0..=RINGS {
+    let y = ring as f32 / RINGS as f32;
+
+    for segment in 0..=SEGMENTS {
+      let angle = segment as f32 / SEGMENTS as f32 * 2. * PI;
+      let (sin, cos) = angle.sin_cos();
+
+      vertices.push(Vertex {
+        position: VertexPosition::new([0.5 * cos, y, 0.5 * sin]),
+        normal: VertexNormal::new([cos, 0., sin]),
+      });
+    }
+  }
+
+  let row = (SEGMENTS + 1) as VertexIndex;
+  for ring in 0..RINGS as VertexIndex {
+    for segment in 0..SEGMENTS as VertexIndex {
+      let a = ring * row + segment;
+      let b = a + row;
+
+      // counter-clockwise seen from outside
+      indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+    }
+  }
+
+  // the roof is a fan around its center
+  let center = vertices.len() as VertexIndex;
+  vertices.push(Vertex {
+    position: VertexPosition::new([0., 1., 0.]),
+    normal: VertexNormal::new([0., 1., 0.]),
+  });
+
+  for segment in 0..=SEGMENTS {
+    let angle = segment as f32 / SEGMENTS as f32 * 2. * PI;
+    let (sin, cos) = angle.sin_cos();
+
+    vertices.push(Vertex {
+      position: VertexPosition::new([0.5 * cos, 1., 0.5 * sin]),
+      normal: VertexNormal::new([0., 1., 0.]),
+    });
+  }
+
+  for segment in 0..SEGMENTS as VertexIndex {
+    indices.extend_from_slice(&[center, center + segment + 2, center + segment + 1]);
+  }
+
+  (vertices, indices)
+}
+
+/// Unit bounding box of the towers: a cube of side 1 standing on the origin.
+pub fn box_mesh() -> (Vec<Vertex>, Vec<VertexIndex>) {
+  let mut vertices = Vec::new();
+  let mut indices = Vec::new();
+
+  // each face is given by its normal and two axes along it, such that u × v = normal
+  let faces = [
+    (Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()),
+    (-Vector3::unit_x(), Vector3::unit_z(), Vector3::unit_y()),
+    (Vector3::unit_y(), Vector3::unit_z(), Vector3::unit_x()),
+    (-Vector3::unit_y(), Vector3::unit_x(), Vector3::unit_z()),
+    (Vector3::unit_z(), Vector3::unit_x(), Vector3::unit_y()),
+    (-Vector3::unit_z(), Vector3::unit_y(), Vector3::unit_x()),
+  ];
+
+  for &(normal, u, v) in &faces {
+    let base = vertices.len() as VertexIndex;
+    let center = Vector3::new(0., 0.5, 0.) + normal * 0.5;
+
+    for &(su, sv) in &[(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)] {
+      vertices.push(Vertex {
+        position: VertexPosition::new((center + u * su + v * sv).into()),
+        normal: VertexNormal::new(normal.into()),
+      });
+    }
+
+    indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+  }
+
+  (vertices, indices)
+}
+
+/// Ground, a square under the whole city.
+pub fn ground_mesh() -> Vec<Vertex> {
+  let s = GRID_SIZE as f32 * SPACING * 0.5 + SPACING;
+  let corner = |x, z| Vertex {
+    position: VertexPosition::new([x, 0., z]),
+    normal: VertexNormal::new([0., 1., 0.]),
+  };
+
+  vec![corner(-s, s), corner(s, s), corner(s, -s), corner(-s, -s)]
+}
+
+/// Position and direction of the camera along the ring road at time `t`.
+///
+/// It drives around the ring, looking ahead and a bit towards the center.
+pub fn camera(t: f32) -> (Point3<f32>, Vector3<f32>) {
+  let angle = t * 0.1;
+  let (sin, cos) = angle.sin_cos();
+  let eye = Point3::new(RING_RADIUS * cos, 1.7, RING_RADIUS * sin);
+  let ahead = Vector3::new(-sin, 0., cos);
+  let inward = Vector3::new(-cos, 0., -sin);
+
+  (eye, ahead + inward * 0.4)
+}
+
+/// Pseudo-random number in [0; 1], the same for the same seeds.
+fn noise(k: usize, seed: usize) -> f32 {
+  let x = (k * 7 + seed * 131) as f32;
+  ((x * 12.9898).sin() * 43_758.547).fract().abs()
+}
@@ -0,0 +1,14 @@
+in vec3 v_normal;
+
+out vec3 frag_color;
+
+uniform vec3 color;
+
+// direction towards the light
+const vec3 LIGHT_DIR = vec3(.4, 1., .6);
+const float AMBIENT = .25;
+
+void main() {
+  float kd = max(dot(normalize(v_normal), normalize(LIGHT_DIR)), 0.);
+  frag_color = color * (AMBIENT + kd);
+}
@@ -0,0 +1,368 @@
+mod city;
+mod queries;
+
+use crate::city::Tower;
+use crate::queries::{GpuTimer, OcclusionQueries};
+use cgmath::{perspective, Matrix4, Rad, SquareMatrix, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance::blending::{Blending, Equation, Factor};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::depth_test::DepthWrite;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_front::tess::Mode;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::ffi::c_void;
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+const VS_STR: &str = include_str!("vs.glsl");
+const FS_STR: &str = include_str!("fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 200.;
+
+const GROUND_COLOR: [f32; 3] = [0.3, 0.32, 0.3];
+
+/// Counters and timings are averaged over this period before being shown.
+const REPORT_PERIOD: Duration = Duration::from_secs(1);
+
+#[derive(Debug, UniformInterface)]
+struct ShaderInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  model: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  color: Uniform<[f32; 3]>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  position: VertexPosition,
+  normal: VertexNormal,
+}
+
+pub type VertexIndex = u32;
+
+/// How the towers to draw are picked.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Culling {
+  /// All of them are drawn, hidden or not.
+  BruteForce,
+  /// Only those whose bounding box was visible the last time it was queried are drawn.
+  Occlusion,
+}
+
+impl Culling {
+  fn name(self) -> &'static str {
+    match self {
+      Culling::BruteForce => "brute force",
+      Culling::Occlusion => "occlusion culling",
+    }
+  }
+
+  fn toggle(self) -> Self {
+    match self {
+      Culling::BruteForce => Culling::Occlusion,
+      Culling::Occlusion => Culling::BruteForce,
+    }
+  }
+}
+
+/// Counters and timings of frames rendered with a culling mode.
+#[derive(Clone, Copy, Debug, Default)]
+struct Report {
+  frames: usize,
+  towers: usize,
+  draws: usize,
+  queries: usize,
+  frame_ms: f32,
+  gpu_ms: f32,
+  gpu_frames: usize,
+}
+
+impl Report {
+  fn add(&mut self, frame: Report) {
+    self.frames += 1;
+    self.towers += frame.towers;
+    self.draws += frame.draws;
+    self.queries += frame.queries;
+    self.frame_ms += frame.frame_ms;
+    self.gpu_ms += frame.gpu_ms;
+    self.gpu_frames += frame.gpu_frames;
+  }
+
+  /// Averages per frame.
+  fn summary(&self, culling: Culling) -> String {
+    let frames = self.frames.max(1) as f32;
+
+    format!(
+      "{}: {:.0} towers, {:.0} draws, {:.0} queries, {:.2} ms/frame ({:.2} ms GPU)",
+      culling.name(),
+      self.towers as f32 / frames,
+      self.draws as f32 / frames,
+      self.queries as f32 / frames,
+      self.frame_ms / frames,
+      self.gpu_ms / self.gpu_frames.max(1) as f32,
+    )
+  }
+}
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  // frame times are compared without waiting for the vertical sync
+  ctxt.window.glfw.set_swap_interval(glfw::SwapInterval::None);
+
+  // queries are issued with raw GL calls; the functions are loaded once, now that the context is
+  // current
+  let window = &mut ctxt.window;
+  gl::load_with(|name| window.get_proc_address(name) as *const c_void);
+
+  let towers = city::towers();
+  println!(
+    "{} towers; press O to switch between brute force and occlusion culling",
+    towers.len()
+  );
+
+  let (vertices, indices) = city::tower_mesh();
+  let tower_triangles = indices.len() / 3;
+  let tower_mesh = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(vertices)
+    .set_indices(indices)
+    .build()
+    .unwrap();
+
+  let (vertices, indices) = city::box_mesh();
+  let bounding_box = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(vertices)
+    .set_indices(indices)
+    .build()
+    .unwrap();
+
+  let ground = ctxt
+    .new_tess()
+    .set_mode(Mode::TriangleFan)
+    .set_vertices(city::ground_mesh())
+    .build()
+    .unwrap();
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let [width, height] = back_buffer.size();
+  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+
+  // bounding boxes are only tested against the depth buffer: they neither show nor hide anything
+  let query_state = RenderState::default()
+    .set_depth_write(DepthWrite::Off)
+    .set_blending(Blending {
+      equation: Equation::Additive,
+      src: Factor::Zero,
+      dst: Factor::One,
+    });
+
+  let mut queries = OcclusionQueries::new(towers.len());
+  let mut timer = GpuTimer::new();
+  // towers are assumed visible until their queries say otherwise
+  let mut visible = vec![true; towers.len()];
+
+  let mut culling = Culling::Occlusion;
+  let mut report = Report::default();
+  // last full report of each mode, brute force first
+  let mut last_reports: [Option<Report>; 2] = [None, None];
+  let mut report_start = Instant::now();
+  let mut last_frame = Instant::now();
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(Key::O, _, Action::Press, _) => {
+          culling = culling.toggle();
+          println!("{}", culling.name());
+
+          // what was visible when occlusion culling was left is long out of date
+          visible.iter_mut().for_each(|v| *v = true);
+          report = Report::default();
+          report_start = Instant::now();
+        }
+
+        _ => (),
+      }
+    }
+
+    let t = start_t.elapsed().as_secs_f32();
+    let (eye, direction) = city::camera(t);
+    let view = Matrix4::look_at_dir(eye, direction, Vector3::unit_y());
+
+    // results of the queries issued during previous frames; those not back yet keep the last known
+    // visibility
+    if culling == Culling::Occlusion {
+      for (i, tower) in towers.iter().enumerate() {
+        if let Some(v) = queries.poll(i) {
+          visible[i] = v;
+        }
+
+        if tower.contains(eye, 2. * Z_NEAR) {
+          visible[i] = true;
+        }
+      }
+    }
+
+    let mut frame = Report::default();
+    timer.begin_frame();
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color([0.55, 0.65, 0.8, 1.]),
+        |_, mut shd_gate| {
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+
+            iface.set(&uni.model, Matrix4::<f32>::identity().into());
+            iface.set(&uni.color, GROUND_COLOR);
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&ground)
+            })?;
+            frame.draws += 1;
+
+            for (i, tower) in towers.iter().enumerate() {
+              if culling == Culling::Occlusion && !visible[i] {
+                continue;
+              }
+
+              iface.set(&uni.model, tower.model().into());
+              iface.set(&uni.color, tower.color);
+              rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+                tess_gate.render(&tower_mesh)
+              })?;
+              frame.towers += 1;
+              frame.draws += 1;
+            }
+
+            // the bounding boxes are queried against the depth of the towers just drawn; a tower
+            // coming into view is then drawn from the next frame on, one frame late
+            towers
+              .iter()
+              .enumerate()
+              .filter(|_| culling == Culling::Occlusion)
+              .try_for_each(|(i, tower)| {
+                if queries.is_pending(i) {
+                  return Ok(());
+                }
+
+                iface.set(&uni.model, tower.model().into());
+                queries.begin(i);
+                let render = rdr_gate.render(&query_state, |mut tess_gate| {
+                  tess_gate.render(&bounding_box)
+                });
+                queries.end();
+                frame.queries += 1;
+                frame.draws += 1;
+
+                render
+              })
+          })
+        },
+      )
+      .assume();
+
+    let gpu_ms = timer.end_frame();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+
+    let now = Instant::now();
+    frame.frame_ms = now.duration_since(last_frame).as_secs_f32() * 1e3;
+    last_frame = now;
+
+    if let Some(gpu_ms) = gpu_ms {
+      frame.gpu_ms = gpu_ms;
+      frame.gpu_frames = 1;
+    }
+
+    report.add(frame);
+
+    // both modes are shown side by side, the one not in use with its last figures
+    if now.duration_since(report_start) >= REPORT_PERIOD {
+      last_reports[(culling == Culling::Occlusion) as usize] = Some(report);
+
+      let summaries = [Culling::BruteForce, Culling::Occlusion]
+        .iter()
+        .zip(&last_reports)
+        .map(|(&mode, report)| match report {
+          Some(report) => report.summary(mode),
+          None => format!("{}: press O to measure", mode.name()),
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+      println!(
+        "{} ({} triangles per tower)",
+        report.summary(culling),
+        tower_triangles
+      );
+      ctxt.window.set_title(&summaries);
+
+      report = Report::default();
+      report_start = now;
+    }
+  }
+}
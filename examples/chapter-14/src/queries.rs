@@ -0,0 +1,119 @@
+//! GPU queries.
+//!
+//! luminance 0.44 has no query objects, so they’re issued with raw GL calls around the draws they
+//! are about. Their results come back asynchronously: waiting for them right away would stall the
+//! CPU until the GPU catches up, so they’re only read once available, a frame or more later.
+
+use gl::types::GLuint;
+
+/// One occlusion query per object, telling whether any sample of what was drawn while it was
+/// active passed the depth test.
+#[derive(Debug)]
+pub struct OcclusionQueries {
+  queries: Vec<GLuint>,
+  /// Whether each query has been issued and its result not read yet.
+  pending: Vec<bool>,
+}
+
+impl OcclusionQueries {
+  pub fn new(count: usize) -> Self {
+    let mut queries = vec![0; count];
+    unsafe { gl::GenQueries(count as i32, queries.as_mut_ptr()) };
+
+    OcclusionQueries {
+      queries,
+      pending: vec![false; count],
+    }
+  }
+
+  /// Whether the query of object `i` is in flight; it can’t be issued again until it’s read.
+  pub fn is_pending(&self, i: usize) -> bool {
+    self.pending[i]
+  }
+
+  /// Start the query of object `i`; everything drawn until `end` counts.
+  pub fn begin(&mut self, i: usize) {
+    self.pending[i] = true;
+    unsafe { gl::BeginQuery(gl::ANY_SAMPLES_PASSED, self.queries[i]) };
+  }
+
+  pub fn end(&self) {
+    unsafe { gl::EndQuery(gl::ANY_SAMPLES_PASSED) };
+  }
+
+  /// Whether object `i` was visible when its query was issued, if the result is available; this
+  /// doesn’t wait for the GPU.
+  pub fn poll(&mut self, i: usize) -> Option<bool> {
+    if !self.pending[i] {
+      return None;
+    }
+
+    let query = self.queries[i];
+    let mut available = 0;
+    unsafe { gl::GetQueryObjectuiv(query, gl::QUERY_RESULT_AVAILABLE, &mut available) };
+
+    if available == 0 {
+      return None;
+    }
+
+    let mut samples_passed = 0;
+    unsafe { gl::GetQueryObjectuiv(query, gl::QUERY_RESULT, &mut samples_passed) };
+    self.pending[i] = false;
+
+    Some(samples_passed != 0)
+  }
+}
+
+impl Drop for OcclusionQueries {
+  fn drop(&mut self) {
+    unsafe { gl::DeleteQueries(self.queries.len() as i32, self.queries.as_ptr()) };
+  }
+}
+
+/// Time spent by the GPU on frames.
+///
+/// Even and odd frames use their own timer query in turn, and the time of a frame is read once the
+/// next one has been submitted, by when it has most likely been rendered.
+#[derive(Debug)]
+pub struct GpuTimer {
+  queries: [GLuint; 2],
+  frames: usize,
+}
+
+impl GpuTimer {
+  pub fn new() -> Self {
+    let mut queries = [0; 2];
+    unsafe { gl::GenQueries(2, queries.as_mut_ptr()) };
+
+    GpuTimer { queries, frames: 0 }
+  }
+
+  /// Start timing a frame; call before submitting anything.
+  pub fn begin_frame(&mut self) {
+    let query = self.queries[self.frames % 2];
+    unsafe { gl::BeginQuery(gl::TIME_ELAPSED, query) };
+  }
+
+  /// Stop timing a frame, returning the GPU time of the previous one, in milliseconds, if there was
+  /// one.
+  pub fn end_frame(&mut self) -> Option<f32> {
+    unsafe { gl::EndQuery(gl::TIME_ELAPSED) };
+    self.frames += 1;
+
+    if self.frames < 2 {
+      return None;
+    }
+
+    let mut ns = 0;
+    let query = self.queries[self.frames % 2];
+    unsafe { gl::GetQueryObjectui64v(query, gl::QUERY_RESULT, &mut ns) };
+
+    Some(ns as f32 * 1e-6)
+  }
+}
+
+impl Drop for GpuTimer {
+  fn drop(&mut self) {
+    unsafe { gl::DeleteQueries(2, self.queries.as_ptr()) };
+  }
+}
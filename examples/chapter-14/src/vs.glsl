@@ -0,0 +1,14 @@
+in vec3 position;
+in vec3 normal;
+
+out vec3 v_normal;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform mat4 model;
+
+void main() {
+  // towers are scaled unevenly, so normals go through the inverse transpose
+  v_normal = transpose(inverse(mat3(model))) * normal;
+  gl_Position = projection * view * model * vec4(position, 1.);
+}
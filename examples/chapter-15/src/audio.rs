@@ -0,0 +1,105 @@
+//! Audio capture.
+//!
+//! cpal calls back on an audio thread of its own whenever the input device has new samples. The
+//! callback only keeps the latest ones in a buffer shared with the render loop, which copies them
+//! once per frame: neither side ever waits for the other for longer than a copy.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{
+  BuildStreamError, Device, InputCallbackInfo, Sample, SampleFormat, Stream, StreamConfig,
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Samples captured from the default input device, mixed down to mono.
+pub struct Capture {
+  samples: Arc<Mutex<VecDeque<f32>>>,
+  sample_rate: u32,
+  // samples stop coming when the stream is dropped
+  _stream: Stream,
+}
+
+impl Capture {
+  /// Start capturing, keeping the latest `len` samples.
+  pub fn start(len: usize) -> Result<Self, String> {
+    let device = cpal::default_host()
+      .default_input_device()
+      .ok_or("no audio input device")?;
+    let name = device
+      .name()
+      .unwrap_or_else(|_| "unnamed device".to_owned());
+    let config = device
+      .default_input_config()
+      .map_err(|e| format!("cannot configure {}: {}", name, e))?;
+    let sample_rate = config.sample_rate().0;
+    let channels = config.channels() as usize;
+    let format = config.sample_format();
+    let samples = Arc::new(Mutex::new(VecDeque::from(vec![0.; len])));
+
+    let stream = match format {
+      SampleFormat::I16 => build_stream::<i16>(&device, &config.into(), channels, samples.clone()),
+      SampleFormat::U16 => build_stream::<u16>(&device, &config.into(), channels, samples.clone()),
+      SampleFormat::F32 => build_stream::<f32>(&device, &config.into(), channels, samples.clone()),
+    }
+    .map_err(|e| format!("cannot capture from {}: {}", name, e))?;
+
+    stream
+      .play()
+      .map_err(|e| format!("cannot capture from {}: {}", name, e))?;
+
+    println!(
+      "capturing from {} ({} Hz, {} channels)",
+      name, sample_rate, channels
+    );
+
+    Ok(Capture {
+      samples,
+      sample_rate,
+      _stream: stream,
+    })
+  }
+
+  pub fn sample_rate(&self) -> u32 {
+    self.sample_rate
+  }
+
+  /// Copy the latest samples into `out`, oldest first.
+  pub fn latest(&self, out: &mut [f32]) {
+    let samples = self.samples.lock().unwrap();
+    let skip = samples.len().saturating_sub(out.len());
+
+    for (o, s) in out.iter_mut().zip(samples.iter().skip(skip)) {
+      *o = *s;
+    }
+  }
+}
+
+fn build_stream<T>(
+  device: &Device,
+  config: &StreamConfig,
+  channels: usize,
+  samples: Arc<Mutex<VecDeque<f32>>>,
+) -> Result<Stream, BuildStreamError>
+where
+  T: Sample,
+{
+  let len = samples.lock().unwrap().len();
+
+  device.build_input_stream(
+    config,
+    move |data: &[T], _: &InputCallbackInfo| {
+      let mut samples = samples.lock().unwrap();
+
+      for frame in data.chunks(channels) {
+        let mono = frame.iter().map(Sample::to_f32).sum::<f32>() / frame.len() as f32;
+
+        if samples.len() == len {
+          samples.pop_front();
+        }
+
+        samples.push_back(mono);
+      }
+    },
+    |e| eprintln!("audio capture error: {}", e),
+  )
+}
@@ -0,0 +1,125 @@
+//! Spectrum analysis.
+//!
+//! The spectrum of the captured samples is computed with an iterative radix-2 fast Fourier
+//! transform, then summed up in a few frequency bands, from bass to treble, that drive the
+//! visuals. The transform is small enough to write by hand and fast enough for a window of a few
+//! thousand samples per frame.
+
+use std::f32::consts::PI;
+
+/// Number of frequency bands.
+pub const BAND_COUNT: usize = 4;
+
+/// Lower and upper frequencies of the bands, in Hz; log-spaced, as pitch is heard.
+const BAND_EDGES: [f32; BAND_COUNT + 1] = [20., 150., 600., 2500., 10000.];
+
+/// Level mapped to 0, in dB relative to a full-scale sine; louder levels map linearly up to 1.
+const FLOOR_DB: f32 = -60.;
+
+/// Speed at which band levels fall back, per second; they rise at once.
+const RELEASE: f32 = 1.5;
+
+/// Magnitude of each frequency bin of `samples`, whose length must be a power of two.
+///
+/// Bin `k` is centered on `k * sample_rate / samples.len()` Hz; only the bins up to half the sample
+/// rate are returned, the others mirror them.
+pub fn spectrum(samples: &[f32]) -> Vec<f32> {
+  let n = samples.len();
+  assert!(n.is_power_of_two(), "FFT size must be a power of two");
+
+  // a Hann window fades the ends of the window out, so that the cut doesn’t leak across the
+  // spectrum
+  let mut re = samples
+    .iter()
+    .enumerate()
+    .map(|(i, s)| s * 0.5 * (1. - (2. * PI * i as f32 / n as f32).cos()))
+    .collect::<Vec<_>>();
+  let mut im = vec![0.; n];
+
+  // bit-reversal permutation, so that the butterflies below work in place
+  let mut j = 0;
+  for i in 1..n {
+    let mut bit = n >> 1;
+
+    while j & bit != 0 {
+      j ^= bit;
+      bit >>= 1;
+    }
+
+    j ^= bit;
+
+    if i < j {
+      re.swap(i, j);
+      im.swap(i, j);
+    }
+  }
+
+  // butterflies, merging transforms of size len / 2 into transforms of size len
+  let mut len = 2;
+  while len <= n {
+    let angle = -2. * PI / len as f32;
+    let (w_re, w_im) = (angle.cos(), angle.sin());
+
+    for start in (0..n).step_by(len) {
+      let (mut c_re, mut c_im) = (1., 0.);
+
+      for k in 0..len / 2 {
+        let (a, b) = (start + k, start + k + len / 2);
+        let t_re = re[b] * c_re - im[b] * c_im;
+        let t_im = re[b] * c_im + im[b] * c_re;
+
+        re[b] = re[a] - t_re;
+        im[b] = im[a] - t_im;
+        re[a] += t_re;
+        im[a] += t_im;
+
+        let next_re = c_re * w_re - c_im * w_im;
+        c_im = c_re * w_im + c_im * w_re;
+        c_re = next_re;
+      }
+    }
+
+    len *= 2;
+  }
+
+  re.iter()
+    .zip(&im)
+    .take(n / 2)
+    .map(|(re, im)| (re * re + im * im).sqrt())
+    .collect()
+}
+
+/// Levels of the frequency bands, between 0 and 1.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bands {
+  levels: [f32; BAND_COUNT],
+}
+
+impl Bands {
+  /// Follow the loudest bin of each band in `spectrum`, `dt` seconds after the last update.
+  pub fn update(&mut self, spectrum: &[f32], sample_rate: u32, dt: f32) {
+    let n = spectrum.len() * 2;
+    let bin = |freq: f32| ((freq * n as f32 / sample_rate as f32) as usize).min(spectrum.len());
+    // a full-scale sine peaks at n / 4 through the Hann window
+    let full_scale = n as f32 / 4.;
+
+    for (band, level) in self.levels.iter_mut().enumerate() {
+      let low = bin(BAND_EDGES[band]);
+      // bands narrower than a bin still get one
+      let high = bin(BAND_EDGES[band + 1]).max(low + 1).min(spectrum.len());
+      let peak = spectrum[low..high]
+        .iter()
+        .fold(0., |peak: f32, &m| peak.max(m));
+
+      let db = 20. * (peak / full_scale).max(1e-6).log10();
+      let target = ((db - FLOOR_DB) / -FLOOR_DB).clamp(0., 1.);
+
+      // beats stand out when levels jump up but fade out slowly
+      *level = target.max(*level - RELEASE * dt);
+    }
+  }
+
+  pub fn levels(&self) -> [f32; BAND_COUNT] {
+    self.levels
+  }
+}
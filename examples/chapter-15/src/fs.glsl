@@ -0,0 +1,22 @@
+in vec3 v_normal;
+in float v_displacement;
+
+out vec3 frag_color;
+
+uniform vec4 bands;
+
+const vec3 LIGHT_DIR = vec3(.3, 1., .6);
+const vec3 CALM_COLOR = vec3(.15, .3, .9);
+const vec3 BASS_COLOR = vec3(1., .25, .4);
+const vec3 PEAK_COLOR = vec3(1., .9, .5);
+
+void main() {
+  // the bass warms the whole sphere up, and the most displaced parts glow
+  vec3 color = mix(CALM_COLOR, BASS_COLOR, bands.x);
+  color = mix(color, PEAK_COLOR, clamp(v_displacement * 2., 0., 1.));
+
+  float kd = max(dot(normalize(v_normal), normalize(LIGHT_DIR)), 0.);
+
+  // treble adds a flat sheen on top
+  frag_color = color * (.25 + .75 * kd) + .2 * bands.w;
+}
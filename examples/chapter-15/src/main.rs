@@ -0,0 +1,210 @@
+mod audio;
+mod fft;
+
+use crate::audio::Capture;
+use crate::fft::Bands;
+use cgmath::{perspective, Matrix4, Point3, Rad, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_front::tess::Mode;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::f32::consts::PI;
+use std::process::exit;
+use std::time::Instant;
+
+const VS_STR: &str = include_str!("vs.glsl");
+const FS_STR: &str = include_str!("fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 10.;
+
+/// Number of samples the spectrum is computed over; at 48 kHz, that’s the last ~21 ms, with bins
+/// ~47 Hz wide.
+const FFT_SIZE: usize = 1024;
+
+/// Rings and segments of the sphere; displacement needs many vertices to look smooth.
+const RINGS: u32 = 128;
+const SEGMENTS: u32 = 256;
+
+#[derive(Debug, UniformInterface)]
+struct ShaderInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  model: Uniform<[[f32; 4]; 4]>,
+  /// Levels of the frequency bands, from bass to treble.
+  #[uniform(unbound)]
+  bands: Uniform<[f32; 4]>,
+  #[uniform(unbound)]
+  time: Uniform<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  position: VertexPosition,
+}
+
+pub type VertexIndex = u32;
+
+fn main() {
+  // without sound, there’s nothing to show
+  let capture = match Capture::start(FFT_SIZE) {
+    Ok(capture) => capture,
+
+    Err(e) => {
+      eprintln!("cannot capture audio: {}", e);
+      exit(1);
+    }
+  };
+
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface, capture);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface, capture: Capture) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  let (vertices, indices) = sphere(RINGS, SEGMENTS);
+  let sphere = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(vertices)
+    .set_indices(indices)
+    .build()
+    .unwrap();
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let [width, height] = back_buffer.size();
+  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+  let view = Matrix4::look_at(
+    Point3::new(0., 0.5, 3.5),
+    Point3::new(0., 0., 0.),
+    Vector3::unit_y(),
+  );
+
+  let mut samples = vec![0.; FFT_SIZE];
+  let mut bands = Bands::default();
+  let mut last_frame = Instant::now();
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+        _ => (),
+      }
+    }
+
+    let now = Instant::now();
+    let dt = now.duration_since(last_frame).as_secs_f32();
+    last_frame = now;
+
+    capture.latest(&mut samples);
+    bands.update(&fft::spectrum(&samples), capture.sample_rate(), dt);
+
+    let t = start_t.elapsed().as_secs_f32();
+    let model = Matrix4::from_angle_y(Rad(t * 0.3)) * Matrix4::from_angle_x(Rad(0.3));
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color([0.02, 0.02, 0.05, 1.]),
+        |_, mut shd_gate| {
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.model, model.into());
+            iface.set(&uni.bands, bands.levels());
+            iface.set(&uni.time, t);
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&sphere)
+            })
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+/// Unit sphere, centered on the origin; its positions are its normals as well.
+fn sphere(rings: u32, segments: u32) -> (Vec<Vertex>, Vec<VertexIndex>) {
+  let mut vertices = Vec::new();
+
+  // the seam and the poles have duplicated vertices, which keeps the indexing regular
+  for i in 0..=rings {
+    let theta = PI * i as f32 / rings as f32;
+
+    for j in 0..=segments {
+      let phi = 2. * PI * j as f32 / segments as f32;
+
+      vertices.push(Vertex {
+        position: VertexPosition::new([
+          theta.sin() * phi.cos(),
+          theta.cos(),
+          theta.sin() * phi.sin(),
+        ]),
+      });
+    }
+  }
+
+  let row = segments + 1;
+  let mut indices = Vec::new();
+
+  for i in 0..rings {
+    for j in 0..segments {
+      let a = i * row + j;
+      let b = a + row;
+      indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+    }
+  }
+
+  (vertices, indices)
+}
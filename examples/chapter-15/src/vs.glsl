@@ -0,0 +1,29 @@
+in vec3 position;
+
+out vec3 v_normal;
+out float v_displacement;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform mat4 model;
+uniform vec4 bands;
+uniform float time;
+
+// level of the bands along the height of the sphere: bass at the bottom, treble at the top
+float band_level(float y) {
+  float x = clamp((y + 1.) * 1.5, 0., 3.);
+  int i = int(min(floor(x), 2.));
+  return mix(bands[i], bands[i + 1], x - float(i));
+}
+
+void main() {
+  // the sphere is a unit one, so positions are normals too
+  vec3 normal = normalize(position);
+  float ripple = .5 + .5 * sin(8. * atan(normal.z, normal.x) + 4. * time);
+  float displacement = band_level(normal.y) * (.15 + .35 * ripple);
+
+  // the normal of the undisplaced sphere is kept; shading gets a bit off where ripples are steep
+  v_normal = mat3(model) * normal;
+  v_displacement = displacement;
+  gl_Position = projection * view * model * vec4(normal * (1. + displacement), 1.);
+}
@@ -0,0 +1,264 @@
+//! The point cloud, cut in square chunks on a horizontal grid.
+//!
+//! A LiDAR survey holds far more points than fit in video memory, and most of them are too far from
+//! the camera to matter anyway. Points are grouped in columns of a fixed footprint, keyed by their
+//! cell on the grid, so that the points around a place can be fetched without going through the
+//! whole cloud.
+//!
+//! Points come either from an XYZ file, kept in system memory, or from a synthetic survey generated
+//! chunk by chunk on demand, far too big to be held at once.
+
+use crate::{Vertex, VertexColor, VertexPosition};
+use cgmath::Point3;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Side of the footprint of a chunk.
+pub const CHUNK_SIZE: f32 = 32.;
+
+/// Number of chunks on each side of the synthetic survey; at 32 points per square unit, that’s
+/// about 8.6 billion points.
+const SYNTHETIC_EXTENT: i32 = 512;
+/// Number of points of a chunk of the synthetic survey.
+const SYNTHETIC_POINTS: usize = 32 * 1024;
+
+/// Cell of a chunk on the grid.
+pub type ChunkKey = [i32; 2];
+
+/// Cell of the chunk a point lies in.
+pub fn chunk_key(x: f32, z: f32) -> ChunkKey {
+  [
+    (x / CHUNK_SIZE).floor() as i32,
+    (z / CHUNK_SIZE).floor() as i32,
+  ]
+}
+
+/// Center of the footprint of a chunk.
+pub fn chunk_center(key: ChunkKey) -> [f32; 2] {
+  [
+    (key[0] as f32 + 0.5) * CHUNK_SIZE,
+    (key[1] as f32 + 0.5) * CHUNK_SIZE,
+  ]
+}
+
+pub enum PointCloud {
+  /// Points read from a file, by chunk.
+  File(HashMap<ChunkKey, Vec<Vertex>>),
+  /// Terrain survey generated on demand, centered on the origin.
+  Synthetic,
+}
+
+impl PointCloud {
+  /// Load an XYZ file: one point per line, `x y z` with Z up as surveys have it, optionally
+  /// followed by an `r g b` color between 0 and 255.
+  ///
+  /// Survey coordinates are often hundreds of kilometers away from their origin, where floats
+  /// can’t tell centimeters apart anymore; the cloud is moved so that its lowest corner is at the
+  /// origin.
+  pub fn load<P>(path: P) -> Result<Self, String>
+  where
+    P: AsRef<Path>,
+  {
+    let content = fs::read_to_string(path).map_err(|e| format!("cannot read: {}", e))?;
+    let mut points = Vec::new();
+
+    for (line_nb, line) in content.lines().enumerate() {
+      let line = line.trim();
+
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+
+      let values = line
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("line {}: {}", line_nb + 1, e))?;
+
+      match *values.as_slice() {
+        [x, y, z] => points.push(([x, y, z], None)),
+        [x, y, z, r, g, b, ..] => points.push(([x, y, z], Some([r, g, b]))),
+        _ => return Err(format!("line {}: expecting x y z [r g b]", line_nb + 1)),
+      }
+    }
+
+    if points.is_empty() {
+      return Err("no point in the file".to_owned());
+    }
+
+    let min = points.iter().fold([f64::INFINITY; 3], |min, (p, _)| {
+      [min[0].min(p[0]), min[1].min(p[1]), min[2].min(p[2])]
+    });
+    let max_height = points
+      .iter()
+      .fold(-f64::INFINITY, |max, (p, _)| max.max(p[2]));
+    let mut chunks = HashMap::<ChunkKey, Vec<Vertex>>::new();
+
+    for (p, color) in points {
+      // Z up to Y up
+      let position = [
+        (p[0] - min[0]) as f32,
+        (p[2] - min[2]) as f32,
+        -(p[1] - min[1]) as f32,
+      ];
+      let color = match color {
+        Some([r, g, b]) => [r as f32 / 255., g as f32 / 255., b as f32 / 255.],
+        None => height_color(position[1] / (max_height - min[2]).max(1.) as f32),
+      };
+
+      chunks
+        .entry(chunk_key(position[0], position[2]))
+        .or_default()
+        .push(Vertex::new(
+          VertexPosition::new(position),
+          VertexColor::new(color),
+        ));
+    }
+
+    Ok(PointCloud::File(chunks))
+  }
+
+  /// Number of points of a chunk; 0 for chunks outside the cloud.
+  pub fn chunk_len(&self, key: ChunkKey) -> usize {
+    match self {
+      PointCloud::File(chunks) => chunks.get(&key).map_or(0, Vec::len),
+      PointCloud::Synthetic if in_synthetic_extent(key) => SYNTHETIC_POINTS,
+      PointCloud::Synthetic => 0,
+    }
+  }
+
+  /// Points of a chunk, read or generated.
+  pub fn chunk(&self, key: ChunkKey) -> Vec<Vertex> {
+    match self {
+      PointCloud::File(chunks) => chunks.get(&key).cloned().unwrap_or_default(),
+      PointCloud::Synthetic if in_synthetic_extent(key) => synthetic_chunk(key),
+      PointCloud::Synthetic => Vec::new(),
+    }
+  }
+
+  /// Total number of points.
+  pub fn point_count(&self) -> u64 {
+    match self {
+      PointCloud::File(chunks) => chunks.values().map(|chunk| chunk.len() as u64).sum(),
+      PointCloud::Synthetic => (SYNTHETIC_EXTENT as u64).pow(2) * SYNTHETIC_POINTS as u64,
+    }
+  }
+
+  /// Place to start exploring from, above the middle of the cloud.
+  pub fn start(&self) -> Point3<f32> {
+    match self {
+      PointCloud::File(chunks) => {
+        let (sum, count) = chunks
+          .values()
+          .flatten()
+          .fold(([0.; 3], 0.), |(sum, count), v| {
+            let p = *v.position;
+            ([sum[0] + p[0], sum[1] + p[1], sum[2] + p[2]], count + 1.)
+          });
+
+        Point3::new(sum[0] / count, sum[1] / count + 20., sum[2] / count)
+      }
+
+      PointCloud::Synthetic => Point3::new(0., synthetic_height(0., 0.) + 20., 0.),
+    }
+  }
+}
+
+fn in_synthetic_extent(key: ChunkKey) -> bool {
+  let half = SYNTHETIC_EXTENT / 2;
+  (-half..half).contains(&key[0]) && (-half..half).contains(&key[1])
+}
+
+/// Points of a chunk of the synthetic survey, scattered over the terrain like laser returns.
+///
+/// They only depend on the key of the chunk, so a chunk dropped and fetched again comes back the
+/// same.
+fn synthetic_chunk(key: ChunkKey) -> Vec<Vertex> {
+  let mut rng = hash(key[0], key[1], 0x5eed);
+  let mut random = move || {
+    // xorshift
+    rng ^= rng << 13;
+    rng ^= rng >> 17;
+    rng ^= rng << 5;
+    rng as f32 / u32::MAX as f32
+  };
+  let x0 = key[0] as f32 * CHUNK_SIZE;
+  let z0 = key[1] as f32 * CHUNK_SIZE;
+
+  (0..SYNTHETIC_POINTS)
+    .map(|_| {
+      let x = x0 + random() * CHUNK_SIZE;
+      let z = z0 + random() * CHUNK_SIZE;
+      let y = synthetic_height(x, z);
+      let shade = 0.85 + 0.3 * random();
+      let [r, g, b] = height_color((y + 60.) / 120.);
+
+      Vertex::new(
+        VertexPosition::new([x, y, z]),
+        VertexColor::new([r * shade, g * shade, b * shade]),
+      )
+    })
+    .collect()
+}
+
+/// Height of the synthetic terrain: a few octaves of value noise.
+fn synthetic_height(x: f32, z: f32) -> f32 {
+  let mut height = 0.;
+  let mut amplitude = 60.;
+  let mut frequency = 1. / 400.;
+
+  for octave in 0..6 {
+    height += amplitude * (2. * value_noise(x * frequency, z * frequency, octave) - 1.);
+    amplitude *= 0.45;
+    frequency *= 2.;
+  }
+
+  height
+}
+
+/// Smooth noise between 0 and 1, interpolating random values at integer coordinates.
+fn value_noise(x: f32, z: f32, seed: u32) -> f32 {
+  let (x0, z0) = (x.floor(), z.floor());
+  let smooth = |t: f32| t * t * (3. - 2. * t);
+  let (tx, tz) = (smooth(x - x0), smooth(z - z0));
+  let (i, j) = (x0 as i32, z0 as i32);
+  let corner = |di, dj| hash(i + di, j + dj, seed) as f32 / u32::MAX as f32;
+
+  let near = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * tx;
+  let far = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * tx;
+
+  near + (far - near) * tz
+}
+
+/// Well-mixed bits out of integer coordinates; never 0, so that it can seed a xorshift.
+fn hash(i: i32, j: i32, seed: u32) -> u32 {
+  let mut h = (i as u32).wrapping_mul(0x8da6_b343)
+    ^ (j as u32).wrapping_mul(0xd816_3841)
+    ^ seed.wrapping_mul(0xcb1a_b31f);
+
+  h ^= h >> 13;
+  h = h.wrapping_mul(0x5bd1_e995);
+  h ^= h >> 15;
+
+  h.max(1)
+}
+
+/// Color of the ground at a relative height between 0 and 1: grass, rock, then snow.
+fn height_color(t: f32) -> [f32; 3] {
+  let t = t.clamp(0., 1.);
+  let mix = |a: [f32; 3], b: [f32; 3], t: f32| {
+    [
+      a[0] + (b[0] - a[0]) * t,
+      a[1] + (b[1] - a[1]) * t,
+      a[2] + (b[2] - a[2]) * t,
+    ]
+  };
+
+  if t < 0.6 {
+    mix([0.2, 0.35, 0.12], [0.45, 0.4, 0.3], t / 0.6)
+  } else {
+    mix([0.45, 0.4, 0.3], [0.95, 0.95, 0.97], (t - 0.6) / 0.4)
+  }
+}
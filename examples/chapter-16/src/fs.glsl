@@ -0,0 +1,14 @@
+in vec3 v_color;
+in float v_distance;
+
+out vec3 frag_color;
+
+uniform vec3 fog_color;
+uniform float view_distance;
+
+void main() {
+  // points fade into the fog before reaching the view distance, so chunks coming in and out of
+  // video memory don’t pop
+  float fog = smoothstep(.5 * view_distance, view_distance, v_distance);
+  frag_color = mix(v_color, fog_color, fog);
+}
@@ -0,0 +1,253 @@
+mod cloud;
+mod stream;
+
+use crate::cloud::PointCloud;
+use crate::stream::{Streamer, VIEW_DISTANCE};
+use cgmath::{perspective, Matrix4, Rad, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::collections::HashSet;
+use std::env;
+use std::f32::consts::FRAC_PI_2;
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+const VS_STR: &str = include_str!("vs.glsl");
+const FS_STR: &str = include_str!("fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.5;
+const Z_FAR: f32 = VIEW_DISTANCE * 1.5;
+
+const FOG_COLOR: [f32; 3] = [0.6, 0.68, 0.75];
+
+/// Points kept in video memory at first; + and - double and halve it.
+const INITIAL_BUDGET: usize = 4 * 1024 * 1024;
+const MIN_BUDGET: usize = 64 * 1024;
+const MAX_BUDGET: usize = 64 * 1024 * 1024;
+
+/// Speed of the camera, in units per second, and how much faster it goes with shift held.
+const SPEED: f32 = 40.;
+const BOOST: f32 = 8.;
+/// Turning speed, in radians per second.
+const TURN_SPEED: f32 = 1.2;
+
+/// Period the window title is refreshed at.
+const TITLE_PERIOD: Duration = Duration::from_millis(250);
+
+#[derive(Debug, UniformInterface)]
+struct ShaderInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  fog_color: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  view_distance: Uniform<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "color", repr = "[f32; 3]", wrapper = "VertexColor")]
+  Color,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  position: VertexPosition,
+  color: VertexColor,
+}
+
+fn main() {
+  // without a file, a synthetic survey far bigger than any budget is explored
+  let cloud = match env::args().nth(1) {
+    Some(path) => {
+      println!("loading {}", path);
+
+      match PointCloud::load(&path) {
+        Ok(cloud) => cloud,
+
+        Err(e) => {
+          eprintln!("cannot load {}: {}", path, e);
+          exit(1);
+        }
+      }
+    }
+
+    None => PointCloud::Synthetic,
+  };
+  println!("{} points", cloud.point_count());
+
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface, cloud);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface, cloud: PointCloud) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+
+  println!("WASD to move, arrows to look around, Q/E to go down/up, shift to go faster");
+  println!("+/- to double/halve the point budget");
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let [width, height] = back_buffer.size();
+  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+
+  // the sky is the fog, far points fade into it
+  let clear_color = [FOG_COLOR[0], FOG_COLOR[1], FOG_COLOR[2], 1.];
+
+  let mut streamer = Streamer::new(INITIAL_BUDGET);
+  let mut eye = cloud.start();
+  // angle around the vertical axis, 0 looking towards -Z, and above the horizon
+  let mut yaw: f32 = 0.;
+  let mut pitch: f32 = -0.3;
+  let mut held = HashSet::new();
+
+  let mut last_frame = Instant::now();
+  let mut last_title = Instant::now();
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(Key::Equal, _, Action::Press, _)
+        | WindowEvent::Key(Key::KpAdd, _, Action::Press, _) => {
+          streamer.set_budget((streamer.budget() * 2).min(MAX_BUDGET));
+          println!("budget: {} points", streamer.budget());
+        }
+
+        WindowEvent::Key(Key::Minus, _, Action::Press, _)
+        | WindowEvent::Key(Key::KpSubtract, _, Action::Press, _) => {
+          streamer.set_budget((streamer.budget() / 2).max(MIN_BUDGET));
+          println!("budget: {} points", streamer.budget());
+        }
+
+        WindowEvent::Key(key, _, Action::Press, _) => {
+          held.insert(key);
+        }
+
+        WindowEvent::Key(key, _, Action::Release, _) => {
+          held.remove(&key);
+        }
+
+        // keys released while the window is in the background are never reported
+        WindowEvent::Focus(false) => held.clear(),
+
+        _ => (),
+      }
+    }
+
+    let now = Instant::now();
+    let dt = now.duration_since(last_frame).as_secs_f32();
+    last_frame = now;
+
+    // -1, 0 or 1 depending on which of two keys is held
+    let axis = |negative: Key, positive: Key| {
+      held.contains(&positive) as i32 as f32 - held.contains(&negative) as i32 as f32
+    };
+    let boost = if held.contains(&Key::LeftShift) {
+      BOOST
+    } else {
+      1.
+    };
+
+    yaw += axis(Key::Left, Key::Right) * TURN_SPEED * dt;
+    pitch = (pitch + axis(Key::Down, Key::Up) * TURN_SPEED * dt)
+      .clamp(-FRAC_PI_2 + 0.01, FRAC_PI_2 - 0.01);
+
+    let (sin_yaw, cos_yaw) = yaw.sin_cos();
+    let (sin_pitch, cos_pitch) = pitch.sin_cos();
+    let forward = Vector3::new(cos_pitch * sin_yaw, sin_pitch, -cos_pitch * cos_yaw);
+    let right = Vector3::new(cos_yaw, 0., sin_yaw);
+    let step = SPEED * boost * dt;
+
+    eye += forward * axis(Key::S, Key::W) * step
+      + right * axis(Key::A, Key::D) * step
+      + Vector3::unit_y() * axis(Key::Q, Key::E) * step;
+
+    if let Err(e) = streamer.update(&mut ctxt, &cloud, eye) {
+      eprintln!("cannot upload points: {}", e);
+      break 'app;
+    }
+
+    let stats = streamer.stats();
+    let view = Matrix4::look_at_dir(eye, forward, Vector3::unit_y());
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color(clear_color),
+        |_, mut shd_gate| {
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.fog_color, FOG_COLOR);
+            iface.set(&uni.view_distance, stats.reach);
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              streamer
+                .chunks()
+                .try_for_each(|chunk| tess_gate.render(chunk))
+            })
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+
+    if now.duration_since(last_title) >= TITLE_PERIOD {
+      ctxt.window.set_title(&format!(
+        "{}/{} points in {} chunks ({} pending), {} uploaded, {} dropped, {:.0} units of reach",
+        stats.points,
+        streamer.budget(),
+        stats.chunks,
+        stats.pending,
+        stats.uploaded,
+        stats.dropped,
+        stats.reach,
+      ));
+      last_title = now;
+    }
+  }
+}
@@ -0,0 +1,151 @@
+//! Streaming of chunks in and out of video memory.
+//!
+//! Every frame, the chunks around the camera are ranked by distance and the closest ones are kept
+//! until their points add up to the budget; the others are dropped, freeing their vertex buffers.
+//! Missing chunks are uploaded closest first, only a few per frame: a camera flying fast would
+//! otherwise stall a frame on uploads every time it crosses a row of chunks.
+
+use crate::cloud::{chunk_center, chunk_key, ChunkKey, PointCloud, CHUNK_SIZE};
+use crate::Vertex;
+use cgmath::Point3;
+use luminance_front::context::GraphicsContext;
+use luminance_front::tess::{Interleaved, Mode, Tess, TessError};
+use luminance_front::Backend;
+use std::collections::HashMap;
+
+/// Distance beyond which chunks are never considered, whatever the budget.
+pub const VIEW_DISTANCE: f32 = 1000.;
+
+/// Maximum number of chunks uploaded per frame.
+const UPLOADS_PER_FRAME: usize = 2;
+
+/// Counters of the streamer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StreamStats {
+  /// Chunks and points in video memory.
+  pub chunks: usize,
+  pub points: usize,
+  /// Chunks wanted but not uploaded yet.
+  pub pending: usize,
+  /// Chunks uploaded and dropped since the start.
+  pub uploaded: usize,
+  pub dropped: usize,
+  /// Distance up to which the budget covers the cloud.
+  pub reach: f32,
+}
+
+pub struct Streamer {
+  /// Chunks in video memory, with their number of points.
+  resident: HashMap<ChunkKey, (Tess<Vertex, (), (), Interleaved>, usize)>,
+  /// Maximum number of points in video memory.
+  budget: usize,
+  stats: StreamStats,
+}
+
+impl Streamer {
+  pub fn new(budget: usize) -> Self {
+    Streamer {
+      resident: HashMap::new(),
+      budget,
+      stats: StreamStats::default(),
+    }
+  }
+
+  pub fn budget(&self) -> usize {
+    self.budget
+  }
+
+  pub fn set_budget(&mut self, budget: usize) {
+    self.budget = budget;
+  }
+
+  pub fn stats(&self) -> StreamStats {
+    self.stats
+  }
+
+  /// Upload and drop chunks for a camera at `eye`.
+  pub fn update<C>(
+    &mut self,
+    ctxt: &mut C,
+    cloud: &PointCloud,
+    eye: Point3<f32>,
+  ) -> Result<(), TessError>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    let (wanted, reach) = self.wanted(cloud, eye);
+
+    let before = self.resident.len();
+    self.resident.retain(|key, _| wanted.contains(key));
+    self.stats.dropped += before - self.resident.len();
+
+    // wanted is sorted closest first
+    let missing = wanted
+      .iter()
+      .filter(|key| !self.resident.contains_key(key))
+      .copied()
+      .collect::<Vec<_>>();
+
+    for &key in missing.iter().take(UPLOADS_PER_FRAME) {
+      let points = cloud.chunk(key);
+      let len = points.len();
+      let tess = ctxt
+        .new_tess()
+        .set_mode(Mode::Point)
+        .set_vertices(points)
+        .build()?;
+
+      self.resident.insert(key, (tess, len));
+      self.stats.uploaded += 1;
+    }
+
+    self.stats.chunks = self.resident.len();
+    self.stats.points = self.resident.values().map(|(_, len)| len).sum();
+    self.stats.pending = missing.len().saturating_sub(UPLOADS_PER_FRAME);
+    self.stats.reach = reach;
+
+    Ok(())
+  }
+
+  /// Chunks in video memory.
+  pub fn chunks(&self) -> impl Iterator<Item = &Tess<Vertex, (), (), Interleaved>> {
+    self.resident.values().map(|(tess, _)| tess)
+  }
+
+  /// Non-empty chunks within the view distance that fit in the budget, closest first, and the
+  /// distance of the first chunk left out.
+  fn wanted(&self, cloud: &PointCloud, eye: Point3<f32>) -> (Vec<ChunkKey>, f32) {
+    let [ci, cj] = chunk_key(eye.x, eye.z);
+    let radius = (VIEW_DISTANCE / CHUNK_SIZE).ceil() as i32;
+    let mut candidates = Vec::new();
+
+    for j in cj - radius..=cj + radius {
+      for i in ci - radius..=ci + radius {
+        let [x, z] = chunk_center([i, j]);
+        let distance = ((x - eye.x).powi(2) + (z - eye.z).powi(2)).sqrt();
+
+        if distance <= VIEW_DISTANCE && cloud.chunk_len([i, j]) > 0 {
+          candidates.push((distance, [i, j]));
+        }
+      }
+    }
+
+    candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let mut points = 0;
+    let mut wanted = Vec::new();
+
+    for (distance, key) in candidates {
+      points += cloud.chunk_len(key);
+
+      if points > self.budget {
+        // chunks are as far as their center, so their closest points are half a chunk nearer
+        return (wanted, (distance - CHUNK_SIZE * 0.5).max(0.));
+      }
+
+      wanted.push(key);
+    }
+
+    (wanted, VIEW_DISTANCE)
+  }
+}
@@ -0,0 +1,16 @@
+in vec3 position;
+in vec3 color;
+
+out vec3 v_color;
+out float v_distance;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+void main() {
+  vec4 eye_position = view * vec4(position, 1.);
+
+  v_color = color;
+  v_distance = length(eye_position.xyz);
+  gl_Position = projection * eye_position;
+}
@@ -0,0 +1,46 @@
+in vec3 v_position;
+
+out vec3 frag_color;
+
+uniform sampler2D reflection;
+uniform vec3 camera_pos;
+uniform vec3 floor_center;
+uniform float floor_radius;
+uniform float roughness;
+uniform vec3 background;
+
+// taps of the blur, spread evenly on a disk (Vogel spiral)
+const int TAPS = 16;
+const float GOLDEN_ANGLE = 2.39996;
+
+vec3 blurred_reflection(vec2 uv) {
+  // a rougher floor scatters the reflected light over a wider area
+  vec2 size = vec2(textureSize(reflection, 0));
+  vec2 radius = roughness * .04 * vec2(size.y / size.x, 1.);
+  vec3 sum = vec3(0.);
+
+  for (int i = 0; i < TAPS; ++i) {
+    float r = sqrt((float(i) + .5) / float(TAPS));
+    float a = float(i) * GOLDEN_ANGLE;
+    sum += texture(reflection, uv + radius * r * vec2(cos(a), sin(a))).rgb;
+  }
+
+  return sum / float(TAPS);
+}
+
+void main() {
+  vec2 uv = gl_FragCoord.xy / vec2(textureSize(reflection, 0));
+  vec3 reflected = blurred_reflection(uv);
+
+  // Schlick’s Fresnel: the floor reflects more at grazing angles, and less the rougher it is
+  vec3 to_camera = normalize(camera_pos - v_position);
+  float fresnel = .04 + .96 * pow(1. - max(to_camera.y, 0.), 5.);
+  float reflectivity = mix(.3, 1., fresnel) * (1. - .5 * roughness);
+
+  // the floor is lit like a studio backdrop, and fades into the background away from the model
+  float d = length(v_position.xz - floor_center.xz) / floor_radius;
+  float spot = 1. - smoothstep(.4, 1., d);
+  vec3 base = vec3(.08, .08, .09);
+
+  frag_color = mix(background, mix(base, reflected, reflectivity), spot);
+}
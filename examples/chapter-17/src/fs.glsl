@@ -0,0 +1,16 @@
+in vec3 v_normal;
+
+out vec3 frag_color;
+
+uniform vec3 color;
+
+const vec3 LIGHT_DIR = vec3(-.4, -1., -.6);
+const vec3 AMBIENT = vec3(.15);
+
+void main() {
+  // a reflection swaps front and back faces, so the normal is taken as it is whatever the side
+  vec3 n = normalize(v_normal);
+  float kd = max(dot(n, -normalize(LIGHT_DIR)), 0.);
+
+  frag_color = color * (AMBIENT + kd);
+}
@@ -0,0 +1,358 @@
+mod mirror;
+mod shapes;
+
+use crate::mirror::{oblique_projection, Mirror};
+use cgmath::{perspective, Matrix4, Point3, Rad, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance::pipeline::TextureBinding;
+use luminance::pixel::Floating;
+use luminance::texture::Dim2;
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::{PipelineError, PipelineState};
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::{Program, Uniform};
+use luminance_front::shading_gate::ShadingGate;
+use luminance_front::tess::{Interleaved, Mode, Tess};
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::process::exit;
+use std::time::Instant;
+
+const VS_STR: &str = include_str!("vs.glsl");
+const FS_STR: &str = include_str!("fs.glsl");
+const MIRROR_VS_STR: &str = include_str!("mirror_vs.glsl");
+const MIRROR_FS_STR: &str = include_str!("mirror_fs.glsl");
+const FLOOR_VS_STR: &str = include_str!("floor_vs.glsl");
+const FLOOR_FS_STR: &str = include_str!("floor_fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 50.;
+
+const BACKGROUND: [f32; 3] = [0.05, 0.05, 0.06];
+
+/// The camera swings from side to side in front of the mirror, at this distance from the scene.
+const CAMERA_DISTANCE: f32 = 7.;
+const CAMERA_HEIGHT: f32 = 2.5;
+
+/// The wall mirror stands behind the scene, facing the camera.
+const MIRROR_CENTER: [f32; 3] = [0., 1.5, -2.5];
+const MIRROR_SIZE: [f32; 2] = [6., 3.];
+
+/// Half the side of the floor; its edges fade into the background.
+const FLOOR_RADIUS: f32 = 6.;
+/// Roughness of the floor when the example starts; 0 makes it a perfect mirror.
+const FLOOR_ROUGHNESS: f32 = 0.3;
+
+const TORUS_COLOR: [f32; 3] = [0.9, 0.45, 0.2];
+const SPHERE_COLOR: [f32; 3] = [0.25, 0.55, 0.9];
+
+#[derive(Debug, UniformInterface)]
+struct ShaderInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  model: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  color: Uniform<[f32; 3]>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct MirrorInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  reflection: Uniform<TextureBinding<Dim2, Floating>>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct FloorInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  reflection: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  floor_center: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  floor_radius: Uniform<f32>,
+  #[uniform(unbound)]
+  roughness: Uniform<f32>,
+  #[uniform(unbound)]
+  background: Uniform<[f32; 3]>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  position: VertexPosition,
+  normal: VertexNormal,
+}
+
+pub type VertexIndex = u32;
+
+/// A mesh of the scene, placed in the world by its model matrix.
+type Object<'a> = (
+  &'a Tess<Vertex, VertexIndex, (), Interleaved>,
+  Matrix4<f32>,
+  [f32; 3],
+);
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  println!("-/= to make the floor smoother/rougher");
+
+  let (vertices, indices) = shapes::torus(0.8, 0.3, 64, 32);
+  let torus = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(vertices)
+    .set_indices(indices)
+    .build()
+    .unwrap();
+
+  let (vertices, indices) = shapes::sphere(0.35, 32, 64);
+  let sphere = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(vertices)
+    .set_indices(indices)
+    .build()
+    .unwrap();
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut mirror_program = ctxt
+    .new_shader_program::<VertexSemantics, (), MirrorInterface>()
+    .from_strings(MIRROR_VS_STR, None, None, MIRROR_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut floor_program = ctxt
+    .new_shader_program::<VertexSemantics, (), FloorInterface>()
+    .from_strings(FLOOR_VS_STR, None, None, FLOOR_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let size = back_buffer.size();
+  let projection = perspective(FOVY, size[0] as f32 / size[1] as f32, Z_NEAR, Z_FAR);
+
+  // both mirrors render their reflection at the size of the screen
+  let mut mirror = Mirror::wall(
+    &mut ctxt,
+    Point3::from(MIRROR_CENTER),
+    Vector3::unit_z(),
+    MIRROR_SIZE,
+    size,
+  )
+  .unwrap_or_else(|e| {
+    eprintln!("{}", e);
+    exit(1);
+  });
+
+  let floor_center = Point3::new(0., 0., 0.);
+  let mut floor = Mirror::floor(
+    &mut ctxt,
+    floor_center,
+    [FLOOR_RADIUS * 2., FLOOR_RADIUS * 2.],
+    size,
+  )
+  .unwrap_or_else(|e| {
+    eprintln!("{}", e);
+    exit(1);
+  });
+
+  let mut roughness = FLOOR_ROUGHNESS;
+  let clear_color = [BACKGROUND[0], BACKGROUND[1], BACKGROUND[2], 1.];
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(Key::Minus, _, Action::Press, _) => {
+          roughness = (roughness - 0.1).max(0.);
+          println!("floor roughness: {:.1}", roughness);
+        }
+
+        WindowEvent::Key(Key::Equal, _, Action::Press, _) => {
+          roughness = (roughness + 0.1).min(1.);
+          println!("floor roughness: {:.1}", roughness);
+        }
+
+        _ => (),
+      }
+    }
+
+    let t = start_t.elapsed().as_secs_f32();
+
+    // the camera never goes behind the mirror, whose reflection would then be its back
+    let swing = 0.6 * (t * 0.25).sin();
+    let eye = Point3::new(
+      CAMERA_DISTANCE * swing.sin(),
+      CAMERA_HEIGHT,
+      CAMERA_DISTANCE * swing.cos(),
+    );
+    let view = Matrix4::look_at(eye, Point3::new(0., 1., 0.), Vector3::unit_y());
+
+    // the torus spins above the floor while the sphere bounces around it
+    let torus_model = Matrix4::from_translation(Vector3::new(0., 1.4, 0.))
+      * Matrix4::from_angle_y(Rad(t * 0.5))
+      * Matrix4::from_angle_x(Rad(1.1));
+    let sphere_model = Matrix4::from_translation(Vector3::new(
+      1.8 * (t * 0.8).cos(),
+      0.35 + 0.8 * (t * 2.4).sin().abs(),
+      1.8 * (t * 0.8).sin(),
+    ));
+    let objects: [Object; 2] = [
+      (&torus, torus_model, TORUS_COLOR),
+      (&sphere, sphere_model, SPHERE_COLOR),
+    ];
+
+    // render the scene from the camera reflected through each mirror’s plane first; the oblique
+    // projection clips what’s behind the mirror, which the camera couldn’t see through it
+    for reflector in &[&mirror, &floor] {
+      let reflected_view = reflector.reflected_view(view);
+      let reflected_projection = oblique_projection(projection, reflected_view, &reflector.plane);
+
+      let render = ctxt
+        .new_pipeline_gate()
+        .pipeline(
+          &reflector.framebuffer,
+          &PipelineState::default().set_clear_color(clear_color),
+          |_, mut shd_gate| {
+            render_objects(
+              &mut shd_gate,
+              &mut program,
+              reflected_projection,
+              reflected_view,
+              &objects,
+            )
+          },
+        )
+        .assume();
+
+      if render.is_err() {
+        break 'app;
+      }
+    }
+
+    // then the scene as the camera sees it, with the mirrors showing what they reflect
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color(clear_color),
+        |pipeline, mut shd_gate| {
+          render_objects(&mut shd_gate, &mut program, projection, view, &objects)?;
+
+          let reflection = pipeline.bind_texture(mirror.framebuffer.color_slot())?;
+
+          shd_gate.shade(&mut mirror_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.reflection, reflection.binding());
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&mirror.quad)
+            })
+          })?;
+
+          let reflection = pipeline.bind_texture(floor.framebuffer.color_slot())?;
+
+          shd_gate.shade(&mut floor_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.reflection, reflection.binding());
+            iface.set(&uni.camera_pos, eye.into());
+            iface.set(&uni.floor_center, floor_center.into());
+            iface.set(&uni.floor_radius, FLOOR_RADIUS);
+            iface.set(&uni.roughness, roughness);
+            iface.set(&uni.background, BACKGROUND);
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&floor.quad)
+            })
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+/// Render the objects of the scene; the mirrors don’t show each other, which would take a
+/// reflection of a reflection.
+fn render_objects(
+  shd_gate: &mut ShadingGate,
+  program: &mut Program<VertexSemantics, (), ShaderInterface>,
+  projection: Matrix4<f32>,
+  view: Matrix4<f32>,
+  objects: &[Object],
+) -> Result<(), PipelineError> {
+  shd_gate.shade(program, |mut iface, uni, mut rdr_gate| {
+    iface.set(&uni.projection, projection.into());
+    iface.set(&uni.view, view.into());
+
+    objects.iter().try_for_each(|&(tess, model, color)| {
+      iface.set(&uni.model, model.into());
+      iface.set(&uni.color, color);
+
+      rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+        tess_gate.render(tess)
+      })
+    })
+  })
+}
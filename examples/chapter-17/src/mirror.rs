@@ -0,0 +1,190 @@
+//! Planar mirrors.
+//!
+//! A mirror shows the scene as seen by the camera reflected through its plane. That camera stands
+//! behind the mirror, so whatever lies between it and the plane would end up in the reflection
+//! although the viewer can’t see it. Instead of discarding those fragments in every shader, the
+//! near plane of the reflected camera is replaced by the mirror’s plane (oblique near-plane
+//! clipping, as described by Eric Lengyel): the geometry behind the mirror is clipped by the GPU,
+//! at the cost of some depth precision.
+
+use crate::{Vertex, VertexNormal, VertexPosition};
+use cgmath::{InnerSpace, Matrix, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+use luminance::pixel::{Depth32F, RGB32F};
+use luminance::texture::{Dim2, MagFilter, MinFilter, Sampler};
+use luminance_front::context::GraphicsContext;
+use luminance_front::framebuffer::Framebuffer;
+use luminance_front::tess::{Interleaved, Mode, Tess};
+use luminance_front::Backend;
+
+/// Plane made of the points p such that normal · p + d = 0.
+#[derive(Clone, Copy, Debug)]
+pub struct Plane {
+  pub normal: Vector3<f32>,
+  pub d: f32,
+}
+
+impl Plane {
+  /// Plane going through a point; the normal must be normalized.
+  pub fn new(normal: Vector3<f32>, point: Point3<f32>) -> Self {
+    let d = -normal.dot(Vector3::new(point.x, point.y, point.z));
+    Plane { normal, d }
+  }
+
+  /// Matrix reflecting points through the plane.
+  pub fn reflection(&self) -> Matrix4<f32> {
+    let Vector3 { x, y, z } = self.normal;
+    let d = self.d;
+
+    Matrix4::new(
+      1. - 2. * x * x,
+      -2. * x * y,
+      -2. * x * z,
+      0.,
+      -2. * x * y,
+      1. - 2. * y * y,
+      -2. * y * z,
+      0.,
+      -2. * x * z,
+      -2. * y * z,
+      1. - 2. * z * z,
+      0.,
+      -2. * d * x,
+      -2. * d * y,
+      -2. * d * z,
+      1.,
+    )
+  }
+
+  /// The plane in the space of a view matrix.
+  fn in_view(&self, view: Matrix4<f32>) -> Vector4<f32> {
+    // planes transform with the inverse transpose of the matrix transforming points
+    let inverse = view.invert().expect("invertible view matrix");
+    inverse.transpose() * self.normal.extend(self.d)
+  }
+}
+
+/// Perspective projection whose near plane is replaced by a plane, given in world space.
+///
+/// The camera, at the origin of the view space, must be on the negative side of the plane; what’s on
+/// that side is clipped away. The far plane stays where it was, though tilted.
+pub fn oblique_projection(
+  projection: Matrix4<f32>,
+  view: Matrix4<f32>,
+  plane: &Plane,
+) -> Matrix4<f32> {
+  let c = plane.in_view(view);
+  let mut m = projection;
+
+  // corner of the view frustum opposite to the plane, in view space; scaling the plane so that this
+  // corner lands on the far plane keeps the depth range of the projection
+  let q = Vector4::new(
+    (c.x.signum() + m.z.x) / m.x.x,
+    (c.y.signum() + m.z.y) / m.y.y,
+    -1.,
+    (1. + m.z.z) / m.w.z,
+  );
+  let c = c * (2. / c.dot(q));
+
+  // the third row becomes the plane, minus the fourth row
+  m.x.z = c.x;
+  m.y.z = c.y;
+  m.z.z = c.z + 1.;
+  m.w.z = c.w;
+
+  m
+}
+
+/// A rectangular mirror and the framebuffer the reflected scene is rendered into.
+pub struct Mirror {
+  pub plane: Plane,
+  pub quad: Tess<Vertex, (), (), Interleaved>,
+  pub framebuffer: Framebuffer<Dim2, RGB32F, Depth32F>,
+}
+
+impl Mirror {
+  /// Vertical mirror of a given width and height, centered on a point and facing a direction.
+  ///
+  /// The framebuffer has the size of the screen, so that the mirror can look its reflection up with
+  /// its screen position.
+  pub fn wall<C>(
+    ctxt: &mut C,
+    center: Point3<f32>,
+    facing: Vector3<f32>,
+    [width, height]: [f32; 2],
+    size: [u32; 2],
+  ) -> Result<Self, String>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    let normal = Vector3::new(facing.x, 0., facing.z).normalize();
+    let right = Vector3::unit_y().cross(normal) * (width * 0.5);
+    let up = Vector3::unit_y() * (height * 0.5);
+
+    Self::new(ctxt, center, normal, right, up, size)
+  }
+
+  /// Horizontal mirror of a given width and depth, centered on a point and facing up.
+  pub fn floor<C>(
+    ctxt: &mut C,
+    center: Point3<f32>,
+    [width, depth]: [f32; 2],
+    size: [u32; 2],
+  ) -> Result<Self, String>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    let right = Vector3::unit_x() * (width * 0.5);
+    let forward = -Vector3::unit_z() * (depth * 0.5);
+
+    Self::new(ctxt, center, Vector3::unit_y(), right, forward, size)
+  }
+
+  /// Mirror spanning center ± right ± up.
+  fn new<C>(
+    ctxt: &mut C,
+    center: Point3<f32>,
+    normal: Vector3<f32>,
+    right: Vector3<f32>,
+    up: Vector3<f32>,
+    size: [u32; 2],
+  ) -> Result<Self, String>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    let corners = [-right - up, right - up, right + up, -right + up];
+    let vertices = corners
+      .iter()
+      .map(|&corner| Vertex {
+        position: VertexPosition::new((center + corner).into()),
+        normal: VertexNormal::new(normal.into()),
+      })
+      .collect::<Vec<_>>();
+
+    let quad = ctxt
+      .new_tess()
+      .set_mode(Mode::TriangleFan)
+      .set_vertices(vertices)
+      .build()
+      .map_err(|e| format!("cannot create the mirror quad: {}", e))?;
+
+    let sampler = Sampler {
+      min_filter: MinFilter::Linear,
+      mag_filter: MagFilter::Linear,
+      ..Sampler::default()
+    };
+    let framebuffer = ctxt
+      .new_framebuffer(size, 0, sampler)
+      .map_err(|e| format!("cannot create the mirror framebuffer: {}", e))?;
+
+    Ok(Mirror {
+      plane: Plane::new(normal, center),
+      quad,
+      framebuffer,
+    })
+  }
+
+  /// View matrix of the camera reflected through the mirror.
+  pub fn reflected_view(&self, view: Matrix4<f32>) -> Matrix4<f32> {
+    view * self.plane.reflection()
+  }
+}
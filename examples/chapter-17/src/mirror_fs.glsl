@@ -0,0 +1,12 @@
+out vec3 frag_color;
+
+uniform sampler2D reflection;
+
+void main() {
+  // the reflection was rendered from the mirrored camera at the size of the screen, so the
+  // fragment’s screen position is where its reflection is
+  vec2 uv = gl_FragCoord.xy / vec2(textureSize(reflection, 0));
+
+  // a real mirror absorbs a bit of the light, with a slight green tint
+  frag_color = texture(reflection, uv).rgb * vec3(.85, .9, .88);
+}
@@ -0,0 +1,8 @@
+in vec3 position;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+void main() {
+  gl_Position = projection * view * vec4(position, 1.);
+}
@@ -0,0 +1,73 @@
+//! Procedural meshes.
+
+use crate::{Vertex, VertexIndex, VertexNormal, VertexPosition};
+use std::f32::consts::PI;
+
+/// UV sphere centered on the origin, made of rings of segments.
+pub fn sphere(radius: f32, rings: u32, segments: u32) -> (Vec<Vertex>, Vec<VertexIndex>) {
+  grid(rings, segments, |u, v| {
+    let theta = PI * u;
+    let phi = 2. * PI * v;
+    let normal = [
+      theta.sin() * phi.cos(),
+      theta.cos(),
+      theta.sin() * phi.sin(),
+    ];
+
+    (
+      [radius * normal[0], radius * normal[1], radius * normal[2]],
+      normal,
+    )
+  })
+}
+
+/// Torus centered on the origin, around the Y axis; `major` is the radius of the ring and `minor`
+/// the one of its tube.
+pub fn torus(major: f32, minor: f32, rings: u32, segments: u32) -> (Vec<Vertex>, Vec<VertexIndex>) {
+  grid(rings, segments, |u, v| {
+    let (sin_ring, cos_ring) = (2. * PI * u).sin_cos();
+    let (sin_tube, cos_tube) = (2. * PI * v).sin_cos();
+    let normal = [cos_tube * cos_ring, sin_tube, cos_tube * sin_ring];
+    let distance = major + minor * cos_tube;
+
+    (
+      [distance * cos_ring, minor * sin_tube, distance * sin_ring],
+      normal,
+    )
+  })
+}
+
+/// Mesh of a parametric surface, sampled on a grid of rows and columns between 0 and 1.
+///
+/// The last row and column duplicate the first ones, which keeps the indexing regular.
+fn grid<F>(rows: u32, columns: u32, surface: F) -> (Vec<Vertex>, Vec<VertexIndex>)
+where
+  F: Fn(f32, f32) -> ([f32; 3], [f32; 3]),
+{
+  let mut vertices = Vec::new();
+
+  for i in 0..=rows {
+    for j in 0..=columns {
+      let (position, normal) = surface(i as f32 / rows as f32, j as f32 / columns as f32);
+
+      vertices.push(Vertex {
+        position: VertexPosition::new(position),
+        normal: VertexNormal::new(normal),
+      });
+    }
+  }
+
+  let row = columns + 1;
+  let mut indices = Vec::new();
+
+  for i in 0..rows {
+    for j in 0..columns {
+      let a = i * row + j;
+      let b = a + row;
+
+      indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+    }
+  }
+
+  (vertices, indices)
+}
@@ -0,0 +1,14 @@
+in vec3 position;
+in vec3 normal;
+
+out vec3 v_normal;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform mat4 model;
+
+void main() {
+  // objects are only rotated and moved, which the upper part of their model matrix does to normals
+  v_normal = mat3(model) * normal;
+  gl_Position = projection * view * model * vec4(position, 1.);
+}
@@ -0,0 +1,13 @@
+in vec3 v_normal;
+
+out vec4 frag_color;
+
+const vec3 LIGHT_DIR = vec3(0., -1., -.5);
+// the skin is as dark as the roots of the strands, so that it doesn’t show between them
+const vec3 SKIN_COLOR = vec3(.55, .4, .25) * .3;
+
+void main() {
+  float kd = max(dot(normalize(v_normal), -normalize(LIGHT_DIR)), 0.) * .8 + .2;
+
+  frag_color = vec4(SKIN_COLOR * kd, 1.);
+}
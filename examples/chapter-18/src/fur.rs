@@ -0,0 +1,71 @@
+//! Shell fur.
+//!
+//! Fur is rendered as a stack of shells: the mesh is drawn several times, each copy pushed a bit
+//! further along the normals, and a noise texture tells which strands go through each shell. The
+//! shells are instances of the mesh, the vertex shader getting the shell index from gl_InstanceID,
+//! so that the whole fur costs a single draw call.
+//!
+//! Alpha-tested strands alias badly. With multisampling, alpha-to-coverage turns the alpha of a
+//! fragment into the fraction of its samples that get covered, which smooths the strands without
+//! sorting anything. luminance doesn’t expose it, so it’s toggled with raw GL calls.
+
+use luminance::pixel::R32F;
+use luminance::texture::{Dim2, GenMipmaps, MagFilter, MinFilter, Sampler, Wrap};
+use luminance_front::context::GraphicsContext;
+use luminance_front::texture::{Texture, TextureError};
+use luminance_front::Backend;
+
+/// Number of shells, the first one being right above the skin.
+pub const SHELLS: usize = 32;
+/// Length of the strands, relative to the radius of the ball.
+pub const LENGTH: f32 = 0.12;
+/// Number of strands across the radius of the ball.
+pub const STRANDS: f32 = 150.;
+/// Size of the strand noise texture, in texels; each texel is a strand.
+pub const NOISE_SIZE: u32 = 128;
+
+/// Texture giving the length of the strands, between 0 and 1.
+pub fn noise_texture<C>(ctxt: &mut C) -> Result<Texture<Dim2, R32F>, TextureError>
+where
+  C: GraphicsContext<Backend = Backend>,
+{
+  // strands must stay crisp, and tile over the whole ball
+  let sampler = Sampler {
+    wrap_s: Wrap::Repeat,
+    wrap_t: Wrap::Repeat,
+    min_filter: MinFilter::Nearest,
+    mag_filter: MagFilter::Nearest,
+    ..Sampler::default()
+  };
+  let texels = (0..NOISE_SIZE * NOISE_SIZE).map(random).collect::<Vec<_>>();
+
+  ctxt.new_texture_raw(
+    [NOISE_SIZE, NOISE_SIZE],
+    0,
+    sampler,
+    GenMipmaps::No,
+    &texels,
+  )
+}
+
+/// Turn alpha-to-coverage on or off; the GL functions must have been loaded.
+pub fn set_alpha_to_coverage(enabled: bool) {
+  unsafe {
+    if enabled {
+      gl::Enable(gl::SAMPLE_ALPHA_TO_COVERAGE);
+    } else {
+      gl::Disable(gl::SAMPLE_ALPHA_TO_COVERAGE);
+    }
+  }
+}
+
+/// Pseudo-random number in [0; 1) for a texel.
+fn random(n: u32) -> f32 {
+  // integer hash from Hugo Elias
+  let n = (n << 13) ^ n;
+  let n = n
+    .wrapping_mul(n.wrapping_mul(n).wrapping_mul(15731).wrapping_add(789221))
+    .wrapping_add(1376312589);
+
+  (n & 0x7fffffff) as f32 / 0x80000000u32 as f32
+}
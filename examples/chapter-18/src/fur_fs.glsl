@@ -0,0 +1,33 @@
+in vec3 v_position;
+in vec3 v_normal;
+in float v_shell;
+
+out vec4 frag_color;
+
+uniform sampler2D noise;
+uniform float strand_density;
+uniform float alpha_cutoff;
+
+void main() {
+  vec3 n = normalize(v_normal);
+
+  // the noise is projected along the main axis of the normal, which works without texture
+  // coordinates
+  vec3 a = abs(n);
+  vec2 uv = a.x > a.y && a.x > a.z ? v_position.yz : (a.y > a.z ? v_position.xz : v_position.xy);
+  float strand = texture(noise, uv * strand_density).r;
+
+  // a strand goes through a shell if it’s long enough, getting thinner towards its tip
+  float alpha = clamp((strand - v_shell) * 8., 0., 1.);
+
+  if (alpha <= alpha_cutoff) {
+    discard;
+  }
+
+  // the roots are shadowed by the strands around them
+  vec3 light_dir = vec3(0., -1., -.5);
+  float kd = max(dot(n, -light_dir), 0.) * .8 + .2;
+  vec3 color = vec3(.55, .4, .25) * kd * mix(.3, 1., v_shell);
+
+  frag_color = vec4(color, alpha);
+}
@@ -0,0 +1,24 @@
+in vec3 position;
+in vec3 normal;
+
+out vec3 v_position;
+out vec3 v_normal;
+out float v_shell;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform float fur_length;
+uniform int shells;
+
+void main() {
+  // each instance is a shell, from right above the skin to the tips of the strands
+  v_shell = float(gl_InstanceID + 1) / float(shells);
+  v_position = position;
+  v_normal = normal;
+
+  // strands droop a little under their own weight
+  vec3 offset = fur_length * v_shell * normalize(normal);
+  offset.y -= .3 * fur_length * v_shell * v_shell;
+
+  gl_Position = projection * view * vec4(position + offset, 1.);
+}
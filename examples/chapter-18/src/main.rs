@@ -0,0 +1,224 @@
+mod fur;
+mod shapes;
+
+use cgmath::{perspective, Matrix4, Point3, Rad, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance::pipeline::TextureBinding;
+use luminance::pixel::Floating;
+use luminance::texture::Dim2;
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_front::tess::{Mode, TessView};
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::ffi::c_void;
+use std::process::exit;
+use std::time::Instant;
+
+const VS_STR: &str = include_str!("vs.glsl");
+const FS_STR: &str = include_str!("fs.glsl");
+const FUR_VS_STR: &str = include_str!("fur_vs.glsl");
+const FUR_FS_STR: &str = include_str!("fur_fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 10.;
+
+/// Radius of the furry ball.
+const RADIUS: f32 = 1.;
+
+/// The camera circles the ball at this distance, this many radians per second.
+const CAMERA_DISTANCE: f32 = 3.5;
+const CAMERA_SPEED: f32 = 0.3;
+
+#[derive(Debug, UniformInterface)]
+struct ShaderInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct FurInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  fur_length: Uniform<f32>,
+  #[uniform(unbound)]
+  shells: Uniform<i32>,
+  #[uniform(unbound)]
+  noise: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  strand_density: Uniform<f32>,
+  #[uniform(unbound)]
+  alpha_cutoff: Uniform<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  position: VertexPosition,
+  normal: VertexNormal,
+}
+
+pub type VertexIndex = u32;
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  // alpha-to-coverage needs several samples per pixel
+  let opt = WindowOpt::default().set_dim(dim).set_num_samples(Some(4));
+  let surface = GlfwSurface::new_gl33("Hello, world!", opt);
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  // luminance doesn’t expose alpha-to-coverage, which is toggled with raw GL calls; the functions
+  // are loaded once, now that the context is current
+  let window = &mut ctxt.window;
+  gl::load_with(|name| window.get_proc_address(name) as *const c_void);
+
+  println!("C to toggle alpha-to-coverage");
+
+  let (vertices, indices) = shapes::sphere(RADIUS, 64, 128);
+  let ball = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(vertices)
+    .set_indices(indices)
+    .build()
+    .unwrap();
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut fur_program = ctxt
+    .new_shader_program::<VertexSemantics, (), FurInterface>()
+    .from_strings(FUR_VS_STR, None, None, FUR_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut noise = fur::noise_texture(&mut ctxt).unwrap_or_else(|e| {
+    eprintln!("cannot create the fur noise: {}", e);
+    exit(1);
+  });
+  let fur_length = RADIUS * fur::LENGTH;
+  let strand_density = fur::STRANDS / (RADIUS * fur::NOISE_SIZE as f32);
+  let mut alpha_to_coverage = true;
+
+  let [width, height] = back_buffer.size();
+  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(Key::C, _, Action::Press, _) => {
+          alpha_to_coverage = !alpha_to_coverage;
+          println!(
+            "alpha-to-coverage: {}",
+            if alpha_to_coverage { "on" } else { "off" }
+          );
+        }
+
+        _ => (),
+      }
+    }
+
+    let angle = start_t.elapsed().as_secs_f32() * CAMERA_SPEED;
+    let eye = Point3::new(
+      CAMERA_DISTANCE * angle.cos(),
+      1.,
+      CAMERA_DISTANCE * angle.sin(),
+    );
+    let view = Matrix4::look_at(eye, Point3::new(0., 0., 0.), Vector3::unit_y());
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color([0.1, 0.12, 0.15, 1.]),
+        |pipeline, mut shd_gate| {
+          // the skin first, which the shells then cover from the inside out
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&ball)
+            })
+          })?;
+
+          let noise = pipeline.bind_texture(&mut noise)?;
+
+          // without alpha-to-coverage, the strands are alpha-tested instead
+          let alpha_cutoff = if alpha_to_coverage { 0. } else { 0.5 };
+          fur::set_alpha_to_coverage(alpha_to_coverage);
+
+          let shells = shd_gate.shade(&mut fur_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.fur_length, fur_length);
+            iface.set(&uni.shells, fur::SHELLS as i32);
+            iface.set(&uni.noise, noise.binding());
+            iface.set(&uni.strand_density, strand_density);
+            iface.set(&uni.alpha_cutoff, alpha_cutoff);
+
+            // all the shells in a single draw call, as instances of the ball
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(TessView::inst_whole(&ball, fur::SHELLS))
+            })
+          });
+
+          fur::set_alpha_to_coverage(false);
+          shells
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
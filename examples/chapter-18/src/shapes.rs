@@ -0,0 +1,42 @@
+//! Procedural meshes.
+
+use crate::{Vertex, VertexIndex, VertexNormal, VertexPosition};
+use std::f32::consts::PI;
+
+/// UV sphere centered on the origin, made of rings of segments.
+pub fn sphere(radius: f32, rings: u32, segments: u32) -> (Vec<Vertex>, Vec<VertexIndex>) {
+  let mut vertices = Vec::new();
+
+  // the seam and the poles have duplicated vertices, which keeps the indexing regular
+  for i in 0..=rings {
+    let theta = PI * i as f32 / rings as f32;
+
+    for j in 0..=segments {
+      let phi = 2. * PI * j as f32 / segments as f32;
+      let normal = [
+        theta.sin() * phi.cos(),
+        theta.cos(),
+        theta.sin() * phi.sin(),
+      ];
+
+      vertices.push(Vertex {
+        position: VertexPosition::new([radius * normal[0], radius * normal[1], radius * normal[2]]),
+        normal: VertexNormal::new(normal),
+      });
+    }
+  }
+
+  let row = segments + 1;
+  let mut indices = Vec::new();
+
+  for i in 0..rings {
+    for j in 0..segments {
+      let a = i * row + j;
+      let b = a + row;
+
+      indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+    }
+  }
+
+  (vertices, indices)
+}
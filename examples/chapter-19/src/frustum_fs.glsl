@@ -0,0 +1,5 @@
+out vec3 frag_color;
+
+void main() {
+  frag_color = vec3(1., .8, .2);
+}
@@ -0,0 +1,280 @@
+mod projector;
+mod shapes;
+
+use crate::projector::{Projector, Slide};
+use crate::shapes::Mesh;
+use cgmath::{perspective, Deg, Matrix4, Point3, Rad, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance::pipeline::TextureBinding;
+use luminance::pixel::{Floating, NormRGB8UI};
+use luminance::texture::{Dim2, GenMipmaps, MagFilter, MinFilter, Sampler, Wrap};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_front::tess::{Interleaved, Mode, Tess};
+use luminance_front::texture::Texture;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::env;
+use std::process::exit;
+use std::time::Instant;
+
+const PROJECTOR_VS_STR: &str = include_str!("projector_vs.glsl");
+const PROJECTOR_FS_STR: &str = include_str!("projector_fs.glsl");
+const FRUSTUM_FS_STR: &str = include_str!("frustum_fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 50.;
+
+/// The projector circles the scene at this distance and height, this many radians per second.
+const PROJECTOR_DISTANCE: f32 = 4.;
+const PROJECTOR_HEIGHT: f32 = 3.;
+const PROJECTOR_SPEED: f32 = 0.3;
+
+/// Edges of a frustum, as pairs of the corners returned by Projector::frustum_corners.
+const FRUSTUM_EDGES: [VertexIndex; 24] = [
+  0, 1, 1, 2, 2, 3, 3, 0, 4, 5, 5, 6, 6, 7, 7, 4, 0, 4, 1, 5, 2, 6, 3, 7,
+];
+
+#[derive(Debug, UniformInterface)]
+struct ProjectorInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  projector: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  projector_pos: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  slide: Uniform<TextureBinding<Dim2, Floating>>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct FrustumInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  position: VertexPosition,
+  normal: VertexNormal,
+}
+
+pub type VertexIndex = u32;
+
+fn main() {
+  // without a slide, a test card is projected
+  let slide = match env::args().nth(1) {
+    Some(path) => Slide::load(&path).unwrap_or_else(|e| {
+      eprintln!("{}", e);
+      exit(1);
+    }),
+
+    None => Slide::test_card(),
+  };
+
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface, slide);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface, slide: Slide) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  println!("up and down to change the field of view of the projector");
+
+  let mut scene = Mesh::default();
+  scene.quad(
+    Point3::new(0., 0., 0.),
+    Vector3::unit_x() * 6.,
+    -Vector3::unit_z() * 6.,
+  );
+  scene.cuboid(Point3::new(-1.5, 0., -1.), [0.5, 0.75, 0.5]);
+  scene.cuboid(Point3::new(1.3, 0., 1.), [0.6, 0.4, 0.6]);
+  scene.sphere(Point3::new(0.2, 0.7, 0.4), 0.7, 32, 64);
+
+  let scene = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(scene.vertices)
+    .set_indices(scene.indices)
+    .build()
+    .unwrap();
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), ProjectorInterface>()
+    .from_strings(PROJECTOR_VS_STR, None, None, PROJECTOR_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut frustum_program = ctxt
+    .new_shader_program::<VertexSemantics, (), FrustumInterface>()
+    .from_strings(PROJECTOR_VS_STR, None, None, FRUSTUM_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  // the border keeps the slide from smearing its edges over the whole scene
+  let [slide_width, slide_height] = slide.size;
+  let slide = slide.with_border();
+  let sampler = Sampler {
+    wrap_s: Wrap::ClampToEdge,
+    wrap_t: Wrap::ClampToEdge,
+    min_filter: MinFilter::Linear,
+    mag_filter: MagFilter::Linear,
+    ..Sampler::default()
+  };
+  let mut texture: Texture<Dim2, NormRGB8UI> = ctxt
+    .new_texture_raw(slide.size, 0, sampler, GenMipmaps::No, &slide.texels)
+    .unwrap_or_else(|e| {
+      eprintln!("cannot create the slide: {}", e);
+      exit(1);
+    });
+
+  let mut projector = Projector {
+    position: Point3::new(PROJECTOR_DISTANCE, PROJECTOR_HEIGHT, 0.),
+    target: Point3::new(0., 0.5, 0.),
+    fovy: Deg(30.),
+    aspect: slide_width as f32 / slide_height as f32,
+    z_near: 0.5,
+    z_far: 12.,
+  };
+
+  let mut frustum = ctxt
+    .new_tess()
+    .set_mode(Mode::Line)
+    .set_vertices(frustum_vertices(&projector))
+    .set_indices(FRUSTUM_EDGES.to_vec())
+    .build()
+    .unwrap();
+
+  let [width, height] = back_buffer.size();
+  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+  let view = Matrix4::look_at(
+    Point3::new(6., 5., 6.),
+    Point3::new(0., 0., 0.),
+    Vector3::unit_y(),
+  );
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(key @ Key::Up, _, Action::Press, _)
+        | WindowEvent::Key(key @ Key::Down, _, Action::Press, _) => {
+          let step = if key == Key::Up { 5. } else { -5. };
+          projector.fovy = Deg((projector.fovy.0 + step).max(5.).min(120.));
+          println!("projector field of view: {}°", projector.fovy.0);
+        }
+
+        _ => (),
+      }
+    }
+
+    // the projector orbits around the scene, aimed at its center; its frustum follows it
+    let angle = start_t.elapsed().as_secs_f32() * PROJECTOR_SPEED;
+    projector.position = Point3::new(
+      angle.cos() * PROJECTOR_DISTANCE,
+      PROJECTOR_HEIGHT,
+      angle.sin() * PROJECTOR_DISTANCE,
+    );
+    upload_vertices(&mut frustum, &frustum_vertices(&projector));
+
+    let projector_matrix: [[f32; 4]; 4] = projector.view_projection().into();
+    let projector_pos: [f32; 3] = projector.position.into();
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color([0.02, 0.02, 0.03, 1.]),
+        |pipeline, mut shd_gate| {
+          let slide = pipeline.bind_texture(&mut texture)?;
+
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.projector, projector_matrix);
+            iface.set(&uni.projector_pos, projector_pos);
+            iface.set(&uni.slide, slide.binding());
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&scene)
+            })
+          })?;
+
+          shd_gate.shade(&mut frustum_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&frustum)
+            })
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+/// Vertices at the corners of the frustum of a projector, to be drawn with FRUSTUM_EDGES.
+fn frustum_vertices(projector: &Projector) -> Vec<Vertex> {
+  projector
+    .frustum_corners()
+    .iter()
+    .map(|&corner| Vertex {
+      position: VertexPosition::new(corner.into()),
+      normal: VertexNormal::new([0., 1., 0.]),
+    })
+    .collect()
+}
+
+/// Update the vertices of a mesh in place; that only works if their number hasn’t changed.
+fn upload_vertices(mesh: &mut Tess<Vertex, VertexIndex, (), Interleaved>, vertices: &[Vertex]) {
+  match mesh.vertices_mut() {
+    Ok(mut mesh_vertices) => mesh_vertices.copy_from_slice(vertices),
+    Err(e) => eprintln!("cannot update vertices: {}", e),
+  }
+}
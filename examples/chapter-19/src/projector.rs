@@ -0,0 +1,189 @@
+//! Projective texturing.
+//!
+//! A projector works like a camera running backwards: its view projection matrix takes a point of
+//! the scene to the spot of the slide that lights it. Outside of the projector’s frustum, the slide
+//! must not repeat nor smear its edges, so it’s sampled with a black border around it. The same
+//! matrix, with a depth texture in place of the slide, gives shadow maps.
+
+use cgmath::{perspective, Deg, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+use std::fs;
+use std::path::Path;
+
+/// Image projected by a projector, as 8-bit RGB texels from the bottom row up.
+#[derive(Clone, Debug)]
+pub struct Slide {
+  pub size: [u32; 2],
+  pub texels: Vec<u8>,
+}
+
+impl Slide {
+  /// Load a binary PPM (P6) image.
+  pub fn load<P>(path: P) -> Result<Self, String>
+  where
+    P: AsRef<Path>,
+  {
+    let path = path.as_ref();
+    let content = fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    Self::parse_ppm(&content).map_err(|e| format!("cannot load {}: {}", path.display(), e))
+  }
+
+  fn parse_ppm(content: &[u8]) -> Result<Self, String> {
+    // the header is made of four tokens separated by whitespace, and may contain comments
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while tokens.len() < 4 {
+      match content.get(i) {
+        None => return Err("truncated header".to_owned()),
+        Some(b'#') => {
+          while i < content.len() && content[i] != b'\n' {
+            i += 1;
+          }
+        }
+        Some(c) if c.is_ascii_whitespace() => i += 1,
+        Some(_) => {
+          let start = i;
+          while i < content.len() && !content[i].is_ascii_whitespace() {
+            i += 1;
+          }
+          tokens.push(String::from_utf8_lossy(&content[start..i]).into_owned());
+        }
+      }
+    }
+
+    if tokens[0] != "P6" {
+      return Err("not a binary PPM image".to_owned());
+    }
+
+    let number = |token: &str| {
+      token
+        .parse::<u32>()
+        .map_err(|_| format!("invalid header value: {}", token))
+    };
+    let width = number(&tokens[1])?;
+    let height = number(&tokens[2])?;
+
+    if number(&tokens[3])? != 255 {
+      return Err("only 8-bit images are supported".to_owned());
+    }
+
+    // a single whitespace character separates the header from the pixels
+    let pixels = content.get(i + 1..).unwrap_or(&[]);
+    let row_len = width as usize * 3;
+
+    if pixels.len() < row_len * height as usize {
+      return Err("truncated pixel data".to_owned());
+    }
+
+    // PPM rows go from top to bottom, texture rows from bottom to top
+    let texels = pixels[..row_len * height as usize]
+      .chunks(row_len)
+      .rev()
+      .flatten()
+      .copied()
+      .collect();
+
+    Ok(Slide {
+      size: [width, height],
+      texels,
+    })
+  }
+
+  /// Test card used when no slide is given: a grid over colored quadrants, with a circle in the
+  /// middle.
+  pub fn test_card() -> Self {
+    let size = 256;
+    let mut texels = Vec::new();
+
+    for y in 0..size {
+      for x in 0..size {
+        let (u, v) = (x as f32 / size as f32 - 0.5, y as f32 / size as f32 - 0.5);
+        let grid = x % 32 < 2 || y % 32 < 2;
+        let circle = ((u * u + v * v).sqrt() - 0.3).abs() < 0.01;
+
+        let rgb = if grid || circle {
+          [255, 255, 255]
+        } else {
+          match (u < 0., v < 0.) {
+            (true, true) => [200, 40, 40],
+            (false, true) => [40, 200, 40],
+            (true, false) => [40, 40, 200],
+            (false, false) => [200, 200, 40],
+          }
+        };
+
+        texels.extend_from_slice(&rgb);
+      }
+    }
+
+    Slide {
+      size: [size, size],
+      texels,
+    }
+  }
+
+  /// Surround the slide with a black border, one texel wide.
+  ///
+  /// Sampled with clamping to the edge, the slide then behaves as if it was clamped to a black
+  /// border.
+  pub fn with_border(&self) -> Self {
+    let [width, height] = self.size;
+    let row_len = (width as usize + 2) * 3;
+    let mut texels = vec![0; row_len];
+
+    for row in self.texels.chunks(width as usize * 3) {
+      texels.extend_from_slice(&[0, 0, 0]);
+      texels.extend_from_slice(row);
+      texels.extend_from_slice(&[0, 0, 0]);
+    }
+
+    texels.resize(texels.len() + row_len, 0);
+
+    Slide {
+      size: [width + 2, height + 2],
+      texels,
+    }
+  }
+}
+
+/// Projector aimed at a point.
+#[derive(Clone, Copy, Debug)]
+pub struct Projector {
+  pub position: Point3<f32>,
+  pub target: Point3<f32>,
+  /// Vertical field of view.
+  pub fovy: Deg<f32>,
+  /// Width over height of the slide.
+  pub aspect: f32,
+  pub z_near: f32,
+  pub z_far: f32,
+}
+
+impl Projector {
+  pub fn view_projection(&self) -> Matrix4<f32> {
+    let projection = perspective(self.fovy, self.aspect, self.z_near, self.z_far);
+    let view = Matrix4::look_at(self.position, self.target, Vector3::unit_y());
+
+    projection * view
+  }
+
+  /// Corners of the frustum, near ones first, each plane going around counterclockwise.
+  pub fn frustum_corners(&self) -> [Point3<f32>; 8] {
+    let inverse = self
+      .view_projection()
+      .invert()
+      .expect("invertible projector matrix");
+    let mut corners = [Point3::new(0., 0., 0.); 8];
+
+    for (i, corner) in corners.iter_mut().enumerate() {
+      let x = if (i + 1) % 4 < 2 { -1. } else { 1. };
+      let y = if i % 4 < 2 { -1. } else { 1. };
+      let z = if i < 4 { -1. } else { 1. };
+      let p = inverse * Vector4::new(x, y, z, 1.);
+
+      *corner = Point3::new(p.x / p.w, p.y / p.w, p.z / p.w);
+    }
+
+    corners
+  }
+}
@@ -0,0 +1,35 @@
+in vec3 v_position;
+in vec3 v_normal;
+in vec4 v_slide_pos;
+
+out vec3 frag_color;
+
+uniform sampler2D slide;
+uniform vec3 projector_pos;
+
+// the scene is barely lit apart from the projector, so that the slide stands out
+const vec3 ALBEDO = vec3(.8);
+const vec3 AMBIENT = vec3(.08);
+
+void main() {
+  vec3 n = normalize(v_normal);
+  vec3 ambient = ALBEDO * AMBIENT * (.75 + .25 * n.y);
+
+  // behind the projector, the perspective division would flip the slide over
+  if (v_slide_pos.w <= 0.) {
+    frag_color = ambient;
+    return;
+  }
+
+  // the slide is surrounded by a black border, one texel wide, that the frustum doesn’t cover
+  vec2 size = vec2(textureSize(slide, 0));
+  vec2 uv = v_slide_pos.xy / v_slide_pos.w * .5 + .5;
+  uv = (uv * (size - 2.) + 1.) / size;
+
+  // the light spreads over the surfaces it reaches at grazing angles; nothing stops it, so
+  // surfaces hidden from the projector get the slide too, which a shadow map would fix
+  vec3 l = normalize(projector_pos - v_position);
+  float kd = max(dot(n, l), 0.);
+
+  frag_color = ambient + ALBEDO * texture(slide, uv).rgb * kd;
+}
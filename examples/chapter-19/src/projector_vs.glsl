@@ -0,0 +1,17 @@
+in vec3 position;
+in vec3 normal;
+
+out vec3 v_position;
+out vec3 v_normal;
+out vec4 v_slide_pos;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform mat4 projector;
+
+void main() {
+  v_position = position;
+  v_normal = normal;
+  v_slide_pos = projector * vec4(position, 1.);
+  gl_Position = projection * view * vec4(position, 1.);
+}
@@ -0,0 +1,85 @@
+//! Procedural meshes.
+
+use crate::{Vertex, VertexIndex, VertexNormal, VertexPosition};
+use cgmath::{InnerSpace, Point3, Vector3};
+use std::f32::consts::PI;
+
+/// Indexed triangle mesh being built.
+#[derive(Debug, Default)]
+pub struct Mesh {
+  pub vertices: Vec<Vertex>,
+  pub indices: Vec<VertexIndex>,
+}
+
+impl Mesh {
+  /// Flat quad spanning center ± right ± up, facing the side right × up points to.
+  pub fn quad(&mut self, center: Point3<f32>, right: Vector3<f32>, up: Vector3<f32>) {
+    let normal = right.cross(up).normalize();
+    let offset = self.vertices.len() as VertexIndex;
+
+    for &corner in &[-right - up, right - up, right + up, -right + up] {
+      self.vertices.push(Vertex {
+        position: VertexPosition::new((center + corner).into()),
+        normal: VertexNormal::new(normal.into()),
+      });
+    }
+
+    self
+      .indices
+      .extend([0, 1, 2, 0, 2, 3].iter().map(|i| offset + i));
+  }
+
+  /// Box of the given half size, resting on the plane y = 0.
+  pub fn cuboid(&mut self, center: Point3<f32>, [x, y, z]: [f32; 3]) {
+    let center = Point3::new(center.x, y, center.z);
+    let (dx, dy, dz) = (
+      Vector3::unit_x() * x,
+      Vector3::unit_y() * y,
+      Vector3::unit_z() * z,
+    );
+
+    self.quad(center + dx, -dz, dy);
+    self.quad(center - dx, dz, dy);
+    self.quad(center + dy, dx, -dz);
+    self.quad(center - dy, dx, dz);
+    self.quad(center + dz, dx, dy);
+    self.quad(center - dz, -dx, dy);
+  }
+
+  /// UV sphere made of rings of segments.
+  pub fn sphere(&mut self, center: Point3<f32>, radius: f32, rings: u32, segments: u32) {
+    let offset = self.vertices.len() as VertexIndex;
+
+    // the seam and the poles have duplicated vertices, which keeps the indexing regular
+    for i in 0..=rings {
+      let theta = PI * i as f32 / rings as f32;
+
+      for j in 0..=segments {
+        let phi = 2. * PI * j as f32 / segments as f32;
+        let normal = Vector3::new(
+          theta.sin() * phi.cos(),
+          theta.cos(),
+          theta.sin() * phi.sin(),
+        );
+
+        self.vertices.push(Vertex {
+          position: VertexPosition::new((center + normal * radius).into()),
+          normal: VertexNormal::new(normal.into()),
+        });
+      }
+    }
+
+    let row = segments + 1;
+
+    for i in 0..rings {
+      for j in 0..segments {
+        let a = offset + i * row + j;
+        let b = a + row;
+
+        self
+          .indices
+          .extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+      }
+    }
+  }
+}
@@ -0,0 +1,198 @@
+//! Topology checks on loaded meshes.
+//!
+//! Vertices are deduplicated by position, normal and texture coordinates when loading, so a hard
+//! edge splits a position in several vertices. Topology doesn’t care about attributes though, so
+//! the first step here is to weld vertices sharing the same position back together.
+
+use crate::obj::Obj;
+use crate::VertexIndex;
+use std::collections::{HashMap, VecDeque};
+
+/// Triangles with an area smaller than this are considered degenerate.
+const AREA_EPSILON: f32 = 1e-12;
+
+#[derive(Debug, Default)]
+pub struct MeshAnalysis {
+  /// Number of edges shared by more than two triangles.
+  pub non_manifold_edges: usize,
+  /// Triangles with (almost) zero area.
+  pub degenerate_triangles: Vec<usize>,
+  /// Triangles using the same three vertices as a previous triangle.
+  pub duplicate_triangles: Vec<usize>,
+  /// Number of connected patches whose winding disagrees with the rest of their surface.
+  pub flipped_islands: usize,
+  /// Triangles belonging to those patches.
+  pub flipped_triangles: Vec<usize>,
+}
+
+impl MeshAnalysis {
+  pub fn analyze(obj: &Obj) -> Self {
+    let positions: Vec<[f32; 3]> = obj.vertices.iter().map(|v| *v.position).collect();
+    let triangles = weld(&positions, &obj.indices);
+    let mut analysis = MeshAnalysis::default();
+
+    // degenerate and duplicate triangles
+    let mut seen = HashMap::new();
+    for (i, &[a, b, c]) in triangles.iter().enumerate() {
+      if a == b
+        || b == c
+        || c == a
+        || area(&positions, &obj.indices[i * 3..i * 3 + 3]) < AREA_EPSILON
+      {
+        analysis.degenerate_triangles.push(i);
+      }
+
+      let mut key = [a, b, c];
+      key.sort_unstable();
+
+      if seen.insert(key, i).is_some() {
+        analysis.duplicate_triangles.push(i);
+      }
+    }
+
+    // map each undirected edge to the triangles using it, along with the direction they use it in
+    let mut edges: HashMap<(usize, usize), Vec<(usize, bool)>> = HashMap::new();
+    for (i, &[a, b, c]) in triangles.iter().enumerate() {
+      for &(from, to) in &[(a, b), (b, c), (c, a)] {
+        if from != to {
+          let key = (from.min(to), from.max(to));
+          edges.entry(key).or_default().push((i, from < to));
+        }
+      }
+    }
+
+    analysis.non_manifold_edges = edges.values().filter(|faces| faces.len() > 2).count();
+
+    // flood-fill patches of consistently wound triangles; two triangles sharing a manifold edge are
+    // consistent if they walk that edge in opposite directions
+    let mut neighbors = vec![Vec::new(); triangles.len()];
+    for faces in edges.values() {
+      if let [(t0, dir0), (t1, dir1)] = faces[..] {
+        neighbors[t0].push((t1, dir0 != dir1));
+        neighbors[t1].push((t0, dir0 != dir1));
+      }
+    }
+
+    let mut patch = vec![usize::MAX; triangles.len()];
+    let mut patches: Vec<Vec<usize>> = Vec::new();
+
+    for seed in 0..triangles.len() {
+      if patch[seed] != usize::MAX {
+        continue;
+      }
+
+      let id = patches.len();
+      let mut members = Vec::new();
+      let mut queue = VecDeque::new();
+      patch[seed] = id;
+      queue.push_back(seed);
+
+      while let Some(t) = queue.pop_front() {
+        members.push(t);
+
+        for &(n, consistent) in &neighbors[t] {
+          if consistent && patch[n] == usize::MAX {
+            patch[n] = id;
+            queue.push_back(n);
+          }
+        }
+      }
+
+      patches.push(members);
+    }
+
+    // group patches into connected surfaces; in each surface, the largest patch is assumed to have
+    // the right winding and all the others are flipped islands
+    let mut surfaces: HashMap<usize, usize> = HashMap::new();
+    let mut surface_of_patch = vec![usize::MAX; patches.len()];
+
+    for start in 0..patches.len() {
+      if surface_of_patch[start] != usize::MAX {
+        continue;
+      }
+
+      let mut queue = VecDeque::new();
+      let mut largest = start;
+      surface_of_patch[start] = start;
+      queue.push_back(start);
+
+      while let Some(p) = queue.pop_front() {
+        if patches[p].len() > patches[largest].len() {
+          largest = p;
+        }
+
+        for &t in &patches[p] {
+          for &(n, _) in &neighbors[t] {
+            if surface_of_patch[patch[n]] == usize::MAX {
+              surface_of_patch[patch[n]] = start;
+              queue.push_back(patch[n]);
+            }
+          }
+        }
+      }
+
+      surfaces.insert(start, largest);
+    }
+
+    for (p, members) in patches.iter().enumerate() {
+      if surfaces[&surface_of_patch[p]] != p {
+        analysis.flipped_islands += 1;
+        analysis.flipped_triangles.extend_from_slice(members);
+      }
+    }
+
+    analysis.flipped_triangles.sort_unstable();
+    analysis
+  }
+
+  /// Triangles that should be highlighted in the viewer, without duplicates.
+  pub fn offending_triangles(&self) -> Vec<usize> {
+    let mut triangles = self.degenerate_triangles.clone();
+    triangles.extend_from_slice(&self.duplicate_triangles);
+    triangles.extend_from_slice(&self.flipped_triangles);
+    triangles.sort_unstable();
+    triangles.dedup();
+    triangles
+  }
+}
+
+/// Turn the index buffer into triangles of welded vertex IDs.
+fn weld(positions: &[[f32; 3]], indices: &[VertexIndex]) -> Vec<[usize; 3]> {
+  let mut ids: HashMap<[u32; 3], usize> = HashMap::new();
+  let welded: Vec<usize> = positions
+    .iter()
+    .map(|p| {
+      let key = [p[0].to_bits(), p[1].to_bits(), p[2].to_bits()];
+      let next = ids.len();
+      *ids.entry(key).or_insert(next)
+    })
+    .collect();
+
+  indices
+    .chunks_exact(3)
+    .map(|t| {
+      [
+        welded[t[0] as usize],
+        welded[t[1] as usize],
+        welded[t[2] as usize],
+      ]
+    })
+    .collect()
+}
+
+fn area(positions: &[[f32; 3]], triangle: &[VertexIndex]) -> f32 {
+  let [a, b, c] = [
+    positions[triangle[0] as usize],
+    positions[triangle[1] as usize],
+    positions[triangle[2] as usize],
+  ];
+  let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+  let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+  let cross = [
+    u[1] * v[2] - u[2] * v[1],
+    u[2] * v[0] - u[0] * v[2],
+    u[0] * v[1] - u[1] * v[0],
+  ];
+
+  0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt()
+}
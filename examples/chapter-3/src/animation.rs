@@ -0,0 +1,105 @@
+//! Keyframed animation.
+//!
+//! A track gives a value at a few key times, and the values in between are interpolated, either
+//! linearly or with a cubic curve going smoothly through the keys. Tracks loop: once past their
+//! last key, they start over from time 0.
+
+use cgmath::{Vector3, VectorSpace};
+use std::str::FromStr;
+
+/// How values are interpolated between keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Interpolation {
+  Linear,
+  /// Catmull-Rom spline: the curve goes through every key, with the tangent at a key pointing from
+  /// the previous key to the next one.
+  Cubic,
+}
+
+impl FromStr for Interpolation {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "linear" => Ok(Interpolation::Linear),
+      "cubic" => Ok(Interpolation::Cubic),
+      _ => Err(format!(
+        "unknown interpolation: {} (expecting linear or cubic)",
+        s
+      )),
+    }
+  }
+}
+
+/// Values of a vector at key times.
+#[derive(Clone, Debug, Default)]
+pub struct Track {
+  /// Keys, as times in seconds and values, in time order.
+  keys: Vec<(f32, Vector3<f32>)>,
+}
+
+impl Track {
+  pub fn new() -> Self {
+    Track::default()
+  }
+
+  /// Add a key after the others.
+  pub fn push(&mut self, t: f32, value: Vector3<f32>) -> Result<(), String> {
+    match self.keys.last() {
+      Some(&(last, _)) if t <= last => Err(format!(
+        "key at {}s comes after one at {}s; keys must be in time order",
+        t, last
+      )),
+      _ => {
+        self.keys.push((t, value));
+        Ok(())
+      }
+    }
+  }
+
+  /// Value of the track at time `t`.
+  ///
+  /// Before the first key, the track holds its value.
+  pub fn sample(&self, t: f32, interpolation: Interpolation) -> Vector3<f32> {
+    let keys = &self.keys;
+    let (end, last) = *keys.last().expect("track without keys");
+    let t = if end > 0. { t.rem_euclid(end) } else { end };
+
+    // index of the first key after t
+    let next = match keys.iter().position(|&(key_t, _)| key_t > t) {
+      Some(0) => return keys[0].1,
+      Some(next) => next,
+      None => return last,
+    };
+
+    let (t0, p0) = keys[next - 1];
+    let (t1, p1) = keys[next];
+    let s = (t - t0) / (t1 - t0);
+
+    match interpolation {
+      Interpolation::Linear => p0.lerp(p1, s),
+
+      Interpolation::Cubic => {
+        // tangents per segment, so that keys unevenly spaced in time don’t overshoot
+        let m0 = self.tangent(next - 1) * (t1 - t0);
+        let m1 = self.tangent(next) * (t1 - t0);
+        let s2 = s * s;
+        let s3 = s2 * s;
+
+        p0 * (2. * s3 - 3. * s2 + 1.)
+          + m0 * (s3 - 2. * s2 + s)
+          + p1 * (-2. * s3 + 3. * s2)
+          + m1 * (s3 - s2)
+      }
+    }
+  }
+
+  /// Tangent of the spline at key `i`, per second; the first and last keys only have one
+  /// neighbour to take it from.
+  fn tangent(&self, i: usize) -> Vector3<f32> {
+    let (t0, p0) = self.keys[i.saturating_sub(1)];
+    let (t1, p1) = self.keys[(i + 1).min(self.keys.len() - 1)];
+
+    (p1 - p0) / (t1 - t0)
+  }
+}
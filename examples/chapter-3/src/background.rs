@@ -0,0 +1,91 @@
+//! Background behind the model.
+//!
+//! A neutral background makes it easier to judge the silhouette and the materials of a model. A
+//! solid color only needs the framebuffer to be cleared; gradients and checkerboards are drawn
+//! over the whole screen before anything else.
+
+use std::str::FromStr;
+
+/// Size of the checkerboard squares, in pixels.
+pub const CHECKER_SQUARE: f32 = 32.;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Background {
+  /// Flat gray.
+  Solid,
+  /// Vertical gradient, dark at the bottom.
+  Gradient,
+  /// Gray checkerboard.
+  Checker,
+}
+
+/// Pattern drawn over the whole screen, with its two colors.
+#[derive(Clone, Copy, Debug)]
+pub struct Pattern {
+  /// Index of the pattern in the background shader.
+  pub index: i32,
+  pub color_a: [f32; 3],
+  pub color_b: [f32; 3],
+}
+
+impl Background {
+  pub fn name(self) -> &'static str {
+    match self {
+      Background::Solid => "solid",
+      Background::Gradient => "gradient",
+      Background::Checker => "checker",
+    }
+  }
+
+  /// Next background, going through all of them in turn.
+  pub fn next(self) -> Self {
+    match self {
+      Background::Solid => Background::Gradient,
+      Background::Gradient => Background::Checker,
+      Background::Checker => Background::Solid,
+    }
+  }
+
+  /// Color framebuffers are cleared with; it’s also the color the background reads as overall,
+  /// for effects that fade into it.
+  pub fn clear_color(self) -> [f32; 4] {
+    match self {
+      Background::Solid => [0.3, 0.3, 0.3, 1.],
+      Background::Gradient => [0.2, 0.22, 0.26, 1.],
+      Background::Checker => [0.33, 0.33, 0.33, 1.],
+    }
+  }
+
+  /// Pattern drawn over the cleared framebuffer, if any.
+  pub fn pattern(self) -> Option<Pattern> {
+    match self {
+      Background::Solid => None,
+      Background::Gradient => Some(Pattern {
+        index: 0,
+        color_a: [0.08, 0.08, 0.1],
+        color_b: [0.35, 0.38, 0.45],
+      }),
+      Background::Checker => Some(Pattern {
+        index: 1,
+        color_a: [0.3, 0.3, 0.3],
+        color_b: [0.36, 0.36, 0.36],
+      }),
+    }
+  }
+}
+
+impl FromStr for Background {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "solid" => Ok(Background::Solid),
+      "gradient" => Ok(Background::Gradient),
+      "checker" => Ok(Background::Checker),
+      _ => Err(format!(
+        "unknown background: {} (expecting solid, gradient or checker)",
+        s
+      )),
+    }
+  }
+}
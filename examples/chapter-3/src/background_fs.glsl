@@ -0,0 +1,20 @@
+in vec2 v_uv;
+
+out vec3 frag_color;
+
+// 0: vertical gradient from color_a at the bottom to color_b at the top
+// 1: checkerboard of color_a and color_b squares
+uniform int pattern;
+uniform vec3 color_a;
+uniform vec3 color_b;
+// size of the checkerboard squares, in pixels
+uniform float square;
+
+void main() {
+  if (pattern == 0) {
+    frag_color = mix(color_a, color_b, v_uv.y);
+  } else {
+    ivec2 cell = ivec2(gl_FragCoord.xy / square);
+    frag_color = (cell.x + cell.y) % 2 == 0 ? color_a : color_b;
+  }
+}
@@ -0,0 +1,172 @@
+//! Static batching.
+//!
+//! Every tess we render costs a draw call, along with the state changes around it. When a scene is
+//! made of many small static objects, it’s much cheaper to transform their vertices once on the CPU
+//! and concatenate them into a single tess. Each vertex remembers which object it comes from, so
+//! that shaders can still tell objects apart.
+
+use crate::material::Material;
+use crate::obj::{Obj, ObjStats};
+use crate::{Vertex, VertexIndex, VertexNormal, VertexObject, VertexPosition};
+use cgmath::{InnerSpace, Matrix, Matrix3, Matrix4, Point3, SquareMatrix, Transform, Vector3};
+
+#[derive(Debug, Default)]
+pub struct MeshBatcher {
+  vertices: Vec<Vertex>,
+  indices: Vec<VertexIndex>,
+  names: Vec<String>,
+  material: Option<Material>,
+  positions: usize,
+  normals: usize,
+  shapes: usize,
+  generated_normals: usize,
+  ignored_shapes: usize,
+}
+
+impl MeshBatcher {
+  pub fn new() -> Self {
+    MeshBatcher::default()
+  }
+
+  /// Add an object to the batch, transformed by `transform`.
+  pub fn add(&mut self, obj: &Obj, transform: Matrix4<f32>) {
+    let object = VertexObject::new(self.names.len() as u32);
+    let offset = self.vertices.len() as VertexIndex;
+
+    let normal_matrix = normal_matrix(transform);
+
+    self.vertices.extend(obj.vertices.iter().map(|vertex| {
+      let position = transform.transform_point(Point3::from(*vertex.position));
+      let normal = normal_matrix * Vector3::from(*vertex.normal);
+      let normal = if normal.magnitude2() > 0. {
+        normal.normalize()
+      } else {
+        normal
+      };
+
+      Vertex {
+        position: VertexPosition::new(position.into()),
+        normal: VertexNormal::new(normal.into()),
+        uv: vertex.uv,
+        object,
+      }
+    }));
+    self
+      .indices
+      .extend(obj.indices.iter().map(|&index| index + offset));
+
+    // a batch has a single material, the first object’s; the other objects are told apart by
+    // their tints anyway
+    if self.names.is_empty() {
+      self.material = obj.material.clone();
+    }
+
+    self.names.push(obj.stats.name.clone());
+    self.positions += obj.stats.positions;
+    self.normals += obj.stats.normals;
+    self.shapes += obj.stats.shapes;
+    self.generated_normals += obj.stats.generated_normals;
+    self.ignored_shapes += obj.stats.ignored_shapes;
+  }
+
+  /// Merge all the objects added so far into a single one.
+  pub fn finish(self) -> Obj {
+    Obj {
+      vertices: self.vertices,
+      indices: self.indices,
+      material: self.material,
+      stats: ObjStats {
+        name: self.names.join(", "),
+        positions: self.positions,
+        normals: self.normals,
+        shapes: self.shapes,
+        generated_normals: self.generated_normals,
+        ignored_shapes: self.ignored_shapes,
+        unit_hint: None,
+        material_error: None,
+      },
+    }
+  }
+}
+
+/// Batch objects where they are, like the meshes of a single file.
+pub fn batch_in_place(objs: &[Obj]) -> Obj {
+  let mut batcher = MeshBatcher::new();
+
+  for obj in objs {
+    batcher.add(obj, Matrix4::identity());
+  }
+
+  batcher.finish()
+}
+
+/// Batch models, each placed by its transform; the meshes of a model move together.
+pub fn batch_placed(models: &[Vec<Obj>], transforms: &[Matrix4<f32>]) -> Obj {
+  let mut batcher = MeshBatcher::new();
+
+  for (meshes, &transform) in models.iter().zip(transforms) {
+    for mesh in meshes {
+      batcher.add(mesh, transform);
+    }
+  }
+
+  batcher.finish()
+}
+
+/// Matrix transforming the normals of an object transformed by `transform`.
+///
+/// Normals must be transformed by the inverse transpose, so that non-uniform scales keep them
+/// perpendicular to the surface.
+pub fn normal_matrix(transform: Matrix4<f32>) -> Matrix3<f32> {
+  let linear = Matrix3::from_cols(
+    transform.x.truncate(),
+    transform.y.truncate(),
+    transform.z.truncate(),
+  );
+
+  linear.invert().unwrap_or(linear).transpose()
+}
+
+/// Transforms laying models out side by side along the X axis.
+///
+/// A single model is left where it is.
+pub fn row_transforms(models: &[Vec<Obj>]) -> Vec<Matrix4<f32>> {
+  if models.len() == 1 {
+    return vec![Matrix4::identity()];
+  }
+
+  let bounds = models
+    .iter()
+    .map(|meshes| model_bounds(meshes))
+    .collect::<Vec<_>>();
+  let gap = bounds
+    .iter()
+    .map(|(min, max)| max[0] - min[0])
+    .fold(0., f32::max)
+    * 0.25;
+  let mut x = 0.;
+
+  bounds
+    .into_iter()
+    .map(|(min, max)| {
+      let transform = Matrix4::from_translation(Vector3::new(x - min[0], 0., 0.));
+      x += max[0] - min[0] + gap;
+      transform
+    })
+    .collect()
+}
+
+/// Bounds of all the meshes of a model.
+fn model_bounds(meshes: &[Obj]) -> ([f32; 3], [f32; 3]) {
+  let mut min = [f32::INFINITY; 3];
+  let mut max = [f32::NEG_INFINITY; 3];
+
+  for (mesh_min, mesh_max) in meshes.iter().map(Obj::bounds) {
+    for i in 0..3 {
+      min[i] = min[i].min(mesh_min[i]);
+      max[i] = max[i].max(mesh_max[i]);
+    }
+  }
+
+  (min, max)
+}
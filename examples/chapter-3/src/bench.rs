@@ -0,0 +1,174 @@
+//! Benchmark mode.
+//!
+//! The camera flies around the model along a fixed path for a given duration, while the timings and
+//! statistics of every frame are recorded. They’re written to a CSV file at the end, so that runs
+//! made before and after a change can be compared.
+//!
+//! The GPU time of a frame is measured with a timer query. Its result is only read once the next
+//! frame has been submitted, so that waiting for it doesn’t stall the pipeline.
+
+use crate::stats::FrameStats;
+use cgmath::{Point3, Vector3};
+use gl::types::GLuint;
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Timings and statistics of a frame.
+#[derive(Clone, Copy, Debug)]
+struct FrameRecord {
+  /// Time since the start of the benchmark, in seconds.
+  t: f32,
+  /// Time since the previous frame, in milliseconds.
+  frame_ms: f32,
+  /// Time spent submitting the frame on the CPU, in milliseconds.
+  cpu_ms: f32,
+  /// Time spent by the GPU rendering the frame, in milliseconds; unknown until the next frame.
+  gpu_ms: Option<f32>,
+  stats: FrameStats,
+}
+
+#[derive(Debug)]
+pub struct Bench {
+  duration: Duration,
+  start: Instant,
+  last: Instant,
+  frame_start: Instant,
+  /// Timer queries, used by even and odd frames in turn.
+  queries: [GLuint; 2],
+  frames: Vec<FrameRecord>,
+}
+
+impl Bench {
+  /// Start a benchmark lasting `duration`.
+  ///
+  /// The GL functions must have been loaded.
+  pub fn new(duration: Duration) -> Self {
+    let mut queries = [0; 2];
+    unsafe { gl::GenQueries(2, queries.as_mut_ptr()) };
+
+    let now = Instant::now();
+    Bench {
+      duration,
+      start: now,
+      last: now,
+      frame_start: now,
+      queries,
+      frames: Vec::new(),
+    }
+  }
+
+  /// Position of the camera along the path: once around the model, getting closer and further and
+  /// going up and down on the way.
+  pub fn eye(&self, center: Point3<f32>, radius: f32) -> Point3<f32> {
+    let progress = self.start.elapsed().as_secs_f32() / self.duration.as_secs_f32();
+    let angle = progress.min(1.) * 2. * PI;
+    let distance = radius * (3. - (2. * angle).cos());
+
+    center
+      + Vector3::new(
+        angle.cos() * distance,
+        radius * (2. * angle).sin(),
+        angle.sin() * distance,
+      )
+  }
+
+  pub fn is_done(&self) -> bool {
+    self.start.elapsed() >= self.duration
+  }
+
+  /// Start timing a frame; call before submitting anything.
+  pub fn begin_frame(&mut self) {
+    self.frame_start = Instant::now();
+
+    let query = self.queries[self.frames.len() % 2];
+    unsafe { gl::BeginQuery(gl::TIME_ELAPSED, query) };
+  }
+
+  /// Stop timing a frame; call once everything is submitted, before swapping buffers.
+  pub fn end_frame(&mut self, stats: FrameStats) {
+    unsafe { gl::EndQuery(gl::TIME_ELAPSED) };
+
+    let now = Instant::now();
+    let record = FrameRecord {
+      t: now.duration_since(self.start).as_secs_f32(),
+      frame_ms: now.duration_since(self.last).as_secs_f32() * 1e3,
+      cpu_ms: now.duration_since(self.frame_start).as_secs_f32() * 1e3,
+      gpu_ms: None,
+      stats,
+    };
+    self.last = now;
+
+    // the previous frame has most likely been rendered by now
+    self.read_gpu_time();
+    self.frames.push(record);
+  }
+
+  /// Read the GPU time of the last recorded frame, waiting for it if needed.
+  fn read_gpu_time(&mut self) {
+    let query = self.queries[(self.frames.len() + 1) % 2];
+
+    if let Some(frame) = self.frames.last_mut() {
+      let mut ns = 0;
+      unsafe { gl::GetQueryObjectui64v(query, gl::QUERY_RESULT, &mut ns) };
+      frame.gpu_ms = Some(ns as f32 * 1e-6);
+    }
+  }
+
+  /// Write the report to a CSV file and print a summary.
+  pub fn finish(mut self, path: &Path) -> io::Result<()> {
+    self.read_gpu_time();
+    unsafe { gl::DeleteQueries(2, self.queries.as_ptr()) };
+
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(
+      file,
+      "frame,t,frame_ms,cpu_ms,gpu_ms,draws,instances,triangles,texture_binds,program_switches"
+    )?;
+
+    for (i, frame) in self.frames.iter().enumerate() {
+      writeln!(
+        file,
+        "{},{:.4},{:.3},{:.3},{},{},{},{},{},{}",
+        i,
+        frame.t,
+        frame.frame_ms,
+        frame.cpu_ms,
+        frame
+          .gpu_ms
+          .map(|ms| format!("{:.3}", ms))
+          .unwrap_or_default(),
+        frame.stats.draws,
+        frame.stats.instances,
+        frame.stats.triangles,
+        frame.stats.texture_binds,
+        frame.stats.program_switches,
+      )?;
+    }
+
+    file.flush()?;
+
+    let frames = self.frames.len().max(1) as f32;
+    let average = |ms: fn(&FrameRecord) -> f32| self.frames.iter().map(ms).sum::<f32>() / frames;
+    let worst = self
+      .frames
+      .iter()
+      .map(|frame| frame.frame_ms)
+      .fold(0., f32::max);
+
+    println!(
+      "benchmark: {} frames in {:.1} s; frame {:.2} ms (worst {:.2} ms), cpu {:.2} ms, gpu {:.2} ms on average; report written to {}",
+      self.frames.len(),
+      self.duration.as_secs_f32(),
+      average(|frame| frame.frame_ms),
+      worst,
+      average(|frame| frame.cpu_ms),
+      average(|frame| frame.gpu_ms.unwrap_or(0.)),
+      path.display()
+    );
+
+    Ok(())
+  }
+}
@@ -0,0 +1,299 @@
+//! Ray casting against a mesh on the CPU.
+//!
+//! Testing a ray against every triangle gets slow as soon as the mesh has more than a few thousand
+//! of them. A bounding volume hierarchy groups the triangles into nested boxes: a ray only gets
+//! tested against the triangles of the boxes it goes through.
+
+use crate::obj::Obj;
+use cgmath::{InnerSpace, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+
+/// Maximum number of triangles in a leaf.
+const LEAF_SIZE: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ray {
+  pub origin: Point3<f32>,
+  /// Direction of the ray; it doesn’t need to be normalized.
+  pub dir: Vector3<f32>,
+}
+
+impl Ray {
+  /// Ray starting on the near plane of a camera and going through a point of the screen, given in
+  /// [0; 1] from the top left corner.
+  pub fn through_screen(view_projection: Matrix4<f32>, [x, y]: [f32; 2]) -> Self {
+    let inverse = view_projection
+      .invert()
+      .expect("invertible view projection matrix");
+    let unproject = |z| {
+      let p = inverse * Vector4::new(2. * x - 1., 1. - 2. * y, z, 1.);
+      Point3::new(p.x / p.w, p.y / p.w, p.z / p.w)
+    };
+    let near = unproject(-1.);
+    let far = unproject(1.);
+
+    Ray {
+      origin: near,
+      dir: far - near,
+    }
+  }
+
+  pub fn at(&self, t: f32) -> Point3<f32> {
+    self.origin + self.dir * t
+  }
+}
+
+/// Closest intersection of a ray with the mesh.
+#[derive(Clone, Copy, Debug)]
+pub struct Hit {
+  /// Index of the triangle hit.
+  pub triangle: usize,
+  /// Distance along the ray, in units of its direction.
+  pub t: f32,
+  /// Barycentric coordinates of the hit in the triangle.
+  pub barycentric: [f32; 3],
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Aabb {
+  min: Point3<f32>,
+  max: Point3<f32>,
+}
+
+impl Aabb {
+  fn empty() -> Self {
+    Aabb {
+      min: Point3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+      max: Point3::new(-f32::INFINITY, -f32::INFINITY, -f32::INFINITY),
+    }
+  }
+
+  fn grow(&mut self, p: Point3<f32>) {
+    self.min = Point3::new(
+      self.min.x.min(p.x),
+      self.min.y.min(p.y),
+      self.min.z.min(p.z),
+    );
+    self.max = Point3::new(
+      self.max.x.max(p.x),
+      self.max.y.max(p.y),
+      self.max.z.max(p.z),
+    );
+  }
+
+  /// Distance along a ray at which it enters the box, if it does before max_t (slab test).
+  fn intersect(&self, ray: &Ray, inv_dir: Vector3<f32>, max_t: f32) -> Option<f32> {
+    let mut t_min = 0.;
+    let mut t_max = max_t;
+
+    for axis in 0..3 {
+      let t0 = (self.min[axis] - ray.origin[axis]) * inv_dir[axis];
+      let t1 = (self.max[axis] - ray.origin[axis]) * inv_dir[axis];
+      let (t0, t1) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+
+      t_min = t0.max(t_min);
+      t_max = t1.min(t_max);
+
+      if t_min > t_max {
+        return None;
+      }
+    }
+
+    Some(t_min)
+  }
+}
+
+#[derive(Debug)]
+enum Node {
+  /// Triangles first..first + count of the reordered triangle list.
+  Leaf {
+    aabb: Aabb,
+    first: usize,
+    count: usize,
+  },
+  /// Indices of the two children in the node list.
+  Inner {
+    aabb: Aabb,
+    left: usize,
+    right: usize,
+  },
+}
+
+impl Node {
+  fn aabb(&self) -> &Aabb {
+    match self {
+      Node::Leaf { aabb, .. } | Node::Inner { aabb, .. } => aabb,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct Bvh {
+  nodes: Vec<Node>,
+  /// Triangle indices, reordered so that each leaf covers a contiguous range.
+  triangles: Vec<usize>,
+  /// Corners of the triangles, in the order of the mesh.
+  corners: Vec<[Point3<f32>; 3]>,
+}
+
+impl Bvh {
+  pub fn new(obj: &Obj) -> Self {
+    let corners = obj
+      .indices
+      .chunks(3)
+      .map(|t| {
+        let corner = |i: u32| Point3::from(*obj.vertices[i as usize].position);
+        [corner(t[0]), corner(t[1]), corner(t[2])]
+      })
+      .collect::<Vec<_>>();
+
+    let mut bvh = Bvh {
+      nodes: Vec::new(),
+      triangles: (0..corners.len()).collect(),
+      corners,
+    };
+
+    if !bvh.triangles.is_empty() {
+      bvh.build(0, bvh.triangles.len());
+    }
+
+    bvh
+  }
+
+  /// Build the subtree of triangles first..first + count, returning the index of its root.
+  fn build(&mut self, first: usize, count: usize) -> usize {
+    let mut aabb = Aabb::empty();
+    let mut centroids = Aabb::empty();
+
+    for &t in &self.triangles[first..first + count] {
+      let [a, b, c] = self.corners[t];
+      aabb.grow(a);
+      aabb.grow(b);
+      aabb.grow(c);
+      centroids.grow(centroid(&self.corners[t]));
+    }
+
+    let index = self.nodes.len();
+
+    if count <= LEAF_SIZE {
+      self.nodes.push(Node::Leaf { aabb, first, count });
+      return index;
+    }
+
+    // split at the median along the axis the centroids spread the most on
+    let extent = centroids.max - centroids.min;
+    let axis = if extent.x > extent.y && extent.x > extent.z {
+      0
+    } else if extent.y > extent.z {
+      1
+    } else {
+      2
+    };
+
+    let corners = &self.corners;
+    self.triangles[first..first + count].sort_by(|&a, &b| {
+      let a = centroid(&corners[a])[axis];
+      let b = centroid(&corners[b])[axis];
+      a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    // the children are filled in once they’re built
+    self.nodes.push(Node::Leaf {
+      aabb,
+      first,
+      count: 0,
+    });
+
+    let half = count / 2;
+    let left = self.build(first, half);
+    let right = self.build(first + half, count - half);
+    self.nodes[index] = Node::Inner { aabb, left, right };
+
+    index
+  }
+
+  /// Closest triangle hit by a ray, if any.
+  pub fn intersect(&self, ray: &Ray) -> Option<Hit> {
+    let inv_dir = Vector3::new(1. / ray.dir.x, 1. / ray.dir.y, 1. / ray.dir.z);
+    let mut closest: Option<Hit> = None;
+    let mut stack = Vec::new();
+
+    if !self.nodes.is_empty() {
+      stack.push(0);
+    }
+
+    while let Some(index) = stack.pop() {
+      let node = &self.nodes[index];
+      let max_t = closest.map_or(f32::INFINITY, |hit| hit.t);
+
+      if node.aabb().intersect(ray, inv_dir, max_t).is_none() {
+        continue;
+      }
+
+      match *node {
+        Node::Leaf { first, count, .. } => {
+          for &t in &self.triangles[first..first + count] {
+            if let Some((dist, barycentric)) = intersect_triangle(ray, &self.corners[t]) {
+              if dist < closest.map_or(f32::INFINITY, |hit| hit.t) {
+                closest = Some(Hit {
+                  triangle: t,
+                  t: dist,
+                  barycentric,
+                });
+              }
+            }
+          }
+        }
+
+        Node::Inner { left, right, .. } => {
+          stack.push(left);
+          stack.push(right);
+        }
+      }
+    }
+
+    closest
+  }
+}
+
+fn centroid([a, b, c]: &[Point3<f32>; 3]) -> Point3<f32> {
+  Point3::new(
+    (a.x + b.x + c.x) / 3.,
+    (a.y + b.y + c.y) / 3.,
+    (a.z + b.z + c.z) / 3.,
+  )
+}
+
+/// Möller–Trumbore ray/triangle intersection; both faces of the triangle are hit.
+fn intersect_triangle(ray: &Ray, [a, b, c]: &[Point3<f32>; 3]) -> Option<(f32, [f32; 3])> {
+  let ab = b - a;
+  let ac = c - a;
+  let p = ray.dir.cross(ac);
+  let det = ab.dot(p);
+
+  if det.abs() < 1e-8 {
+    return None;
+  }
+
+  let inv_det = 1. / det;
+  let ao = ray.origin - a;
+  let u = ao.dot(p) * inv_det;
+
+  if !(0. ..=1.).contains(&u) {
+    return None;
+  }
+
+  let q = ao.cross(ab);
+  let v = ray.dir.dot(q) * inv_det;
+
+  if v < 0. || u + v > 1. {
+    return None;
+  }
+
+  let t = ac.dot(q) * inv_det;
+
+  if t > 0. {
+    Some((t, [1. - u - v, u, v]))
+  } else {
+    None
+  }
+}
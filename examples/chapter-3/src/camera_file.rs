@@ -0,0 +1,284 @@
+//! Camera export and import.
+//!
+//! The camera is saved as JSON, in Blender’s conventions, so that a render of the viewer can be
+//! matched in Blender (or any tool reading the same properties) and the other way around:
+//!
+//! - Blender is Z-up while the viewer is Y-up; the viewer’s (x, y, z) is Blender’s (x, -z, y).
+//! - The orientation is given as XYZ Euler angles, in radians, of a camera looking down its local
+//!   -Z axis with its local +Y axis up, as both Blender and OpenGL cameras do.
+//! - The field of view is given as a focal length, in millimeters, over a sensor fitted
+//!   vertically; the angle itself is saved too.
+//!
+//! The viewer’s camera has no roll, so the roll of an imported camera is lost.
+
+use cgmath::{Deg, InnerSpace, Matrix, Matrix3, Point3, Rad, Vector3};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Height of the sensor, in millimeters; Blender’s default sensor is 36 × 24.
+const SENSOR_HEIGHT: f32 = 24.;
+
+/// Viewer to Blender world axes.
+fn to_blender() -> Matrix3<f32> {
+  // columns are the images of the viewer’s X, Y and Z axes
+  Matrix3::new(1., 0., 0., 0., 0., 1., 0., -1., 0.)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CameraFile {
+  pub position: Point3<f32>,
+  /// Direction the camera looks at.
+  pub forward: Vector3<f32>,
+  /// Vertical field of view.
+  pub fovy: Deg<f32>,
+  pub z_near: f32,
+  pub z_far: f32,
+  /// Size of the viewport, in pixels.
+  pub resolution: [u32; 2],
+}
+
+impl CameraFile {
+  pub fn save<P>(&self, path: P) -> Result<(), String>
+  where
+    P: AsRef<Path>,
+  {
+    let path = path.as_ref();
+
+    // camera axes in world space: right, up and backward
+    let forward = self.forward.normalize();
+    let right = forward.cross(Vector3::unit_y()).normalize();
+    let up = right.cross(forward);
+    let rotation = to_blender() * Matrix3::from_cols(right, up, -forward);
+    let [x, y, z] = euler_xyz(rotation);
+
+    let location = to_blender() * Vector3::new(self.position.x, self.position.y, self.position.z);
+    let fovy = Rad::from(self.fovy).0;
+    let lens = SENSOR_HEIGHT * 0.5 / (fovy * 0.5).tan();
+
+    let json = format!(
+      "{{\n  \"location\": [{}, {}, {}],\n  \"rotation_euler\": [{}, {}, {}],\n  \"lens\": {},\n  \"sensor_fit\": \"VERTICAL\",\n  \"sensor_height\": {},\n  \"angle_y\": {},\n  \"clip_start\": {},\n  \"clip_end\": {},\n  \"resolution\": [{}, {}]\n}}\n",
+      location.x,
+      location.y,
+      location.z,
+      x,
+      y,
+      z,
+      lens,
+      SENSOR_HEIGHT,
+      fovy,
+      self.z_near,
+      self.z_far,
+      self.resolution[0],
+      self.resolution[1],
+    );
+
+    fs::write(path, json).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+  }
+
+  pub fn load<P>(path: P) -> Result<Self, String>
+  where
+    P: AsRef<Path>,
+  {
+    let path = path.as_ref();
+    let content =
+      fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let fields = parse_object(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let field = |name: &str| {
+      fields
+        .get(name)
+        .ok_or_else(|| format!("{}: missing {}", path.display(), name))
+    };
+    let number = |name: &str| match field(name)? {
+      Value::Number(x) => Ok(*x),
+      _ => Err(format!("{}: {} must be a number", path.display(), name)),
+    };
+    let triple = |name: &str| match field(name)? {
+      Value::Numbers(v) if v.len() == 3 => Ok(Vector3::new(v[0], v[1], v[2])),
+      _ => Err(format!("{}: {} must be 3 numbers", path.display(), name)),
+    };
+
+    let location = to_blender().transpose() * triple("location")?;
+    let rotation = to_blender().transpose() * from_euler_xyz(triple("rotation_euler")?);
+
+    let resolution = match fields.get("resolution") {
+      Some(Value::Numbers(v)) if v.len() == 2 => [v[0] as u32, v[1] as u32],
+      _ => [16, 9],
+    };
+    // the angle is used if given, otherwise it’s derived from the focal length and the sensor
+    let fovy = match fields.get("angle_y") {
+      Some(Value::Number(angle)) => *angle,
+      _ => {
+        let lens = number("lens")?;
+        let sensor_fit = match fields.get("sensor_fit") {
+          Some(Value::String(fit)) => fit.as_str(),
+          _ => "AUTO",
+        };
+        let aspect = resolution[0] as f32 / resolution[1].max(1) as f32;
+
+        match sensor_fit {
+          "VERTICAL" => 2. * (number("sensor_height")? * 0.5 / lens).atan(),
+          // Blender fits the sensor width on the largest side of the image
+          _ => {
+            let sensor_width = number("sensor_width").unwrap_or(36.);
+            let half_width = sensor_width * 0.5 / lens;
+
+            if sensor_fit == "HORIZONTAL" || aspect >= 1. {
+              2. * (half_width / aspect).atan()
+            } else {
+              2. * half_width.atan()
+            }
+          }
+        }
+      }
+    };
+
+    Ok(CameraFile {
+      position: Point3::new(location.x, location.y, location.z),
+      forward: -rotation.z,
+      fovy: Rad(fovy).into(),
+      z_near: number("clip_start").unwrap_or(0.1),
+      z_far: number("clip_end").unwrap_or(100.),
+      resolution,
+    })
+  }
+}
+
+/// XYZ Euler angles of a rotation, which is Rz · Ry · Rx.
+fn euler_xyz(m: Matrix3<f32>) -> [f32; 3] {
+  // m.c.r is the element at column c, row r
+  let y = (-m.x.z).clamp(-1., 1.).asin();
+  let x = m.y.z.atan2(m.z.z);
+  let z = m.x.y.atan2(m.x.x);
+
+  [x, y, z]
+}
+
+fn from_euler_xyz(angles: Vector3<f32>) -> Matrix3<f32> {
+  Matrix3::from_angle_z(Rad(angles.z))
+    * Matrix3::from_angle_y(Rad(angles.y))
+    * Matrix3::from_angle_x(Rad(angles.x))
+}
+
+/// Values of the fields of a camera file.
+#[derive(Clone, Debug)]
+enum Value {
+  Number(f32),
+  Numbers(Vec<f32>),
+  String(String),
+}
+
+/// Parse a flat JSON object, whose values are numbers, arrays of numbers or strings.
+fn parse_object(s: &str) -> Result<HashMap<String, Value>, String> {
+  let mut parser = Parser {
+    s: s.as_bytes(),
+    i: 0,
+  };
+  let mut fields = HashMap::new();
+
+  parser.expect(b'{')?;
+
+  if !parser.eat(b'}') {
+    loop {
+      let key = parser.string()?;
+      parser.expect(b':')?;
+
+      let value = match parser.peek() {
+        Some(b'"') => Value::String(parser.string()?),
+        Some(b'[') => {
+          parser.expect(b'[')?;
+          let mut numbers = Vec::new();
+
+          if !parser.eat(b']') {
+            loop {
+              numbers.push(parser.number()?);
+
+              if parser.eat(b']') {
+                break;
+              }
+
+              parser.expect(b',')?;
+            }
+          }
+
+          Value::Numbers(numbers)
+        }
+        _ => Value::Number(parser.number()?),
+      };
+
+      fields.insert(key, value);
+
+      if parser.eat(b'}') {
+        break;
+      }
+
+      parser.expect(b',')?;
+    }
+  }
+
+  Ok(fields)
+}
+
+struct Parser<'a> {
+  s: &'a [u8],
+  i: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn skip_whitespace(&mut self) {
+    while self.i < self.s.len() && self.s[self.i].is_ascii_whitespace() {
+      self.i += 1;
+    }
+  }
+
+  fn peek(&mut self) -> Option<u8> {
+    self.skip_whitespace();
+    self.s.get(self.i).copied()
+  }
+
+  fn eat(&mut self, c: u8) -> bool {
+    if self.peek() == Some(c) {
+      self.i += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn expect(&mut self, c: u8) -> Result<(), String> {
+    if self.eat(c) {
+      Ok(())
+    } else {
+      Err(format!("expecting '{}' at byte {}", c as char, self.i))
+    }
+  }
+
+  /// String without escape sequences, which camera files don’t need.
+  fn string(&mut self) -> Result<String, String> {
+    self.expect(b'"')?;
+    let start = self.i;
+
+    while self.i < self.s.len() && self.s[self.i] != b'"' {
+      self.i += 1;
+    }
+
+    let string = String::from_utf8_lossy(&self.s[start..self.i]).into_owned();
+    self.expect(b'"')?;
+    Ok(string)
+  }
+
+  fn number(&mut self) -> Result<f32, String> {
+    self.skip_whitespace();
+    let start = self.i;
+
+    while self.i < self.s.len() && b"+-.0123456789eE".contains(&self.s[self.i]) {
+      self.i += 1;
+    }
+
+    let number = String::from_utf8_lossy(&self.s[start..self.i]);
+    number
+      .parse()
+      .map_err(|_| format!("invalid number at byte {}: {}", start, number))
+  }
+}
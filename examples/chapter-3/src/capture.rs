@@ -0,0 +1,52 @@
+//! Frame captures with RenderDoc.
+//!
+//! When built with the `renderdoc` feature and run from RenderDoc (or with its library available),
+//! a key press asks RenderDoc to capture the next frame, so that every draw call and the state it
+//! uses can be inspected. Without the feature, asking for a capture only prints a message.
+
+#[cfg(feature = "renderdoc")]
+use renderdoc::{RenderDoc, V110};
+
+pub struct FrameCapture {
+  #[cfg(feature = "renderdoc")]
+  renderdoc: Option<RenderDoc<V110>>,
+}
+
+impl FrameCapture {
+  /// Connect to RenderDoc; this must be done before the graphics context gets created.
+  #[cfg(feature = "renderdoc")]
+  pub fn new() -> Self {
+    let renderdoc = match RenderDoc::new() {
+      Ok(renderdoc) => Some(renderdoc),
+      Err(e) => {
+        eprintln!("RenderDoc not available: {}", e);
+        None
+      }
+    };
+
+    FrameCapture { renderdoc }
+  }
+
+  #[cfg(not(feature = "renderdoc"))]
+  pub fn new() -> Self {
+    FrameCapture {}
+  }
+
+  /// Capture the next frame.
+  #[cfg(feature = "renderdoc")]
+  pub fn trigger(&mut self) {
+    match self.renderdoc {
+      Some(ref mut renderdoc) => {
+        renderdoc.trigger_capture();
+        println!("capturing frame");
+      }
+
+      None => eprintln!("cannot capture frame: RenderDoc not available"),
+    }
+  }
+
+  #[cfg(not(feature = "renderdoc"))]
+  pub fn trigger(&mut self) {
+    eprintln!("cannot capture frame: built without the renderdoc feature");
+  }
+}
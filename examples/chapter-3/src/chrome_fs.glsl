@@ -0,0 +1,15 @@
+in vec3 v_position;
+in vec3 v_normal;
+
+out vec3 frag_color;
+
+uniform samplerCube environment;
+uniform vec3 camera_pos;
+
+void main() {
+  vec3 n = normalize(v_normal);
+  vec3 r = reflect(normalize(v_position - camera_pos), n);
+
+  // chrome reflects nearly everything, with a slightly warm tint
+  frag_color = texture(environment, r).rgb * vec3(.95, .93, .9);
+}
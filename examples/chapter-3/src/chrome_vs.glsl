@@ -0,0 +1,23 @@
+in vec3 position;
+in vec3 normal;
+in vec2 uv;
+
+out vec3 v_position;
+out vec3 v_normal;
+out vec2 v_uv;
+
+uniform mat4 projection;
+uniform mat4 view;
+// model matrix of the object being drawn; the sphere and batched models are already in the world
+uniform mat4 model = mat4(1.);
+// inverse transpose of the model matrix, which keeps normals perpendicular under any scale
+uniform mat3 normal_matrix = mat3(1.);
+
+void main() {
+  vec4 world = model * vec4(position, 1.);
+
+  v_position = world.xyz;
+  v_normal = normal_matrix * normal;
+  v_uv = uv;
+  gl_Position = projection * view * world;
+}
@@ -0,0 +1,354 @@
+//! Command-line arguments.
+
+use crate::background::Background;
+use crate::depth_offset::DepthOffset;
+use crate::failure::ErrorFormat;
+use crate::gl_debug::Severity;
+use crate::obj::{unit_to_meters, UpAxis};
+use crate::shading::Shading;
+use std::env;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "usage: chapter-3 [options] [model.obj...]
+
+options:
+  --validate        load the model, print a report and exit
+  --json            print the validation report as JSON
+  --highlight       highlight triangles with topology issues
+  --no-fit-unit     keep the original position and scale of the model
+  --no-batch        draw each model with its own draw call and model matrix instead of
+                    merging them into a single mesh
+  --scene <file>    view the objects listed in a scene file, with their own placement and
+                    render states, instead of models
+  --script <file>   set up and animate a scene with a rhai script, instead of a scene
+                    file (see script.rs; needs the rhai feature)
+  --up <y|z>        axis pointing up in the model (default: y)
+  --flip-x          mirror the model along the X axis
+  --background <b>  background behind the model: solid, gradient or checker
+                    (default: gradient); B cycles through them
+  --chrome          put a chrome sphere reflecting the model next to it
+  --glass           shade the model as glass, in a photo studio
+  --ior <n>         index of refraction of the glass (default: 1.5)
+  --dispersion <d>  spread of the index of refraction across colors (default: 0.02)
+  --pick            click on the model to pick a triangle
+  --depth-offset <factor,units>
+                    polygon offset pulling overlays drawn over the model (highlighted
+                    and picked triangles) towards the camera
+                    (default: -1,-1)
+  --two-sided       draw the back of faces too, for leaves, cloth or open meshes
+  --split <a,b>     shade the left and right halves of the screen differently
+                    (lambert, phong, glass, albedo, normals, checker); drag the
+                    divider with the mouse
+  --bench <seconds> fly the camera around the model, write frame timings and exit
+  --bench-report <file>
+                    CSV file the benchmark report is written to (default: bench.csv)
+  --frames <n>      render n frames and exit, with a failure status if they can’t all be
+                    rendered
+  --last-frame <file>
+                    save the last frame rendered with --frames as a binary PPM image
+  --config <file>   settings applied live, every time the file is saved: background,
+                    lighting, shading, camera, overdraw and stats (see config.rs)
+  --camera-file <file>
+                    JSON file the camera is exported to with F5 and imported from with
+                    F6, in Blender’s conventions (default: camera.json)
+  --unit-scale <s>  scale to meters, either a factor or a unit (mm, cm, m, in, ft); the
+                    view is fitted afterwards, so it only sizes models relative to each
+                    other, or the model itself with --no-fit-unit
+  --remote <port>   accept commands over a WebSocket on localhost: actions, camera,
+                    fov and screenshot (see remote.rs)
+  --record <file>   record the input session to a file
+  --replay <file>   replay an input session recorded with --record
+  --error-format <f>
+                    print fatal errors as text or json (default: text); each kind of
+                    error exits with its own status: 2 for invalid arguments, 3 for
+                    missing files, 4 for files that can’t be parsed, 5 for graphics
+                    errors, 6 for shader errors and 1 for anything else
+  --gl-debug <s>    print GL debug messages at least as severe as s
+                    (notification, low, medium, high)
+  --gl-break        abort on the first GL error (implies --gl-debug medium)
+
+mouse:
+  drag              turn the camera around the model
+  scroll            move the camera closer to the model or away from it";
+
+/// Options passed on the command line.
+#[derive(Clone, Debug)]
+pub struct CliArgs {
+  /// Paths of the .obj files to view; several files are batched together.
+  pub paths: Vec<PathBuf>,
+  /// Run the loading pipeline, print a report and exit without opening a window.
+  pub validate: bool,
+  /// Print the validation report as JSON.
+  pub json: bool,
+  /// Highlight triangles with topology issues when viewing the model.
+  pub highlight: bool,
+  /// Center the model and scale it to fit in a unit cube.
+  pub fit_unit: bool,
+  /// Merge all the models into a single mesh, drawn in a single draw call.
+  pub batch: bool,
+  /// Scene file listing the objects to view; they’re drawn one by one.
+  pub scene: Option<PathBuf>,
+  /// Script spawning the objects to view, and moving them along with the lights and camera.
+  pub script: Option<PathBuf>,
+  /// Axis pointing up in the model.
+  pub up: UpAxis,
+  /// Mirror the model along X, for files authored with the other handedness.
+  pub flip_x: bool,
+  /// Background behind the model.
+  pub background: Background,
+  /// Put a chrome sphere next to the model, reflecting it through a dynamic environment map.
+  pub chrome: bool,
+  /// Shade the model as glass, refracting and reflecting a studio environment.
+  pub glass: bool,
+  /// Index of refraction of the glass.
+  pub ior: f32,
+  /// Difference between the indices of refraction of green and red or blue light.
+  pub dispersion: f32,
+  /// Pick triangles of the model with the mouse.
+  pub pick: bool,
+  /// Polygon offset of the overlays drawn over the model.
+  pub depth_offset: DepthOffset,
+  /// Draw the back of the faces of the model too.
+  pub two_sided: bool,
+  /// Shadings of the left and right halves of the screen, to compare them.
+  pub split: Option<[Shading; 2]>,
+  /// Duration of the benchmark, in seconds, if benchmarking.
+  pub bench: Option<f32>,
+  /// File the benchmark report is written to.
+  pub bench_report: PathBuf,
+  /// Number of frames to render before exiting, for smoke tests.
+  pub frames: Option<u32>,
+  /// File the last frame is saved to, with `frames`.
+  pub last_frame: Option<PathBuf>,
+  /// Configuration file watched and applied live.
+  pub config: Option<PathBuf>,
+  /// File the camera is exported to and imported from.
+  pub camera_file: PathBuf,
+  /// Scale converting the model units to meters; detected from the file if not set.
+  ///
+  /// It’s applied before `fit_unit`, which scales everything back to a unit size: it only
+  /// shows on the size of models relative to each other, unless fitting is disabled.
+  pub unit_scale: Option<f32>,
+  /// Port of the WebSocket remote control.
+  pub remote: Option<u16>,
+  /// File to record the input session to.
+  pub record: Option<PathBuf>,
+  /// File to replay an input session from.
+  pub replay: Option<PathBuf>,
+  /// How fatal errors are printed.
+  pub error_format: ErrorFormat,
+  /// Minimum severity of GL debug messages to print, if enabled.
+  pub gl_debug: Option<Severity>,
+  /// Abort on the first GL error.
+  pub gl_break: bool,
+}
+
+impl Default for CliArgs {
+  fn default() -> Self {
+    CliArgs {
+      paths: Vec::new(),
+      validate: false,
+      json: false,
+      highlight: false,
+      fit_unit: true,
+      batch: true,
+      scene: None,
+      script: None,
+      up: UpAxis::Y,
+      flip_x: false,
+      background: Background::Gradient,
+      chrome: false,
+      glass: false,
+      ior: 1.5,
+      dispersion: 0.02,
+      pick: false,
+      depth_offset: DepthOffset::default(),
+      two_sided: false,
+      split: None,
+      bench: None,
+      bench_report: "bench.csv".into(),
+      frames: None,
+      last_frame: None,
+      config: None,
+      camera_file: PathBuf::from("camera.json"),
+      unit_scale: None,
+      remote: None,
+      record: None,
+      replay: None,
+      error_format: ErrorFormat::Text,
+      gl_debug: None,
+      gl_break: false,
+    }
+  }
+}
+
+impl CliArgs {
+  pub fn parse() -> Result<Self, String> {
+    Self::parse_from(env::args().skip(1))
+  }
+
+  fn parse_from<I>(args: I) -> Result<Self, String>
+  where
+    I: IntoIterator<Item = String>,
+  {
+    let mut cli = CliArgs::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+      match arg.as_str() {
+        "--validate" => cli.validate = true,
+        "--json" => cli.json = true,
+        "--highlight" => cli.highlight = true,
+        "--fit-unit" => cli.fit_unit = true,
+        "--no-fit-unit" => cli.fit_unit = false,
+        "--no-batch" => cli.batch = false,
+        "--scene" => cli.scene = Some(value(&mut args, "--scene")?.into()),
+        "--script" => cli.script = Some(value(&mut args, "--script")?.into()),
+        "--up" => cli.up = value(&mut args, "--up")?.parse()?,
+        "--flip-x" => cli.flip_x = true,
+        "--background" => cli.background = value(&mut args, "--background")?.parse()?,
+        "--chrome" => cli.chrome = true,
+        "--glass" => cli.glass = true,
+        "--pick" => cli.pick = true,
+        "--depth-offset" => cli.depth_offset = value(&mut args, "--depth-offset")?.parse()?,
+        "--two-sided" => cli.two_sided = true,
+        "--split" => cli.split = Some(parse_split(&value(&mut args, "--split")?)?),
+        "--ior" => cli.ior = parse_number(&value(&mut args, "--ior")?, "--ior")?,
+        "--dispersion" => {
+          cli.dispersion = parse_number(&value(&mut args, "--dispersion")?, "--dispersion")?
+        }
+        "--bench" => cli.bench = Some(parse_number(&value(&mut args, "--bench")?, "--bench")?),
+        "--bench-report" => cli.bench_report = value(&mut args, "--bench-report")?.into(),
+        "--frames" => {
+          let n = value(&mut args, "--frames")?;
+          cli.frames = Some(
+            n.parse()
+              .map_err(|_| format!("invalid value for --frames: {}", n))?,
+          );
+        }
+        "--last-frame" => cli.last_frame = Some(value(&mut args, "--last-frame")?.into()),
+        "--config" => cli.config = Some(value(&mut args, "--config")?.into()),
+        "--camera-file" => cli.camera_file = value(&mut args, "--camera-file")?.into(),
+        "--unit-scale" => {
+          cli.unit_scale = Some(parse_unit_scale(&value(&mut args, "--unit-scale")?)?)
+        }
+        "--remote" => {
+          let port = value(&mut args, "--remote")?;
+          cli.remote = Some(
+            port
+              .parse()
+              .map_err(|_| format!("invalid value for --remote: {}", port))?,
+          );
+        }
+        "--record" => cli.record = Some(value(&mut args, "--record")?.into()),
+        "--replay" => cli.replay = Some(value(&mut args, "--replay")?.into()),
+        "--error-format" => cli.error_format = value(&mut args, "--error-format")?.parse()?,
+        "--gl-debug" => cli.gl_debug = Some(value(&mut args, "--gl-debug")?.parse()?),
+        "--gl-break" => cli.gl_break = true,
+        flag if flag.starts_with("--") => return Err(format!("unknown option: {}", flag)),
+        _ => cli.paths.push(arg.into()),
+      }
+    }
+
+    if cli.json && !cli.validate {
+      return Err("--json only makes sense with --validate".to_owned());
+    }
+
+    if cli.record.is_some() && cli.replay.is_some() {
+      return Err("cannot record and replay at the same time".to_owned());
+    }
+
+    if cli.ior < 1. {
+      return Err(format!(
+        "index of refraction must be at least 1: {}",
+        cli.ior
+      ));
+    }
+
+    if cli.dispersion < 0. || cli.ior - cli.dispersion < 1. {
+      return Err(format!(
+        "dispersion must be between 0 and the index of refraction minus 1: {}",
+        cli.dispersion
+      ));
+    }
+
+    if let Some(seconds) = cli.bench {
+      if seconds <= 0. {
+        return Err(format!("benchmark duration must be positive: {}", seconds));
+      }
+    }
+
+    if cli.frames == Some(0) {
+      return Err("--frames needs at least one frame".to_owned());
+    }
+
+    if cli.last_frame.is_some() && cli.frames.is_none() {
+      return Err("--last-frame only makes sense with --frames".to_owned());
+    }
+
+    if cli.gl_break && cli.gl_debug.is_none() {
+      cli.gl_debug = Some(Severity::Medium);
+    }
+
+    Ok(cli)
+  }
+}
+
+/// Error format given on the command line, looked for even if the other arguments are invalid.
+pub fn error_format() -> ErrorFormat {
+  let args = env::args().collect::<Vec<_>>();
+
+  args
+    .windows(2)
+    .rev()
+    .find_map(|w| match w[0].as_str() {
+      "--error-format" => w[1].parse().ok(),
+      _ => None,
+    })
+    .unwrap_or(ErrorFormat::Text)
+}
+
+/// Get the value following an option.
+fn value<I>(args: &mut I, option: &str) -> Result<String, String>
+where
+  I: Iterator<Item = String>,
+{
+  args
+    .next()
+    .ok_or_else(|| format!("missing value for {}", option))
+}
+
+fn parse_split(s: &str) -> Result<[Shading; 2], String> {
+  let mut shadings = s.split(',');
+
+  match (shadings.next(), shadings.next(), shadings.next()) {
+    (Some(left), Some(right), None) => Ok([left.parse()?, right.parse()?]),
+    _ => Err(format!(
+      "expecting two shadings separated by a comma: {}",
+      s
+    )),
+  }
+}
+
+/// Parse a finite number; NaN would get through every range check.
+fn parse_number(s: &str, option: &str) -> Result<f32, String> {
+  s.parse()
+    .ok()
+    .filter(|v: &f32| v.is_finite())
+    .ok_or_else(|| format!("invalid value for {}: {}", option, s))
+}
+
+fn parse_unit_scale(s: &str) -> Result<f32, String> {
+  let scale = s
+    .parse()
+    .ok()
+    .filter(|scale: &f32| scale.is_finite())
+    .or_else(|| unit_to_meters(s))
+    .ok_or_else(|| format!("invalid unit scale: {}", s))?;
+
+  if scale > 0. {
+    Ok(scale)
+  } else {
+    Err(format!("unit scale must be positive: {}", s))
+  }
+}
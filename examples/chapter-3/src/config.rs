@@ -0,0 +1,177 @@
+//! Configuration file applied live.
+//!
+//! The configuration is a `key = value` file, like the state file: it accepts the same `lighting`,
+//! `lighting.<preset>.<property>`, `shading`, `orbit` and `fov` keys, plus a few settings of its
+//! own, `background`, `overdraw` and `stats`, the latter two being `on` or `off`. Unlike the state,
+//! it’s only ever read: it’s watched while the viewer runs and applied every time it’s saved,
+//! without restarting. Settings it leaves out keep their current value.
+//!
+//! As for the shaders of chapter 10, the directory of the file is watched rather than the file
+//! itself, so that editors saving by renaming a new file over the old one are noticed.
+
+use crate::background::Background;
+use crate::orbit::Orbit;
+use crate::shading::Shading;
+use crate::state::{entries, parse_fov, parse_orbit};
+use cgmath::Deg;
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+/// Time events are gathered for before being reported; saving a file often comes as several
+/// events.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Settings read from the configuration file; `None` leaves a setting alone.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+  pub background: Option<Background>,
+  /// Name of the lighting preset to select.
+  pub lighting: Option<String>,
+  /// Properties of the lighting presets, as `<preset>.<property>` and their value.
+  pub preset_properties: Vec<(String, String)>,
+  pub shading: Option<Shading>,
+  pub orbit: Option<Orbit>,
+  pub fov: Option<Deg<f32>>,
+  pub overdraw: Option<bool>,
+  pub stats: Option<bool>,
+}
+
+impl Config {
+  /// Read the configuration; invalid entries are reported and ignored.
+  pub fn load<P>(path: P) -> Result<Self, String>
+  where
+    P: AsRef<Path>,
+  {
+    let path = path.as_ref();
+    let content =
+      fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let mut config = Config::default();
+
+    for (key, value) in entries(&content) {
+      let valid = match key {
+        "background" => {
+          config.background = value.parse().ok();
+          config.background.is_some()
+        }
+
+        "lighting" => {
+          config.lighting = Some(value.to_owned());
+          true
+        }
+
+        "shading" => {
+          config.shading = value.parse().ok();
+          config.shading.is_some()
+        }
+
+        "orbit" => {
+          config.orbit = parse_orbit(value);
+          config.orbit.is_some()
+        }
+
+        "fov" => {
+          config.fov = parse_fov(value);
+          config.fov.is_some()
+        }
+
+        "overdraw" => {
+          config.overdraw = parse_switch(value);
+          config.overdraw.is_some()
+        }
+
+        "stats" => {
+          config.stats = parse_switch(value);
+          config.stats.is_some()
+        }
+
+        _ => match key.strip_prefix("lighting.") {
+          Some(property) => {
+            // checked when applied, against the presets they modify
+            let property = (property.to_owned(), value.to_owned());
+            config.preset_properties.push(property);
+            true
+          }
+
+          None => {
+            eprintln!("{}: unknown setting {}", path.display(), key);
+            continue;
+          }
+        },
+      };
+
+      if !valid {
+        eprintln!("{}: invalid {}: {}", path.display(), key, value);
+      }
+    }
+
+    Ok(config)
+  }
+}
+
+fn parse_switch(s: &str) -> Option<bool> {
+  match s {
+    "on" => Some(true),
+    "off" => Some(false),
+    _ => None,
+  }
+}
+
+pub struct ConfigWatcher {
+  path: PathBuf,
+  events: Receiver<DebouncedEvent>,
+  // events stop being sent when the watcher is dropped
+  _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+  /// Watch the configuration file at `path`.
+  pub fn new<P>(path: P) -> Result<Self, String>
+  where
+    P: Into<PathBuf>,
+  {
+    let path = path.into();
+    let dir = match path.parent() {
+      Some(dir) if !dir.as_os_str().is_empty() => dir.to_owned(),
+      _ => PathBuf::from("."),
+    };
+    let (tx, events) = channel();
+    let mut watcher =
+      watcher(tx, DEBOUNCE).map_err(|e| format!("cannot create the file watcher: {}", e))?;
+
+    watcher
+      .watch(&dir, RecursiveMode::NonRecursive)
+      .map_err(|e| format!("cannot watch {}: {}", dir.display(), e))?;
+
+    Ok(ConfigWatcher {
+      path,
+      events,
+      _watcher: watcher,
+    })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  /// Whether the file changed since the last call.
+  pub fn changed(&self) -> bool {
+    let mut changed = false;
+
+    for event in self.events.try_iter() {
+      match event {
+        DebouncedEvent::Create(ref path)
+        | DebouncedEvent::Write(ref path)
+        | DebouncedEvent::Rename(_, ref path) => {
+          changed |= path.file_name() == self.path.file_name()
+        }
+        DebouncedEvent::Error(e, _) => eprintln!("file watcher error: {}", e),
+        _ => (),
+      }
+    }
+
+    changed
+  }
+}
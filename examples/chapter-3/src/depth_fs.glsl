@@ -0,0 +1,23 @@
+in vec2 v_uv;
+
+out vec3 frag_color;
+
+uniform sampler2D depth;
+uniform float z_near;
+uniform float z_far;
+// 0: gray levels, 1: false colors
+uniform int heatmap;
+
+// blue for what’s close, through green and yellow, to red for what’s far
+vec3 heat(float x) {
+  return clamp(1.5 - abs(4. * x - vec3(3., 2., 1.)), 0., 1.);
+}
+
+void main() {
+  // back from the non-linear depth buffer to the distance along the view axis
+  float z = texture(depth, v_uv).r * 2. - 1.;
+  float view_z = 2. * z_near * z_far / (z_far + z_near - z * (z_far - z_near));
+  float d = (view_z - z_near) / (z_far - z_near);
+
+  frag_color = heatmap == 1 ? heat(d) : vec3(d);
+}
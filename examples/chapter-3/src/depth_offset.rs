@@ -0,0 +1,65 @@
+//! Depth offset of overlays.
+//!
+//! Overlays such as the highlighted or picked triangles are drawn over the surface of the mesh, at
+//! the same depth. Comparing depths with less-or-equal only works if both are transformed by the
+//! very same vertex shader; any other computation, like the Phong or glass shadings’, gets rounded
+//! differently and the overlay flickers through the surface (z-fighting).
+//!
+//! A polygon offset pushes the depth of the overlay towards the camera: `factor` scales the slope
+//! of the polygon in depth, so that grazing polygons get pushed further, and `units` adds a
+//! constant number of the smallest steps of the depth buffer. luminance doesn’t expose it, so it’s
+//! set with raw GL calls around the draws of overlays.
+
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DepthOffset {
+  pub factor: f32,
+  pub units: f32,
+}
+
+impl Default for DepthOffset {
+  /// Pull overlays towards the camera by the least amount that reliably wins the depth test.
+  fn default() -> Self {
+    DepthOffset {
+      factor: -1.,
+      units: -1.,
+    }
+  }
+}
+
+impl FromStr for DepthOffset {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut values = s.split(',').map(str::parse::<f32>);
+
+    match (values.next(), values.next(), values.next()) {
+      (Some(Ok(factor)), Some(Ok(units)), None) if factor.is_finite() && units.is_finite() => {
+        Ok(DepthOffset { factor, units })
+      }
+      _ => Err(format!(
+        "expecting a factor and units separated by a comma: {}",
+        s
+      )),
+    }
+  }
+}
+
+/// Offset the depth of the polygons drawn from now on, or stop offsetting it.
+pub fn set_depth_offset(offset: Option<DepthOffset>) {
+  unsafe {
+    match offset {
+      Some(DepthOffset { factor, units }) => {
+        gl::Enable(gl::POLYGON_OFFSET_FILL);
+        gl::Enable(gl::POLYGON_OFFSET_LINE);
+        gl::PolygonOffset(factor, units);
+      }
+
+      None => {
+        gl::Disable(gl::POLYGON_OFFSET_FILL);
+        gl::Disable(gl::POLYGON_OFFSET_LINE);
+      }
+    }
+  }
+}
@@ -0,0 +1,34 @@
+//! Depth buffer visualization.
+//!
+//! A perspective projection spends most of the precision of the depth buffer close to the near
+//! plane, which is hard to see by looking at the raw values. The scene’s depth is rendered to a
+//! texture, then shown linearized, as gray levels or false colors, so that the depth range can be
+//! checked against the scene.
+
+/// How depth is shown.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DepthView {
+  /// Black on the near plane, white on the far one.
+  Gray,
+  /// From blue on the near plane to red on the far one.
+  Heatmap,
+}
+
+impl DepthView {
+  /// Next view, going back to the normal rendering after the last one.
+  pub fn cycle(view: Option<Self>) -> Option<Self> {
+    match view {
+      None => Some(DepthView::Gray),
+      Some(DepthView::Gray) => Some(DepthView::Heatmap),
+      Some(DepthView::Heatmap) => None,
+    }
+  }
+
+  pub fn name(view: Option<Self>) -> &'static str {
+    match view {
+      None => "off",
+      Some(DepthView::Gray) => "gray",
+      Some(DepthView::Heatmap) => "heatmap",
+    }
+  }
+}
@@ -0,0 +1,26 @@
+layout (triangles) in;
+layout (triangle_strip, max_vertices = 3) out;
+
+in vec3 vs_normal[];
+in vec2 vs_uv[];
+flat in uint vs_object[];
+
+out vec3 v_normal;
+out vec2 v_uv;
+flat out uint v_object;
+
+// cubemap face the triangles are rendered to
+uniform int face;
+
+void main() {
+  for (int i = 0; i < 3; ++i) {
+    gl_Layer = face;
+    gl_Position = gl_in[i].gl_Position;
+    v_normal = vs_normal[i];
+    v_uv = vs_uv[i];
+    v_object = vs_object[i];
+    EmitVertex();
+  }
+
+  EndPrimitive();
+}
@@ -0,0 +1,17 @@
+in vec3 position;
+in vec3 normal;
+in vec2 uv;
+in uint object;
+
+out vec3 vs_normal;
+out vec2 vs_uv;
+flat out uint vs_object;
+
+uniform mat4 view_projection;
+
+void main() {
+  vs_normal = normal;
+  vs_uv = uv;
+  vs_object = object;
+  gl_Position = view_projection * vec4(position, 1.);
+}
@@ -0,0 +1,72 @@
+//! Environment maps rendered on the fly.
+//!
+//! An environment map is a cubemap capturing everything around a point; reflective objects look it
+//! up with their reflection vector. A cubemap framebuffer attaches all six faces at once, so the
+//! scene is rendered face by face with a geometry shader sending the triangles to the face being
+//! rendered through gl_Layer.
+
+use cgmath::{perspective, Deg, Matrix4, Point3, Vector3};
+use luminance::pixel::{Depth32F, RGB32F};
+use luminance::texture::{Cubemap, MagFilter, MinFilter, Sampler};
+use luminance_front::context::GraphicsContext;
+use luminance_front::framebuffer::Framebuffer;
+use luminance_front::Backend;
+
+/// Size of a face of the cubemap, in pixels.
+const SIZE: u32 = 256;
+/// Near and far planes of the cameras rendering the faces.
+const Z_NEAR: f32 = 0.01;
+const Z_FAR: f32 = 10.;
+
+/// Direction and up vector of the camera rendering each face, in the order of the cubemap layers.
+///
+/// Cubemap faces are stored as seen from outside of the cube, hence the upside down cameras.
+const FACES: [([f32; 3], [f32; 3]); 6] = [
+  ([1., 0., 0.], [0., -1., 0.]),
+  ([-1., 0., 0.], [0., -1., 0.]),
+  ([0., 1., 0.], [0., 0., 1.]),
+  ([0., -1., 0.], [0., 0., -1.]),
+  ([0., 0., 1.], [0., -1., 0.]),
+  ([0., 0., -1.], [0., -1., 0.]),
+];
+
+/// Cubemap of what can be seen from a point.
+pub struct EnvMap {
+  pub center: Point3<f32>,
+  pub framebuffer: Framebuffer<Cubemap, RGB32F, Depth32F>,
+}
+
+impl EnvMap {
+  pub fn new<C>(ctxt: &mut C, center: Point3<f32>) -> Result<Self, String>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    let sampler = Sampler {
+      min_filter: MinFilter::Linear,
+      mag_filter: MagFilter::Linear,
+      ..Sampler::default()
+    };
+    let framebuffer = ctxt
+      .new_framebuffer(SIZE, 0, sampler)
+      .map_err(|e| format!("cannot create the environment map framebuffer: {}", e))?;
+
+    Ok(EnvMap {
+      center,
+      framebuffer,
+    })
+  }
+
+  /// View projection matrices of the cameras rendering the faces, in the order of the layers.
+  pub fn face_view_projections(&self) -> Vec<[[f32; 4]; 4]> {
+    let projection = perspective(Deg(90.), 1., Z_NEAR, Z_FAR);
+
+    FACES
+      .iter()
+      .map(|&(dir, up)| {
+        let target = self.center + Vector3::from(dir);
+        let view = Matrix4::look_at(self.center, target, Vector3::from(up));
+        (projection * view).into()
+      })
+      .collect()
+  }
+}
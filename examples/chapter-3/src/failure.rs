@@ -0,0 +1,99 @@
+//! Fatal errors.
+//!
+//! Scripts running the viewer need to tell why it failed without parsing its output: each kind of
+//! error exits with its own status, and errors can be printed as JSON instead of plain text.
+
+use crate::validate::json_string;
+use std::fmt::Display;
+use std::path::Path;
+use std::process::exit;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Print errors as JSON rather than plain text.
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Kinds of fatal errors; the discriminant is the exit status.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorKind {
+  /// Anything not covered by the other kinds.
+  Other = 1,
+  /// Invalid command-line arguments.
+  Usage = 2,
+  /// A file given to the viewer doesn’t exist.
+  NotFound = 3,
+  /// A file given to the viewer can’t be parsed.
+  Parse = 4,
+  /// The graphics context or a GPU resource can’t be created, or rendering failed.
+  Gpu = 5,
+  /// A shader doesn’t compile or link.
+  Shader = 6,
+}
+
+impl ErrorKind {
+  /// Kind of an error loading a file: either it’s not there, or it can’t be parsed.
+  pub fn loading(path: &Path) -> Self {
+    if path.is_file() {
+      ErrorKind::Parse
+    } else {
+      ErrorKind::NotFound
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      ErrorKind::Other => "other",
+      ErrorKind::Usage => "usage",
+      ErrorKind::NotFound => "not-found",
+      ErrorKind::Parse => "parse",
+      ErrorKind::Gpu => "gpu",
+      ErrorKind::Shader => "shader",
+    }
+  }
+}
+
+/// How fatal errors are printed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorFormat {
+  Text,
+  Json,
+}
+
+impl FromStr for ErrorFormat {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "text" => Ok(ErrorFormat::Text),
+      "json" => Ok(ErrorFormat::Json),
+      _ => Err(format!(
+        "unknown error format: {} (expecting text or json)",
+        s
+      )),
+    }
+  }
+}
+
+pub fn set_format(format: ErrorFormat) {
+  JSON.store(format == ErrorFormat::Json, Ordering::Relaxed);
+}
+
+pub fn is_json() -> bool {
+  JSON.load(Ordering::Relaxed)
+}
+
+/// Print an error to stderr and exit with the status of its kind.
+pub fn fail(kind: ErrorKind, message: impl Display) -> ! {
+  if is_json() {
+    eprintln!(
+      "{{\"error\":{},\"status\":{},\"message\":{}}}",
+      json_string(kind.name()),
+      kind as i32,
+      json_string(&message.to_string())
+    );
+  } else {
+    eprintln!("{}", message);
+  }
+
+  exit(kind as i32)
+}
@@ -1,11 +1,49 @@
 in vec3 v_normal;
+in vec2 v_uv;
+flat in uint v_object;
 
 out vec3 frag_color;
 
+// material of the model; programs that don’t set it shade with the default gray
+uniform vec3 ambient = vec3(0.);
+uniform vec3 diffuse = vec3(.6);
+// texture modulating the diffuse color, if the material has one
+uniform sampler2D diffuse_map;
+uniform bool textured = false;
+// back faces are drawn too, and lit from their own side
+uniform bool two_sided = false;
+
+// key, fill and rim lights of the lighting preset, one per column, and the direction they travel
+// in; programs that don’t set them get a single white light from above
+uniform mat3 light_dirs = mat3(vec3(0., -1., -.5), vec3(0., -1., 0.), vec3(0., -1., 0.));
+uniform mat3 light_colors = mat3(vec3(1.), vec3(0.), vec3(0.));
+uniform vec3 ambient_light = vec3(.2);
+uniform float exposure = 1.;
+
+// batched objects get a tint each, so that they can be told apart
+vec3 object_color(uint object) {
+  if (object == 0u) {
+    return textured ? diffuse * texture(diffuse_map, v_uv).rgb : diffuse;
+  }
+
+  float hue = fract(float(object) * .618034);
+  return .45 + .25 * cos(6.28318 * (hue + vec3(0., .33, .67)));
+}
+
+// light received by a surface facing n
+vec3 lambert(vec3 n) {
+  vec3 light = vec3(0.);
+
+  for (int i = 0; i < 3; ++i) {
+    light += light_colors[i] * max(dot(n, -normalize(light_dirs[i])), 0.);
+  }
+
+  return light;
+}
+
 void main() {
-  vec3 obj_color = vec3(.6, .6, .6);
-  vec3 light_dir = vec3(0., -1., -.5);
-  float kd = dot(v_normal, -light_dir);
+  vec3 obj_color = object_color(v_object);
+  vec3 n = normalize(two_sided && !gl_FrontFacing ? -v_normal : v_normal);
 
-  frag_color = obj_color * kd;
+  frag_color = (ambient * ambient_light + obj_color * lambert(n)) * exposure;
 }
@@ -0,0 +1,8 @@
+out vec2 v_uv;
+
+void main() {
+  // a single triangle covering the whole screen; scissor regions can restrict it further
+  vec2 p = vec2(float((gl_VertexID & 1) << 2), float((gl_VertexID & 2) << 1)) - 1.;
+  v_uv = p * .5 + .5;
+  gl_Position = vec4(p, 0., 1.);
+}
@@ -0,0 +1,157 @@
+//! OpenGL debug output.
+//!
+//! luminance catches a lot of misuses, but as soon as shaders or GL state get modified by hand,
+//! some errors are silently swallowed by the driver. When the driver supports `KHR_debug` (core
+//! in OpenGL 4.3), it can report them along with warnings (performance, deprecated behavior, etc.)
+//! through a callback.
+
+use gl::types::{GLchar, GLenum, GLsizei, GLuint};
+use std::ffi::{c_void, CStr};
+use std::process::abort;
+use std::ptr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Stop the program on the first error, so that a debugger shows where it happened.
+static BREAK_ON_ERROR: AtomicBool = AtomicBool::new(false);
+
+/// Severity of debug messages, from the least to the most severe.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+  Notification,
+  Low,
+  Medium,
+  High,
+}
+
+impl FromStr for Severity {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "notification" => Ok(Severity::Notification),
+      "low" => Ok(Severity::Low),
+      "medium" => Ok(Severity::Medium),
+      "high" => Ok(Severity::High),
+      _ => Err(format!(
+        "unknown severity: {} (expecting notification, low, medium or high)",
+        s
+      )),
+    }
+  }
+}
+
+impl Severity {
+  const ALL: [Severity; 4] = [
+    Severity::Notification,
+    Severity::Low,
+    Severity::Medium,
+    Severity::High,
+  ];
+
+  fn from_gl(severity: GLenum) -> Self {
+    match severity {
+      gl::DEBUG_SEVERITY_HIGH => Severity::High,
+      gl::DEBUG_SEVERITY_MEDIUM => Severity::Medium,
+      gl::DEBUG_SEVERITY_LOW => Severity::Low,
+      _ => Severity::Notification,
+    }
+  }
+
+  fn to_gl(self) -> GLenum {
+    match self {
+      Severity::Notification => gl::DEBUG_SEVERITY_NOTIFICATION,
+      Severity::Low => gl::DEBUG_SEVERITY_LOW,
+      Severity::Medium => gl::DEBUG_SEVERITY_MEDIUM,
+      Severity::High => gl::DEBUG_SEVERITY_HIGH,
+    }
+  }
+}
+
+/// Enable the debug output for messages at least as severe as `min_severity`.
+///
+/// The GL functions must have been loaded.
+pub fn enable(min_severity: Severity, break_on_error: bool) -> Result<(), String> {
+  if !gl::DebugMessageCallback::is_loaded() || !gl::DebugMessageControl::is_loaded() {
+    return Err("KHR_debug is not supported by the driver".to_owned());
+  }
+
+  BREAK_ON_ERROR.store(break_on_error, Ordering::Relaxed);
+
+  unsafe {
+    gl::Enable(gl::DEBUG_OUTPUT);
+    // report messages from the offending call rather than later, from another thread
+    gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+    gl::DebugMessageCallback(Some(callback), ptr::null());
+
+    // let the driver filter out messages we’re not interested in
+    for &severity in &Severity::ALL {
+      let enabled = if severity >= min_severity {
+        gl::TRUE
+      } else {
+        gl::FALSE
+      };
+
+      gl::DebugMessageControl(
+        gl::DONT_CARE,
+        gl::DONT_CARE,
+        severity.to_gl(),
+        0,
+        ptr::null(),
+        enabled,
+      );
+    }
+  }
+
+  Ok(())
+}
+
+extern "system" fn callback(
+  source: GLenum,
+  kind: GLenum,
+  id: GLuint,
+  severity: GLenum,
+  _length: GLsizei,
+  message: *const GLchar,
+  _user_param: *mut c_void,
+) {
+  let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+
+  eprintln!(
+    "GL {:?} [{}, {}, {}]: {}",
+    Severity::from_gl(severity),
+    source_name(source),
+    kind_name(kind),
+    id,
+    message
+  );
+
+  // panicking across the FFI boundary is not an option, so abort instead
+  if kind == gl::DEBUG_TYPE_ERROR && BREAK_ON_ERROR.load(Ordering::Relaxed) {
+    eprintln!("aborting on GL error");
+    abort();
+  }
+}
+
+fn source_name(source: GLenum) -> &'static str {
+  match source {
+    gl::DEBUG_SOURCE_API => "api",
+    gl::DEBUG_SOURCE_WINDOW_SYSTEM => "window system",
+    gl::DEBUG_SOURCE_SHADER_COMPILER => "shader compiler",
+    gl::DEBUG_SOURCE_THIRD_PARTY => "third party",
+    gl::DEBUG_SOURCE_APPLICATION => "application",
+    _ => "other",
+  }
+}
+
+fn kind_name(kind: GLenum) -> &'static str {
+  match kind {
+    gl::DEBUG_TYPE_ERROR => "error",
+    gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated behavior",
+    gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+    gl::DEBUG_TYPE_PORTABILITY => "portability",
+    gl::DEBUG_TYPE_PERFORMANCE => "performance",
+    gl::DEBUG_TYPE_MARKER => "marker",
+    _ => "other",
+  }
+}
@@ -0,0 +1,29 @@
+in vec3 v_position;
+in vec3 v_normal;
+
+out vec3 frag_color;
+
+uniform samplerCube environment;
+uniform vec3 camera_pos;
+uniform float ior;
+uniform float dispersion;
+
+void main() {
+  vec3 n = normalize(v_normal);
+  vec3 i = normalize(v_position - camera_pos);
+
+  // light is only refracted once, entering the glass; the index of refraction varies with the
+  // wavelength, so each channel is bent its own way
+  vec3 refracted = vec3(
+    texture(environment, refract(i, n, 1. / (ior - dispersion))).r,
+    texture(environment, refract(i, n, 1. / ior)).g,
+    texture(environment, refract(i, n, 1. / (ior + dispersion))).b
+  );
+  vec3 reflected = texture(environment, reflect(i, n)).rgb;
+
+  // Schlick’s Fresnel, with the reflectance at normal incidence given by the index of refraction
+  float f0 = pow((ior - 1.) / (ior + 1.), 2.);
+  float fresnel = f0 + (1. - f0) * pow(1. - max(dot(-i, n), 0.), 5.);
+
+  frag_color = mix(refracted, reflected, fresnel);
+}
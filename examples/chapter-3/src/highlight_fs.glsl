@@ -0,0 +1,8 @@
+in vec3 v_normal;
+
+out vec3 frag_color;
+
+void main() {
+  // flat red, so that offending triangles stand out whatever the lighting
+  frag_color = vec3(1., 0., 0.);
+}
@@ -0,0 +1,429 @@
+//! Input bindings.
+//!
+//! Rather than matching raw GLFW events in the main loop, key presses and clicks are turned into
+//! named actions. Each action is bound to a chord: a key or mouse button along with the exact
+//! modifiers that must be held, so that N and Shift+N can do different things.
+//!
+//! Continuous inputs are read from axes instead: 2D values gathered over a frame from the cursor
+//! moving while a chord is held, from the scroll wheel or from keys held down, so that dragging the
+//! mouse and holding WASD turn the camera the same way.
+
+use glfw::{Action as KeyAction, Key, Modifiers, MouseButton, WindowEvent};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::time::Instant;
+
+/// Distance the cursor can move between pressing and releasing a button for a click, in pixels.
+const CLICK_SLOP: f64 = 3.;
+
+/// Everything the user can ask the viewer to do.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Action {
+  Quit,
+  RecomputeNormals,
+  FlipNormals,
+  SlowDown,
+  SpeedUp,
+  CaptureFrame,
+  ToggleStats,
+  WidenFov,
+  NarrowFov,
+  ToggleDollyZoom,
+  CycleBackground,
+  CycleLighting,
+  CycleDepthView,
+  ToggleOverdraw,
+  CycleShading,
+  ExportCamera,
+  ImportCamera,
+}
+
+impl Action {
+  pub fn name(self) -> &'static str {
+    match self {
+      Action::Quit => "quit",
+      Action::RecomputeNormals => "recompute-normals",
+      Action::FlipNormals => "flip-normals",
+      Action::SlowDown => "slow-down",
+      Action::SpeedUp => "speed-up",
+      Action::CaptureFrame => "capture-frame",
+      Action::ToggleStats => "toggle-stats",
+      Action::WidenFov => "widen-fov",
+      Action::NarrowFov => "narrow-fov",
+      Action::ToggleDollyZoom => "toggle-dolly-zoom",
+      Action::CycleBackground => "cycle-background",
+      Action::CycleLighting => "cycle-lighting",
+      Action::CycleDepthView => "cycle-depth-view",
+      Action::ToggleOverdraw => "toggle-overdraw",
+      Action::CycleShading => "cycle-shading",
+      Action::ExportCamera => "export-camera",
+      Action::ImportCamera => "import-camera",
+    }
+  }
+}
+
+impl FromStr for Action {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "quit" => Ok(Action::Quit),
+      "recompute-normals" => Ok(Action::RecomputeNormals),
+      "flip-normals" => Ok(Action::FlipNormals),
+      "slow-down" => Ok(Action::SlowDown),
+      "speed-up" => Ok(Action::SpeedUp),
+      "capture-frame" => Ok(Action::CaptureFrame),
+      "toggle-stats" => Ok(Action::ToggleStats),
+      "widen-fov" => Ok(Action::WidenFov),
+      "narrow-fov" => Ok(Action::NarrowFov),
+      "toggle-dolly-zoom" => Ok(Action::ToggleDollyZoom),
+      "cycle-background" => Ok(Action::CycleBackground),
+      "cycle-lighting" => Ok(Action::CycleLighting),
+      "cycle-depth-view" => Ok(Action::CycleDepthView),
+      "toggle-overdraw" => Ok(Action::ToggleOverdraw),
+      "cycle-shading" => Ok(Action::CycleShading),
+      "export-camera" => Ok(Action::ExportCamera),
+      "import-camera" => Ok(Action::ImportCamera),
+      _ => Err(format!("unknown action: {}", s)),
+    }
+  }
+}
+
+/// Key or mouse button starting a chord.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Trigger {
+  Key(Key),
+  Mouse(MouseButton),
+}
+
+/// A key or mouse button and the modifiers held with it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Chord {
+  pub trigger: Trigger,
+  pub mods: Modifiers,
+}
+
+impl Chord {
+  pub fn key(key: Key) -> Self {
+    Chord {
+      trigger: Trigger::Key(key),
+      mods: Modifiers::empty(),
+    }
+  }
+
+  pub fn mouse(button: MouseButton) -> Self {
+    Chord {
+      trigger: Trigger::Mouse(button),
+      mods: Modifiers::empty(),
+    }
+  }
+
+  pub fn shift(self) -> Self {
+    Chord {
+      mods: self.mods | Modifiers::Shift,
+      ..self
+    }
+  }
+
+  /// Chord pressed by an event, if it’s a key or button press.
+  fn pressed(event: &WindowEvent) -> Option<Self> {
+    let (trigger, mods) = match *event {
+      WindowEvent::Key(key, _, KeyAction::Press, mods) => (Trigger::Key(key), mods),
+      WindowEvent::MouseButton(button, KeyAction::Press, mods) => (Trigger::Mouse(button), mods),
+      _ => return None,
+    };
+
+    // lock keys must not prevent chords from matching
+    let mods = mods & (Modifiers::Shift | Modifiers::Control | Modifiers::Alt | Modifiers::Super);
+
+    Some(Chord { trigger, mods })
+  }
+}
+
+/// Everything the user can steer continuously.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Axis {
+  /// Turn the camera around its target, in pixels dragged: x around the vertical axis, y above or
+  /// below the horizon.
+  Orbit,
+  /// Move the camera closer to its target, in scroll steps along y.
+  Zoom,
+}
+
+/// What moves an axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AxisBinding {
+  /// The cursor moving while the chord is held, scaled.
+  Drag { chord: Chord, scale: f32 },
+  /// The scroll wheel, scaled.
+  Scroll { scale: f32 },
+  /// A key held, pushing the axis by `value` per second.
+  Key { key: Key, value: [f32; 2] },
+}
+
+/// Map chords to actions, and inputs to axes.
+#[derive(Clone, Debug)]
+pub struct Bindings {
+  bindings: Vec<(Chord, Action)>,
+  axes: Vec<(AxisBinding, Axis)>,
+}
+
+impl Default for Bindings {
+  fn default() -> Self {
+    let mut bindings = Bindings {
+      bindings: Vec::new(),
+      axes: Vec::new(),
+    };
+
+    bindings.bind(Chord::key(Key::Escape), Action::Quit);
+    bindings.bind(Chord::key(Key::N), Action::RecomputeNormals);
+    bindings.bind(Chord::key(Key::N).shift(), Action::FlipNormals);
+    bindings.bind(Chord::key(Key::LeftBracket), Action::SlowDown);
+    bindings.bind(Chord::key(Key::RightBracket), Action::SpeedUp);
+    bindings.bind(Chord::key(Key::F9), Action::CaptureFrame);
+    bindings.bind(Chord::key(Key::F3), Action::ToggleStats);
+    bindings.bind(Chord::key(Key::Period), Action::WidenFov);
+    bindings.bind(Chord::key(Key::Comma), Action::NarrowFov);
+    bindings.bind(Chord::key(Key::Z), Action::ToggleDollyZoom);
+    bindings.bind(Chord::key(Key::B), Action::CycleBackground);
+    bindings.bind(Chord::key(Key::L), Action::CycleLighting);
+    bindings.bind(Chord::key(Key::V), Action::CycleDepthView);
+    bindings.bind(Chord::key(Key::O), Action::ToggleOverdraw);
+    bindings.bind(Chord::key(Key::M), Action::CycleShading);
+    bindings.bind(Chord::key(Key::F5), Action::ExportCamera);
+    bindings.bind(Chord::key(Key::F6), Action::ImportCamera);
+
+    bindings.bind_axis(
+      AxisBinding::Drag {
+        chord: Chord::mouse(MouseButton::Button1),
+        scale: 1.,
+      },
+      Axis::Orbit,
+    );
+    for &(key, value) in &[
+      (Key::A, [-300., 0.]),
+      (Key::D, [300., 0.]),
+      (Key::S, [0., -300.]),
+      (Key::W, [0., 300.]),
+    ] {
+      bindings.bind_axis(AxisBinding::Key { key, value }, Axis::Orbit);
+    }
+    bindings.bind_axis(AxisBinding::Scroll { scale: 1. }, Axis::Zoom);
+    // dragging up with Shift held gets closer, for touchpads without a scroll wheel
+    bindings.bind_axis(
+      AxisBinding::Drag {
+        chord: Chord::mouse(MouseButton::Button1).shift(),
+        scale: -0.02,
+      },
+      Axis::Zoom,
+    );
+    bindings.bind_axis(
+      AxisBinding::Key {
+        key: Key::E,
+        value: [0., 5.],
+      },
+      Axis::Zoom,
+    );
+    bindings.bind_axis(
+      AxisBinding::Key {
+        key: Key::Q,
+        value: [0., -5.],
+      },
+      Axis::Zoom,
+    );
+
+    bindings
+  }
+}
+
+impl Bindings {
+  /// Bind a chord to an action, replacing any previous binding of that chord.
+  pub fn bind(&mut self, chord: Chord, action: Action) {
+    self.bindings.retain(|&(c, _)| c != chord);
+    self.bindings.push((chord, action));
+  }
+
+  /// Bind an input to an axis; an axis can be moved by several inputs.
+  pub fn bind_axis(&mut self, binding: AxisBinding, axis: Axis) {
+    self.axes.push((binding, axis));
+  }
+
+  /// Action triggered by an event, if any.
+  pub fn action(&self, event: &WindowEvent) -> Option<Action> {
+    let chord = Chord::pressed(event)?;
+
+    self
+      .bindings
+      .iter()
+      .find(|&&(c, _)| c == chord)
+      .map(|&(_, action)| action)
+  }
+
+  /// Axis dragged by holding a chord, if any.
+  fn drag_axis(&self, chord: Chord) -> Option<(Axis, f32)> {
+    self.axes.iter().find_map(|&(binding, axis)| match binding {
+      AxisBinding::Drag { chord: c, scale } if c == chord => Some((axis, scale)),
+      _ => None,
+    })
+  }
+}
+
+/// State of the axes: cursor, drag in progress, keys held and values gathered since they were last
+/// read.
+#[derive(Debug)]
+pub struct Axes {
+  values: HashMap<Axis, [f32; 2]>,
+  keys: HashSet<Key>,
+  cursor: [f64; 2],
+  drag: Option<AxisDrag>,
+  last_read: Instant,
+}
+
+/// Chord held across the window.
+#[derive(Clone, Copy, Debug)]
+struct AxisDrag {
+  button: MouseButton,
+  axis: Axis,
+  scale: f32,
+  drag: Drag,
+}
+
+impl Axes {
+  pub fn new() -> Self {
+    Axes {
+      values: HashMap::new(),
+      keys: HashSet::new(),
+      cursor: [0., 0.],
+      drag: None,
+      last_read: Instant::now(),
+    }
+  }
+
+  /// Follow an event.
+  ///
+  /// When it releases a drag during which the cursor barely moved, the drag is a click rather than
+  /// a drag, and its axis is returned.
+  pub fn handle(&mut self, bindings: &Bindings, event: &WindowEvent) -> Option<Axis> {
+    match *event {
+      WindowEvent::Key(key, _, KeyAction::Press, _) => {
+        self.keys.insert(key);
+      }
+
+      WindowEvent::Key(key, _, KeyAction::Release, _) => {
+        self.keys.remove(&key);
+      }
+
+      WindowEvent::MouseButton(button, KeyAction::Press, _) if self.drag.is_none() => {
+        let chord = Chord::pressed(event)?;
+        let (axis, scale) = bindings.drag_axis(chord)?;
+
+        self.drag = Some(AxisDrag {
+          button,
+          axis,
+          scale,
+          drag: Drag::new(self.cursor),
+        });
+      }
+
+      WindowEvent::MouseButton(button, KeyAction::Release, _) => {
+        let drag = self.drag.filter(|drag| drag.button == button)?;
+        self.drag = None;
+
+        if drag.drag.is_click() {
+          return Some(drag.axis);
+        }
+      }
+
+      WindowEvent::CursorPos(x, y) => {
+        self.cursor = [x, y];
+
+        if let Some(ref mut drag) = self.drag {
+          let [dx, dy] = drag.drag.move_to([x, y]);
+          let (axis, scale) = (drag.axis, drag.scale);
+          self.push(axis, [dx * scale, dy * scale]);
+        }
+      }
+
+      WindowEvent::Scroll(x, y) => {
+        for &(binding, axis) in &bindings.axes {
+          if let AxisBinding::Scroll { scale } = binding {
+            self.push(axis, [x as f32 * scale, y as f32 * scale]);
+          }
+        }
+      }
+
+      _ => (),
+    }
+
+    None
+  }
+
+  /// Values of the axes since they were last read; keys held count for the time elapsed since.
+  pub fn read(&mut self, bindings: &Bindings) -> AxisValues {
+    let now = Instant::now();
+    let dt = now.duration_since(self.last_read).as_secs_f32();
+    self.last_read = now;
+
+    for &(binding, axis) in &bindings.axes {
+      if let AxisBinding::Key { key, value } = binding {
+        if self.keys.contains(&key) {
+          self.push(axis, [value[0] * dt, value[1] * dt]);
+        }
+      }
+    }
+
+    AxisValues(self.values.drain().collect())
+  }
+
+  fn push(&mut self, axis: Axis, [x, y]: [f32; 2]) {
+    let value = self.values.entry(axis).or_insert([0., 0.]);
+    value[0] += x;
+    value[1] += y;
+  }
+}
+
+/// Values of the axes over a frame.
+#[derive(Clone, Debug, Default)]
+pub struct AxisValues(HashMap<Axis, [f32; 2]>);
+
+impl AxisValues {
+  pub fn get(&self, axis: Axis) -> [f32; 2] {
+    self.0.get(&axis).copied().unwrap_or([0., 0.])
+  }
+}
+
+/// Mouse drag, telling clicks from drags.
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+  /// Where the button was pressed.
+  start: [f64; 2],
+  /// Where the cursor was last seen.
+  last: [f64; 2],
+}
+
+impl Drag {
+  fn new(cursor: [f64; 2]) -> Self {
+    Drag {
+      start: cursor,
+      last: cursor,
+    }
+  }
+
+  /// Follow the cursor to a new position, returning how far it moved since last time, in pixels.
+  fn move_to(&mut self, cursor: [f64; 2]) -> [f32; 2] {
+    let delta = [
+      (cursor[0] - self.last[0]) as f32,
+      (cursor[1] - self.last[1]) as f32,
+    ];
+    self.last = cursor;
+    delta
+  }
+
+  /// Whether the cursor stayed where the button was pressed, in which case the drag is a click.
+  fn is_click(&self) -> bool {
+    let dx = self.last[0] - self.start[0];
+    let dy = self.last[1] - self.start[1];
+
+    dx * dx + dy * dy <= CLICK_SLOP * CLICK_SLOP
+  }
+}
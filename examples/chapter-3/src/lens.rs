@@ -0,0 +1,97 @@
+//! Camera lens.
+//!
+//! The field of view can be changed at runtime. Widening it makes the scene look smaller and
+//! exaggerates perspective; narrowing it flattens the scene, like a telephoto lens.
+//!
+//! A dolly zoom (the “vertigo effect”) changes the field of view while moving the camera so that
+//! the subject keeps the same size on screen: only the perspective changes, the background
+//! stretching away or closing in.
+
+use cgmath::{Angle, Deg};
+
+/// Range of the field of view.
+const MIN_FOVY: Deg<f32> = Deg(10.);
+const MAX_FOVY: Deg<f32> = Deg(150.);
+
+/// Change of the field of view per key press.
+const FOVY_STEP: Deg<f32> = Deg(5.);
+
+/// Angular speed of the dolly zoom oscillation, in radians per second.
+const DOLLY_ZOOM_SPEED: f32 = 0.8;
+
+#[derive(Clone, Copy, Debug)]
+struct DollyZoom {
+  /// Time at which the dolly zoom started.
+  start: f32,
+  /// Field of view when the dolly zoom started, around which it oscillates.
+  fovy: Deg<f32>,
+  /// Width of the view at the subject, kept constant.
+  width: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Lens {
+  /// Vertical field of view.
+  pub fovy: Deg<f32>,
+  dolly_zoom: Option<DollyZoom>,
+}
+
+impl Lens {
+  pub fn new(fovy: Deg<f32>) -> Self {
+    Lens {
+      fovy,
+      dolly_zoom: None,
+    }
+  }
+
+  pub fn widen(&mut self) {
+    self.set_fovy(self.fovy + FOVY_STEP);
+  }
+
+  pub fn narrow(&mut self) {
+    self.set_fovy(self.fovy - FOVY_STEP);
+  }
+
+  pub fn set_fovy(&mut self, fovy: Deg<f32>) {
+    // the dolly zoom drives the field of view on its own
+    if self.dolly_zoom.is_none() {
+      self.fovy = Deg(fovy.0.clamp(MIN_FOVY.0, MAX_FOVY.0));
+    }
+  }
+
+  pub fn is_dolly_zooming(&self) -> bool {
+    self.dolly_zoom.is_some()
+  }
+
+  /// Start a dolly zoom at time `t` on a subject at `distance` from the camera, or stop it and get
+  /// back to the field of view it started from.
+  pub fn toggle_dolly_zoom(&mut self, t: f32, distance: f32) {
+    match self.dolly_zoom.take() {
+      Some(dolly_zoom) => self.fovy = dolly_zoom.fovy,
+
+      None => {
+        self.dolly_zoom = Some(DollyZoom {
+          start: t,
+          fovy: self.fovy,
+          width: 2. * distance * (self.fovy / 2.).tan(),
+        });
+      }
+    }
+  }
+
+  /// Animate the field of view at time `t`.
+  ///
+  /// During a dolly zoom, return the distance at which the camera must stand from the subject.
+  pub fn update(&mut self, t: f32) -> Option<f32> {
+    let dolly_zoom = self.dolly_zoom?;
+
+    // oscillate around the initial field of view, as far as the range allows on both sides
+    let amplitude = (dolly_zoom.fovy - MIN_FOVY)
+      .0
+      .min((MAX_FOVY - dolly_zoom.fovy).0);
+    let phase = (t - dolly_zoom.start) * DOLLY_ZOOM_SPEED;
+    self.fovy = dolly_zoom.fovy + Deg(amplitude * phase.sin());
+
+    Some(dolly_zoom.width / (2. * (self.fovy / 2.).tan()))
+  }
+}
@@ -0,0 +1,219 @@
+//! Lighting presets.
+//!
+//! The model is lit by up to three directional lights, named after the three-point lighting of
+//! photo studios: the key light gives the main shading, the fill light softens the shadows it
+//! leaves and the rim light, from behind, outlines the silhouette. A preset sets all three lights
+//! at once, along with the ambient light, the background and the exposure, so that the model can be
+//! checked under very different conditions with a single key.
+//!
+//! Four presets are built in. They are stored in the viewer state file along with the others, where
+//! they can be tweaked, and new presets can be added there too.
+
+use crate::background::Background;
+use std::str::FromStr;
+
+/// Directional light.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+  /// Direction the light travels in.
+  pub direction: [f32; 3],
+  /// Color and intensity; black for a light that’s off.
+  pub color: [f32; 3],
+}
+
+const OFF: Light = Light {
+  direction: [0., -1., 0.],
+  color: [0., 0., 0.],
+};
+
+/// Names of the key, fill and rim lights in the state file.
+const LIGHT_NAMES: [&str; 3] = ["key", "fill", "rim"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lighting {
+  pub name: String,
+  /// Key, fill and rim lights.
+  pub lights: [Light; 3],
+  /// Light coming from everywhere, multiplying the ambient color of the material.
+  pub ambient: [f32; 3],
+  pub background: Background,
+  /// Factor the shading is multiplied by, so that dim presets stay readable.
+  pub exposure: f32,
+}
+
+impl Lighting {
+  /// Preset with all the lights off, to be filled in.
+  pub fn dark(name: &str) -> Self {
+    Lighting {
+      name: name.to_owned(),
+      lights: [OFF; 3],
+      ambient: [0., 0., 0.],
+      background: Background::Solid,
+      exposure: 1.,
+    }
+  }
+
+  /// Built-in presets.
+  pub fn builtins() -> Vec<Self> {
+    vec![
+      // a single white light from above, under a clear sky
+      Lighting {
+        lights: [
+          Light {
+            direction: [0., -1., -0.5],
+            color: [1., 1., 1.],
+          },
+          OFF,
+          OFF,
+        ],
+        ambient: [0.2, 0.2, 0.2],
+        background: Background::Gradient,
+        ..Lighting::dark("noon")
+      },
+      // warm key light, cool fill light and a rim light, on a plain background
+      Lighting {
+        lights: [
+          Light {
+            direction: [0.6, -0.7, -0.5],
+            color: [1., 0.92, 0.8],
+          },
+          Light {
+            direction: [-0.8, -0.2, -0.4],
+            color: [0.25, 0.3, 0.4],
+          },
+          Light {
+            direction: [0., -0.3, 1.],
+            color: [0.8, 0.8, 0.8],
+          },
+        ],
+        ambient: [0.15, 0.15, 0.15],
+        ..Lighting::dark("studio")
+      },
+      // low orange sun with a purple sky
+      Lighting {
+        lights: [
+          Light {
+            direction: [-0.9, -0.2, -0.3],
+            color: [1.2, 0.55, 0.25],
+          },
+          Light {
+            direction: [0.5, -0.5, 0.5],
+            color: [0.15, 0.1, 0.25],
+          },
+          OFF,
+        ],
+        ambient: [0.2, 0.12, 0.2],
+        background: Background::Gradient,
+        ..Lighting::dark("sunset")
+      },
+      // dim blue moonlight, exposed brighter
+      Lighting {
+        lights: [
+          Light {
+            direction: [0.3, -0.8, 0.4],
+            color: [0.25, 0.3, 0.5],
+          },
+          OFF,
+          OFF,
+        ],
+        ambient: [0.03, 0.04, 0.08],
+        exposure: 2.5,
+        ..Lighting::dark("night")
+      },
+    ]
+  }
+
+  /// Directions of the key, fill and rim lights, as the columns of a matrix.
+  pub fn directions(&self) -> [[f32; 3]; 3] {
+    let [key, fill, rim] = self.lights;
+    [key.direction, fill.direction, rim.direction]
+  }
+
+  /// Colors of the key, fill and rim lights, as the columns of a matrix.
+  pub fn colors(&self) -> [[f32; 3]; 3] {
+    let [key, fill, rim] = self.lights;
+    [key.color, fill.color, rim.color]
+  }
+
+  /// Set a property of the preset from the state file.
+  ///
+  /// Lights are given as their direction followed by their color, the ambient light as a color.
+  pub fn set(&mut self, property: &str, value: &str) -> Result<(), String> {
+    if let Some(i) = LIGHT_NAMES.iter().position(|&name| name == property) {
+      let v = parse_floats(value, 6)?;
+
+      // the shaders normalize the direction, which a zero vector would turn into NaNs
+      if v[..3].iter().all(|&c| c == 0.) {
+        return Err(format!("{} light has no direction: {}", property, value));
+      }
+
+      self.lights[i] = Light {
+        direction: [v[0], v[1], v[2]],
+        color: [v[3], v[4], v[5]],
+      };
+      return Ok(());
+    }
+
+    match property {
+      "ambient" => {
+        let v = parse_floats(value, 3)?;
+        self.ambient = [v[0], v[1], v[2]];
+      }
+      "background" => self.background = value.parse()?,
+      "exposure" => {
+        self.exposure = value
+          .parse()
+          .ok()
+          .filter(|exposure: &f32| exposure.is_finite() && *exposure > 0.)
+          .ok_or_else(|| format!("invalid exposure: {}", value))?
+      }
+      _ => return Err(format!("unknown lighting property: {}", property)),
+    }
+
+    Ok(())
+  }
+
+  /// Properties of the preset, as written in the state file.
+  pub fn properties(&self) -> Vec<(&'static str, String)> {
+    let join = |values: &[f32]| {
+      values
+        .iter()
+        .map(f32::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+    };
+    let mut properties = LIGHT_NAMES
+      .iter()
+      .zip(&self.lights)
+      .map(|(&name, light)| {
+        let [x, y, z] = light.direction;
+        let [r, g, b] = light.color;
+        (name, join(&[x, y, z, r, g, b]))
+      })
+      .collect::<Vec<_>>();
+
+    properties.push(("ambient", join(&self.ambient)));
+    properties.push(("background", self.background.name().to_owned()));
+    properties.push(("exposure", self.exposure.to_string()));
+    properties
+  }
+}
+
+/// Parse exactly `n` finite numbers separated by spaces.
+fn parse_floats(s: &str, n: usize) -> Result<Vec<f32>, String> {
+  let values = s
+    .split_whitespace()
+    .map(f32::from_str)
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| format!("invalid number in {}: {}", s, e))?;
+
+  if values.iter().any(|v| !v.is_finite()) {
+    return Err(format!("invalid number in {}", s));
+  }
+
+  if values.len() != n {
+    return Err(format!("expecting {} numbers: {}", n, s));
+  }
+
+  Ok(values)
+}
@@ -1,30 +1,125 @@
-use cgmath::{perspective, EuclideanSpace, Matrix4, Point3, Rad, Vector3};
-use glfw::{Action, Context as _, Key, WindowEvent};
+mod analysis;
+mod animation;
+mod background;
+mod batch;
+mod bench;
+mod bvh;
+mod camera_file;
+mod capture;
+mod cli;
+mod config;
+mod depth_offset;
+mod depth_view;
+mod envmap;
+mod failure;
+mod gl_debug;
+mod input;
+mod lens;
+mod lighting;
+mod material;
+mod obj;
+mod orbit;
+mod remote;
+mod scene;
+mod scene_file;
+mod script;
+mod session;
+mod shading;
+mod shapes;
+mod snapshot;
+mod state;
+mod stats;
+mod texture;
+mod time;
+mod uniform_cache;
+mod validate;
+
+use crate::analysis::MeshAnalysis;
+use crate::background::{Background, CHECKER_SQUARE};
+use crate::batch::{batch_placed, normal_matrix, row_transforms};
+use crate::bench::Bench;
+use crate::bvh::{Bvh, Ray};
+use crate::camera_file::CameraFile;
+use crate::capture::FrameCapture;
+use crate::cli::{CliArgs, USAGE};
+use crate::config::{Config, ConfigWatcher};
+use crate::depth_offset::set_depth_offset;
+use crate::depth_view::DepthView;
+use crate::envmap::EnvMap;
+use crate::failure::{fail, ErrorKind};
+use crate::input::{Action, Axes, Axis, Bindings};
+use crate::lens::Lens;
+use crate::lighting::Lighting;
+use crate::material::Material;
+use crate::obj::Obj;
+use crate::orbit::Orbit;
+use crate::remote::{Command, Remote};
+use crate::scene::SceneObject;
+use crate::scene_file::{Overrides, SceneFile};
+use crate::script::Script;
+use crate::session::{FrameInput, Session};
+use crate::shading::Shading;
+use crate::state::{set_preset_property, ViewerState};
+use crate::stats::{FrameStats, StatsReport};
+use crate::texture::load_texture;
+use crate::time::Time;
+use crate::uniform_cache::ShaderCache;
+use cgmath::{
+  perspective, EuclideanSpace, InnerSpace, Matrix3, Matrix4, Point3, Rad, SquareMatrix, Vector3,
+};
+use glfw::{Context as _, MouseButton, WindowEvent};
+use luminance::blending::{Blending, Equation, Factor};
+use luminance::face_culling::{FaceCulling, FaceCullingMode, FaceCullingOrder};
+use luminance::pipeline::TextureBinding;
+use luminance::pixel::{Depth32F, Floating, NormRGBA8UI, R32F};
+use luminance::scissor::ScissorRegion;
+use luminance::shader::ProgramError;
+use luminance::texture::{Cubemap, Dim2, Sampler};
 use luminance_derive::{Semantics, UniformInterface, Vertex};
 use luminance_front::context::GraphicsContext;
-use luminance_front::pipeline::PipelineState;
+use luminance_front::depth_test::DepthComparison;
+use luminance_front::pipeline::{BoundTexture, PipelineError, PipelineState};
 use luminance_front::render_state::RenderState;
-use luminance_front::shader::Uniform;
-use luminance_front::tess::{Interleaved, Mode, Tess, TessError};
-use luminance_front::Backend;
+use luminance_front::shader::{Program, Uniform};
+use luminance_front::shading_gate::ShadingGate;
+use luminance_front::tess::{Interleaved, Mode, Tess};
 use luminance_glfw::GlfwSurface;
 use luminance_windowing::{WindowDim, WindowOpt};
-use std::collections::HashMap;
-use std::env;
-use std::fs::File;
-use std::io::Read as _;
-use std::path::Path;
+use std::ffi::c_void;
+use std::fs;
 use std::process::exit;
-use std::time::Instant;
-use try_guard::verify;
-use wavefront_obj::obj;
+use std::time::Duration;
 
 const VS_STR: &str = include_str!("vs.glsl");
 const FS_STR: &str = include_str!("fs.glsl");
+const HIGHLIGHT_FS_STR: &str = include_str!("highlight_fs.glsl");
+const ENV_VS_STR: &str = include_str!("env_vs.glsl");
+const ENV_GS_STR: &str = include_str!("env_gs.glsl");
+const CHROME_VS_STR: &str = include_str!("chrome_vs.glsl");
+const CHROME_FS_STR: &str = include_str!("chrome_fs.glsl");
+const STUDIO_FS_STR: &str = include_str!("studio_fs.glsl");
+const GLASS_FS_STR: &str = include_str!("glass_fs.glsl");
+const PHONG_FS_STR: &str = include_str!("phong_fs.glsl");
+const FULLSCREEN_VS_STR: &str = include_str!("fullscreen_vs.glsl");
+const DIVIDER_FS_STR: &str = include_str!("divider_fs.glsl");
+const BACKGROUND_FS_STR: &str = include_str!("background_fs.glsl");
+const DEPTH_FS_STR: &str = include_str!("depth_fs.glsl");
+const OVERRIDE_VS_STR: &str = include_str!("override_vs.glsl");
+const OVERRIDE_FS_STR: &str = include_str!("override_fs.glsl");
+const OVERDRAW_FS_STR: &str = include_str!("overdraw_fs.glsl");
+const OVERDRAW_VIEW_FS_STR: &str = include_str!("overdraw_view_fs.glsl");
 
 const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_2);
-const Z_NEAR: f32 = 0.1;
-const Z_FAR: f32 = 10.;
+/// Number of fragments per pixel shown in red in the overdraw view.
+const OVERDRAW_MAX: f32 = 8.;
+
+/// Smallest ratio between the near and far planes; closer near planes waste depth precision.
+const MIN_NEAR_FAR_RATIO: f32 = 1e-3;
+/// Radius of the studio around a glass model.
+const STUDIO_RADIUS: f32 = 5.;
+
+/// Distance from the divider of the split view under which it can be dragged, in pixels.
+const DIVIDER_GRAB: f32 = 8.;
 
 #[derive(Debug, UniformInterface)]
 struct ShaderInterface {
@@ -32,6 +127,180 @@ struct ShaderInterface {
   projection: Uniform<[[f32; 4]; 4]>,
   #[uniform(unbound)]
   view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  model: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  normal_matrix: Uniform<[[f32; 3]; 3]>,
+  #[uniform(unbound)]
+  ambient: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  diffuse: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  diffuse_map: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  textured: Uniform<bool>,
+  #[uniform(unbound)]
+  two_sided: Uniform<bool>,
+  #[uniform(unbound)]
+  light_dirs: Uniform<[[f32; 3]; 3]>,
+  #[uniform(unbound)]
+  light_colors: Uniform<[[f32; 3]; 3]>,
+  #[uniform(unbound)]
+  ambient_light: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  exposure: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct EnvInterface {
+  #[uniform(unbound)]
+  view_projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  face: Uniform<i32>,
+  #[uniform(unbound)]
+  ambient: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  diffuse: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  diffuse_map: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  textured: Uniform<bool>,
+  #[uniform(unbound)]
+  light_dirs: Uniform<[[f32; 3]; 3]>,
+  #[uniform(unbound)]
+  light_colors: Uniform<[[f32; 3]; 3]>,
+  #[uniform(unbound)]
+  ambient_light: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  exposure: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct ChromeInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  environment: Uniform<TextureBinding<Cubemap, Floating>>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct GlassInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  model: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  normal_matrix: Uniform<[[f32; 3]; 3]>,
+  #[uniform(unbound)]
+  environment: Uniform<TextureBinding<Cubemap, Floating>>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  ior: Uniform<f32>,
+  #[uniform(unbound)]
+  dispersion: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct PhongInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  model: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  normal_matrix: Uniform<[[f32; 3]; 3]>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  ambient: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  diffuse: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  specular: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  shininess: Uniform<f32>,
+  #[uniform(unbound)]
+  diffuse_map: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  textured: Uniform<bool>,
+  #[uniform(unbound)]
+  two_sided: Uniform<bool>,
+  #[uniform(unbound)]
+  light_dirs: Uniform<[[f32; 3]; 3]>,
+  #[uniform(unbound)]
+  light_colors: Uniform<[[f32; 3]; 3]>,
+  #[uniform(unbound)]
+  ambient_light: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  exposure: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct BackgroundInterface {
+  #[uniform(unbound)]
+  pattern: Uniform<i32>,
+  #[uniform(unbound)]
+  color_a: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  color_b: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  square: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct DepthInterface {
+  #[uniform(unbound)]
+  depth: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  z_near: Uniform<f32>,
+  #[uniform(unbound)]
+  z_far: Uniform<f32>,
+  #[uniform(unbound)]
+  heatmap: Uniform<i32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct OverrideInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  model: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  normal_matrix: Uniform<[[f32; 3]; 3]>,
+  #[uniform(unbound)]
+  mode: Uniform<i32>,
+  #[uniform(unbound)]
+  cell: Uniform<f32>,
+  #[uniform(unbound)]
+  diffuse: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  diffuse_map: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  textured: Uniform<bool>,
+  #[uniform(unbound)]
+  two_sided: Uniform<bool>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct OverdrawInterface {
+  projection: Uniform<[[f32; 4]; 4]>,
+  view: Uniform<[[f32; 4]; 4]>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct OverdrawViewInterface {
+  counts: Uniform<TextureBinding<Dim2, Floating>>,
+  max_count: Uniform<f32>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
@@ -40,176 +309,1725 @@ pub enum VertexSemantics {
   Position,
   #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
   Normal,
+  #[sem(name = "uv", repr = "[f32; 2]", wrapper = "VertexUV")]
+  UV,
+  #[sem(name = "object", repr = "u32", wrapper = "VertexObject")]
+  Object,
 }
 
 #[derive(Clone, Copy, Debug, Vertex)]
 #[vertex(sem = "VertexSemantics")]
-struct Vertex {
+pub struct Vertex {
   position: VertexPosition,
   normal: VertexNormal,
+  uv: VertexUV,
+  object: VertexObject,
 }
 
-type VertexIndex = u32;
-
-struct Obj {
-  vertices: Vec<Vertex>,
-  indices: Vec<VertexIndex>,
-}
+pub type VertexIndex = u32;
 
-impl Obj {
-  fn to_tess<C>(self, ctxt: &mut C) -> Result<Tess<Vertex, VertexIndex, (), Interleaved>, TessError>
-  where
-    C: GraphicsContext<Backend = Backend>,
-  {
-    ctxt
-      .new_tess()
-      .set_mode(Mode::Triangle)
-      .set_vertices(self.vertices)
-      .set_indices(self.indices)
-      .build()
-  }
+fn main() {
+  let cli = match CliArgs::parse() {
+    Ok(cli) => cli,
+    Err(e) => {
+      // the error format is looked for on its own, since the arguments can’t be parsed
+      failure::set_format(cli::error_format());
 
-  fn load<P>(path: P) -> Result<Self, String>
-  where
-    P: AsRef<Path>,
-  {
-    let file_content = {
-      let mut file = File::open(path).map_err(|e| format!("cannot open file: {}", e))?;
-      let mut content = String::new();
-      file.read_to_string(&mut content).unwrap();
-      content
-    };
-    let obj_set = obj::parse(file_content).map_err(|e| format!("cannot parse: {:?}", e))?;
-    let objects = obj_set.objects;
-
-    verify!(objects.len() == 1).ok_or("expecting a single object".to_owned())?;
-
-    let object = objects.into_iter().next().unwrap();
-
-    verify!(object.geometry.len() == 1).ok_or("expecting a single geometry".to_owned())?;
-
-    let geometry = object.geometry.into_iter().next().unwrap();
-
-    println!("loading {}", object.name);
-    println!("{} vertices", object.vertices.len());
-    println!("{} shapes", geometry.shapes.len());
-
-    // build up vertices; for this to work, we remove duplicated vertices by putting them in a
-    // map associating the vertex with its ID
-    let mut vertex_cache: HashMap<obj::VTNIndex, VertexIndex> = HashMap::new();
-    let mut vertices: Vec<Vertex> = Vec::new();
-    let mut indices: Vec<VertexIndex> = Vec::new();
-
-    for shape in geometry.shapes {
-      if let obj::Primitive::Triangle(a, b, c) = shape.primitive {
-        for key in &[a, b, c] {
-          if let Some(vertex_index) = vertex_cache.get(key) {
-            indices.push(*vertex_index);
-          } else {
-            let p = object.vertices[key.0];
-            let n = object.normals[key.2.ok_or("missing normal for a vertex".to_owned())?];
-            let position = VertexPosition::new([p.x as f32, p.y as f32, p.z as f32]);
-            let normal = VertexNormal::new([n.x as f32, n.y as f32, n.z as f32]);
-            let vertex = Vertex { position, normal };
-            let vertex_index = vertices.len() as VertexIndex;
-
-            vertex_cache.insert(*key, vertex_index);
-            vertices.push(vertex);
-            indices.push(vertex_index);
-          }
-        }
+      // the usage is only meant for people reading the error
+      let message = if failure::is_json() {
+        e
       } else {
-        return Err("unsupported non-triangle shape".to_owned());
-      }
+        format!("{}\n{}", e, USAGE)
+      };
+
+      fail(ErrorKind::Usage, message)
+    }
+  };
+
+  failure::set_format(cli.error_format);
+
+  if cli.validate {
+    if cli.paths.is_empty() {
+      fail(
+        ErrorKind::Usage,
+        "--validate requires the path of the .obj file to check",
+      );
     }
 
-    Ok(Obj { vertices, indices })
+    exit(validate::run(&cli.paths, cli.json));
   }
-}
 
-fn main() {
-  let dim = WindowDim::Windowed {
-    width: 960,
-    height: 540,
-  };
+  if cli.scene.is_some() && cli.script.is_some() {
+    fail(
+      ErrorKind::Usage,
+      "--scene and --script cannot be used together",
+    );
+  }
+
+  // RenderDoc must hook into the process before the graphics context exists
+  let capture = FrameCapture::new();
+  let state = ViewerState::load();
+  let [width, height] = state.window_size;
+  let dim = WindowDim::Windowed { width, height };
   let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
 
   match surface {
     Ok(surface) => {
       eprintln!("graphics surface created");
-      main_loop(surface);
+      main_loop(surface, cli, state, capture);
     }
 
-    Err(e) => {
-      eprintln!("cannot create graphics surface:\n{}", e);
-      exit(1);
-    }
+    Err(e) => fail(
+      ErrorKind::Gpu,
+      format!("cannot create graphics surface:\n{}", e),
+    ),
   }
 }
 
-fn main_loop(surface: GlfwSurface) {
-  let path = env::args()
-    .skip(1)
-    .next()
-    .expect("first argument must be the path of the .obj file to view");
-  println!("loading {}", path);
+fn main_loop(
+  surface: GlfwSurface,
+  cli: CliArgs,
+  mut state: ViewerState,
+  mut capture: FrameCapture,
+) {
+  // smoke tests, benchmarks and replays leave the state of the user’s own sessions alone
+  let interactive = cli.frames.is_none() && cli.bench.is_none() && cli.replay.is_none();
+
+  // a script spawns the objects of its scene when it’s run, and moves them every frame
+  let (mut script, mut scene) = match cli.script {
+    Some(ref path) => {
+      let (script, scene) = Script::load(path).unwrap_or_else(|e| {
+        fail(
+          ErrorKind::loading(path),
+          format!("cannot run {}: {}", path.display(), e),
+        )
+      });
+
+      (Some(script), Some(scene))
+    }
+
+    None => {
+      let scene = cli.scene.as_ref().map(|path| {
+        SceneFile::load(path).unwrap_or_else(|e| {
+          fail(
+            ErrorKind::loading(path),
+            format!("cannot load {}: {}", path.display(), e),
+          )
+        })
+      });
+
+      (None, scene)
+    }
+  };
+
+  // if no path is given, reopen the models from the previous run
+  let paths = if let Some(ref scene) = scene {
+    scene
+      .entries
+      .iter()
+      .map(|entry| entry.path.clone())
+      .collect()
+  } else if cli.paths.is_empty() {
+    state.last_models.clone()
+  } else {
+    cli.paths
+  };
+
+  if paths.is_empty() {
+    fail(
+      ErrorKind::Usage,
+      "first argument must be the path of the .obj file to view",
+    );
+  }
 
   let mut ctxt = surface.context;
   let events = surface.events_rx;
   let back_buffer = ctxt.back_buffer().expect("back buffer");
-  let start_t = Instant::now();
+  let mut time = Time::new();
+
+  // luminance doesn’t expose debug output, polygon offsets, timer queries nor reading the back
+  // buffer, which are done with raw GL calls; the functions are loaded once, now that the context
+  // is current
+  let window = &mut ctxt.window;
+  gl::load_with(|name| window.get_proc_address(name) as *const c_void);
+
+  if let Some(severity) = cli.gl_debug {
+    match gl_debug::enable(severity, cli.gl_break) {
+      Ok(()) => println!("GL debug output enabled"),
+      Err(e) => eprintln!("cannot enable GL debug output: {}", e),
+    }
+  }
+
+  // benchmarks measure frame times without waiting for the vertical sync
+  let mut bench = cli.bench.map(|seconds| {
+    ctxt.window.glfw.set_swap_interval(glfw::SwapInterval::None);
+    Bench::new(Duration::from_secs_f32(seconds))
+  });
+  let bench_report = cli.bench_report;
+
+  // smoke tests render a given number of frames; they fail if the loop stops before
+  let frames = cli.frames;
+  let last_frame = cli.last_frame;
+  let mut frames_rendered = 0;
+
+  if let Some([x, y]) = state.window_pos {
+    ctxt.window.set_pos(x, y);
+  }
+
+  // meshes of each file
+  let mut models = Vec::new();
+
+  for path in &paths {
+    println!("loading {}", path.display());
+
+    let mut meshes = Obj::load(path).unwrap_or_else(|e| {
+      fail(
+        ErrorKind::loading(path),
+        format!("cannot load {}: {}", path.display(), e),
+      )
+    });
+
+    for obj in &mut meshes {
+      println!("loading {}", obj.stats.name);
+      println!("{} vertices", obj.stats.positions);
+      println!("{} shapes", obj.stats.shapes);
+
+      if obj.stats.generated_normals > 0 {
+        println!(
+          "{} vertices without normals; smoothing them",
+          obj.stats.generated_normals
+        );
+      }
+
+      if obj.stats.ignored_shapes > 0 {
+        println!("{} points and lines ignored", obj.stats.ignored_shapes);
+      }
+
+      if let Some(ref e) = obj.stats.material_error {
+        eprintln!("{}; using the default material", e);
+      }
+
+      obj.convert_basis(cli.up, cli.flip_x);
+
+      // units are converted before the view is fitted, which undoes the scale of a single
+      // model; they still size models against each other, and fitting can be disabled
+      if let Some(scale) = cli.unit_scale {
+        obj.scale(scale);
+      } else if let Some(scale) = obj.stats.unit_hint {
+        println!("file units detected; scaling by {}", scale);
+        obj.scale(scale);
+      }
+    }
+
+    models.push(meshes);
+  }
+
+  // the models of a scene stand where it puts them, and are otherwise laid out in a row
+  let transforms = match scene {
+    Some(ref scene) => scene
+      .entries
+      .iter()
+      .map(|entry| entry.transform(0.))
+      .collect(),
+    None => row_transforms(&models),
+  };
+
+  // all the meshes are merged in a single one, so that they’re drawn in a single draw call;
+  // without batching, the merged mesh is still what picking and the effects around the model use
+  let mut obj = batch_placed(&models, &transforms);
+  let fit = if cli.fit_unit {
+    obj.fit_unit()
+  } else {
+    Matrix4::identity()
+  };
+
+  let mut material = obj.material.clone().unwrap_or_default();
+  material.two_sided |= cli.two_sided;
+  println!("material: {}", material.name);
+
+  // a texture that can’t be loaded leaves the model with its plain diffuse color
+  let mut diffuse_texture = material.diffuse_map.as_ref().and_then(|path| {
+    load_texture(&mut ctxt, path)
+      .map_err(|e| eprintln!("{}; using the diffuse color only", e))
+      .ok()
+  });
+
+  for (statement, map) in material.maps() {
+    if statement != "map_Kd" {
+      println!(
+        "ignoring {} {}: only diffuse maps are supported",
+        statement,
+        map.display()
+      );
+    }
+  }
 
-  let mesh = Obj::load(path).unwrap().to_tess(&mut ctxt).unwrap();
+  // triangles with topology issues are drawn a second time on top of the mesh, in red
+  let highlight = if cli.highlight {
+    let offending = MeshAnalysis::analyze(&obj).offending_triangles();
+    println!("{} triangles with topology issues", offending.len());
+
+    if offending.is_empty() {
+      None
+    } else {
+      let indices = offending
+        .iter()
+        .flat_map(|&t| obj.indices[t * 3..t * 3 + 3].iter().copied())
+        .collect::<Vec<_>>();
+      let tess = ctxt
+        .new_tess()
+        .set_mode(Mode::Triangle)
+        .set_vertices(obj.vertices.clone())
+        .set_indices(indices)
+        .build()
+        .unwrap();
+
+      Some((tess, offending.len()))
+    }
+  } else {
+    None
+  };
+
+  let mut mesh = obj
+    .to_tess(&mut ctxt)
+    .unwrap_or_else(|e| fail(ErrorKind::Gpu, format!("cannot upload the model: {}", e)));
+  let mesh_triangles = obj.indices.len() / 3;
+
+  // without batching, the model is shaded mesh by mesh, each placed where the batch has it; the
+  // objects of a scene are never batched, since they can each be drawn their own way
+  let overrides = match scene {
+    Some(ref scene) => scene.entries.iter().map(|entry| entry.overrides).collect(),
+    None => vec![Overrides::default(); models.len()],
+  };
+  let mut objects = if cli.batch && scene.is_none() {
+    Vec::new()
+  } else {
+    models
+      .iter()
+      .zip(transforms.iter().zip(&overrides))
+      .flat_map(|(meshes, placement)| meshes.iter().map(move |mesh| (mesh, placement)))
+      .enumerate()
+      .map(|(index, (mesh, (&transform, &overrides)))| {
+        SceneObject::new(&mut ctxt, mesh, index as u32, fit * transform).map(|mut object| {
+          object.overrides = overrides;
+          object
+        })
+      })
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap()
+  };
+  // model each object comes from, which is also its entry in the scene
+  let object_models = models
+    .iter()
+    .enumerate()
+    .flat_map(|(model, meshes)| meshes.iter().map(move |_| model))
+    .collect::<Vec<_>>();
+  let identity: [[f32; 4]; 4] = Matrix4::identity().into();
+  let identity_normals: [[f32; 3]; 3] = Matrix3::identity().into();
 
   let mut program = ctxt
     .new_shader_program::<VertexSemantics, (), ShaderInterface>()
     .from_strings(VS_STR, None, None, FS_STR)
-    .unwrap()
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut highlight_program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, HIGHLIGHT_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  // the highlighted triangles have exactly the same depth as the mesh ones, which the depth offset
+  // pulls them slightly in front of
+  let overlay_offset = Some(cli.depth_offset);
+  let highlight_state = RenderState::default().set_depth_test(Some(DepthComparison::LessOrEqual));
+
+  // the mesh is rendered to the faces of the environment map with the same fragment shader
+  let mut env_program = ctxt
+    .new_shader_program::<VertexSemantics, (), EnvInterface>()
+    .from_strings(ENV_VS_STR, None, Some(ENV_GS_STR), FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut chrome_program = ctxt
+    .new_shader_program::<VertexSemantics, (), ChromeInterface>()
+    .from_strings(CHROME_VS_STR, None, None, CHROME_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut studio_program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, STUDIO_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut studio_env_program = ctxt
+    .new_shader_program::<VertexSemantics, (), EnvInterface>()
+    .from_strings(ENV_VS_STR, None, Some(ENV_GS_STR), STUDIO_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut glass_program = ctxt
+    .new_shader_program::<VertexSemantics, (), GlassInterface>()
+    .from_strings(CHROME_VS_STR, None, None, GLASS_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut phong_program = ctxt
+    .new_shader_program::<VertexSemantics, (), PhongInterface>()
+    .from_strings(CHROME_VS_STR, None, None, PHONG_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut divider_program = ctxt
+    .new_shader_program::<(), (), ()>()
+    .from_strings(FULLSCREEN_VS_STR, None, None, DIVIDER_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut background_program = ctxt
+    .new_shader_program::<(), (), BackgroundInterface>()
+    .from_strings(FULLSCREEN_VS_STR, None, None, BACKGROUND_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut depth_program = ctxt
+    .new_shader_program::<(), (), DepthInterface>()
+    .from_strings(FULLSCREEN_VS_STR, None, None, DEPTH_FS_STR)
+    .unwrap_or_else(program_error)
     .ignore_warnings();
 
+  let mut override_program = ctxt
+    .new_shader_program::<VertexSemantics, (), OverrideInterface>()
+    .from_strings(OVERRIDE_VS_STR, None, None, OVERRIDE_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut overdraw_program = ctxt
+    .new_shader_program::<VertexSemantics, (), OverdrawInterface>()
+    .from_strings(VS_STR, None, None, OVERDRAW_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  let mut overdraw_view_program = ctxt
+    .new_shader_program::<(), (), OverdrawViewInterface>()
+    .from_strings(FULLSCREEN_VS_STR, None, None, OVERDRAW_VIEW_FS_STR)
+    .unwrap_or_else(program_error)
+    .ignore_warnings();
+
+  // attributeless triangle covering the whole screen
+  let fullscreen_tess = ctxt
+    .new_tess()
+    .set_vertex_nb(3)
+    .set_mode(Mode::Triangle)
+    .build()
+    .unwrap();
+
+  let mut cache = ShaderCache::default();
+  let mut highlight_cache = ShaderCache::default();
+  let mut chrome_cache = ShaderCache::default();
+  let mut studio_cache = ShaderCache::default();
+  let mut glass_cache = ShaderCache::default();
+  let mut phong_cache = ShaderCache::default();
+  let mut overdraw_cache = ShaderCache::default();
+  let mut override_cache = ShaderCache::default();
+
   let [width, height] = back_buffer.size();
-  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+  let (min, max) = obj.bounds();
+  let min = Point3::from(min);
+  let max = Point3::from(max);
+  let center = min.midpoint(max);
+  let radius = (max - min).magnitude() * 0.5;
 
-  let view = Matrix4::<f32>::look_at(Point3::new(2., 2., 2.), Point3::origin(), Vector3::unit_y());
+  // radius of a sphere around the model bounding everything drawn, growing as things get added;
+  // the depth range of the camera is fitted on it
+  let mut scene_radius = radius;
 
-  'app: loop {
-    // handle events
-    ctxt.window.glfw.poll_events();
-    for (_, event) in glfw::flush_messages(&events) {
-      match event {
-        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
-        _ => (),
-      }
+  // the camera orbits around the target, dragged with the mouse, and is updated every frame; the
+  // dolly zoom moves it between its orbit and the target
+  let mut target = Point3::origin();
+  let mut orbit = state
+    .orbit
+    .unwrap_or_else(|| Orbit::looking_at(Point3::new(2., 2., 2.), target));
+  let aspect = width as f32 / height as f32;
+  let mut lens = Lens::new(state.fov.unwrap_or_else(|| FOVY.into()));
+  let mut eye = orbit.eye(target);
+  let mut camera_depth = depth_range(eye, center, scene_radius);
+  let mut camera_projection = perspective(lens.fovy, aspect, camera_depth.0, camera_depth.1);
+  let mut camera_view = Matrix4::<f32>::look_at(eye, target, Vector3::unit_y());
+
+  // the chrome sphere rests on the ground next to the model, and reflects it
+  let mut chrome = if cli.chrome {
+    let forward = Vector3::new(center.x - eye.x, 0., center.z - eye.z).normalize();
+    let right = forward.cross(Vector3::unit_y());
+    let sphere_radius = radius * 0.4;
+    let sphere_center =
+      Point3::new(center.x, min.y + sphere_radius, center.z) + right * (radius * 1.5);
+
+    let (vertices, indices) = shapes::sphere(sphere_center, sphere_radius, 32, 64);
+    scene_radius = scene_radius.max((sphere_center - center).magnitude() + sphere_radius);
+    let sphere_triangles = indices.len() / 3;
+    let sphere = ctxt
+      .new_tess()
+      .set_mode(Mode::Triangle)
+      .set_vertices(vertices)
+      .set_indices(indices)
+      .build()
+      .unwrap();
+
+    match EnvMap::new(&mut ctxt, sphere_center) {
+      Ok(env_map) => Some((env_map, sphere, sphere_triangles)),
+      Err(e) => fail(ErrorKind::Gpu, e),
     }
+  } else {
+    None
+  };
 
-    // rendering code goes here
-    // get the current time and create a color based on the time
-    let t = start_t.elapsed().as_millis() as f32 * 1e-3;
-    let color = [t.cos(), t.sin(), 0.5, 1.];
+  // picking casts rays against the triangles on the CPU, through a BVH built once; the picked
+  // triangle is drawn on top of the mesh like the highlighted ones
+  let mut picking = if cli.pick && mesh_triangles > 0 {
+    let bvh = Bvh::new(&obj);
+    println!("BVH built over {} triangles", mesh_triangles);
+
+    // the first triangle only gives the buffer its size; it’s replaced by the one picked
+    let picked_tess = ctxt
+      .new_tess()
+      .set_mode(Mode::Triangle)
+      .set_vertices(
+        obj.indices[..3]
+          .iter()
+          .map(|&i| obj.vertices[i as usize])
+          .collect::<Vec<_>>(),
+      )
+      .set_indices(vec![0, 1, 2])
+      .build()
+      .unwrap_or_else(|e| {
+        fail(
+          ErrorKind::Gpu,
+          format!("cannot create the picked triangle: {}", e),
+        )
+      });
+
+    Some((bvh, picked_tess))
+  } else {
+    if cli.pick {
+      println!("no triangles to pick");
+    }
+
+    None
+  };
+  let mut picked = false;
+
+  let ior = cli.ior;
+  let dispersion = cli.dispersion;
+
+  // the split view compares two shadings of the model, on each side of a divider; without it, the
+  // model is shaded the same way on the whole screen
+  let split = cli.split;
+  let mut shading = if cli.glass {
+    Shading::Glass
+  } else {
+    state.shading
+  };
+  let mut divider = 0.5;
+  let mut dragging_divider = false;
+
+  // a glass model stands in a studio, a large sphere around it; the studio doesn’t change, so its
+  // environment map is rendered once and for all
+  let needs_studio = shading == Shading::Glass
+    || split.iter().flatten().any(|&s| s == Shading::Glass)
+    || overrides.iter().any(|o| o.shading == Some(Shading::Glass));
+  let mut studio = if needs_studio {
+    let (vertices, indices) = shapes::sphere(center, STUDIO_RADIUS, 32, 64);
+    scene_radius = scene_radius.max(STUDIO_RADIUS);
+    let backdrop_triangles = indices.len() / 3;
+    let backdrop = ctxt
+      .new_tess()
+      .set_mode(Mode::Triangle)
+      .set_vertices(vertices)
+      .set_indices(indices)
+      .build()
+      .unwrap();
+
+    let env_map = EnvMap::new(&mut ctxt, center).unwrap_or_else(|e| fail(ErrorKind::Gpu, e));
+    let view_projections = env_map.face_view_projections();
 
     let render = ctxt
       .new_pipeline_gate()
       .pipeline(
-        &back_buffer,
-        &PipelineState::default().set_clear_color(color),
+        &env_map.framebuffer,
+        &PipelineState::default(),
         |_, mut shd_gate| {
-          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
-            iface.set(&uni.projection, projection.into());
-            iface.set(&uni.view, view.into());
-
-            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
-              tess_gate.render(&mesh)
-            })
-          })
+          render_env_faces(
+            &mut shd_gate,
+            &mut studio_env_program,
+            &view_projections,
+            &backdrop,
+            backdrop_triangles,
+            None,
+            &mut FrameStats::default(),
+          )
         },
       )
       .assume();
 
-    // swap buffer chains
-    if render.is_ok() {
-      ctxt.window.swap_buffers();
-    } else {
-      break 'app;
+    if render.is_err() {
+      fail(ErrorKind::Gpu, "cannot render the studio environment map");
     }
+
+    Some((env_map, backdrop, backdrop_triangles))
+  } else {
+    None
+  };
+
+  let mut background = cli.background;
+  let mut presets = state.presets.clone();
+  let mut preset = presets
+    .iter()
+    .position(|preset| preset.name == state.lighting)
+    .unwrap_or(0);
+  let mut depth_view = None;
+  let mut show_overdraw = false;
+  let mut overdraw_framebuffer = ctxt
+    .new_framebuffer::<Dim2, R32F, ()>([width, height], 0, Sampler::default())
+    .unwrap_or_else(|e| {
+      fail(
+        ErrorKind::Gpu,
+        format!("cannot create the overdraw framebuffer: {}", e),
+      )
+    });
+  // every fragment is counted, hidden or not
+  let overdraw_state = RenderState::default()
+    .set_depth_test(None)
+    .set_blending(Blending {
+      equation: Equation::Additive,
+      src: Factor::One,
+      dst: Factor::One,
+    });
+  let mut depth_framebuffer = ctxt
+    .new_framebuffer::<Dim2, (), Depth32F>([width, height], 0, Sampler::default())
+    .unwrap_or_else(|e| {
+      fail(
+        ErrorKind::Gpu,
+        format!("cannot create the depth framebuffer: {}", e),
+      )
+    });
+  let bindings = Bindings::default();
+  let mut axes = Axes::new();
+  let mut show_stats = false;
+  let mut stats_report = StatsReport::new();
+  let mut session = match (cli.record, cli.replay) {
+    (Some(path), _) => Session::record(path).unwrap_or_else(|e| fail(ErrorKind::Other, e)),
+    (_, Some(path)) => {
+      Session::replay(&path).unwrap_or_else(|e| fail(ErrorKind::loading(&path), e))
+    }
+    _ => Session::Live,
+  };
+
+  // the configuration is applied once at startup, then every time it changes
+  let config_watcher = cli
+    .config
+    .map(|path| ConfigWatcher::new(path).unwrap_or_else(|e| fail(ErrorKind::Other, e)));
+  let mut config_changed = config_watcher.is_some();
+
+  let remote = cli.remote.map(|port| {
+    let remote = Remote::listen(port).unwrap_or_else(|e| fail(ErrorKind::Other, e));
+    println!("remote control on ws://127.0.0.1:{}", port);
+    remote
+  });
+  let mut screenshots = Vec::new();
+
+  let mut render_failed = false;
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    let mut input = FrameInput::default();
+    for (_, event) in glfw::flush_messages(&events) {
+      if let WindowEvent::Close = event {
+        break 'app;
+      }
+
+      // the divider of the split view is dragged on its own; grabbing it takes precedence over the
+      // bindings
+      match event {
+        WindowEvent::MouseButton(MouseButton::Button1, glfw::Action::Press, _) => {
+          let (x, _) = ctxt.window.get_cursor_pos();
+          let (w, _) = ctxt.window.get_size();
+
+          if split.is_some() && (x as f32 - divider * w as f32).abs() < DIVIDER_GRAB {
+            dragging_divider = true;
+            continue;
+          }
+        }
+
+        WindowEvent::MouseButton(MouseButton::Button1, glfw::Action::Release, _) => {
+          dragging_divider = false;
+        }
+
+        WindowEvent::CursorPos(x, _) if dragging_divider => {
+          let (w, _) = ctxt.window.get_size();
+          input.divider = Some((x as f32 / w as f32).clamp(0., 1.));
+        }
+
+        _ => (),
+      }
+
+      // clicking without turning the camera picks the triangle under the cursor
+      if axes.handle(&bindings, &event) == Some(Axis::Orbit) && picking.is_some() {
+        let (x, y) = ctxt.window.get_cursor_pos();
+        let (w, h) = ctxt.window.get_size();
+        input.picks.push([x as f32 / w as f32, y as f32 / h as f32]);
+      }
+
+      input.actions.extend(bindings.action(&event));
+    }
+
+    // remote actions go along with the user’s, and get recorded the same way; screenshots wait for
+    // the frame to be rendered
+    if let Some(ref remote) = remote {
+      for request in remote.requests() {
+        match request.command {
+          Command::Action(action) => input.actions.push(action),
+          Command::Camera(remote_orbit) => orbit = remote_orbit,
+          Command::Fov(fovy) => lens.set_fovy(fovy),
+          Command::Screenshot(_) => {
+            screenshots.push(request);
+            continue;
+          }
+        }
+
+        request.reply(Ok(()));
+      }
+    }
+
+    let axis_values = axes.read(&bindings);
+    input.orbit = axis_values.get(Axis::Orbit);
+    input.zoom = axis_values.get(Axis::Zoom)[1];
+
+    // everything the frame depends on goes through the session, to be recorded or replayed
+    let input = match session.frame(input, &mut time) {
+      Some(input) => input,
+      None => {
+        println!("end of replay");
+        break 'app;
+      }
+    };
+
+    let [dx, dy] = input.orbit;
+    orbit.rotate(dx, dy);
+    orbit.zoom(input.zoom);
+
+    if let Some(x) = input.divider {
+      divider = x;
+    }
+
+    if let Some((bvh, picked_tess)) = &mut picking {
+      for &position in &input.picks {
+        let ray = Ray::through_screen(camera_projection * camera_view, position);
+        picked = pick(bvh, &ray, &obj, picked_tess);
+      }
+    }
+
+    for action in input.actions {
+      match action {
+        Action::Quit => break 'app,
+
+        Action::RecomputeNormals => {
+          println!("recomputing normals");
+          obj.recompute_normals();
+          upload_vertices(&mut mesh, &obj.vertices);
+
+          for (object, obj) in objects.iter_mut().zip(models.iter_mut().flatten()) {
+            obj.recompute_normals();
+            object.update(obj);
+          }
+        }
+
+        Action::FlipNormals => {
+          println!("flipping normals");
+          obj.flip_normals();
+          upload_vertices(&mut mesh, &obj.vertices);
+
+          for (object, obj) in objects.iter_mut().zip(models.iter_mut().flatten()) {
+            obj.flip_normals();
+            object.update(obj);
+          }
+        }
+
+        Action::SlowDown => {
+          time.slow_down();
+          println!("time scale: {}", time.scale());
+        }
+
+        Action::SpeedUp => {
+          time.speed_up();
+          println!("time scale: {}", time.scale());
+        }
+
+        Action::CaptureFrame => capture.trigger(),
+
+        Action::ToggleStats => {
+          show_stats = !show_stats;
+          stats_report.reset();
+        }
+
+        Action::CycleDepthView => {
+          depth_view = DepthView::cycle(depth_view);
+          println!("depth view: {}", DepthView::name(depth_view));
+        }
+
+        Action::ToggleOverdraw => {
+          show_overdraw = !show_overdraw;
+          println!(
+            "overdraw view: {}",
+            if show_overdraw { "on" } else { "off" }
+          );
+        }
+
+        Action::CycleShading => {
+          shading = shading.next();
+
+          // glass needs the studio, only set up if asked for on the command line
+          if shading == Shading::Glass && studio.is_none() {
+            shading = shading.next();
+          }
+
+          println!("shading: {}", shading.name());
+        }
+
+        Action::CycleBackground => {
+          background = background.next();
+          println!("background: {}", background.name());
+        }
+
+        Action::CycleLighting => {
+          preset = (preset + 1) % presets.len();
+          background = presets[preset].background;
+          println!("lighting: {}", presets[preset].name);
+        }
+
+        Action::WidenFov | Action::NarrowFov if lens.is_dolly_zooming() => {
+          println!("the field of view is driven by the dolly zoom");
+        }
+
+        Action::WidenFov => {
+          lens.widen();
+          println!("field of view: {:.0}°", lens.fovy.0);
+        }
+
+        Action::NarrowFov => {
+          lens.narrow();
+          println!("field of view: {:.0}°", lens.fovy.0);
+        }
+
+        Action::ToggleDollyZoom => {
+          lens.toggle_dolly_zoom(time.t(), orbit.distance);
+          println!(
+            "dolly zoom: {}",
+            if lens.is_dolly_zooming() { "on" } else { "off" }
+          );
+        }
+
+        Action::ExportCamera => {
+          // the camera of the last frame rendered, looking down the -Z axis of its view
+          let forward = -Vector3::new(camera_view.x.z, camera_view.y.z, camera_view.z.z);
+          let file = CameraFile {
+            position: eye,
+            forward,
+            fovy: lens.fovy,
+            z_near: camera_depth.0,
+            z_far: camera_depth.1,
+            resolution: [width, height],
+          };
+
+          match file.save(&cli.camera_file) {
+            Ok(()) => println!("camera exported to {}", cli.camera_file.display()),
+            Err(e) => eprintln!("{}", e),
+          }
+        }
+
+        Action::ImportCamera => match CameraFile::load(&cli.camera_file) {
+          Ok(file) => {
+            // the target stays in front of the camera, as far as the model, so that dolly zooms
+            // keep working; the depth range is fitted on the scene, so the clip planes are ignored
+            let home = file.position;
+            target = home + file.forward.normalize() * (center - home).magnitude().max(radius);
+            orbit = Orbit::looking_at(home, target);
+            lens = Lens::new(file.fovy);
+            println!(
+              "camera imported from {} (field of view: {:.0}°)",
+              cli.camera_file.display(),
+              lens.fovy.0
+            );
+          }
+
+          Err(e) => eprintln!("{}", e),
+        },
+      }
+    }
+
+    if let Some(ref config_watcher) = config_watcher {
+      config_changed |= config_watcher.changed();
+
+      if config_changed {
+        config_changed = false;
+
+        match Config::load(config_watcher.path()) {
+          Ok(config) => {
+            for (property, value) in &config.preset_properties {
+              set_preset_property(&mut presets, property, value);
+            }
+
+            if let Some(name) = config.lighting {
+              match presets.iter().position(|preset| preset.name == name) {
+                Some(i) => {
+                  preset = i;
+                  background = presets[preset].background;
+                }
+                None => eprintln!("unknown lighting preset: {}", name),
+              }
+            }
+
+            background = config.background.unwrap_or(background);
+
+            match config.shading {
+              // glass needs the studio, only set up if asked for on the command line
+              Some(Shading::Glass) if studio.is_none() => {
+                eprintln!("glass shading needs --glass on the command line")
+              }
+              Some(new_shading) => shading = new_shading,
+              None => (),
+            }
+
+            orbit = config.orbit.unwrap_or(orbit);
+
+            if let Some(fovy) = config.fov {
+              lens.set_fovy(fovy);
+            }
+
+            show_overdraw = config.overdraw.unwrap_or(show_overdraw);
+
+            if let Some(stats) = config.stats {
+              show_stats = stats;
+              stats_report.reset();
+            }
+
+            println!(
+              "configuration applied from {}",
+              config_watcher.path().display()
+            );
+          }
+
+          Err(e) => eprintln!("{}", e),
+        }
+      }
+    }
+
+    // rendering code goes here
+    let t = time.t();
+
+    // the script moves the objects of its scene, the lights and the camera; it stops at its first
+    // error, leaving everything where it was
+    if let Some(ref mut running) = script {
+      match running.frame(t) {
+        Ok(()) => {
+          if let Some(ref mut scene) = scene {
+            scene.entries = running.entries();
+          }
+
+          orbit = running.take_orbit().unwrap_or(orbit);
+        }
+
+        Err(e) => {
+          eprintln!("script stopped: {}", e);
+          script = None;
+        }
+      }
+    }
+
+    let scripted_lighting = script
+      .as_ref()
+      .map(|script| script.lighting(&presets[preset]));
+    let lighting = scripted_lighting.as_ref().unwrap_or(&presets[preset]);
+    let color = background.clear_color();
+    let mut frame_stats = FrameStats::default();
+
+    // animated objects of a scene follow the clock, or the script; the merged mesh, which picking
+    // and the effects around the model use, stays where they start
+    if let Some(ref scene) = scene {
+      for (object, &model) in objects.iter_mut().zip(&object_models) {
+        let entry = &scene.entries[model];
+
+        if entry.is_animated() || script.is_some() {
+          object.transform = fit * entry.transform(t);
+        }
+      }
+    }
+
+    // the benchmark flies the camera along its path; otherwise, it stays on its orbit unless a
+    // dolly zoom moves it
+    let look_at = if bench.is_some() { center } else { target };
+    let home = orbit.eye(target);
+    let distance = lens.update(t);
+    eye = match (&bench, distance) {
+      (Some(bench), _) => bench.eye(center, radius),
+      (None, Some(distance)) => target + (home - target).normalize() * distance,
+      (None, None) => home,
+    };
+    camera_view = Matrix4::look_at(eye, look_at, Vector3::unit_y());
+
+    camera_depth = depth_range(eye, center, scene_radius);
+    let (z_near, z_far) = camera_depth;
+    camera_projection = perspective(lens.fovy, aspect, z_near, z_far);
+    let projection: [[f32; 4]; 4] = camera_projection.into();
+    let view: [[f32; 4]; 4] = camera_view.into();
+
+    if let Some(ref mut bench) = bench {
+      bench.begin_frame();
+    }
+
+    // the environment map of the chrome sphere is rendered first, one face after the other
+    if let Some((ref env_map, _, _)) = chrome {
+      let view_projections = env_map.face_view_projections();
+
+      let render = ctxt
+        .new_pipeline_gate()
+        .pipeline(
+          &env_map.framebuffer,
+          &PipelineState::default().set_clear_color(color),
+          |pipeline, mut shd_gate| {
+            let diffuse_map = match diffuse_texture {
+              Some(ref mut texture) => {
+                frame_stats.texture_binds += 1;
+                Some(pipeline.bind_texture(texture)?)
+              }
+              None => None,
+            };
+
+            render_env_faces(
+              &mut shd_gate,
+              &mut env_program,
+              &view_projections,
+              &mesh,
+              mesh_triangles,
+              Some((&material, diffuse_map.as_ref(), lighting)),
+              &mut frame_stats,
+            )
+          },
+        )
+        .assume();
+
+      if render.is_err() {
+        render_failed = true;
+        break 'app;
+      }
+    }
+
+    // the depth view shows the depth of the scene as rendered by the camera, which the back buffer
+    // can’t give back; it’s rendered to a texture first
+    if depth_view.is_some() {
+      let render = ctxt
+        .new_pipeline_gate()
+        .pipeline(
+          &depth_framebuffer,
+          &PipelineState::default(),
+          |_, mut shd_gate| {
+            shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+              frame_stats.program_switches += 1;
+
+              cache.set_camera(&mut iface, &uni.projection, &uni.view, projection, view);
+
+              // the batch is already in the world, unlike the last object drawn one by one
+              iface.set(&uni.model, identity);
+              iface.set(&uni.normal_matrix, identity_normals);
+
+              rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+                frame_stats.draw(mesh_triangles, 1);
+                tess_gate.render(&mesh)?;
+
+                if let Some((_, ref sphere, sphere_triangles)) = chrome {
+                  frame_stats.draw(sphere_triangles, 1);
+                  tess_gate.render(sphere)?;
+                }
+
+                Ok(())
+              })
+            })
+          },
+        )
+        .assume();
+
+      if render.is_err() {
+        render_failed = true;
+        break 'app;
+      }
+    }
+
+    // the overdraw view counts the fragments drawn over each pixel of the scene
+    if show_overdraw {
+      let render = ctxt
+        .new_pipeline_gate()
+        .pipeline(
+          &overdraw_framebuffer,
+          &PipelineState::default().set_clear_color([0., 0., 0., 0.]),
+          |_, mut shd_gate| {
+            shd_gate.shade(&mut overdraw_program, |mut iface, uni, mut rdr_gate| {
+              frame_stats.program_switches += 1;
+
+              overdraw_cache.set_camera(&mut iface, &uni.projection, &uni.view, projection, view);
+
+              rdr_gate.render(&overdraw_state, |mut tess_gate| {
+                frame_stats.draw(mesh_triangles, 1);
+                tess_gate.render(&mesh)?;
+
+                if let Some((_, ref sphere, sphere_triangles)) = chrome {
+                  frame_stats.draw(sphere_triangles, 1);
+                  tess_gate.render(sphere)?;
+                }
+
+                if let Some((_, ref backdrop, backdrop_triangles)) = studio {
+                  frame_stats.draw(backdrop_triangles, 1);
+                  tess_gate.render(backdrop)?;
+                }
+
+                Ok(())
+              })
+            })
+          },
+        )
+        .assume();
+
+      if render.is_err() {
+        render_failed = true;
+        break 'app;
+      }
+    }
+
+    // the model is drawn in a single draw call, or object by object with their model matrices
+    let draws = if objects.is_empty() {
+      vec![(&mesh, identity, mesh_triangles, Overrides::default())]
+    } else {
+      objects
+        .iter()
+        .map(|object| {
+          let model = object.transform.into();
+          (&object.tess, model, object.triangles, object.overrides)
+        })
+        .collect()
+    };
+
+    // each side of the split view is clipped with a scissor region, given by its left column and
+    // its width
+    let divider_x = (divider * width as f32) as u32;
+    let sides = match split {
+      Some([left, right]) => vec![
+        (left, Some([0, divider_x])),
+        (right, Some([divider_x, width - divider_x])),
+      ],
+      None => vec![(shading, None)],
+    };
+
+    // objects are drawn in passes sharing a shading, each with its own render state; blended
+    // objects are drawn last, over the opaque ones
+    let mut passes: Vec<(Shading, Vec<_>)> = Vec::new();
+
+    for &blended in &[false, true] {
+      for &(side_shading, columns) in &sides {
+        let first = passes.len();
+
+        for &(tess, model, triangles, overrides) in &draws {
+          if overrides.is_blended() != blended {
+            continue;
+          }
+
+          let state = overrides.apply(model_state(&material));
+          let state = match columns {
+            Some([x, side_width]) => state.set_scissor(ScissorRegion {
+              x,
+              y: 0,
+              width: side_width,
+              height,
+            }),
+            None => state,
+          };
+          let shading = overrides.shading.unwrap_or(side_shading);
+          let draw = (tess, model, triangles, state);
+
+          match passes[first..].iter_mut().find(|(s, _)| *s == shading) {
+            Some((_, pass)) => pass.push(draw),
+            None => passes.push((shading, vec![draw])),
+          }
+        }
+      }
+    }
+
+    let divider_state = RenderState::default()
+      .set_depth_test(None)
+      .set_scissor(ScissorRegion {
+        x: divider_x.max(1) - 1,
+        y: 0,
+        width: 2,
+        height,
+      });
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color(color),
+        |pipeline, mut shd_gate| {
+          let diffuse_map = match diffuse_texture {
+            Some(ref mut texture) => {
+              frame_stats.texture_binds += 1;
+              Some(pipeline.bind_texture(texture)?)
+            }
+            None => None,
+          };
+
+          // the background pattern is drawn first, behind everything
+          if let Some(pattern) = background.pattern() {
+            shd_gate.shade(&mut background_program, |mut iface, uni, mut rdr_gate| {
+              frame_stats.program_switches += 1;
+
+              iface.set(&uni.pattern, pattern.index);
+              iface.set(&uni.color_a, pattern.color_a);
+              iface.set(&uni.color_b, pattern.color_b);
+              iface.set(&uni.square, CHECKER_SQUARE);
+
+              rdr_gate.render(
+                &RenderState::default().set_depth_test(None),
+                |mut tess_gate| {
+                  frame_stats.draw(1, 1);
+                  tess_gate.render(&fullscreen_tess)
+                },
+              )
+            })?;
+          }
+
+          let model = passes.iter().try_for_each(|(shading, pass)| match shading {
+            Shading::Lambert => shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+              frame_stats.program_switches += 1;
+
+              cache.set_camera(&mut iface, &uni.projection, &uni.view, projection, view);
+
+              iface.set(&uni.ambient, material.ambient);
+              iface.set(&uni.diffuse, material.diffuse);
+              iface.set(&uni.two_sided, material.two_sided);
+              cache.set_lighting(
+                &mut iface,
+                &uni.light_dirs,
+                &uni.light_colors,
+                &uni.ambient_light,
+                &uni.exposure,
+                lighting,
+              );
+
+              if let Some(ref diffuse_map) = diffuse_map {
+                iface.set(&uni.diffuse_map, diffuse_map.binding());
+              }
+
+              iface.set(&uni.textured, diffuse_map.is_some());
+
+              pass.iter().try_for_each(|(tess, model, triangles, state)| {
+                iface.set(&uni.model, *model);
+                iface.set(&uni.normal_matrix, normal_matrix((*model).into()).into());
+
+                rdr_gate.render(state, |mut tess_gate| {
+                  frame_stats.draw(*triangles, 1);
+                  tess_gate.render(*tess)
+                })
+              })
+            }),
+
+            Shading::Phong => shd_gate.shade(&mut phong_program, |mut iface, uni, mut rdr_gate| {
+              frame_stats.program_switches += 1;
+
+              phong_cache.set_camera(&mut iface, &uni.projection, &uni.view, projection, view);
+
+              iface.set(&uni.camera_pos, eye.into());
+              iface.set(&uni.ambient, material.ambient);
+              iface.set(&uni.diffuse, material.diffuse);
+              iface.set(&uni.specular, material.specular);
+              iface.set(&uni.shininess, material.shininess);
+              iface.set(&uni.two_sided, material.two_sided);
+              phong_cache.set_lighting(
+                &mut iface,
+                &uni.light_dirs,
+                &uni.light_colors,
+                &uni.ambient_light,
+                &uni.exposure,
+                lighting,
+              );
+
+              if let Some(ref diffuse_map) = diffuse_map {
+                iface.set(&uni.diffuse_map, diffuse_map.binding());
+              }
+
+              iface.set(&uni.textured, diffuse_map.is_some());
+
+              pass.iter().try_for_each(|(tess, model, triangles, state)| {
+                iface.set(&uni.model, *model);
+                iface.set(&uni.normal_matrix, normal_matrix((*model).into()).into());
+
+                rdr_gate.render(state, |mut tess_gate| {
+                  frame_stats.draw(*triangles, 1);
+                  tess_gate.render(*tess)
+                })
+              })
+            }),
+
+            Shading::Glass => {
+              let env_map = &mut studio.as_mut().expect("studio environment map").0;
+              let environment = pipeline.bind_texture(env_map.framebuffer.color_slot())?;
+              frame_stats.texture_binds += 1;
+
+              shd_gate.shade(&mut glass_program, |mut iface, uni, mut rdr_gate| {
+                frame_stats.program_switches += 1;
+
+                glass_cache.set_camera(&mut iface, &uni.projection, &uni.view, projection, view);
+
+                iface.set(&uni.environment, environment.binding());
+                iface.set(&uni.camera_pos, eye.into());
+                iface.set(&uni.ior, ior);
+                iface.set(&uni.dispersion, dispersion);
+
+                pass.iter().try_for_each(|(tess, model, triangles, state)| {
+                  iface.set(&uni.model, *model);
+                  iface.set(&uni.normal_matrix, normal_matrix((*model).into()).into());
+
+                  rdr_gate.render(state, |mut tess_gate| {
+                    frame_stats.draw(*triangles, 1);
+                    tess_gate.render(*tess)
+                  })
+                })
+              })
+            }
+
+            // the other shadings override the material with diagnostic outputs
+            shading => shd_gate.shade(&mut override_program, |mut iface, uni, mut rdr_gate| {
+              frame_stats.program_switches += 1;
+
+              override_cache.set_camera(&mut iface, &uni.projection, &uni.view, projection, view);
+
+              let mode = shading.override_mode().expect("material override");
+              iface.set(&uni.mode, mode);
+              iface.set(&uni.cell, radius / 8.);
+              iface.set(&uni.diffuse, material.diffuse);
+              iface.set(&uni.two_sided, material.two_sided);
+
+              if let Some(ref diffuse_map) = diffuse_map {
+                iface.set(&uni.diffuse_map, diffuse_map.binding());
+              }
+
+              iface.set(&uni.textured, diffuse_map.is_some());
+
+              pass.iter().try_for_each(|(tess, model, triangles, state)| {
+                iface.set(&uni.model, *model);
+                iface.set(&uni.normal_matrix, normal_matrix((*model).into()).into());
+
+                rdr_gate.render(state, |mut tess_gate| {
+                  frame_stats.draw(*triangles, 1);
+                  tess_gate.render(*tess)
+                })
+              })
+            }),
+          });
+
+          model
+            .and_then(|_| match studio {
+              Some((_, ref backdrop, backdrop_triangles)) => {
+                shd_gate.shade(&mut studio_program, |mut iface, uni, mut rdr_gate| {
+                  frame_stats.program_switches += 1;
+
+                  studio_cache.set_camera(&mut iface, &uni.projection, &uni.view, projection, view);
+
+                  rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+                    frame_stats.draw(backdrop_triangles, 1);
+                    tess_gate.render(backdrop)
+                  })
+                })
+              }
+
+              None => Ok(()),
+            })
+            .and_then(|_| match highlight {
+              Some((ref highlight, highlight_triangles)) => {
+                shd_gate.shade(&mut highlight_program, |mut iface, uni, mut rdr_gate| {
+                  frame_stats.program_switches += 1;
+
+                  highlight_cache.set_camera(
+                    &mut iface,
+                    &uni.projection,
+                    &uni.view,
+                    projection,
+                    view,
+                  );
+
+                  set_depth_offset(overlay_offset);
+                  let render = rdr_gate.render(&highlight_state, |mut tess_gate| {
+                    frame_stats.draw(highlight_triangles, 1);
+                    tess_gate.render(highlight)
+                  });
+                  set_depth_offset(None);
+
+                  render
+                })
+              }
+
+              None => Ok(()),
+            })
+            .and_then(|_| {
+              let picked_tess = match picking {
+                Some((_, ref picked_tess)) if picked => picked_tess,
+                _ => return Ok(()),
+              };
+
+              shd_gate.shade(&mut highlight_program, |mut iface, uni, mut rdr_gate| {
+                frame_stats.program_switches += 1;
+
+                highlight_cache.set_camera(
+                  &mut iface,
+                  &uni.projection,
+                  &uni.view,
+                  projection,
+                  view,
+                );
+
+                set_depth_offset(overlay_offset);
+                let render = rdr_gate.render(&highlight_state, |mut tess_gate| {
+                  frame_stats.draw(1, 1);
+                  tess_gate.render(picked_tess)
+                });
+                set_depth_offset(None);
+
+                render
+              })
+            })
+            .and_then(|_| match chrome {
+              Some((ref mut env_map, ref sphere, sphere_triangles)) => {
+                let environment = pipeline.bind_texture(env_map.framebuffer.color_slot())?;
+                frame_stats.texture_binds += 1;
+
+                shd_gate.shade(&mut chrome_program, |mut iface, uni, mut rdr_gate| {
+                  frame_stats.program_switches += 1;
+
+                  chrome_cache.set_camera(&mut iface, &uni.projection, &uni.view, projection, view);
+
+                  iface.set(&uni.environment, environment.binding());
+                  iface.set(&uni.camera_pos, eye.into());
+
+                  rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+                    frame_stats.draw(sphere_triangles, 1);
+                    tess_gate.render(sphere)
+                  })
+                })
+              }
+
+              None => Ok(()),
+            })
+            .and_then(|_| match depth_view {
+              // the depth view covers everything else
+              Some(mode) => {
+                let depth = pipeline.bind_texture(depth_framebuffer.depth_slot())?;
+                frame_stats.texture_binds += 1;
+
+                shd_gate.shade(&mut depth_program, |mut iface, uni, mut rdr_gate| {
+                  frame_stats.program_switches += 1;
+
+                  iface.set(&uni.depth, depth.binding());
+                  iface.set(&uni.z_near, z_near);
+                  iface.set(&uni.z_far, z_far);
+                  iface.set(&uni.heatmap, (mode == DepthView::Heatmap) as i32);
+
+                  rdr_gate.render(
+                    &RenderState::default().set_depth_test(None),
+                    |mut tess_gate| {
+                      frame_stats.draw(1, 1);
+                      tess_gate.render(&fullscreen_tess)
+                    },
+                  )
+                })
+              }
+
+              None => Ok(()),
+            })
+            .and_then(|_| {
+              if !show_overdraw {
+                return Ok(());
+              }
+
+              let counts = pipeline.bind_texture(overdraw_framebuffer.color_slot())?;
+              frame_stats.texture_binds += 1;
+
+              shd_gate.shade(
+                &mut overdraw_view_program,
+                |mut iface, uni, mut rdr_gate| {
+                  frame_stats.program_switches += 1;
+
+                  iface.set(&uni.counts, counts.binding());
+                  iface.set(&uni.max_count, OVERDRAW_MAX);
+
+                  rdr_gate.render(
+                    &RenderState::default().set_depth_test(None),
+                    |mut tess_gate| {
+                      frame_stats.draw(1, 1);
+                      tess_gate.render(&fullscreen_tess)
+                    },
+                  )
+                },
+              )
+            })
+            .and_then(|_| match split {
+              Some(_) => shd_gate.shade(&mut divider_program, |_, _, mut rdr_gate| {
+                frame_stats.program_switches += 1;
+
+                rdr_gate.render(&divider_state, |mut tess_gate| {
+                  frame_stats.draw(1, 1);
+                  tess_gate.render(&fullscreen_tess)
+                })
+              }),
+
+              None => Ok(()),
+            })
+        },
+      )
+      .assume();
+
+    for cache in &mut [
+      &mut cache,
+      &mut highlight_cache,
+      &mut chrome_cache,
+      &mut studio_cache,
+      &mut glass_cache,
+      &mut phong_cache,
+      &mut overdraw_cache,
+      &mut override_cache,
+    ] {
+      cache.count(&mut frame_stats);
+    }
+
+    if show_stats {
+      stats_report.add(frame_stats);
+    }
+
+    if let Some(ref mut bench) = bench {
+      bench.end_frame(frame_stats);
+    }
+
+    // swap buffer chains
+    if render.is_err() {
+      render_failed = true;
+      break 'app;
+    }
+
+    frames_rendered += 1;
+
+    if let (Some(path), Some(n)) = (last_frame.as_ref(), frames) {
+      if frames_rendered == n {
+        let (width, height) = ctxt.window.get_framebuffer_size();
+
+        match snapshot::save_back_buffer(path, [width as u32, height as u32]) {
+          Ok(()) => println!("last frame saved to {}", path.display()),
+          Err(e) => fail(
+            ErrorKind::Other,
+            format!("cannot save the last frame to {}: {}", path.display(), e),
+          ),
+        }
+      }
+    }
+
+    for request in screenshots.drain(..) {
+      if let Command::Screenshot(ref path) = request.command {
+        let (width, height) = ctxt.window.get_framebuffer_size();
+        let result = snapshot::save_back_buffer(path, [width as u32, height as u32])
+          .map_err(|e| format!("cannot save {}: {}", path.display(), e));
+        request.reply(result);
+      }
+    }
+
+    ctxt.window.swap_buffers();
+
+    if frames == Some(frames_rendered) {
+      break 'app;
+    }
+
+    if matches!(bench, Some(ref bench) if bench.is_done()) {
+      break 'app;
+    }
+  }
+
+  if let Some(n) = frames {
+    if frames_rendered < n && !render_failed {
+      fail(
+        ErrorKind::Other,
+        format!("only {} frames out of {} rendered", frames_rendered, n),
+      );
+    }
+  }
+
+  if let Some(bench) = bench {
+    if let Err(e) = bench.finish(&bench_report) {
+      eprintln!("cannot write the benchmark report: {}", e);
+    }
+  }
+
+  // remember where we left off for the next run
+  if interactive {
+    let (x, y) = ctxt.window.get_pos();
+    let (width, height) = ctxt.window.get_size();
+    state.window_pos = Some([x, y]);
+    state.window_size = [width as u32, height as u32];
+    state.lighting = presets[preset].name.clone();
+    state.shading = shading;
+    state.orbit = Some(orbit);
+    // a dolly zoom in progress is stopped, so that the field of view it started from is kept
+    if lens.is_dolly_zooming() {
+      lens.toggle_dolly_zoom(time.t(), orbit.distance);
+    }
+    state.fov = Some(lens.fovy);
+    // a scene is opened again with --scene or --script, not as the models it’s made of
+    if scene.is_none() {
+      state.last_models = paths
+        .into_iter()
+        .map(|path| fs::canonicalize(&path).unwrap_or(path))
+        .collect();
+    }
+
+    if let Err(e) = state.save() {
+      eprintln!("cannot save viewer state: {}", e);
+    }
+  }
+
+  if render_failed {
+    fail(ErrorKind::Gpu, "rendering failed");
+  }
+}
+
+/// Near and far planes of a camera at `eye` fitted on a sphere, the near plane never getting closer
+/// than MIN_NEAR_FAR_RATIO times the far one (when the camera is in the sphere, for instance).
+fn depth_range(eye: Point3<f32>, center: Point3<f32>, radius: f32) -> (f32, f32) {
+  let distance = (eye - center).magnitude();
+  let z_far = distance + radius;
+  let z_near = (distance - radius).max(z_far * MIN_NEAR_FAR_RATIO);
+
+  (z_near, z_far)
+}
+
+/// Render state of the model: back faces are culled, unless its material is two-sided.
+fn model_state(material: &Material) -> RenderState {
+  if material.two_sided {
+    RenderState::default()
+  } else {
+    RenderState::default().set_face_culling(FaceCulling::new(
+      FaceCullingOrder::CCW,
+      FaceCullingMode::Back,
+    ))
+  }
+}
+
+/// Exit on a shader program that doesn’t build.
+fn program_error(e: ProgramError) -> ! {
+  fail(
+    ErrorKind::Shader,
+    format!("cannot build shader program:\n{}", e),
+  )
+}
+
+/// Render a mesh to all the faces of an environment map, one after the other.
+///
+/// Meshes drawn with the model’s shading need its material, and its diffuse texture if it has one.
+fn render_env_faces(
+  shd_gate: &mut ShadingGate,
+  program: &mut Program<VertexSemantics, (), EnvInterface>,
+  view_projections: &[[[f32; 4]; 4]],
+  tess: &Tess<Vertex, VertexIndex, (), Interleaved>,
+  triangles: usize,
+  material: Option<(
+    &Material,
+    Option<&BoundTexture<Dim2, NormRGBA8UI>>,
+    &Lighting,
+  )>,
+  frame_stats: &mut FrameStats,
+) -> Result<(), PipelineError> {
+  shd_gate.shade(program, |mut iface, uni, mut rdr_gate| {
+    frame_stats.program_switches += 1;
+
+    if let Some((material, diffuse_map, lighting)) = material {
+      iface.set(&uni.ambient, material.ambient);
+      iface.set(&uni.diffuse, material.diffuse);
+      iface.set(&uni.light_dirs, lighting.directions());
+      iface.set(&uni.light_colors, lighting.colors());
+      iface.set(&uni.ambient_light, lighting.ambient);
+      iface.set(&uni.exposure, lighting.exposure);
+
+      if let Some(diffuse_map) = diffuse_map {
+        iface.set(&uni.diffuse_map, diffuse_map.binding());
+      }
+
+      iface.set(&uni.textured, diffuse_map.is_some());
+    }
+
+    view_projections
+      .iter()
+      .enumerate()
+      .try_for_each(|(face, &view_projection)| {
+        iface.set(&uni.face, face as i32);
+        iface.set(&uni.view_projection, view_projection);
+
+        rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+          frame_stats.draw(triangles, 1);
+          tess_gate.render(tess)
+        })
+      })
+  })
+}
+
+/// Cast a ray against the mesh, print what it hits and put the triangle hit in `picked_tess`.
+///
+/// Return whether a triangle was hit.
+fn pick(
+  bvh: &Bvh,
+  ray: &Ray,
+  obj: &Obj,
+  picked_tess: &mut Tess<Vertex, VertexIndex, (), Interleaved>,
+) -> bool {
+  let hit = match bvh.intersect(ray) {
+    Some(hit) => hit,
+    None => {
+      println!("nothing picked");
+      return false;
+    }
+  };
+
+  let corners = &obj.indices[hit.triangle * 3..hit.triangle * 3 + 3];
+  let vertices = corners
+    .iter()
+    .map(|&i| obj.vertices[i as usize])
+    .collect::<Vec<_>>();
+
+  // the normal of the surface, interpolated like the shaders do
+  let normal = vertices
+    .iter()
+    .zip(&hit.barycentric)
+    .map(|(vertex, &w)| Vector3::from(*vertex.normal) * w)
+    .fold(Vector3::new(0., 0., 0.), |sum, n| sum + n)
+    .normalize();
+  let position = ray.at(hit.t);
+
+  println!(
+    "picked triangle {} at [{:.3}, {:.3}, {:.3}], normal [{:.3}, {:.3}, {:.3}]",
+    hit.triangle, position.x, position.y, position.z, normal.x, normal.y, normal.z
+  );
+
+  upload_vertices(picked_tess, &vertices);
+  true
+}
+
+/// Update the vertices of a mesh in place; that only works if their number hasn’t changed.
+fn upload_vertices(mesh: &mut Tess<Vertex, VertexIndex, (), Interleaved>, vertices: &[Vertex]) {
+  match mesh.vertices_mut() {
+    Ok(mut mesh_vertices) => mesh_vertices.copy_from_slice(vertices),
+    Err(e) => eprintln!("cannot update vertices: {}", e),
   }
 }
@@ -0,0 +1,141 @@
+//! Wavefront MTL materials.
+//!
+//! OBJ files point to a material library (`mtllib`), an .mtl file next to them, and pick
+//! materials from it by name (`usemtl`). wavefront_obj can parse .mtl files too, but it rejects a
+//! whole file over any statement it doesn’t know, and exporters write plenty of those (PBR
+//! extensions, bump maps, reflection maps…). This parser reads what the viewer uses and skips the
+//! rest.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Material of a model, with the Phong reflection model’s colors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+  pub name: String,
+  /// Color under ambient light (`Ka`).
+  pub ambient: [f32; 3],
+  /// Color under direct light (`Kd`).
+  pub diffuse: [f32; 3],
+  /// Color of the highlights (`Ks`).
+  pub specular: [f32; 3],
+  /// Sharpness of the highlights, as the exponent of the Phong lobe (`Ns`).
+  pub shininess: f32,
+  /// Textures modulating the colors (`map_Ka`, `map_Kd` and `map_Ks`); their paths are relative
+  /// to the .mtl file in the library, and resolved against its directory here.
+  pub ambient_map: Option<PathBuf>,
+  pub diffuse_map: Option<PathBuf>,
+  pub specular_map: Option<PathBuf>,
+  /// Draw the back of the faces too, lit from their own side, for thin geometry such as leaves,
+  /// cloth or open meshes; closed meshes are drawn faster with their back faces culled. MTL has no
+  /// statement for it.
+  pub two_sided: bool,
+}
+
+impl Default for Material {
+  /// The plain gray models without materials are shaded with.
+  fn default() -> Self {
+    Material {
+      name: "default".to_owned(),
+      ambient: [0., 0., 0.],
+      diffuse: [0.6, 0.6, 0.6],
+      specular: [0.4, 0.4, 0.4],
+      shininess: 64.,
+      ambient_map: None,
+      diffuse_map: None,
+      specular_map: None,
+      two_sided: false,
+    }
+  }
+}
+
+impl Material {
+  /// Texture maps of the material, with the statements they come from.
+  pub fn maps(&self) -> Vec<(&'static str, &Path)> {
+    let maps = [
+      ("map_Ka", &self.ambient_map),
+      ("map_Kd", &self.diffuse_map),
+      ("map_Ks", &self.specular_map),
+    ];
+
+    maps
+      .iter()
+      .filter_map(|&(statement, map)| Some((statement, map.as_deref()?)))
+      .collect()
+  }
+}
+
+/// Load all the materials of a material library.
+pub fn load_library(path: &Path) -> Result<Vec<Material>, String> {
+  let content = fs::read_to_string(path).map_err(|e| format!("cannot read: {}", e))?;
+  let dir = path.parent().unwrap_or_else(|| Path::new(""));
+  let mut materials: Vec<Material> = Vec::new();
+
+  for (line_nb, line) in content.lines().enumerate() {
+    let mut words = line.split_whitespace();
+    let statement = match words.next() {
+      Some(statement) if !statement.starts_with('#') => statement,
+      _ => continue,
+    };
+    let args = words.collect::<Vec<_>>();
+    let error = |what: &str| format!("line {}: {}", line_nb + 1, what);
+
+    if statement == "newmtl" {
+      let name = args.join(" ");
+      materials.push(Material {
+        name,
+        ..Material::default()
+      });
+      continue;
+    }
+
+    // everything else describes the last material declared
+    let material = match materials.last_mut() {
+      Some(material) => material,
+      None => continue,
+    };
+
+    match statement {
+      // colors can also be given as spectral curves or CIE XYZ, which aren’t supported
+      "Ka" | "Kd" | "Ks" if matches!(args.first(), Some(&"spectral") | Some(&"xyz")) => (),
+      "Ka" => material.ambient = parse_color(&args).ok_or_else(|| error("invalid Ka"))?,
+      "Kd" => material.diffuse = parse_color(&args).ok_or_else(|| error("invalid Kd"))?,
+      "Ks" => material.specular = parse_color(&args).ok_or_else(|| error("invalid Ks"))?,
+      "Ns" => {
+        material.shininess = args
+          .first()
+          .and_then(|ns| ns.parse().ok())
+          .ok_or_else(|| error("invalid Ns"))?
+      }
+      // options such as -s or -o come before the file name, which is last
+      "map_Ka" | "map_Kd" | "map_Ks" => {
+        let file = args.last().ok_or_else(|| error("missing texture file"))?;
+        let map = Some(dir.join(file));
+
+        match statement {
+          "map_Ka" => material.ambient_map = map,
+          "map_Kd" => material.diffuse_map = map,
+          _ => material.specular_map = map,
+        }
+      }
+      _ => (),
+    }
+  }
+
+  Ok(materials)
+}
+
+/// Parse an RGB color; a single value is a gray.
+fn parse_color(args: &[&str]) -> Option<[f32; 3]> {
+  let values = args
+    .iter()
+    .map(|arg| arg.parse::<f32>())
+    .collect::<Result<Vec<_>, _>>()
+    .ok()?;
+
+  match *values.as_slice() {
+    [gray] => Some([gray, gray, gray]),
+    [r, g, b] => Some([r, g, b]),
+    _ => None,
+  }
+}
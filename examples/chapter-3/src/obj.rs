@@ -0,0 +1,690 @@
+//! Wavefront OBJ loading.
+
+use crate::material::{self, Material};
+use crate::{Vertex, VertexIndex, VertexNormal, VertexObject, VertexPosition, VertexUV};
+use cgmath::{InnerSpace, Matrix4, Point3, SquareMatrix, Transform, Vector3, Zero};
+use luminance_front::context::GraphicsContext;
+use luminance_front::tess::{Interleaved, Mode, Tess, TessError};
+use luminance_front::Backend;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Read as _;
+use std::path::Path;
+use std::str::FromStr;
+use try_guard::verify;
+use wavefront_obj::obj;
+
+/// Axis pointing up in the source file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpAxis {
+  Y,
+  Z,
+}
+
+impl FromStr for UpAxis {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "y" | "Y" => Ok(UpAxis::Y),
+      "z" | "Z" => Ok(UpAxis::Z),
+      _ => Err(format!("unknown up axis: {} (expecting y or z)", s)),
+    }
+  }
+}
+
+/// Statistics about the source file, gathered while loading.
+#[derive(Clone, Debug)]
+pub struct ObjStats {
+  pub name: String,
+  pub positions: usize,
+  pub normals: usize,
+  pub shapes: usize,
+  /// Number of vertices the file gives no normal for; they get smooth ones instead.
+  pub generated_normals: usize,
+  /// Number of points and lines, such as the loose edges Blender exports, which aren’t drawn.
+  pub ignored_shapes: usize,
+  /// Scale converting the file units to meters, if the file says which units it uses.
+  pub unit_hint: Option<f32>,
+  /// Why the material of the model couldn’t be loaded, if it couldn’t; the model is then shaded
+  /// with the default material.
+  pub material_error: Option<String>,
+}
+
+pub struct Obj {
+  pub vertices: Vec<Vertex>,
+  pub indices: Vec<VertexIndex>,
+  /// Material picked from the material library of the file, if any.
+  pub material: Option<Material>,
+  pub stats: ObjStats,
+}
+
+impl Obj {
+  pub fn to_tess<C>(
+    &self,
+    ctxt: &mut C,
+  ) -> Result<Tess<Vertex, VertexIndex, (), Interleaved>, TessError>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    ctxt
+      .new_tess()
+      .set_mode(Mode::Triangle)
+      .set_vertices(self.vertices.clone())
+      .set_indices(self.indices.clone())
+      .build()
+  }
+
+  /// Replace the normals by smooth ones.
+  pub fn recompute_normals(&mut self) {
+    let vertices = (0..self.vertices.len()).collect::<Vec<_>>();
+    self.smooth_normals(&vertices);
+  }
+
+  /// Replace the normals of some vertices by smooth ones, computed by summing the face normals
+  /// around their positions.
+  ///
+  /// Face normals are not normalized before being summed, so that larger faces weigh more.
+  fn smooth_normals(&mut self, vertices: &[usize]) {
+    let mut normals: HashMap<[u32; 3], Vector3<f32>> = HashMap::new();
+
+    for triangle in self.indices.chunks_exact(3) {
+      let a = Vector3::from(*self.vertices[triangle[0] as usize].position);
+      let b = Vector3::from(*self.vertices[triangle[1] as usize].position);
+      let c = Vector3::from(*self.vertices[triangle[2] as usize].position);
+      let face_normal = (b - a).cross(c - a);
+
+      for &index in triangle {
+        let key = position_key(&self.vertices[index as usize]);
+        *normals.entry(key).or_insert_with(Vector3::zero) += face_normal;
+      }
+    }
+
+    for &index in vertices {
+      let vertex = &mut self.vertices[index];
+      let normal = normals
+        .get(&position_key(vertex))
+        .copied()
+        .filter(|n| n.magnitude2() > 0.)
+        .map_or_else(Vector3::zero, InnerSpace::normalize);
+      vertex.normal = VertexNormal::new(normal.into());
+    }
+  }
+
+  /// Convert the mesh from the basis it was authored in to ours (Y up, right-handed).
+  ///
+  /// Z-up models are rotated around the X axis. Flipping X mirrors the model, which changes its
+  /// handedness; the winding of the triangles is then reversed too, so that front faces stay front
+  /// faces.
+  pub fn convert_basis(&mut self, up: UpAxis, flip_x: bool) {
+    let convert = |[x, y, z]: [f32; 3]| {
+      let x = if flip_x { -x } else { x };
+
+      match up {
+        UpAxis::Y => [x, y, z],
+        UpAxis::Z => [x, z, -y],
+      }
+    };
+
+    for vertex in &mut self.vertices {
+      vertex.position = VertexPosition::new(convert(*vertex.position));
+      vertex.normal = VertexNormal::new(convert(*vertex.normal));
+    }
+
+    if flip_x {
+      for triangle in self.indices.chunks_exact_mut(3) {
+        triangle.swap(1, 2);
+      }
+    }
+  }
+
+  /// Scale the mesh uniformly.
+  pub fn scale(&mut self, factor: f32) {
+    for vertex in &mut self.vertices {
+      let [x, y, z] = *vertex.position;
+      vertex.position = VertexPosition::new([x * factor, y * factor, z * factor]);
+    }
+  }
+
+  /// Axis-aligned bounding box of the mesh, as its minimum and maximum corners.
+  pub fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+
+    for vertex in &self.vertices {
+      for (i, &x) in vertex.position.iter().enumerate() {
+        min[i] = min[i].min(x);
+        max[i] = max[i].max(x);
+      }
+    }
+
+    (min, max)
+  }
+
+  /// Move the mesh to the origin and scale it so that its largest extent is 1, and return the
+  /// transform that did it.
+  ///
+  /// The camera and clip planes are set for a model of about that size, so this makes any model
+  /// viewable, whatever units it was authored in.
+  pub fn fit_unit(&mut self) -> Matrix4<f32> {
+    let (min, max) = self.bounds();
+    let extent = (0..3).map(|i| max[i] - min[i]).fold(0., f32::max);
+
+    if extent <= 0. {
+      return Matrix4::identity();
+    }
+
+    let center = Vector3::new(
+      (min[0] + max[0]) * 0.5,
+      (min[1] + max[1]) * 0.5,
+      (min[2] + max[2]) * 0.5,
+    );
+    let transform = Matrix4::from_scale(1. / extent) * Matrix4::from_translation(-center);
+
+    for vertex in &mut self.vertices {
+      let position = transform.transform_point(Point3::from(*vertex.position));
+      vertex.position = VertexPosition::new(position.into());
+    }
+
+    transform
+  }
+
+  /// Make all normals point the other way.
+  pub fn flip_normals(&mut self) {
+    for vertex in &mut self.vertices {
+      let [x, y, z] = *vertex.normal;
+      vertex.normal = VertexNormal::new([-x, -y, -z]);
+    }
+  }
+
+  /// Load every mesh of a file: one per geometry group of each of its objects.
+  ///
+  /// The meshes of a file share its space, so they stay where they are relative to each other.
+  pub fn load<P>(path: P) -> Result<Vec<Self>, String>
+  where
+    P: AsRef<Path>,
+  {
+    let path = path.as_ref();
+    let file_content = {
+      let mut file = File::open(path).map_err(|e| format!("cannot open file: {}", e))?;
+      let mut content = String::new();
+      file
+        .read_to_string(&mut content)
+        .map_err(|e| format!("cannot read file: {}", e))?;
+      content
+    };
+    let unit_hint = detect_unit(&file_content);
+    let obj_set = obj::parse(triangulate_polygons(&file_content))
+      .map_err(|e| format!("cannot parse: {:?}", e))?;
+
+    // a missing or broken material library isn’t worth giving up on the model; it’s loaded once
+    // for all the meshes using it
+    let library = obj_set.material_library.map(|library| {
+      let library = path.parent().unwrap_or_else(|| Path::new("")).join(library);
+      let materials = material::load_library(&library)
+        .map_err(|e| format!("cannot load {}: {}", library.display(), e));
+      (library, materials)
+    });
+
+    let mut meshes = Vec::new();
+
+    for mut object in obj_set.objects {
+      let geometries = std::mem::take(&mut object.geometry);
+      let several = geometries.len() > 1;
+
+      for (i, geometry) in geometries.into_iter().enumerate() {
+        let (material, material_error) = match (&library, &geometry.material_name) {
+          (Some((library, materials)), Some(name)) => match find_material(library, materials, name)
+          {
+            Ok(material) => (Some(material), None),
+            Err(e) => (None, Some(e)),
+          },
+          _ => (None, None),
+        };
+
+        let name = if several {
+          format!("{}[{}]", object.name, i)
+        } else {
+          object.name.clone()
+        };
+
+        let mut mesh = Self::from_geometry(&object, geometry, name)?;
+        mesh.material = material;
+        mesh.stats.unit_hint = unit_hint;
+        mesh.stats.material_error = material_error;
+
+        // the positions and normals of an object are shared by all its geometries; they’re counted
+        // with the first one only, so that they add up to the number in the file
+        if i > 0 {
+          mesh.stats.positions = 0;
+          mesh.stats.normals = 0;
+        }
+
+        meshes.push(mesh);
+      }
+    }
+
+    verify!(!meshes.is_empty()).ok_or("no geometry in the file".to_owned())?;
+
+    Ok(meshes)
+  }
+
+  /// Build the mesh of a geometry group of an object.
+  fn from_geometry(
+    object: &obj::Object,
+    geometry: obj::Geometry,
+    name: String,
+  ) -> Result<Self, String> {
+    let shapes = geometry.shapes.len();
+
+    // build up vertices; for this to work, we remove duplicated vertices by putting them in a
+    // map associating the vertex with its ID
+    let mut vertex_cache: HashMap<obj::VTNIndex, VertexIndex> = HashMap::new();
+    let mut vertices: Vec<Vertex> = Vec::new();
+    let mut indices: Vec<VertexIndex> = Vec::new();
+    // vertices without normals, which get smooth ones once all the faces are known
+    let mut missing_normals: Vec<usize> = Vec::new();
+    let mut ignored_shapes = 0;
+
+    // quads are already split into triangle fans by the parser, and larger polygons into ears
+    // before parsing
+    for shape in geometry.shapes {
+      if let obj::Primitive::Triangle(a, b, c) = shape.primitive {
+        for key in &[a, b, c] {
+          if let Some(vertex_index) = vertex_cache.get(key) {
+            indices.push(*vertex_index);
+          } else {
+            let p = object.vertices[key.0];
+            let position = VertexPosition::new([p.x as f32, p.y as f32, p.z as f32]);
+            let normal = match key.2 {
+              Some(i) => {
+                let n = object.normals[i];
+                VertexNormal::new([n.x as f32, n.y as f32, n.z as f32])
+              }
+              None => {
+                missing_normals.push(vertices.len());
+                VertexNormal::new([0., 0., 0.])
+              }
+            };
+            // vertices without texture coordinates all sample the corner of the texture
+            let uv = match key.1 {
+              Some(i) => {
+                let t = object.tex_vertices[i];
+                VertexUV::new([t.u as f32, t.v as f32])
+              }
+              None => VertexUV::new([0., 0.]),
+            };
+            let object = VertexObject::new(0);
+            let vertex = Vertex {
+              position,
+              normal,
+              uv,
+              object,
+            };
+            let vertex_index = vertices.len() as VertexIndex;
+
+            vertex_cache.insert(*key, vertex_index);
+            vertices.push(vertex);
+            indices.push(vertex_index);
+          }
+        }
+      } else {
+        ignored_shapes += 1;
+      }
+    }
+
+    let stats = ObjStats {
+      name,
+      positions: object.vertices.len(),
+      normals: object.normals.len(),
+      shapes,
+      generated_normals: missing_normals.len(),
+      ignored_shapes,
+      unit_hint: None,
+      material_error: None,
+    };
+    let mut obj = Obj {
+      vertices,
+      indices,
+      material: None,
+      stats,
+    };
+
+    if !missing_normals.is_empty() {
+      obj.smooth_normals(&missing_normals);
+    }
+
+    Ok(obj)
+  }
+}
+
+/// Look for a material in the materials loaded from a material library.
+fn find_material(
+  library: &Path,
+  materials: &Result<Vec<Material>, String>,
+  name: &str,
+) -> Result<Material, String> {
+  materials
+    .as_ref()
+    .map_err(Clone::clone)?
+    .iter()
+    .find(|material| material.name == name)
+    .cloned()
+    .ok_or_else(|| format!("no material {} in {}", name, library.display()))
+}
+
+/// Scale factor converting a length unit to meters, the unit the viewer works in.
+pub fn unit_to_meters(unit: &str) -> Option<f32> {
+  match unit.to_lowercase().as_str() {
+    "mm" | "millimeter" | "millimeters" | "millimetre" | "millimetres" => Some(0.001),
+    "cm" | "centimeter" | "centimeters" | "centimetre" | "centimetres" => Some(0.01),
+    "m" | "meter" | "meters" | "metre" | "metres" => Some(1.),
+    "in" | "inch" | "inches" => Some(0.0254),
+    "ft" | "foot" | "feet" => Some(0.3048),
+    _ => None,
+  }
+}
+
+/// Look for the units of the file in its comments.
+///
+/// OBJ has no standard way to store units, but several CAD exporters leave a comment such as
+/// `# Units: millimeters` in the header. Only a unit right after a `unit` or `units` keyword
+/// counts, so that comments merely mentioning units aren’t taken for a declaration.
+fn detect_unit(content: &str) -> Option<f32> {
+  content
+    .lines()
+    .filter_map(|line| line.trim_start().strip_prefix('#'))
+    .find_map(|comment| {
+      let comment = comment.to_lowercase();
+      let mut words = comment.split_whitespace();
+
+      while let Some(word) = words.next() {
+        // the separator is either glued to the keyword or a word of its own
+        let (keyword, rest) = match word.find(&[':', '='][..]) {
+          Some(i) => (&word[..i], &word[i + 1..]),
+          None => (word, ""),
+        };
+
+        if keyword != "unit" && keyword != "units" {
+          continue;
+        }
+
+        let token = match rest {
+          "" => match words.next()? {
+            ":" | "=" => words.next()?,
+            token => token,
+          },
+          rest => rest,
+        };
+
+        return unit_to_meters(token.trim_end_matches(&[',', ';', '.'][..]));
+      }
+
+      None
+    })
+}
+
+/// Split the faces of more than four vertices of an OBJ file into triangles, by ear clipping.
+///
+/// The parser splits polygons into triangle fans, which only works for convex ones: the fan of a
+/// concave polygon, such as the outline of a letter, covers its notches. The triangles reuse the
+/// vertex references of the face, so they’re parsed like the rest of the file.
+fn triangulate_polygons(content: &str) -> String {
+  let mut positions = Vec::new();
+  let mut triangulated = String::with_capacity(content.len());
+
+  for line in content.lines() {
+    let mut words = line.split('#').next().unwrap_or("").split_whitespace();
+
+    match words.next() {
+      Some("v") => {
+        // a broken position is reported by the parser; it still takes its index
+        let p = words
+          .take(3)
+          .filter_map(|word| word.parse().ok())
+          .collect::<Vec<f32>>();
+        positions.push(match *p.as_slice() {
+          [x, y, z] => Vector3::new(x, y, z),
+          _ => Vector3::zero(),
+        });
+      }
+
+      Some("f") => {
+        let corners = words.collect::<Vec<_>>();
+        let points = corners
+          .iter()
+          .map(|corner| corner_position(corner, &positions))
+          .collect::<Option<Vec<_>>>();
+
+        if let (true, Some(points)) = (corners.len() > 4, points) {
+          for [a, b, c] in ear_clip(&points) {
+            let _ = writeln!(
+              triangulated,
+              "f {} {} {}",
+              corners[a], corners[b], corners[c]
+            );
+          }
+
+          continue;
+        }
+      }
+
+      _ => (),
+    }
+
+    triangulated.push_str(line);
+    triangulated.push('\n');
+  }
+
+  triangulated
+}
+
+/// Position a face corner such as `3/1/2` or `-1//4` refers to, among the ones read so far.
+fn corner_position(corner: &str, positions: &[Vector3<f32>]) -> Option<Vector3<f32>> {
+  let index = corner.split('/').next()?.parse::<i64>().ok()?;
+  let index = if index < 0 {
+    positions.len() as i64 + index
+  } else {
+    index - 1
+  };
+
+  positions.get(usize::try_from(index).ok()?).copied()
+}
+
+/// Triangles covering a simple polygon, as indices of its points.
+///
+/// The polygon is flattened on the axis-aligned plane it faces the most, then ears (triangles of
+/// three consecutive corners with no other corner inside) are cut off one by one. Degenerate
+/// polygons, which run out of ears, get what’s left fanned.
+fn ear_clip(points: &[Vector3<f32>]) -> Vec<[usize; 3]> {
+  let len = points.len();
+
+  // Newell’s normal, which holds for concave polygons too
+  let normal = (0..len).fold(Vector3::zero(), |normal, i| {
+    let (p, q) = (points[i], points[(i + 1) % len]);
+    normal
+      + Vector3::new(
+        (p.y - q.y) * (p.z + q.z),
+        (p.z - q.z) * (p.x + q.x),
+        (p.x - q.x) * (p.y + q.y),
+      )
+  });
+  let (u, v, w) = if normal.x.abs() >= normal.y.abs() && normal.x.abs() >= normal.z.abs() {
+    (1, 2, 0)
+  } else if normal.y.abs() >= normal.z.abs() {
+    (2, 0, 1)
+  } else {
+    (0, 1, 2)
+  };
+
+  // flipped if needed so that the polygon winds counterclockwise
+  let sign = normal[w].signum();
+  let flat = points
+    .iter()
+    .map(|p| [p[u], p[v] * sign])
+    .collect::<Vec<_>>();
+  let cross = |a: [f32; 2], b: [f32; 2], c: [f32; 2]| {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+  };
+
+  let mut remaining = (0..len).collect::<Vec<_>>();
+  let mut triangles = Vec::with_capacity(len - 2);
+
+  while remaining.len() > 3 {
+    let n = remaining.len();
+    let corners = |i: usize| {
+      (
+        remaining[(i + n - 1) % n],
+        remaining[i],
+        remaining[(i + 1) % n],
+      )
+    };
+    let ear = (0..n).find(|&i| {
+      let (a, b, c) = corners(i);
+
+      cross(flat[a], flat[b], flat[c]) > 0.
+        && remaining.iter().all(|&j| {
+          let p = flat[j];
+          j == a
+            || j == b
+            || j == c
+            || cross(flat[a], flat[b], p) < 0.
+            || cross(flat[b], flat[c], p) < 0.
+            || cross(flat[c], flat[a], p) < 0.
+        })
+    });
+
+    match ear {
+      Some(i) => {
+        let (a, b, c) = corners(i);
+        triangles.push([a, b, c]);
+        remaining.remove(i);
+      }
+
+      None => break,
+    }
+  }
+
+  for i in 1..remaining.len() - 1 {
+    triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+  }
+
+  triangles
+}
+
+/// Vertices sharing the same position share the same key, whatever their other attributes.
+fn position_key(vertex: &Vertex) -> [u32; 3] {
+  let [x, y, z] = *vertex.position;
+  [x.to_bits(), y.to_bits(), z.to_bits()]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// An L lying on the XZ plane, facing up; its fan from the first corner crosses the notch.
+  const CONCAVE: &str = "v 0 0 0
+v 2 0 0
+v 2 0 -1
+v 1 0 -1
+v 1 0 -2
+v 0 0 -2
+f 2 3 4 5 6 1
+";
+
+  #[test]
+  fn ear_clip_concave_polygon() {
+    let triangulated = triangulate_polygons(CONCAVE);
+    let faces = triangulated
+      .lines()
+      .filter_map(|line| line.strip_prefix("f "))
+      .map(|face| {
+        face
+          .split(' ')
+          .map(|i| i.parse::<usize>().unwrap() - 1)
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+    let positions = CONCAVE
+      .lines()
+      .filter_map(|line| line.strip_prefix("v "))
+      .map(|p| {
+        let p = p
+          .split(' ')
+          .map(|x| x.parse().unwrap())
+          .collect::<Vec<f32>>();
+        Vector3::new(p[0], p[1], p[2])
+      })
+      .collect::<Vec<_>>();
+
+    assert_eq!(faces.len(), 4);
+
+    // the triangles face up like the polygon, add up to its area and leave the notch empty
+    let notch = [
+      Vector3::new(1.1, 0., -1.5),
+      Vector3::new(1.5, 0., -1.5),
+      Vector3::new(1.9, 0., -1.1),
+    ];
+    let mut area = 0.;
+
+    for face in faces {
+      let [a, b, c] = [positions[face[0]], positions[face[1]], positions[face[2]]];
+      let normal = (b - a).cross(c - a);
+
+      assert!(normal.y > 0.);
+      area += normal.magnitude() * 0.5;
+
+      for &p in &notch {
+        let inside = [(a, b), (b, c), (c, a)]
+          .iter()
+          .all(|&(from, to)| (to - from).cross(p - from).y > 0.);
+        assert!(!inside, "{:?} covers {:?}", face, p);
+      }
+    }
+
+    assert!((area - 3.).abs() < 1e-6);
+  }
+
+  #[test]
+  fn triangulate_keeps_quads_and_references() {
+    let content = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n";
+    assert_eq!(triangulate_polygons(content), content);
+
+    let content = "v 0 0 0\nv 1 0 0\nv 2 1 0\nv 1 2 0\nv 0 1 0\nf -5/1 -4/2 -3/3 -2/4 -1/5\n";
+    let triangulated = triangulate_polygons(content);
+    let faces = triangulated
+      .lines()
+      .filter_map(|line| line.strip_prefix("f "))
+      .collect::<Vec<_>>();
+
+    assert_eq!(faces.len(), 3);
+    assert!(faces
+      .iter()
+      .flat_map(|face| face.split(' '))
+      .all(|corner| ["-5/1", "-4/2", "-3/3", "-2/4", "-1/5"].contains(&corner)));
+  }
+
+  #[test]
+  fn detect_unit_declarations() {
+    assert_eq!(detect_unit("# Units: millimeters\nv 0 0 0"), Some(0.001));
+    assert_eq!(detect_unit("# unit: cm"), Some(0.01));
+    assert_eq!(detect_unit("# units inches"), Some(0.0254));
+    assert_eq!(
+      detect_unit("# exported by some tool, units = ft"),
+      Some(0.3048)
+    );
+  }
+
+  #[test]
+  fn detect_unit_ignores_mentions() {
+    assert_eq!(
+      detect_unit("# normals are unit length in object space"),
+      None
+    );
+    assert_eq!(detect_unit("# units are not meters"), None);
+    assert_eq!(detect_unit("# unit\n# mm"), None);
+    assert_eq!(detect_unit("v 0 0 0 # units: mm"), None);
+  }
+}
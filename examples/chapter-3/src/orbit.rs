@@ -0,0 +1,86 @@
+//! Orbit camera.
+//!
+//! The camera turns around a target, on a sphere: dragging the mouse changes its azimuth (around
+//! the vertical axis) and its elevation (above or below the horizon), and scrolling changes its
+//! distance to the target. The elevation stops short of the poles, where the up vector of the view
+//! would be aligned with the direction of the camera.
+
+use cgmath::{InnerSpace, Point3, Rad, Vector3};
+use std::f32::consts::FRAC_PI_2;
+
+/// Rotation per pixel dragged, in radians.
+const SENSITIVITY: f32 = 0.01;
+/// Highest elevation, in radians, just under the vertical.
+const MAX_ELEVATION: f32 = FRAC_PI_2 - 0.01;
+/// Change of the distance per scroll step.
+const ZOOM_STEP: f32 = 1.1;
+/// Closest the camera gets to its target.
+const MIN_DISTANCE: f32 = 1e-3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Orbit {
+  /// Angle around the vertical axis, 0 looking from +Z.
+  pub azimuth: Rad<f32>,
+  /// Angle above the horizon.
+  pub elevation: Rad<f32>,
+  /// Distance to the target.
+  pub distance: f32,
+}
+
+impl Orbit {
+  /// Orbit at `distance` from the target, its elevation kept off the poles.
+  pub fn new(azimuth: Rad<f32>, elevation: Rad<f32>, distance: f32) -> Self {
+    Orbit {
+      azimuth,
+      elevation: Rad(elevation.0.clamp(-MAX_ELEVATION, MAX_ELEVATION)),
+      distance: distance.max(MIN_DISTANCE),
+    }
+  }
+
+  /// Orbit going through `eye`.
+  pub fn looking_at(eye: Point3<f32>, target: Point3<f32>) -> Self {
+    let offset = eye - target;
+    let distance = offset.magnitude();
+
+    if distance < MIN_DISTANCE {
+      return Orbit {
+        azimuth: Rad(0.),
+        elevation: Rad(0.),
+        distance: 1.,
+      };
+    }
+
+    Orbit {
+      azimuth: Rad(offset.x.atan2(offset.z)),
+      elevation: Rad(
+        (offset.y / distance)
+          .asin()
+          .clamp(-MAX_ELEVATION, MAX_ELEVATION),
+      ),
+      distance,
+    }
+  }
+
+  /// Position of the camera.
+  pub fn eye(&self, target: Point3<f32>) -> Point3<f32> {
+    let (sin_az, cos_az) = self.azimuth.0.sin_cos();
+    let (sin_el, cos_el) = self.elevation.0.sin_cos();
+
+    target + Vector3::new(cos_el * sin_az, sin_el, cos_el * cos_az) * self.distance
+  }
+
+  /// Turn around the target after dragging the mouse by `dx` and `dy` pixels.
+  ///
+  /// The model follows the cursor: dragging right brings its left side into view, and dragging
+  /// down shows it from above.
+  pub fn rotate(&mut self, dx: f32, dy: f32) {
+    self.azimuth -= Rad(dx * SENSITIVITY);
+    self.elevation =
+      Rad((self.elevation.0 + dy * SENSITIVITY).clamp(-MAX_ELEVATION, MAX_ELEVATION));
+  }
+
+  /// Move closer to the target by a number of scroll steps, or away from it if negative.
+  pub fn zoom(&mut self, steps: f32) {
+    self.distance = (self.distance * ZOOM_STEP.powf(-steps)).max(MIN_DISTANCE);
+  }
+}
@@ -0,0 +1,6 @@
+out float frag_count;
+
+void main() {
+  // blended additively, so that each pixel counts the fragments drawn over it
+  frag_count = 1.;
+}
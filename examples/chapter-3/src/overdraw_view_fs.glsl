@@ -0,0 +1,22 @@
+in vec2 v_uv;
+
+out vec3 frag_color;
+
+uniform sampler2D counts;
+// number of fragments per pixel shown in red
+uniform float max_count;
+
+// blue for a single fragment, through green and yellow, to red for max_count fragments or more
+vec3 heat(float x) {
+  return clamp(1.5 - abs(4. * x - vec3(3., 2., 1.)), 0., 1.);
+}
+
+void main() {
+  float count = texture(counts, v_uv).r;
+
+  if (count < .5) {
+    frag_color = vec3(0.);
+  } else {
+    frag_color = heat((count - 1.) / (max_count - 1.));
+  }
+}
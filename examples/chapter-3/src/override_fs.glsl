@@ -0,0 +1,45 @@
+in vec3 v_position;
+in vec3 v_normal;
+in vec2 v_uv;
+flat in uint v_object;
+
+out vec3 frag_color;
+
+// 0: albedo, the unlit color of the objects
+// 1: world normals, mapped from [-1; 1] to [0; 1]
+// 2: checkerboard in world space, which doesn’t depend on the texture coordinates
+uniform int mode;
+// size of the checker cells
+uniform float cell;
+// diffuse color of the model’s material
+uniform vec3 diffuse = vec3(.6);
+// texture modulating the diffuse color, if the material has one
+uniform sampler2D diffuse_map;
+uniform bool textured = false;
+// back faces are drawn too; their normals point to their own side
+uniform bool two_sided = false;
+
+// same tints as the regular shading
+vec3 object_color(uint object) {
+  if (object == 0u) {
+    return textured ? diffuse * texture(diffuse_map, v_uv).rgb : diffuse;
+  }
+
+  float hue = fract(float(object) * .618034);
+  return .45 + .25 * cos(6.28318 * (hue + vec3(0., .33, .67)));
+}
+
+void main() {
+  vec3 n = normalize(two_sided && !gl_FrontFacing ? -v_normal : v_normal);
+
+  if (mode == 0) {
+    frag_color = object_color(v_object);
+  } else if (mode == 1) {
+    frag_color = n * .5 + .5;
+  } else {
+    ivec3 c = ivec3(floor(v_position / cell));
+    float checker = ((c.x + c.y + c.z) & 1) == 0 ? .9 : .2;
+    // a bit of lighting keeps the shape readable
+    frag_color = vec3(checker) * (.5 + .5 * abs(n.y));
+  }
+}
@@ -0,0 +1,26 @@
+in vec3 position;
+in vec3 normal;
+in vec2 uv;
+in uint object;
+
+out vec3 v_position;
+out vec3 v_normal;
+out vec2 v_uv;
+flat out uint v_object;
+
+uniform mat4 projection;
+uniform mat4 view;
+// identity, unless objects are drawn one by one
+uniform mat4 model = mat4(1.);
+// inverse transpose of the model matrix, which keeps normals perpendicular under any scale
+uniform mat3 normal_matrix = mat3(1.);
+
+void main() {
+  vec4 world = model * vec4(position, 1.);
+
+  v_position = world.xyz;
+  v_normal = normal_matrix * normal;
+  v_uv = uv;
+  v_object = object;
+  gl_Position = projection * view * world;
+}
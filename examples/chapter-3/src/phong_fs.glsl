@@ -0,0 +1,44 @@
+in vec3 v_position;
+in vec3 v_normal;
+in vec2 v_uv;
+
+out vec3 frag_color;
+
+uniform vec3 camera_pos;
+
+// material of the model
+uniform vec3 ambient = vec3(0.);
+uniform vec3 diffuse = vec3(.6);
+uniform vec3 specular = vec3(.4);
+uniform float shininess = 64.;
+// texture modulating the diffuse color, if the material has one
+uniform sampler2D diffuse_map;
+uniform bool textured = false;
+// back faces are drawn too, and lit from their own side
+uniform bool two_sided = false;
+
+// lights of the lighting preset, the same as the Lambert shading’s
+uniform mat3 light_dirs;
+uniform mat3 light_colors;
+uniform vec3 ambient_light;
+uniform float exposure;
+
+void main() {
+  vec3 n = normalize(two_sided && !gl_FrontFacing ? -v_normal : v_normal);
+  vec3 to_camera = normalize(camera_pos - v_position);
+  vec3 albedo = textured ? diffuse * texture(diffuse_map, v_uv).rgb : diffuse;
+  vec3 color = ambient * ambient_light;
+
+  // the same lights as the Lambert shading, plus a highlight where the normal is halfway between
+  // the light and the camera
+  for (int i = 0; i < 3; ++i) {
+    vec3 to_light = -normalize(light_dirs[i]);
+    float kd = max(dot(n, to_light), 0.);
+    // a null exponent would light the whole model up as a highlight
+    float ks = pow(max(dot(n, normalize(to_camera + to_light)), 0.), max(shininess, 1.));
+
+    color += light_colors[i] * (albedo * kd + specular * ks);
+  }
+
+  frag_color = color * exposure;
+}
@@ -0,0 +1,393 @@
+//! Remote control over a local WebSocket.
+//!
+//! The viewer listens on localhost for WebSocket connections, so that scripts and asset pipelines
+//! can drive it, e.g. to render thumbnails. Every text message is a command, answered by a text
+//! message, `ok` or `error: <reason>`:
+//!
+//! - the name of an action, as in input recordings: `cycle-shading`, `export-camera`, `quit`…
+//! - `camera <azimuth> <elevation> <distance>`, an orbit as in the state file;
+//! - `fov <degrees>`;
+//! - `screenshot <file>`, saving the next frame to a binary PPM image; it’s answered once saved.
+//!
+//! Models can’t be loaded remotely: everything built around them, from the batch to the picking
+//! hierarchy and the environment maps, is set up once at startup.
+//!
+//! Only what clients need of the protocol (RFC 6455) is implemented, with the standard library
+//! alone: the handshake, unfragmented text messages, pings and closing. Every connection is served
+//! by a thread of its own, which hands commands to the render loop and waits for their answer.
+
+use crate::input::Action;
+use crate::orbit::Orbit;
+use crate::state::{parse_fov, parse_orbit};
+use cgmath::Deg;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender, TryIter};
+use std::thread;
+
+/// Appended to the key of the client to prove the server understood the handshake.
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Largest message accepted; commands are a few words long.
+const MAX_PAYLOAD: u64 = 64 * 1024;
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+#[derive(Clone, Debug)]
+pub enum Command {
+  Action(Action),
+  Camera(Orbit),
+  Fov(Deg<f32>),
+  Screenshot(PathBuf),
+}
+
+impl Command {
+  fn parse(s: &str) -> Result<Self, String> {
+    let s = s.trim();
+    let (name, args) = s.split_once(' ').unwrap_or((s, ""));
+
+    match name {
+      "camera" => parse_orbit(args)
+        .map(Command::Camera)
+        .ok_or_else(|| format!("invalid camera: {}", args)),
+      "fov" => parse_fov(args)
+        .map(Command::Fov)
+        .ok_or_else(|| format!("invalid field of view: {}", args)),
+      "screenshot" if !args.trim().is_empty() => Ok(Command::Screenshot(args.trim().into())),
+      "screenshot" => Err("missing screenshot file".to_owned()),
+      _ => s.parse().map(Command::Action),
+    }
+  }
+}
+
+/// Command waiting to be applied by the render loop.
+#[derive(Debug)]
+pub struct Request {
+  pub command: Command,
+  reply: Sender<Result<(), String>>,
+}
+
+impl Request {
+  /// Answer the client, once the command is applied.
+  pub fn reply(self, result: Result<(), String>) {
+    // the client may be gone already
+    let _ = self.reply.send(result);
+  }
+}
+
+pub struct Remote {
+  requests: Receiver<Request>,
+}
+
+impl Remote {
+  /// Listen for connections on `port` of the loopback interface.
+  pub fn listen(port: u16) -> Result<Self, String> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+      .map_err(|e| format!("cannot listen on port {}: {}", port, e))?;
+    let (tx, requests) = channel();
+
+    thread::spawn(move || {
+      for stream in listener.incoming() {
+        match stream {
+          Ok(stream) => {
+            let tx = tx.clone();
+
+            thread::spawn(move || {
+              if let Err(e) = serve(stream, tx) {
+                eprintln!("remote connection closed: {}", e);
+              }
+            });
+          }
+
+          Err(e) => eprintln!("cannot accept remote connection: {}", e),
+        }
+      }
+    });
+
+    Ok(Remote { requests })
+  }
+
+  /// Commands received since the last call.
+  pub fn requests(&self) -> TryIter<'_, Request> {
+    self.requests.try_iter()
+  }
+}
+
+/// Serve a connection until it’s closed.
+fn serve(stream: TcpStream, requests: Sender<Request>) -> io::Result<()> {
+  let mut writer = stream.try_clone()?;
+  let mut reader = BufReader::new(stream);
+
+  handshake(&mut reader, &mut writer)?;
+
+  loop {
+    let (opcode, payload) = read_frame(&mut reader)?;
+
+    match opcode {
+      OPCODE_TEXT => {
+        let result = match String::from_utf8(payload) {
+          Ok(text) => Command::parse(&text).and_then(|command| {
+            let (reply, answer) = channel();
+            let request = Request { command, reply };
+
+            requests
+              .send(request)
+              .map_err(|_| "the viewer is shutting down".to_owned())?;
+            answer
+              .recv()
+              .map_err(|_| "the viewer is shutting down".to_owned())?
+          }),
+
+          Err(_) => Err("invalid UTF-8".to_owned()),
+        };
+
+        let answer = match result {
+          Ok(()) => "ok".to_owned(),
+          Err(e) => format!("error: {}", e),
+        };
+
+        write_frame(&mut writer, OPCODE_TEXT, answer.as_bytes())?;
+      }
+
+      OPCODE_CLOSE => return write_frame(&mut writer, OPCODE_CLOSE, &[]),
+      OPCODE_PING => write_frame(&mut writer, OPCODE_PONG, &payload)?,
+      OPCODE_PONG => (),
+      _ => write_frame(
+        &mut writer,
+        OPCODE_TEXT,
+        b"error: only text messages are supported",
+      )?,
+    }
+  }
+}
+
+/// Read the HTTP upgrade request of the client and accept it.
+fn handshake(reader: &mut impl BufRead, writer: &mut impl Write) -> io::Result<()> {
+  let mut key = None;
+
+  loop {
+    let mut line = String::new();
+
+    if reader.read_line(&mut line)? == 0 {
+      return Err(invalid_data("connection closed during the handshake"));
+    }
+
+    let line = line.trim_end();
+
+    if line.is_empty() {
+      break;
+    }
+
+    if let Some((name, value)) = line.split_once(':') {
+      if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+        key = Some(value.trim().to_owned());
+      }
+    }
+  }
+
+  let key = key.ok_or_else(|| invalid_data("not a WebSocket handshake"))?;
+
+  write!(
+    writer,
+    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+     Sec-WebSocket-Accept: {}\r\n\r\n",
+    accept_key(&key)
+  )
+}
+
+/// Key proving the server understood the handshake of the client.
+fn accept_key(key: &str) -> String {
+  base64(&sha1(format!("{}{}", key, HANDSHAKE_GUID).as_bytes()))
+}
+
+/// Read a complete frame, returning its opcode and its unmasked payload.
+fn read_frame(reader: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+  let mut header = [0; 2];
+  reader.read_exact(&mut header)?;
+
+  let fin = header[0] & 0x80 != 0;
+  let opcode = header[0] & 0x0F;
+  let masked = header[1] & 0x80 != 0;
+
+  if !fin {
+    return Err(invalid_data("fragmented messages are not supported"));
+  }
+
+  let len = match header[1] & 0x7F {
+    126 => {
+      let mut len = [0; 2];
+      reader.read_exact(&mut len)?;
+      u16::from_be_bytes(len) as u64
+    }
+
+    127 => {
+      let mut len = [0; 8];
+      reader.read_exact(&mut len)?;
+      u64::from_be_bytes(len)
+    }
+
+    len => len as u64,
+  };
+
+  if len > MAX_PAYLOAD {
+    return Err(invalid_data("message too large"));
+  }
+
+  // clients mask their frames; an all-zero mask leaves the others as they are
+  let mut mask = [0; 4];
+
+  if masked {
+    reader.read_exact(&mut mask)?;
+  }
+
+  let mut payload = vec![0; len as usize];
+  reader.read_exact(&mut payload)?;
+
+  for (i, byte) in payload.iter_mut().enumerate() {
+    *byte ^= mask[i % 4];
+  }
+
+  Ok((opcode, payload))
+}
+
+/// Write an unfragmented frame; servers don’t mask their frames.
+fn write_frame(writer: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+  let mut frame = vec![0x80 | opcode];
+
+  match payload.len() {
+    len if len < 126 => frame.push(len as u8),
+    len if len <= u16::MAX as usize => {
+      frame.push(126);
+      frame.extend_from_slice(&(len as u16).to_be_bytes());
+    }
+    len => {
+      frame.push(127);
+      frame.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+  }
+
+  frame.extend_from_slice(payload);
+  writer.write_all(&frame)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// SHA-1 digest, which the handshake requires; it’s not used for anything security related.
+fn sha1(data: &[u8]) -> [u8; 20] {
+  let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+  // the message is padded with a single set bit, zeros and its length in bits, to whole blocks
+  let mut message = data.to_vec();
+  message.push(0x80);
+
+  while message.len() % 64 != 56 {
+    message.push(0);
+  }
+
+  message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+  for block in message.chunks(64) {
+    let mut w = [0u32; 80];
+
+    for (w, word) in w.iter_mut().zip(block.chunks(4)) {
+      *w = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+    }
+
+    for i in 16..80 {
+      w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = h;
+
+    for (i, &w) in w.iter().enumerate() {
+      let (f, k) = match i {
+        0..=19 => ((b & c) | (!b & d), 0x5A827999),
+        20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+        40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+        _ => (b ^ c ^ d, 0xCA62C1D6),
+      };
+
+      let t = a
+        .rotate_left(5)
+        .wrapping_add(f)
+        .wrapping_add(e)
+        .wrapping_add(k)
+        .wrapping_add(w);
+      e = d;
+      d = c;
+      c = b.rotate_left(30);
+      b = a;
+      a = t;
+    }
+
+    for (h, x) in h.iter_mut().zip(&[a, b, c, d, e]) {
+      *h = h.wrapping_add(*x);
+    }
+  }
+
+  let mut digest = [0; 20];
+
+  for (bytes, h) in digest.chunks_mut(4).zip(&h) {
+    bytes.copy_from_slice(&h.to_be_bytes());
+  }
+
+  digest
+}
+
+/// Standard base64, with padding.
+fn base64(data: &[u8]) -> String {
+  const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+  let mut encoded = String::new();
+
+  for chunk in data.chunks(3) {
+    let bytes = [
+      chunk[0],
+      *chunk.get(1).unwrap_or(&0),
+      *chunk.get(2).unwrap_or(&0),
+    ];
+    let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+    for i in 0..4 {
+      if i <= chunk.len() {
+        encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+      } else {
+        encoded.push('=');
+      }
+    }
+  }
+
+  encoded
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn accept_key_of_the_rfc_example() {
+    assert_eq!(
+      accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+      "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+    );
+  }
+
+  #[test]
+  fn frames_round_trip() {
+    // a masked frame from a client, as in the RFC
+    let frame = [
+      0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58,
+    ];
+    let (opcode, payload) = read_frame(&mut &frame[..]).unwrap();
+    assert_eq!((opcode, &payload[..]), (OPCODE_TEXT, &b"Hello"[..]));
+
+    let mut written = Vec::new();
+    write_frame(&mut written, OPCODE_TEXT, b"Hello").unwrap();
+    assert_eq!(written, [0x81, 0x05, b'H', b'e', b'l', b'l', b'o']);
+  }
+}
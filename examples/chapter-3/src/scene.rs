@@ -0,0 +1,69 @@
+//! Objects drawn one by one.
+//!
+//! The opposite of static batching: each object keeps its own tess, in the space it was loaded in,
+//! and is placed in the world by a model matrix set right before drawing it. That costs a draw call
+//! per object, but an object can then move or be updated without touching the others.
+
+use crate::obj::Obj;
+use crate::scene_file::Overrides;
+use crate::{upload_vertices, Vertex, VertexIndex, VertexObject};
+use cgmath::Matrix4;
+use luminance_front::context::GraphicsContext;
+use luminance_front::tess::{Interleaved, Mode, Tess, TessError};
+use luminance_front::Backend;
+
+pub struct SceneObject {
+  pub tess: Tess<Vertex, VertexIndex, (), Interleaved>,
+  /// Model matrix, from the space of the object to the world.
+  pub transform: Matrix4<f32>,
+  pub triangles: usize,
+  /// Render states of the object that differ from the rest of the model’s.
+  pub overrides: Overrides,
+  /// Index of the object in the scene.
+  index: u32,
+}
+
+impl SceneObject {
+  /// Object number `index` of the scene, placed in the world by `transform`.
+  ///
+  /// Its vertices are tagged with its index, so that it gets the same tint as when batched.
+  pub fn new<C>(
+    ctxt: &mut C,
+    obj: &Obj,
+    index: u32,
+    transform: Matrix4<f32>,
+  ) -> Result<Self, TessError>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    let tess = ctxt
+      .new_tess()
+      .set_mode(Mode::Triangle)
+      .set_vertices(tagged_vertices(obj, index))
+      .set_indices(obj.indices.clone())
+      .build()?;
+
+    Ok(SceneObject {
+      tess,
+      transform,
+      triangles: obj.indices.len() / 3,
+      overrides: Overrides::default(),
+      index,
+    })
+  }
+
+  /// Upload the vertices of `obj` again, after its normals changed.
+  pub fn update(&mut self, obj: &Obj) {
+    upload_vertices(&mut self.tess, &tagged_vertices(obj, self.index));
+  }
+}
+
+fn tagged_vertices(obj: &Obj, index: u32) -> Vec<Vertex> {
+  let object = VertexObject::new(index);
+
+  obj
+    .vertices
+    .iter()
+    .map(|&vertex| Vertex { object, ..vertex })
+    .collect()
+}
@@ -0,0 +1,329 @@
+//! Scene files.
+//!
+//! A scene lists models along with where they stand and how they’re drawn, so that a scene mixing
+//! opaque and see-through objects can be set up without touching the code. The format is a
+//! line-based one, like OBJ and MTL: `object` starts a new object, and the statements following it
+//! describe that object.
+//!
+//! ```text
+//! # a tinted glass cube spinning in front of a teapot
+//! object teapot.obj
+//! object cube.obj
+//! translate 0 0 1.5
+//! scale 0.5
+//! blend multiply
+//! cull none
+//! interpolation cubic
+//! rotate_key 0 0 0 0
+//! rotate_key 2 0 180 0
+//! rotate_key 4 0 360 0
+//! ```
+//!
+//! Statements:
+//!
+//! - `object <file>`: model to load, relative to the scene file.
+//! - `translate <x> <y> <z>`, `rotate <x> <y> <z>` (Euler angles in degrees, applied in X, Y, Z
+//!   order) and `scale <s>` or `scale <x> <y> <z>`: placement of the object, scaled, then rotated,
+//!   then translated.
+//! - `blend <none|additive|multiply>`: how the object is blended over what’s behind it. The
+//!   shadings output no alpha, so colors are added (glows) or multiplied (tinted glass).
+//! - `cull <back|front|none>`: faces left out.
+//! - `depth_test <on|off>`: whether the object is hidden by what’s in front of it.
+//! - `shading <s>`: shading of the object, instead of the viewer’s.
+//! - `translate_key <t> <x> <y> <z>`, `rotate_key <t> …` and `scale_key <t> …`: keys of the
+//!   animation tracks of the object, at time `t` in seconds, in time order. An animated placement
+//!   overrides the static one; the tracks loop.
+//! - `interpolation <linear|cubic>`: how the tracks of the object are interpolated between keys
+//!   (default: linear). Rotations are interpolated as Euler angles.
+
+use crate::animation::{Interpolation, Track};
+use crate::shading::Shading;
+use cgmath::{Deg, Matrix4, Vector3};
+use luminance::blending::{Blending, Equation, Factor};
+use luminance::face_culling::{FaceCulling, FaceCullingMode, FaceCullingOrder};
+use luminance_front::depth_test::{DepthComparison, DepthWrite};
+use luminance_front::render_state::RenderState;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// How an object is blended over what’s behind it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Blend {
+  None,
+  Additive,
+  Multiply,
+}
+
+impl FromStr for Blend {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "none" => Ok(Blend::None),
+      "additive" => Ok(Blend::Additive),
+      "multiply" => Ok(Blend::Multiply),
+      _ => Err(format!(
+        "unknown blending: {} (expecting none, additive or multiply)",
+        s
+      )),
+    }
+  }
+}
+
+/// Faces left out when drawing an object.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Cull {
+  Back,
+  Front,
+  None,
+}
+
+impl FromStr for Cull {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "back" => Ok(Cull::Back),
+      "front" => Ok(Cull::Front),
+      "none" => Ok(Cull::None),
+      _ => Err(format!(
+        "unknown culling: {} (expecting back, front or none)",
+        s
+      )),
+    }
+  }
+}
+
+/// Render states of an object that differ from the viewer’s; unset ones are left as they are.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Overrides {
+  pub blend: Option<Blend>,
+  pub cull: Option<Cull>,
+  pub depth_test: Option<bool>,
+  pub shading: Option<Shading>,
+}
+
+impl Overrides {
+  /// Whether the object is blended, and must then be drawn after the opaque ones.
+  pub fn is_blended(&self) -> bool {
+    matches!(self.blend, Some(Blend::Additive) | Some(Blend::Multiply))
+  }
+
+  /// Override the render state the viewer draws the model with.
+  pub fn apply(&self, mut state: RenderState) -> RenderState {
+    match self.blend {
+      Some(Blend::None) | None => (),
+
+      Some(blend) => {
+        let (src, dst) = if blend == Blend::Additive {
+          (Factor::One, Factor::One)
+        } else {
+          (Factor::Zero, Factor::SrcColor)
+        };
+
+        // blended objects don’t hide each other, whatever order they’re drawn in
+        state = state
+          .set_blending(Blending {
+            equation: Equation::Additive,
+            src,
+            dst,
+          })
+          .set_depth_write(DepthWrite::Off);
+      }
+    }
+
+    state = match self.cull {
+      Some(Cull::Back) => state.set_face_culling(FaceCulling::new(
+        FaceCullingOrder::CCW,
+        FaceCullingMode::Back,
+      )),
+      Some(Cull::Front) => state.set_face_culling(FaceCulling::new(
+        FaceCullingOrder::CCW,
+        FaceCullingMode::Front,
+      )),
+      Some(Cull::None) => state.set_face_culling(None),
+      None => state,
+    };
+
+    match self.depth_test {
+      Some(true) => state.set_depth_test(Some(DepthComparison::Less)),
+      Some(false) => state.set_depth_test(None),
+      None => state,
+    }
+  }
+}
+
+/// Object of a scene.
+#[derive(Clone, Debug)]
+pub struct SceneEntry {
+  pub path: PathBuf,
+  pub translation: Vector3<f32>,
+  /// Euler angles, in degrees.
+  pub rotation: Vector3<f32>,
+  pub scale: Vector3<f32>,
+  pub overrides: Overrides,
+  /// Animation of the placement; each track overrides its static counterpart.
+  pub translation_track: Option<Track>,
+  pub rotation_track: Option<Track>,
+  pub scale_track: Option<Track>,
+  pub interpolation: Interpolation,
+}
+
+impl SceneEntry {
+  /// Object loaded from `path`, standing at the origin.
+  pub fn new(path: PathBuf) -> Self {
+    SceneEntry {
+      path,
+      translation: Vector3::new(0., 0., 0.),
+      rotation: Vector3::new(0., 0., 0.),
+      scale: Vector3::new(1., 1., 1.),
+      overrides: Overrides::default(),
+      translation_track: None,
+      rotation_track: None,
+      scale_track: None,
+      interpolation: Interpolation::Linear,
+    }
+  }
+
+  pub fn is_animated(&self) -> bool {
+    self.translation_track.is_some() || self.rotation_track.is_some() || self.scale_track.is_some()
+  }
+
+  /// Model matrix of the object at time `t`.
+  pub fn transform(&self, t: f32) -> Matrix4<f32> {
+    let sample = |track: &Option<Track>, value: Vector3<f32>| match track {
+      Some(track) => track.sample(t, self.interpolation),
+      None => value,
+    };
+    let translation = sample(&self.translation_track, self.translation);
+    let rotation = sample(&self.rotation_track, self.rotation);
+    let scale = sample(&self.scale_track, self.scale);
+
+    Matrix4::from_translation(translation)
+      * Matrix4::from_angle_z(Deg(rotation.z))
+      * Matrix4::from_angle_y(Deg(rotation.y))
+      * Matrix4::from_angle_x(Deg(rotation.x))
+      * Matrix4::from_nonuniform_scale(scale.x, scale.y, scale.z)
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct SceneFile {
+  pub entries: Vec<SceneEntry>,
+}
+
+impl SceneFile {
+  pub fn load<P>(path: P) -> Result<Self, String>
+  where
+    P: AsRef<Path>,
+  {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(|e| format!("cannot read: {}", e))?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut entries: Vec<SceneEntry> = Vec::new();
+
+    for (line_nb, line) in content.lines().enumerate() {
+      let mut words = line.split_whitespace();
+      let statement = match words.next() {
+        Some(statement) if !statement.starts_with('#') => statement,
+        _ => continue,
+      };
+      let args = words.collect::<Vec<_>>();
+      let error = |what: &str| format!("line {}: {}", line_nb + 1, what);
+
+      if statement == "object" {
+        let file = args.join(" ");
+
+        if file.is_empty() {
+          return Err(error("missing model file"));
+        }
+
+        entries.push(SceneEntry::new(dir.join(file)));
+        continue;
+      }
+
+      // everything else describes the last object declared
+      let entry = entries
+        .last_mut()
+        .ok_or_else(|| error(&format!("{} before any object", statement)))?;
+
+      match statement {
+        "translate" => {
+          entry.translation = parse_vector(&args).ok_or_else(|| error("invalid translate"))?
+        }
+        "rotate" => entry.rotation = parse_vector(&args).ok_or_else(|| error("invalid rotate"))?,
+        "scale" => entry.scale = parse_scale(&args).ok_or_else(|| error("invalid scale"))?,
+        "blend" => entry.overrides.blend = Some(parse_arg(&args).map_err(|e| error(&e))?),
+        "cull" => entry.overrides.cull = Some(parse_arg(&args).map_err(|e| error(&e))?),
+        "depth_test" => {
+          entry.overrides.depth_test = match args.as_slice() {
+            ["on"] => Some(true),
+            ["off"] => Some(false),
+            _ => return Err(error("invalid depth_test (expecting on or off)")),
+          }
+        }
+        "shading" => entry.overrides.shading = Some(parse_arg(&args).map_err(|e| error(&e))?),
+        "translate_key" | "rotate_key" | "scale_key" => {
+          let invalid = || error(&format!("invalid {}", statement));
+          let (t, value) = args.split_first().ok_or_else(invalid)?;
+          let t = t.parse::<f32>().map_err(|_| invalid())?;
+          let value = if statement == "scale_key" {
+            parse_scale(value)
+          } else {
+            parse_vector(value)
+          }
+          .ok_or_else(invalid)?;
+          let track = match statement {
+            "translate_key" => &mut entry.translation_track,
+            "rotate_key" => &mut entry.rotation_track,
+            _ => &mut entry.scale_track,
+          };
+
+          track
+            .get_or_insert_with(Track::new)
+            .push(t, value)
+            .map_err(|e| error(&e))?;
+        }
+        "interpolation" => entry.interpolation = parse_arg(&args).map_err(|e| error(&e))?,
+        _ => return Err(error(&format!("unknown statement {}", statement))),
+      }
+    }
+
+    if entries.is_empty() {
+      return Err("no object in the scene".to_owned());
+    }
+
+    Ok(SceneFile { entries })
+  }
+}
+
+fn parse_vector(args: &[&str]) -> Option<Vector3<f32>> {
+  match *args {
+    [x, y, z] => Some(Vector3::new(
+      x.parse().ok()?,
+      y.parse().ok()?,
+      z.parse().ok()?,
+    )),
+    _ => None,
+  }
+}
+
+/// Parse a scale, either uniform or along each axis.
+fn parse_scale(args: &[&str]) -> Option<Vector3<f32>> {
+  match *args {
+    [s] => s.parse().map(|s| Vector3::new(s, s, s)).ok(),
+    _ => parse_vector(args),
+  }
+}
+
+/// Parse the single argument of a statement.
+fn parse_arg<T>(args: &[&str]) -> Result<T, String>
+where
+  T: FromStr<Err = String>,
+{
+  match *args {
+    [arg] => arg.parse(),
+    _ => Err("expecting a single argument".to_owned()),
+  }
+}
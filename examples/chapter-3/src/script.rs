@@ -0,0 +1,396 @@
+//! Scene scripts.
+//!
+//! Instead of a scene file, a scene can be set up by a [rhai] script, so that it can be prototyped
+//! and animated without recompiling the viewer. The script is run once when the viewer starts, to
+//! spawn the objects of the scene, and its `frame` function, if it has one, is then called every
+//! frame with the time in seconds, to move the objects, the lights and the camera. Objects are the
+//! entries of a scene file, so a script can do whatever a scene file does.
+//!
+//! ```text
+//! // a teapot spinning behind a tinted glass cube, under a key light going round it
+//! spawn("teapot.obj");
+//! let cube = spawn("cube.obj");
+//! translate(cube, 0.0, 0.0, 1.5);
+//! scale(cube, 0.5);
+//! blend(cube, "multiply");
+//!
+//! fn frame(t) {
+//!   rotate(0, 0.0, t * 90.0, 0.0);
+//!   light(0, cos(t), -1.0, sin(t));
+//!   camera(t * 10.0, 20.0, 5.0);
+//! }
+//! ```
+//!
+//! Functions, numbers being floats except for object and light indices:
+//!
+//! - `spawn(file)`: add the model in `file`, relative to the script, and return its index; objects
+//!   are numbered from 0, in the order they’re spawned.
+//! - `translate(i, x, y, z)`, `rotate(i, x, y, z)` and `scale(i, s)` or `scale(i, x, y, z)`:
+//!   placement of object `i`, as in scene files.
+//! - `blend(i, b)`, `cull(i, c)`, `depth_test(i, on)` and `shading(i, s)`: render states of object
+//!   `i`, as in scene files.
+//! - `light(n, x, y, z)` and `light_color(n, r, g, b)`: direction and color of the key (0), fill
+//!   (1) or rim (2) light, replacing those of the lighting preset.
+//! - `ambient(r, g, b)`: ambient light, replacing that of the lighting preset.
+//! - `camera(azimuth, elevation, distance)`: orbit of the camera, angles in degrees.
+//!
+//! Objects are loaded before the first frame, and the shading they need is set up along with
+//! them: `spawn` and the render states can only be used when the script starts. Like any rhai
+//! function, `frame` can’t see the variables of the script, and refers to objects by their index.
+//!
+//! Scripting needs the `rhai` feature; without it, `--script` only prints a message.
+//!
+//! [rhai]: https://rhai.rs
+
+use crate::lighting::Lighting;
+use crate::orbit::Orbit;
+use crate::scene_file::{SceneEntry, SceneFile};
+#[cfg(feature = "rhai")]
+use crate::{
+  scene_file::{Blend, Cull},
+  shading::Shading,
+};
+#[cfg(feature = "rhai")]
+use cgmath::{Deg, Vector3};
+#[cfg(feature = "rhai")]
+use rhai::{Engine, Scope, AST, FLOAT, INT};
+#[cfg(feature = "rhai")]
+use std::cell::RefCell;
+use std::path::Path;
+#[cfg(feature = "rhai")]
+use std::path::PathBuf;
+#[cfg(feature = "rhai")]
+use std::rc::Rc;
+
+/// What the functions of a script change, shared between the engine and the viewer.
+#[cfg(feature = "rhai")]
+#[derive(Debug, Default)]
+struct ScriptState {
+  /// Directory of the script, which models are relative to.
+  dir: PathBuf,
+  entries: Vec<SceneEntry>,
+  /// Whether the script is past its start, after which objects can’t be spawned anymore.
+  started: bool,
+  /// Directions and colors of the key, fill and rim lights, replacing those of the preset.
+  light_directions: [Option<[f32; 3]>; 3],
+  light_colors: [Option<[f32; 3]>; 3],
+  ambient: Option<[f32; 3]>,
+  /// Orbit set by the last frame, not applied yet.
+  orbit: Option<Orbit>,
+  /// First error met by a function; the script goes on, and it’s reported once it returns.
+  error: Option<String>,
+}
+
+#[cfg(feature = "rhai")]
+impl ScriptState {
+  fn fail(&mut self, error: String) {
+    self.error.get_or_insert(error);
+  }
+
+  /// Entry of object `i`.
+  fn entry(&mut self, i: INT) -> Option<&mut SceneEntry> {
+    if i < 0 || i as usize >= self.entries.len() {
+      let error = format!("no object {} ({} spawned)", i, self.entries.len());
+      self.fail(error);
+      return None;
+    }
+
+    Some(&mut self.entries[i as usize])
+  }
+
+  /// Entry of object `i`, for a render state only set when the script starts.
+  fn starting_entry(&mut self, i: INT, what: &str) -> Option<&mut SceneEntry> {
+    if self.started {
+      self.fail(format!("{} can only be set when the script starts", what));
+      return None;
+    }
+
+    self.entry(i)
+  }
+
+  /// Index of light `n`.
+  fn light(&mut self, n: INT) -> Option<usize> {
+    match n {
+      0..=2 => Some(n as usize),
+      _ => {
+        self.fail(format!("no light {} (expecting 0, 1 or 2)", n));
+        None
+      }
+    }
+  }
+}
+
+#[cfg(feature = "rhai")]
+pub struct Script {
+  engine: Engine,
+  /// Functions of the script, without its statements, which only run once.
+  functions: AST,
+  scope: Scope<'static>,
+  state: Rc<RefCell<ScriptState>>,
+  /// Whether the script has a `frame` function.
+  animated: bool,
+}
+
+#[cfg(feature = "rhai")]
+impl Script {
+  /// Run the script at `path`, and return it along with the scene it spawned.
+  pub fn load<P>(path: P) -> Result<(Self, SceneFile), String>
+  where
+    P: AsRef<Path>,
+  {
+    let path = path.as_ref();
+    let state = Rc::new(RefCell::new(ScriptState {
+      dir: path.parent().unwrap_or_else(|| Path::new("")).to_owned(),
+      ..ScriptState::default()
+    }));
+    let engine = new_engine(&state);
+    let ast = engine
+      .compile_file(path.to_owned())
+      .map_err(|e| e.to_string())?;
+    let mut script = Script {
+      functions: ast.clone_functions_only(),
+      animated: ast
+        .iter_functions()
+        .any(|f| f.name == "frame" && f.params.len() == 1),
+      engine,
+      scope: Scope::new(),
+      state,
+    };
+
+    script
+      .engine
+      .run_ast_with_scope(&mut script.scope, &ast)
+      .map_err(|e| e.to_string())?;
+    script.check()?;
+
+    let mut state = script.state.borrow_mut();
+    state.started = true;
+
+    if state.entries.is_empty() {
+      return Err("no object spawned".to_owned());
+    }
+
+    let scene = SceneFile {
+      entries: state.entries.clone(),
+    };
+    drop(state);
+
+    Ok((script, scene))
+  }
+
+  /// Call the `frame` function of the script, if any, at time `t`.
+  pub fn frame(&mut self, t: f32) -> Result<(), String> {
+    if self.animated {
+      self
+        .engine
+        .call_fn::<()>(&mut self.scope, &self.functions, "frame", (t as FLOAT,))
+        .map_err(|e| e.to_string())?;
+    }
+
+    self.check()
+  }
+
+  /// Objects of the scene, as placed by the last frame.
+  pub fn entries(&self) -> Vec<SceneEntry> {
+    self.state.borrow().entries.clone()
+  }
+
+  /// Lighting `preset`, with the lights the script set.
+  pub fn lighting(&self, preset: &Lighting) -> Lighting {
+    let state = self.state.borrow();
+    let mut lighting = preset.clone();
+
+    for (i, light) in lighting.lights.iter_mut().enumerate() {
+      light.direction = state.light_directions[i].unwrap_or(light.direction);
+      light.color = state.light_colors[i].unwrap_or(light.color);
+    }
+
+    lighting.ambient = state.ambient.unwrap_or(lighting.ambient);
+    lighting
+  }
+
+  /// Orbit the last frame set the camera on, if it did.
+  pub fn take_orbit(&mut self) -> Option<Orbit> {
+    self.state.borrow_mut().orbit.take()
+  }
+
+  /// Report the first error met by a function since the last call.
+  fn check(&mut self) -> Result<(), String> {
+    match self.state.borrow_mut().error.take() {
+      Some(error) => Err(error),
+      None => Ok(()),
+    }
+  }
+}
+
+/// Engine with the functions of scripts registered, changing `state`.
+#[cfg(feature = "rhai")]
+fn new_engine(state: &Rc<RefCell<ScriptState>>) -> Engine {
+  let mut engine = Engine::new();
+
+  let st = state.clone();
+  engine.register_fn("spawn", move |file: &str| -> INT {
+    let mut state = st.borrow_mut();
+
+    if state.started {
+      state.fail("objects can only be spawned when the script starts".to_owned());
+      return -1;
+    }
+
+    let path = state.dir.join(file);
+    state.entries.push(SceneEntry::new(path));
+    state.entries.len() as INT - 1
+  });
+
+  let st = state.clone();
+  engine.register_fn("translate", move |i: INT, x: FLOAT, y: FLOAT, z: FLOAT| {
+    if let Some(entry) = st.borrow_mut().entry(i) {
+      entry.translation = vector(x, y, z);
+    }
+  });
+
+  let st = state.clone();
+  engine.register_fn("rotate", move |i: INT, x: FLOAT, y: FLOAT, z: FLOAT| {
+    if let Some(entry) = st.borrow_mut().entry(i) {
+      entry.rotation = vector(x, y, z);
+    }
+  });
+
+  let st = state.clone();
+  engine.register_fn("scale", move |i: INT, s: FLOAT| {
+    if let Some(entry) = st.borrow_mut().entry(i) {
+      entry.scale = vector(s, s, s);
+    }
+  });
+
+  let st = state.clone();
+  engine.register_fn("scale", move |i: INT, x: FLOAT, y: FLOAT, z: FLOAT| {
+    if let Some(entry) = st.borrow_mut().entry(i) {
+      entry.scale = vector(x, y, z);
+    }
+  });
+
+  let st = state.clone();
+  engine.register_fn("blend", move |i: INT, blend: &str| {
+    let mut state = st.borrow_mut();
+
+    match blend.parse::<Blend>() {
+      Ok(blend) => {
+        if let Some(entry) = state.starting_entry(i, "blending") {
+          entry.overrides.blend = Some(blend);
+        }
+      }
+      Err(e) => state.fail(e),
+    }
+  });
+
+  let st = state.clone();
+  engine.register_fn("cull", move |i: INT, cull: &str| {
+    let mut state = st.borrow_mut();
+
+    match cull.parse::<Cull>() {
+      Ok(cull) => {
+        if let Some(entry) = state.starting_entry(i, "culling") {
+          entry.overrides.cull = Some(cull);
+        }
+      }
+      Err(e) => state.fail(e),
+    }
+  });
+
+  let st = state.clone();
+  engine.register_fn("depth_test", move |i: INT, on: bool| {
+    if let Some(entry) = st.borrow_mut().starting_entry(i, "depth test") {
+      entry.overrides.depth_test = Some(on);
+    }
+  });
+
+  let st = state.clone();
+  engine.register_fn("shading", move |i: INT, shading: &str| {
+    let mut state = st.borrow_mut();
+
+    match shading.parse::<Shading>() {
+      Ok(shading) => {
+        if let Some(entry) = state.starting_entry(i, "shading") {
+          entry.overrides.shading = Some(shading);
+        }
+      }
+      Err(e) => state.fail(e),
+    }
+  });
+
+  let st = state.clone();
+  engine.register_fn("light", move |n: INT, x: FLOAT, y: FLOAT, z: FLOAT| {
+    let mut state = st.borrow_mut();
+
+    if let Some(n) = state.light(n) {
+      state.light_directions[n] = Some(vector(x, y, z).into());
+    }
+  });
+
+  let st = state.clone();
+  engine.register_fn(
+    "light_color",
+    move |n: INT, r: FLOAT, g: FLOAT, b: FLOAT| {
+      let mut state = st.borrow_mut();
+
+      if let Some(n) = state.light(n) {
+        state.light_colors[n] = Some(vector(r, g, b).into());
+      }
+    },
+  );
+
+  let st = state.clone();
+  engine.register_fn("ambient", move |r: FLOAT, g: FLOAT, b: FLOAT| {
+    st.borrow_mut().ambient = Some(vector(r, g, b).into());
+  });
+
+  let st = state.clone();
+  engine.register_fn(
+    "camera",
+    move |azimuth: FLOAT, elevation: FLOAT, distance: FLOAT| {
+      let orbit = Orbit::new(
+        Deg(azimuth as f32).into(),
+        Deg(elevation as f32).into(),
+        distance as f32,
+      );
+      st.borrow_mut().orbit = Some(orbit);
+    },
+  );
+
+  engine
+}
+
+#[cfg(feature = "rhai")]
+fn vector(x: FLOAT, y: FLOAT, z: FLOAT) -> Vector3<f32> {
+  Vector3::new(x as f32, y as f32, z as f32)
+}
+
+#[cfg(not(feature = "rhai"))]
+pub struct Script {}
+
+#[cfg(not(feature = "rhai"))]
+impl Script {
+  pub fn load<P>(_: P) -> Result<(Self, SceneFile), String>
+  where
+    P: AsRef<Path>,
+  {
+    Err("built without the rhai feature".to_owned())
+  }
+
+  pub fn frame(&mut self, _: f32) -> Result<(), String> {
+    Ok(())
+  }
+
+  pub fn entries(&self) -> Vec<SceneEntry> {
+    Vec::new()
+  }
+
+  pub fn lighting(&self, preset: &Lighting) -> Lighting {
+    preset.clone()
+  }
+
+  pub fn take_orbit(&mut self) -> Option<Orbit> {
+    None
+  }
+}
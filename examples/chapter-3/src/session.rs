@@ -0,0 +1,184 @@
+//! Recording and replaying input sessions.
+//!
+//! A recording stores, for every frame, the time elapsed since the previous frame and the input of
+//! that frame: the actions triggered, how far the camera was turned and zoomed, where the divider
+//! of the split view was dragged and where triangles were picked. Replaying feeds those back in
+//! place of the wall clock and the live input, so that the exact same frames are produced,
+//! whatever the speed of the machine. The recording must be replayed with the same model, options
+//! and window size for that to hold.
+//!
+//! The file format is plain text with one line per frame: the elapsed time followed by the names
+//! of the actions and by `name=value` pairs for the rest of the input, separated by spaces. Values
+//! that are zero or absent are left out: `orbit=<dx>,<dy>`, `zoom=<steps>`, `divider=<x>` and
+//! `pick=<x>,<y>`, once per click, positions being fractions of the window size.
+
+use crate::input::Action;
+use crate::time::Time;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write as _};
+use std::path::Path;
+use std::str::SplitWhitespace;
+use std::vec;
+
+const HEADER: &str = "# chapter-3 input recording";
+
+/// Input of a frame.
+#[derive(Clone, Debug, Default)]
+pub struct FrameInput {
+  pub actions: Vec<Action>,
+  /// Values of the orbit and zoom axes.
+  pub orbit: [f32; 2],
+  pub zoom: f32,
+  /// Where the divider of the split view was dragged to, as a fraction of the window width.
+  pub divider: Option<f32>,
+  /// Clicks picking triangles, as fractions of the window size.
+  pub picks: Vec<[f32; 2]>,
+}
+
+impl FrameInput {
+  fn parse(words: SplitWhitespace) -> Result<Self, String> {
+    let mut input = FrameInput::default();
+
+    for word in words {
+      match word.split_once('=') {
+        None => input.actions.push(word.parse()?),
+        Some(("orbit", value)) => input.orbit = parse_pair(value)?,
+        Some(("zoom", value)) => input.zoom = parse_value(value)?,
+        Some(("divider", value)) => input.divider = Some(parse_value(value)?),
+        Some(("pick", value)) => input.picks.push(parse_pair(value)?),
+        Some((name, _)) => return Err(format!("unknown input: {}", name)),
+      }
+    }
+
+    Ok(input)
+  }
+
+  fn write(&self, line: &mut String) {
+    for action in &self.actions {
+      line.push(' ');
+      line.push_str(action.name());
+    }
+
+    if self.orbit != [0., 0.] {
+      line.push_str(&format!(" orbit={},{}", self.orbit[0], self.orbit[1]));
+    }
+
+    if self.zoom != 0. {
+      line.push_str(&format!(" zoom={}", self.zoom));
+    }
+
+    if let Some(divider) = self.divider {
+      line.push_str(&format!(" divider={}", divider));
+    }
+
+    for [x, y] in &self.picks {
+      line.push_str(&format!(" pick={},{}", x, y));
+    }
+  }
+}
+
+fn parse_value(value: &str) -> Result<f32, String> {
+  value
+    .parse()
+    .map_err(|_| format!("invalid input value: {}", value))
+}
+
+fn parse_pair(value: &str) -> Result<[f32; 2], String> {
+  match value.split_once(',') {
+    Some((x, y)) => Ok([parse_value(x)?, parse_value(y)?]),
+    None => Err(format!("invalid input value: {}", value)),
+  }
+}
+
+#[derive(Debug)]
+pub struct Frame {
+  dt: f32,
+  input: FrameInput,
+}
+
+pub enum Session {
+  /// Input comes from the user and time from the wall clock.
+  Live,
+  /// Same as live, but every frame gets written to a file.
+  Record(BufWriter<File>),
+  /// Input and time come from a previous recording.
+  Replay(vec::IntoIter<Frame>),
+}
+
+impl Session {
+  pub fn record<P>(path: P) -> Result<Self, String>
+  where
+    P: AsRef<Path>,
+  {
+    let path = path.as_ref();
+    let file =
+      File::create(path).map_err(|e| format!("cannot create {}: {}", path.display(), e))?;
+    let mut writer = BufWriter::new(file);
+    writeln!(writer, "{}", HEADER).map_err(|e| format!("cannot write recording: {}", e))?;
+
+    Ok(Session::Record(writer))
+  }
+
+  pub fn replay<P>(path: P) -> Result<Self, String>
+  where
+    P: AsRef<Path>,
+  {
+    let path = path.as_ref();
+    let content =
+      fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let mut frames = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+      if line.starts_with('#') || line.trim().is_empty() {
+        continue;
+      }
+
+      let mut words = line.split_whitespace();
+      let dt = words
+        .next()
+        .and_then(|dt| dt.parse().ok())
+        .ok_or_else(|| format!("line {}: invalid frame time", i + 1))?;
+      let input = FrameInput::parse(words).map_err(|e| format!("line {}: {}", i + 1, e))?;
+
+      frames.push(Frame { dt, input });
+    }
+
+    Ok(Session::Replay(frames.into_iter()))
+  }
+
+  /// Advance the clock by one frame and return the input to apply for that frame.
+  ///
+  /// `input` is the input of the user. When replaying, it’s replaced by the recorded one, except
+  /// for quitting. `None` is returned once the replay is over.
+  pub fn frame(&mut self, input: FrameInput, time: &mut Time) -> Option<FrameInput> {
+    match self {
+      Session::Live => {
+        time.tick();
+        Some(input)
+      }
+
+      Session::Record(writer) => {
+        let dt = time.tick();
+        let mut line = dt.to_string();
+        input.write(&mut line);
+
+        if let Err(e) = writeln!(writer, "{}", line) {
+          eprintln!("cannot write recording: {}", e);
+        }
+
+        Some(input)
+      }
+
+      Session::Replay(frames) => {
+        let mut frame = frames.next()?;
+        time.advance(frame.dt);
+
+        if input.actions.contains(&Action::Quit) {
+          frame.input.actions.push(Action::Quit);
+        }
+
+        Some(frame.input)
+      }
+    }
+  }
+}
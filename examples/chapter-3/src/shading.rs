@@ -0,0 +1,74 @@
+//! Shading modes of the model.
+
+use std::str::FromStr;
+
+/// How the model is shaded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Shading {
+  /// Diffuse lighting only.
+  Lambert,
+  /// Diffuse lighting with Blinn-Phong highlights.
+  Phong,
+  /// Glass refracting and reflecting a studio.
+  Glass,
+  /// Unlit color of the objects.
+  Albedo,
+  /// World-space normals as colors.
+  Normals,
+  /// Checkerboard in world space, to judge scale and spot stretched geometry.
+  Checker,
+}
+
+impl Shading {
+  pub fn name(self) -> &'static str {
+    match self {
+      Shading::Lambert => "lambert",
+      Shading::Phong => "phong",
+      Shading::Glass => "glass",
+      Shading::Albedo => "albedo",
+      Shading::Normals => "normals",
+      Shading::Checker => "checker",
+    }
+  }
+
+  /// Next shading, going through all of them in turn.
+  pub fn next(self) -> Self {
+    match self {
+      Shading::Lambert => Shading::Phong,
+      Shading::Phong => Shading::Glass,
+      Shading::Glass => Shading::Albedo,
+      Shading::Albedo => Shading::Normals,
+      Shading::Normals => Shading::Checker,
+      Shading::Checker => Shading::Lambert,
+    }
+  }
+
+  /// Mode of the diagnostic shader overriding the material, if this shading is one of them.
+  pub fn override_mode(self) -> Option<i32> {
+    match self {
+      Shading::Albedo => Some(0),
+      Shading::Normals => Some(1),
+      Shading::Checker => Some(2),
+      _ => None,
+    }
+  }
+}
+
+impl FromStr for Shading {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "lambert" => Ok(Shading::Lambert),
+      "phong" => Ok(Shading::Phong),
+      "glass" => Ok(Shading::Glass),
+      "albedo" => Ok(Shading::Albedo),
+      "normals" => Ok(Shading::Normals),
+      "checker" => Ok(Shading::Checker),
+      _ => Err(format!(
+        "unknown shading: {} (expecting lambert, phong, glass, albedo, normals or checker)",
+        s
+      )),
+    }
+  }
+}
@@ -0,0 +1,40 @@
+//! Snapshots of the back buffer.
+//!
+//! luminance can’t read the back buffer back, so its pixels are read with raw GL calls. They’re
+//! saved as binary PPM images, which need no encoder and are read by most image tools.
+
+use std::ffi::c_void;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Save the back buffer, before it gets swapped, to a binary PPM (P6) image.
+pub fn save_back_buffer(path: &Path, [width, height]: [u32; 2]) -> io::Result<()> {
+  let row_len = width as usize * 3;
+  let mut pixels = vec![0u8; row_len * height as usize];
+
+  unsafe {
+    // rows of RGB pixels aren’t aligned on 4 bytes
+    gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+    gl::ReadBuffer(gl::BACK);
+    gl::ReadPixels(
+      0,
+      0,
+      width as i32,
+      height as i32,
+      gl::RGB,
+      gl::UNSIGNED_BYTE,
+      pixels.as_mut_ptr() as *mut c_void,
+    );
+  }
+
+  let mut file = BufWriter::new(File::create(path)?);
+  write!(file, "P6\n{} {}\n255\n", width, height)?;
+
+  // GL rows go from bottom to top, PPM rows from top to bottom
+  for row in pixels.chunks(row_len).rev() {
+    file.write_all(row)?;
+  }
+
+  file.flush()
+}
@@ -0,0 +1,221 @@
+//! Viewer state persisted across runs.
+//!
+//! The state is stored as a tiny `key = value` file in the platform configuration directory, so
+//! that the window shows up where it was left and the last models get reopened when no path is
+//! passed on the command line. The lighting preset, the shading mode and the camera are kept too,
+//! so that a model is seen again the way it was last looked at. Only interactive sessions save it:
+//! smoke tests, benchmarks and replays would otherwise overwrite it with their own settings.
+//!
+//! The lighting presets themselves are defined here as well, one `lighting.<preset>.<property>`
+//! line per property: the built-in presets are written out on exit, so that they can be tweaked,
+//! and presets with new names are added to them.
+
+use crate::lighting::Lighting;
+use crate::orbit::Orbit;
+use crate::shading::Shading;
+use cgmath::{Deg, Rad};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// State saved on exit and restored at startup.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewerState {
+  pub window_size: [u32; 2],
+  pub window_pos: Option<[i32; 2]>,
+  pub last_models: Vec<PathBuf>,
+  /// Name of the selected lighting preset.
+  pub lighting: String,
+  /// Lighting presets, the built-in ones first.
+  pub presets: Vec<Lighting>,
+  pub shading: Shading,
+  /// Last camera orbit and field of view; the viewer picks its own when there are none.
+  pub orbit: Option<Orbit>,
+  pub fov: Option<Deg<f32>>,
+}
+
+impl Default for ViewerState {
+  fn default() -> Self {
+    ViewerState {
+      window_size: [960, 540],
+      window_pos: None,
+      last_models: Vec::new(),
+      lighting: "noon".to_owned(),
+      presets: Lighting::builtins(),
+      shading: Shading::Lambert,
+      orbit: None,
+      fov: None,
+    }
+  }
+}
+
+impl ViewerState {
+  /// Path of the state file, if the platform has a configuration directory.
+  fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("learn-luminance").join("chapter-3.state"))
+  }
+
+  /// Load the state from the previous run; missing or invalid entries fall back to defaults.
+  pub fn load() -> Self {
+    let mut state = ViewerState::default();
+    let content = match Self::path().and_then(|path| fs::read_to_string(path).ok()) {
+      Some(content) => content,
+      None => return state,
+    };
+
+    for (key, value) in entries(&content) {
+      match key {
+        "window_size" => {
+          if let Some(size) = parse_pair(value) {
+            state.window_size = size;
+          }
+        }
+
+        "window_pos" => state.window_pos = parse_pair(value),
+        "last_model" if !value.is_empty() => state.last_models.push(value.into()),
+
+        "lighting" => state.lighting = value.to_owned(),
+
+        "shading" => {
+          if let Ok(shading) = value.parse() {
+            state.shading = shading;
+          }
+        }
+
+        "orbit" => state.orbit = parse_orbit(value),
+
+        "fov" => state.fov = parse_fov(value),
+
+        _ => {
+          if let Some(property) = key.strip_prefix("lighting.") {
+            set_preset_property(&mut state.presets, property, value);
+          }
+        }
+      }
+    }
+
+    state
+  }
+
+  /// Save the state so that the next run can restore it.
+  pub fn save(&self) -> Result<(), String> {
+    let path = Self::path().ok_or("no configuration directory available".to_owned())?;
+
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)
+        .map_err(|e| format!("cannot create {}: {}", parent.display(), e))?;
+    }
+
+    let mut content = format!(
+      "window_size = {} {}\n",
+      self.window_size[0], self.window_size[1]
+    );
+
+    if let Some([x, y]) = self.window_pos {
+      content += &format!("window_pos = {} {}\n", x, y);
+    }
+
+    content += &format!("lighting = {}\n", self.lighting);
+    content += &format!("shading = {}\n", self.shading.name());
+
+    if let Some(orbit) = self.orbit {
+      content += &format!(
+        "orbit = {} {} {}\n",
+        orbit.azimuth.0, orbit.elevation.0, orbit.distance
+      );
+    }
+
+    if let Some(fov) = self.fov {
+      content += &format!("fov = {}\n", fov.0);
+    }
+
+    for model in &self.last_models {
+      content += &format!("last_model = {}\n", model.display());
+    }
+
+    for preset in &self.presets {
+      for (property, value) in preset.properties() {
+        content += &format!("lighting.{}.{} = {}\n", preset.name, property, value);
+      }
+    }
+
+    fs::write(&path, content).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+  }
+}
+
+/// Entries of a `key = value` file, trimmed; lines without `=` are skipped.
+pub fn entries(content: &str) -> impl Iterator<Item = (&str, &str)> {
+  content.lines().filter_map(|line| {
+    let mut kv = line.splitn(2, '=');
+
+    match (kv.next(), kv.next()) {
+      (Some(key), Some(value)) => Some((key.trim(), value.trim())),
+      _ => None,
+    }
+  })
+}
+
+/// Set a property of a lighting preset, given as `<preset>.<property>`, adding the preset if it’s
+/// not known yet; invalid properties are reported and ignored.
+pub fn set_preset_property(presets: &mut Vec<Lighting>, property: &str, value: &str) {
+  let mut split = property.splitn(2, '.');
+  let (name, property) = match (split.next(), split.next()) {
+    (Some(name), Some(property)) if !name.is_empty() => (name, property),
+    _ => return,
+  };
+
+  let preset = match presets.iter().position(|preset| preset.name == name) {
+    Some(i) => &mut presets[i],
+    None => {
+      presets.push(Lighting::dark(name));
+      presets.last_mut().unwrap()
+    }
+  };
+
+  if let Err(e) = preset.set(property, value) {
+    eprintln!("lighting preset {}: {}", name, e);
+  }
+}
+
+/// Parse a vertical field of view, in degrees.
+pub fn parse_fov(s: &str) -> Option<Deg<f32>> {
+  s.parse()
+    .ok()
+    .filter(|&fov| fov > 0. && fov < 180.)
+    .map(Deg)
+}
+
+/// Parse an orbit given as its azimuth and elevation, in radians, and its distance.
+pub fn parse_orbit(s: &str) -> Option<Orbit> {
+  let values = s
+    .split_whitespace()
+    .map(str::parse)
+    .collect::<Result<Vec<f32>, _>>()
+    .ok()?;
+
+  match values[..] {
+    [azimuth, elevation, distance]
+      if azimuth.is_finite() && elevation.is_finite() && distance.is_finite() && distance > 0. =>
+    {
+      Some(Orbit {
+        azimuth: Rad(azimuth),
+        elevation: Rad(elevation),
+        distance,
+      })
+    }
+
+    _ => None,
+  }
+}
+
+fn parse_pair<T>(s: &str) -> Option<[T; 2]>
+where
+  T: FromStr,
+{
+  let mut it = s.split_whitespace().map(str::parse);
+
+  match (it.next(), it.next(), it.next()) {
+    (Some(Ok(a)), Some(Ok(b)), None) => Some([a, b]),
+    _ => None,
+  }
+}
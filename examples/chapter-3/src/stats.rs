@@ -0,0 +1,83 @@
+//! Frame submission statistics.
+//!
+//! Counters are incremented in the gate closures as the frame gets submitted, then averaged over a
+//! second and printed, which gives quantitative feedback when optimizing a scene.
+
+use std::time::{Duration, Instant};
+
+const REPORT_PERIOD: Duration = Duration::from_secs(1);
+
+/// What has been submitted during a frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameStats {
+  pub draws: usize,
+  pub instances: usize,
+  pub triangles: usize,
+  pub texture_binds: usize,
+  pub program_switches: usize,
+  /// Uniforms uploaded, and uploads skipped because the uniform already had the value.
+  pub uniform_uploads: usize,
+  pub uniforms_skipped: usize,
+}
+
+impl FrameStats {
+  /// Record a draw call of `instances` instances of a mesh with `triangles` triangles.
+  pub fn draw(&mut self, triangles: usize, instances: usize) {
+    self.draws += 1;
+    self.instances += instances;
+    self.triangles += triangles * instances;
+  }
+}
+
+/// Accumulate frame statistics and print their average periodically.
+#[derive(Debug)]
+pub struct StatsReport {
+  frames: usize,
+  total: FrameStats,
+  since: Instant,
+}
+
+impl StatsReport {
+  pub fn new() -> Self {
+    StatsReport {
+      frames: 0,
+      total: FrameStats::default(),
+      since: Instant::now(),
+    }
+  }
+
+  /// Restart accumulating from now.
+  pub fn reset(&mut self) {
+    *self = StatsReport::new();
+  }
+
+  pub fn add(&mut self, frame: FrameStats) {
+    self.frames += 1;
+    self.total.draws += frame.draws;
+    self.total.instances += frame.instances;
+    self.total.triangles += frame.triangles;
+    self.total.texture_binds += frame.texture_binds;
+    self.total.program_switches += frame.program_switches;
+    self.total.uniform_uploads += frame.uniform_uploads;
+    self.total.uniforms_skipped += frame.uniforms_skipped;
+
+    let elapsed = self.since.elapsed();
+    if elapsed >= REPORT_PERIOD {
+      let frames = self.frames as f32;
+
+      println!(
+        "{:.1} fps; per frame: {:.0} draws, {:.0} instances, {:.0} triangles, {:.0} texture binds, {:.0} program switches, {:.0} uniform uploads ({:.0} skipped)",
+        frames / elapsed.as_secs_f32(),
+        self.total.draws as f32 / frames,
+        self.total.instances as f32 / frames,
+        self.total.triangles as f32 / frames,
+        self.total.texture_binds as f32 / frames,
+        self.total.program_switches as f32 / frames,
+        self.total.uniform_uploads as f32 / frames,
+        self.total.uniforms_skipped as f32 / frames,
+      );
+
+      self.reset();
+    }
+  }
+}
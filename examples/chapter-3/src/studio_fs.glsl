@@ -0,0 +1,32 @@
+in vec3 v_normal;
+
+out vec3 frag_color;
+
+// softboxes lighting the studio, seen from the model
+const vec3 SOFTBOXES[3] = vec3[](
+  vec3(.6, .5, .3),
+  vec3(-.7, .4, .6),
+  vec3(-.1, .7, -.7)
+);
+
+// a procedural photo studio: walls darkening towards the ceiling, a checkered floor and a few
+// bright softboxes, which give refractions and reflections something to show
+void main() {
+  vec3 dir = normalize(v_normal);
+  vec3 color = mix(vec3(.55, .55, .6), vec3(.08, .08, .1), smoothstep(0., .9, dir.y));
+
+  // the floor lies one unit below, and fades into the walls towards the horizon
+  if (dir.y < 0.) {
+    vec2 p = dir.xz / -dir.y;
+    float checker = mod(floor(p.x * 2.) + floor(p.y * 2.), 2.);
+    vec3 floor_color = mix(vec3(.15), vec3(.7), checker);
+
+    color = mix(color, floor_color, smoothstep(0., .3, -dir.y));
+  }
+
+  for (int i = 0; i < 3; ++i) {
+    color += vec3(3.) * smoothstep(.97, .98, dot(dir, normalize(SOFTBOXES[i])));
+  }
+
+  frag_color = color;
+}
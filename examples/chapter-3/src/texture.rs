@@ -0,0 +1,45 @@
+//! Image textures.
+//!
+//! Materials point to their textures by path (`map_Kd` and friends); those can be any of the
+//! formats exporters write, PNG, JPEG, TGA, BMP…, so they’re decoded with the image crate rather
+//! than by hand.
+
+use luminance::pixel::NormRGBA8UI;
+use luminance::texture::{Dim2, GenMipmaps, MagFilter, MinFilter, Sampler, Wrap};
+use luminance_front::context::GraphicsContext;
+use luminance_front::texture::Texture;
+use luminance_front::Backend;
+use std::path::Path;
+
+/// Load an image into a mipmapped, repeating texture.
+pub fn load_texture<C>(ctxt: &mut C, path: &Path) -> Result<Texture<Dim2, NormRGBA8UI>, String>
+where
+  C: GraphicsContext<Backend = Backend>,
+{
+  let image = image::open(path).map_err(|e| format!("cannot load {}: {}", path.display(), e))?;
+
+  // image rows go from top to bottom, texture rows from bottom to top; RGBA avoids the unpack
+  // alignment issues of RGB rows whose length isn’t a multiple of four
+  let image = image.flipv().into_rgba8();
+  let (width, height) = image.dimensions();
+
+  // UVs outside of [0; 1] tile the texture, and mipmaps keep it from shimmering from afar
+  let sampler = Sampler {
+    wrap_s: Wrap::Repeat,
+    wrap_t: Wrap::Repeat,
+    min_filter: MinFilter::LinearMipmapLinear,
+    mag_filter: MagFilter::Linear,
+    ..Sampler::default()
+  };
+  let mipmaps = (width.max(height) as f32).log2() as usize;
+
+  ctxt
+    .new_texture_raw(
+      [width, height],
+      mipmaps,
+      sampler,
+      GenMipmaps::Yes,
+      image.as_raw(),
+    )
+    .map_err(|e| format!("cannot create the texture of {}: {}", path.display(), e))
+}
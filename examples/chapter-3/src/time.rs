@@ -0,0 +1,60 @@
+//! Application clock.
+//!
+//! Everything animated reads its time from here rather than from the wall clock, so that time can
+//! be slowed down, sped up or even reversed.
+
+use std::time::Instant;
+
+/// Available time scales; `[` and `]` step through them.
+const SCALES: [f32; 11] = [-4., -2., -1., -0.5, -0.25, 0., 0.25, 0.5, 1., 2., 4.];
+
+/// Index of the 1× scale in SCALES.
+const NORMAL_SCALE: usize = 8;
+
+#[derive(Debug)]
+pub struct Time {
+  last: Instant,
+  t: f32,
+  scale: usize,
+}
+
+impl Time {
+  pub fn new() -> Self {
+    Time {
+      last: Instant::now(),
+      t: 0.,
+      scale: NORMAL_SCALE,
+    }
+  }
+
+  /// Advance the clock by the time elapsed since the last tick, and return that (unscaled) time.
+  pub fn tick(&mut self) -> f32 {
+    let now = Instant::now();
+    let dt = now.duration_since(self.last).as_secs_f32();
+    self.last = now;
+    self.advance(dt);
+    dt
+  }
+
+  /// Advance the clock by `dt` unscaled seconds, regardless of the wall clock.
+  pub fn advance(&mut self, dt: f32) {
+    self.t += dt * self.scale();
+  }
+
+  /// Current application time, in seconds.
+  pub fn t(&self) -> f32 {
+    self.t
+  }
+
+  pub fn scale(&self) -> f32 {
+    SCALES[self.scale]
+  }
+
+  pub fn slow_down(&mut self) {
+    self.scale = self.scale.saturating_sub(1);
+  }
+
+  pub fn speed_up(&mut self) {
+    self.scale = (self.scale + 1).min(SCALES.len() - 1);
+  }
+}
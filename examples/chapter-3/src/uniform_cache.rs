@@ -0,0 +1,126 @@
+//! Redundant uniform upload elimination.
+//!
+//! Uniform values are stored in the shader program, so they survive from one frame to the next.
+//! Setting a uniform to the value it already has is wasted work: each program keeps a cache of the
+//! last values it uploaded and only sets the ones that changed.
+
+use crate::lighting::Lighting;
+use crate::stats::FrameStats;
+use luminance_front::shader::{ProgramInterface, Uniform};
+use std::mem;
+
+/// Last value uploaded to a uniform, along with upload statistics.
+#[derive(Debug)]
+pub struct UniformCache<T> {
+  last: Option<T>,
+  uploads: usize,
+  skipped: usize,
+}
+
+impl<T> Default for UniformCache<T> {
+  fn default() -> Self {
+    UniformCache {
+      last: None,
+      uploads: 0,
+      skipped: 0,
+    }
+  }
+}
+
+impl<T> UniformCache<T>
+where
+  T: Copy + PartialEq,
+{
+  /// Whether `value` must be uploaded; if so, it’s remembered as the last uploaded value.
+  pub fn update(&mut self, value: T) -> bool {
+    if self.last == Some(value) {
+      self.skipped += 1;
+      false
+    } else {
+      self.last = Some(value);
+      self.uploads += 1;
+      true
+    }
+  }
+
+  /// Uploads and skipped uploads since the last call.
+  pub fn take_counts(&mut self) -> (usize, usize) {
+    (mem::take(&mut self.uploads), mem::take(&mut self.skipped))
+  }
+}
+
+/// Last values uploaded to a program: the camera matrices, and the lights of the programs that are
+/// lit.
+#[derive(Debug, Default)]
+pub struct ShaderCache {
+  projection: UniformCache<[[f32; 4]; 4]>,
+  view: UniformCache<[[f32; 4]; 4]>,
+  light_dirs: UniformCache<[[f32; 3]; 3]>,
+  light_colors: UniformCache<[[f32; 3]; 3]>,
+  ambient_light: UniformCache<[f32; 3]>,
+  exposure: UniformCache<f32>,
+}
+
+impl ShaderCache {
+  /// Set the camera matrices that changed since they were last set.
+  pub fn set_camera(
+    &mut self,
+    iface: &mut ProgramInterface,
+    projection_uniform: &Uniform<[[f32; 4]; 4]>,
+    view_uniform: &Uniform<[[f32; 4]; 4]>,
+    projection: [[f32; 4]; 4],
+    view: [[f32; 4]; 4],
+  ) {
+    if self.projection.update(projection) {
+      iface.set(projection_uniform, projection);
+    }
+
+    if self.view.update(view) {
+      iface.set(view_uniform, view);
+    }
+  }
+
+  /// Set the lights that changed since they were last set.
+  pub fn set_lighting(
+    &mut self,
+    iface: &mut ProgramInterface,
+    dirs_uniform: &Uniform<[[f32; 3]; 3]>,
+    colors_uniform: &Uniform<[[f32; 3]; 3]>,
+    ambient_uniform: &Uniform<[f32; 3]>,
+    exposure_uniform: &Uniform<f32>,
+    lighting: &Lighting,
+  ) {
+    if self.light_dirs.update(lighting.directions()) {
+      iface.set(dirs_uniform, lighting.directions());
+    }
+
+    if self.light_colors.update(lighting.colors()) {
+      iface.set(colors_uniform, lighting.colors());
+    }
+
+    if self.ambient_light.update(lighting.ambient) {
+      iface.set(ambient_uniform, lighting.ambient);
+    }
+
+    if self.exposure.update(lighting.exposure) {
+      iface.set(exposure_uniform, lighting.exposure);
+    }
+  }
+
+  /// Add the uploads and skipped uploads since the last call to the statistics of a frame.
+  pub fn count(&mut self, frame_stats: &mut FrameStats) {
+    let counts = [
+      self.projection.take_counts(),
+      self.view.take_counts(),
+      self.light_dirs.take_counts(),
+      self.light_colors.take_counts(),
+      self.ambient_light.take_counts(),
+      self.exposure.take_counts(),
+    ];
+
+    for (uploads, skipped) in counts.iter() {
+      frame_stats.uniform_uploads += uploads;
+      frame_stats.uniforms_skipped += skipped;
+    }
+  }
+}
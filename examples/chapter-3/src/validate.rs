@@ -0,0 +1,258 @@
+//! Headless validation of a model.
+//!
+//! This runs the same loading pipeline as the viewer (parsing, triangle extraction, vertex
+//! deduplication) plus a couple of sanity and topology checks, and prints a report instead of opening a window.
+//! The exit code tells whether the model can be viewed, so that it can be used in asset pipelines.
+
+use crate::analysis::MeshAnalysis;
+use crate::batch::batch_in_place;
+use crate::obj::Obj;
+use std::path::{Path, PathBuf};
+
+/// Normals whose length is off by more than this are reported as non-unit.
+const NORMAL_EPSILON: f32 = 1e-3;
+
+#[derive(Debug, Default)]
+pub struct Report {
+  pub path: String,
+  pub object: Option<String>,
+  pub positions: usize,
+  pub normals: usize,
+  pub shapes: usize,
+  pub vertices: usize,
+  pub triangles: usize,
+  pub non_manifold_edges: usize,
+  pub degenerate_triangles: usize,
+  pub duplicate_triangles: usize,
+  pub flipped_islands: usize,
+  pub errors: Vec<String>,
+  pub warnings: Vec<String>,
+}
+
+impl Report {
+  pub fn is_ok(&self) -> bool {
+    self.errors.is_empty()
+  }
+
+  fn to_text(&self) -> String {
+    let mut text = format!("model: {}\n", self.path);
+
+    if let Some(ref object) = self.object {
+      text += &format!("object: {}\n", object);
+      text += &format!("positions: {}\n", self.positions);
+      text += &format!("normals: {}\n", self.normals);
+      text += &format!("shapes: {}\n", self.shapes);
+      text += &format!("unique vertices: {}\n", self.vertices);
+      text += &format!("triangles: {}\n", self.triangles);
+      text += &format!("non-manifold edges: {}\n", self.non_manifold_edges);
+      text += &format!("degenerate triangles: {}\n", self.degenerate_triangles);
+      text += &format!("duplicate triangles: {}\n", self.duplicate_triangles);
+      text += &format!("flipped winding islands: {}\n", self.flipped_islands);
+    }
+
+    for warning in &self.warnings {
+      text += &format!("warning: {}\n", warning);
+    }
+
+    for error in &self.errors {
+      text += &format!("error: {}\n", error);
+    }
+
+    text += if self.is_ok() {
+      "status: ok"
+    } else {
+      "status: failed"
+    };
+    text
+  }
+
+  fn to_json(&self) -> String {
+    let object = self
+      .object
+      .as_ref()
+      .map_or_else(|| "null".to_owned(), |object| json_string(object));
+    let warnings = self
+      .warnings
+      .iter()
+      .map(|w| json_string(w))
+      .collect::<Vec<_>>();
+    let errors = self
+      .errors
+      .iter()
+      .map(|e| json_string(e))
+      .collect::<Vec<_>>();
+
+    format!(
+      "{{\"path\":{},\"object\":{},\"positions\":{},\"normals\":{},\"shapes\":{},\"vertices\":{},\"triangles\":{},\"non_manifold_edges\":{},\"degenerate_triangles\":{},\"duplicate_triangles\":{},\"flipped_islands\":{},\"warnings\":[{}],\"errors\":[{}],\"ok\":{}}}",
+      json_string(&self.path),
+      object,
+      self.positions,
+      self.normals,
+      self.shapes,
+      self.vertices,
+      self.triangles,
+      self.non_manifold_edges,
+      self.degenerate_triangles,
+      self.duplicate_triangles,
+      self.flipped_islands,
+      warnings.join(","),
+      errors.join(","),
+      self.is_ok()
+    )
+  }
+}
+
+/// Load the model at `path` and check it.
+pub fn validate(path: &Path) -> Report {
+  let mut report = Report {
+    path: path.display().to_string(),
+    ..Report::default()
+  };
+
+  // the meshes of the file are checked together, as they’re viewed
+  let obj = match Obj::load(path) {
+    Ok(meshes) => batch_in_place(&meshes),
+    Err(e) => {
+      report.errors.push(e);
+      return report;
+    }
+  };
+
+  report.object = Some(obj.stats.name.clone());
+  report.positions = obj.stats.positions;
+  report.normals = obj.stats.normals;
+  report.shapes = obj.stats.shapes;
+  report.vertices = obj.vertices.len();
+  report.triangles = obj.indices.len() / 3;
+
+  if obj.indices.is_empty() {
+    report.errors.push("no triangles to render".to_owned());
+  }
+
+  if obj.stats.generated_normals > 0 {
+    report.warnings.push(format!(
+      "{} vertices have no normal in the file and get smooth ones",
+      obj.stats.generated_normals
+    ));
+  }
+
+  if obj.stats.ignored_shapes > 0 {
+    report.warnings.push(format!(
+      "{} points and lines aren’t drawn",
+      obj.stats.ignored_shapes
+    ));
+  }
+
+  let mut zero_normals = 0;
+  let mut non_unit_normals = 0;
+
+  for vertex in &obj.vertices {
+    let [x, y, z] = *vertex.normal;
+    let len = (x * x + y * y + z * z).sqrt();
+
+    if len < NORMAL_EPSILON {
+      zero_normals += 1;
+    } else if (len - 1.).abs() > NORMAL_EPSILON {
+      non_unit_normals += 1;
+    }
+  }
+
+  if zero_normals > 0 {
+    report.warnings.push(format!(
+      "{} vertices have a zero-length normal",
+      zero_normals
+    ));
+  }
+
+  if non_unit_normals > 0 {
+    report.warnings.push(format!(
+      "{} vertices have a non-unit normal",
+      non_unit_normals
+    ));
+  }
+
+  let analysis = MeshAnalysis::analyze(&obj);
+  report.non_manifold_edges = analysis.non_manifold_edges;
+  report.degenerate_triangles = analysis.degenerate_triangles.len();
+  report.duplicate_triangles = analysis.duplicate_triangles.len();
+  report.flipped_islands = analysis.flipped_islands;
+
+  if analysis.non_manifold_edges > 0 {
+    report.warnings.push(format!(
+      "{} edges are shared by more than two triangles",
+      analysis.non_manifold_edges
+    ));
+  }
+
+  if !analysis.degenerate_triangles.is_empty() {
+    report.warnings.push(format!(
+      "{} triangles have a zero area",
+      analysis.degenerate_triangles.len()
+    ));
+  }
+
+  if !analysis.duplicate_triangles.is_empty() {
+    report.warnings.push(format!(
+      "{} triangles are duplicated",
+      analysis.duplicate_triangles.len()
+    ));
+  }
+
+  if analysis.flipped_islands > 0 {
+    report.warnings.push(format!(
+      "{} groups of triangles ({} triangles) have a flipped winding",
+      analysis.flipped_islands,
+      analysis.flipped_triangles.len()
+    ));
+  }
+
+  report
+}
+
+/// Validate the models at `paths`, print their reports and return the process exit code.
+///
+/// Text reports are separated by a blank line; JSON reports are printed one per line.
+pub fn run(paths: &[PathBuf], json: bool) -> i32 {
+  let mut ok = true;
+
+  for (i, path) in paths.iter().enumerate() {
+    let report = validate(path);
+    ok &= report.is_ok();
+
+    if json {
+      println!("{}", report.to_json());
+    } else {
+      if i > 0 {
+        println!();
+      }
+
+      println!("{}", report.to_text());
+    }
+  }
+
+  if ok {
+    0
+  } else {
+    1
+  }
+}
+
+pub fn json_string(s: &str) -> String {
+  let mut escaped = String::with_capacity(s.len() + 2);
+  escaped.push('"');
+
+  for c in s.chars() {
+    match c {
+      '"' => escaped.push_str("\\\""),
+      '\\' => escaped.push_str("\\\\"),
+      '\n' => escaped.push_str("\\n"),
+      '\r' => escaped.push_str("\\r"),
+      '\t' => escaped.push_str("\\t"),
+      c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+      c => escaped.push(c),
+    }
+  }
+
+  escaped.push('"');
+  escaped
+}
@@ -1,12 +1,22 @@
 in vec3 position;
 in vec3 normal;
+in vec2 uv;
+in uint object;
 
 out vec3 v_normal;
+out vec2 v_uv;
+flat out uint v_object;
 
 uniform mat4 projection;
 uniform mat4 view;
+// objects drawn one by one are placed in the world by their model matrix; batched ones already are
+uniform mat4 model = mat4(1.);
+// inverse transpose of the model matrix, which keeps normals perpendicular under any scale
+uniform mat3 normal_matrix = mat3(1.);
 
 void main() {
-  v_normal = normal;
-  gl_Position = projection * view * vec4(position, 1.);
+  v_normal = normal_matrix * normal;
+  v_uv = uv;
+  v_object = object;
+  gl_Position = projection * view * model * vec4(position, 1.);
 }
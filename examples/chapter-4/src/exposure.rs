@@ -0,0 +1,91 @@
+//! Eye adaptation.
+//!
+//! The scene is shaded in HDR: a sunny afternoon is orders of magnitude brighter than the island
+//! at night. Like an eye or a camera, the exposure follows the brightness of what’s on screen so
+//! that both end up readable.
+//!
+//! The brightness of the frame is the average of the logarithm of its luminance, computed by
+//! halving the frame over and over down to a single texel, like a mipmap chain. The log average
+//! (the geometric mean) keeps a few very bright pixels, like the sun, from darkening the whole
+//! frame. The exposure then moves towards the one mapping that average to middle gray, slowly,
+//! and within limits: a night too dark to see shouldn’t look like daylight either.
+
+/// Luminance the average of the frame is exposed to.
+const MIDDLE_GRAY: f32 = 0.18;
+/// Lowest exposure, for the brightest frames.
+const MIN_EXPOSURE: f32 = 0.25;
+/// Highest exposure, for the darkest frames.
+const MAX_EXPOSURE: f32 = 8.;
+/// Adaptation rate to a brighter frame, per second.
+const BRIGHTENING_RATE: f32 = 3.;
+/// Adaptation rate to a darker frame, per second; eyes take longer to get used to the dark.
+const DARKENING_RATE: f32 = 1.;
+/// Change of the manual exposure per key press, in stops.
+const MANUAL_STEP: f32 = 1. / 3.;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Exposure {
+  /// Whether the exposure adapts to the frame, or is set by hand.
+  pub auto: bool,
+  /// Current exposure.
+  exposure: f32,
+}
+
+impl Exposure {
+  pub fn new() -> Self {
+    Exposure {
+      auto: true,
+      exposure: 1.,
+    }
+  }
+
+  pub fn value(&self) -> f32 {
+    self.exposure
+  }
+
+  /// Move towards the exposure of a frame whose average luminance is `luminance`, `dt` seconds
+  /// after the previous one.
+  pub fn adapt(&mut self, luminance: f32, dt: f32) {
+    if !self.auto || !luminance.is_finite() {
+      return;
+    }
+
+    let target = (MIDDLE_GRAY / luminance.max(1e-4)).clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+    let rate = if target < self.exposure {
+      BRIGHTENING_RATE
+    } else {
+      DARKENING_RATE
+    };
+
+    // exponential smoothing, so that the speed doesn’t depend on the frame rate
+    self.exposure += (target - self.exposure) * (1. - (-rate * dt).exp());
+  }
+
+  /// Switch between automatic and manual exposure; the manual exposure starts where the automatic
+  /// one was.
+  pub fn toggle_auto(&mut self) {
+    self.auto = !self.auto;
+  }
+
+  /// Change the manual exposure by a number of steps; it switches to manual exposure.
+  pub fn step(&mut self, steps: f32) {
+    self.auto = false;
+    self.exposure =
+      (self.exposure * 2f32.powf(steps * MANUAL_STEP)).clamp(MIN_EXPOSURE, MAX_EXPOSURE);
+  }
+}
+
+/// Sizes of the successive halvings of a frame of size `size`, down to a single texel.
+///
+/// Odd sizes are rounded up, so that no row or column of the frame is left out.
+pub fn reduction_sizes([mut width, mut height]: [u32; 2]) -> Vec<[u32; 2]> {
+  let mut sizes = Vec::new();
+
+  while width > 1 || height > 1 {
+    width = (width + 1) / 2;
+    height = (height + 1) / 2;
+    sizes.push([width, height]);
+  }
+
+  sizes
+}
@@ -0,0 +1,68 @@
+//! View frustum culling.
+
+use cgmath::{InnerSpace, Matrix4, Point3, Vector3, Vector4};
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Copy, Debug)]
+pub struct Aabb {
+  pub min: Point3<f32>,
+  pub max: Point3<f32>,
+}
+
+impl Aabb {
+  /// Distance between a point and the closest point of the box.
+  pub fn distance(&self, p: Point3<f32>) -> f32 {
+    Vector3::new(
+      (self.min.x - p.x).max(p.x - self.max.x).max(0.),
+      (self.min.y - p.y).max(p.y - self.max.y).max(0.),
+      (self.min.z - p.z).max(p.z - self.max.z).max(0.),
+    )
+    .magnitude()
+  }
+}
+
+/// The six planes bounding what a camera sees, with their normals pointing inwards.
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+  planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+  /// Extract the planes of a view-projection matrix; they’re expressed in world space.
+  pub fn new(view_projection: Matrix4<f32>) -> Self {
+    let m = view_projection;
+    let row = |i: usize| Vector4::new(m.x[i], m.y[i], m.z[i], m.w[i]);
+    let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+    Frustum {
+      planes: [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2],
+    }
+  }
+
+  /// Whether a box is at least partly inside the frustum.
+  ///
+  /// Boxes outside of the frustum but close to its edges can be reported as intersecting it; that’s
+  /// fine for culling.
+  pub fn intersects(&self, aabb: &Aabb) -> bool {
+    self.planes.iter().all(|plane| {
+      // the corner of the box the furthest along the plane’s normal
+      let x = if plane.x >= 0. {
+        aabb.max.x
+      } else {
+        aabb.min.x
+      };
+      let y = if plane.y >= 0. {
+        aabb.max.y
+      } else {
+        aabb.min.y
+      };
+      let z = if plane.z >= 0. {
+        aabb.max.z
+      } else {
+        aabb.min.z
+      };
+
+      plane.x * x + plane.y * y + plane.z * z + plane.w >= 0.
+    })
+  }
+}
@@ -0,0 +1,165 @@
+//! Grass blades scattered over the island.
+//!
+//! Every terrain chunk gets a patch of blades, drawn in a single instanced draw call. The blade
+//! geometry is shared and the instances only hold where a blade grows, how it’s turned and a random
+//! value. The vertex shader compares that value to a density mask to decide whether the blade
+//! actually grows, so that the grass can be thinned out without rebuilding the patches.
+
+use crate::frustum::Aabb;
+use crate::terrain::{height, Terrain};
+use crate::{
+  GrassInstance, InstanceBlade, InstanceOffset, Vertex, VertexIndex, VertexNormal, VertexPosition,
+  EXTENT,
+};
+use cgmath::{InnerSpace, Vector3};
+use luminance_front::context::GraphicsContext;
+use luminance_front::tess::{Interleaved, Mode, Tess, TessError};
+use luminance_front::Backend;
+
+/// Number of blade candidates on each side of a patch.
+const BLADES_PER_SIDE: u32 = 32;
+/// Height of the tallest blades.
+pub const BLADE_HEIGHT: f32 = 0.45;
+/// Resolution of the density mask on each side.
+pub const MASK_SIZE: u32 = 256;
+
+/// Blades growing on a terrain chunk.
+pub struct Patch {
+  pub tess: Tess<Vertex, VertexIndex, GrassInstance, Interleaved>,
+  pub aabb: Aabb,
+}
+
+pub struct Grass {
+  patches: Vec<Patch>,
+}
+
+impl Grass {
+  pub fn new<C>(ctxt: &mut C, terrain: &Terrain) -> Result<Self, TessError>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    let (vertices, indices) = blade();
+    let mut patches = Vec::new();
+
+    for (c, chunk) in terrain.chunks().iter().enumerate() {
+      let instances = scatter(&chunk.aabb, c as u32);
+
+      // chunks entirely under the water don’t grow any grass
+      if instances.is_empty() {
+        continue;
+      }
+
+      let tess = ctxt
+        .new_tess()
+        .set_mode(Mode::Triangle)
+        .set_vertices(vertices.clone())
+        .set_indices(indices.clone())
+        .set_instances(instances)
+        .build()?;
+      let mut aabb = chunk.aabb;
+      aabb.max.y += BLADE_HEIGHT;
+
+      patches.push(Patch { tess, aabb });
+    }
+
+    Ok(Grass { patches })
+  }
+
+  pub fn patches(&self) -> &[Patch] {
+    &self.patches
+  }
+}
+
+/// Two crossed quads, tapering towards their top; the root is at the origin and the top at y = 1.
+fn blade() -> (Vec<Vertex>, Vec<VertexIndex>) {
+  let v = |p, n| Vertex::new(VertexPosition::new(p), VertexNormal::new(n));
+  let vertices = vec![
+    v([-0.5, 0., 0.], [0., 0., 1.]),
+    v([0.5, 0., 0.], [0., 0., 1.]),
+    v([0.1, 1., 0.], [0., 0., 1.]),
+    v([-0.1, 1., 0.], [0., 0., 1.]),
+    v([0., 0., -0.5], [1., 0., 0.]),
+    v([0., 0., 0.5], [1., 0., 0.]),
+    v([0., 1., 0.1], [1., 0., 0.]),
+    v([0., 1., -0.1], [1., 0., 0.]),
+  ];
+  let indices = vec![0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7];
+
+  (vertices, indices)
+}
+
+/// Scatter blade candidates over the area of a chunk, on a jittered grid; seed makes each chunk’s
+/// randomness different.
+fn scatter(aabb: &Aabb, seed: u32) -> Vec<GrassInstance> {
+  let size = aabb.max.x - aabb.min.x;
+  let cell = size / BLADES_PER_SIDE as f32;
+  let mut instances = Vec::new();
+
+  for j in 0..BLADES_PER_SIDE {
+    for i in 0..BLADES_PER_SIDE {
+      let n = (seed * BLADES_PER_SIDE + j) * BLADES_PER_SIDE + i;
+      let x = aabb.min.x + (i as f32 + random(n, 0)) * cell;
+      let z = aabb.min.z + (j as f32 + random(n, 1)) * cell;
+      let y = height(x, z);
+
+      // no grass under the water
+      if y < 0. {
+        continue;
+      }
+
+      instances.push(GrassInstance::new(
+        InstanceOffset::new([x, y, z]),
+        InstanceBlade::new([random(n, 2) * std::f32::consts::PI, random(n, 3)]),
+      ));
+    }
+  }
+
+  instances
+}
+
+/// Density of the grass over the whole island, as a MASK_SIZE × MASK_SIZE single channel image.
+///
+/// Grass grows above the beach, fades out near the summit and avoids steep slopes; some noise breaks
+/// it in clumps.
+pub fn density_mask() -> Vec<f32> {
+  let step = 2. * EXTENT / MASK_SIZE as f32;
+  let mut texels = Vec::with_capacity((MASK_SIZE * MASK_SIZE) as usize);
+
+  for j in 0..MASK_SIZE {
+    for i in 0..MASK_SIZE {
+      let x = -EXTENT + (i as f32 + 0.5) * step;
+      let z = -EXTENT + (j as f32 + 0.5) * step;
+      let y = height(x, z);
+      let dx = height(x + step, z) - height(x - step, z);
+      let dz = height(x, z + step) - height(x, z - step);
+      let up = Vector3::new(-dx, 2. * step, -dz).normalize().y;
+
+      let band = smoothstep(0.15, 0.4, y) * (1. - smoothstep(1.6, 2., y));
+      let flat = smoothstep(0.75, 0.9, up);
+      let clumps =
+        0.6 + 0.4 * ((x * 1.3).sin() * (z * 1.7).cos() + (x * 0.7 + z * 2.9).sin() * 0.5);
+
+      texels.push((band * flat * clumps).clamp(0., 1.));
+    }
+  }
+
+  texels
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+  let t = ((x - edge0) / (edge1 - edge0)).clamp(0., 1.);
+  t * t * (3. - 2. * t)
+}
+
+/// Pseudo-random number in [0; 1[ from an index and a dimension.
+fn random(n: u32, dim: u32) -> f32 {
+  // integer hash from Chris Wellons’ “hash prospector”
+  let mut h = n.wrapping_mul(4).wrapping_add(dim);
+  h ^= h >> 16;
+  h = h.wrapping_mul(0x7feb_352d);
+  h ^= h >> 15;
+  h = h.wrapping_mul(0x846c_a68b);
+  h ^= h >> 16;
+
+  (h >> 8) as f32 / (1 << 24) as f32
+}
@@ -0,0 +1,24 @@
+in vec3 v_world;
+in vec3 v_normal;
+in float v_height;
+
+out vec4 frag_color;
+
+uniform vec4 clip_plane;
+uniform vec3 sun_dir;
+uniform vec3 camera_pos;
+
+void main() {
+  if (dot(vec4(v_world, 1.), clip_plane) < 0.) {
+    discard;
+  }
+
+  // darker at the root, where the blades shadow each other
+  vec3 albedo = mix(vec3(.08, .2, .04), vec3(.45, .65, .2), v_height);
+
+  // blades are thin and let light through, so they’re lit from both sides
+  float kd = abs(dot(normalize(v_normal), sun_dir));
+  float daylight = smoothstep(-.2, .1, sun_dir.y);
+
+  frag_color = vec4(albedo * (.05 + (.2 + .75 * kd) * daylight), distance(camera_pos, v_world));
+}
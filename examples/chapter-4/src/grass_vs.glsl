@@ -0,0 +1,47 @@
+in vec3 position;
+in vec3 normal;
+in vec3 offset;
+in vec2 blade;
+
+out vec3 v_world;
+out vec3 v_normal;
+out float v_height;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform vec3 camera_pos;
+uniform float time;
+uniform sampler2D density_mask;
+uniform float extent;
+uniform float blade_height;
+uniform float fade_distance;
+
+const float BLADE_WIDTH = .06;
+
+void main() {
+  // the mask covers the whole island; a blade only grows if its random value is below the density
+  float density = textureLod(density_mask, offset.xz / (2. * extent) + .5, 0.).r;
+
+  // blades shrink into the ground as they get far, rather than popping out
+  float fade = 1. - smoothstep(fade_distance * .6, fade_distance, distance(camera_pos, offset));
+  float scale = blade.y < density ? fade * (.6 + .4 * fract(blade.y * 7.)) : 0.;
+
+  float c = cos(blade.x);
+  float s = sin(blade.x);
+  mat2 rotation = mat2(c, s, -s, c);
+
+  vec3 p = vec3(position.x * BLADE_WIDTH, position.y * blade_height, position.z * BLADE_WIDTH) * scale;
+  p.xz = rotation * p.xz;
+
+  // the wind bends the blades more towards their tip
+  vec2 wind = vec2(sin(time * 1.7 + offset.x * .6 + offset.z * .3), cos(time * 1.3 + offset.z * .5));
+  p.xz += wind * .08 * position.y * position.y * scale;
+
+  vec3 n = normal;
+  n.xz = rotation * n.xz;
+
+  v_world = offset + p;
+  v_normal = n;
+  v_height = position.y;
+  gl_Position = projection * view * vec4(v_world, 1.);
+}
@@ -0,0 +1,32 @@
+//! Luminance histogram of the final frame.
+//!
+//! The histogram tells at a glance how the tones of the frame are spread: a frame bunched up on
+//! the left is underexposed, one bunched up on the right is overexposed, and the bins at either end
+//! count the pixels crushed to black or clipped to white. It’s computed on the CPU from a
+//! downscaled copy of the frame, which is plenty for a distribution and cheap to read back.
+
+/// Number of bins between black and white.
+pub const BINS: usize = 64;
+
+/// Histogram of the luma of RGBA texels, whose channels are within [0; 1].
+///
+/// Each bin holds its number of texels relative to the fullest bin, so that the tallest bar fills
+/// the overlay.
+pub fn histogram(texels: &[f32]) -> Vec<f32> {
+  let mut bins = vec![0.; BINS];
+
+  for rgba in texels.chunks_exact(4) {
+    let luma = 0.2126 * rgba[0] + 0.7152 * rgba[1] + 0.0722 * rgba[2];
+    let bin = (luma.clamp(0., 1.) * (BINS - 1) as f32).round() as usize;
+    bins[bin] += 1.;
+  }
+
+  let fullest = bins.iter().cloned().fold(0., f32::max);
+  if fullest > 0. {
+    for bin in &mut bins {
+      *bin /= fullest;
+    }
+  }
+
+  bins
+}
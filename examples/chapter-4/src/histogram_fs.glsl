@@ -0,0 +1,25 @@
+in vec2 v_uv;
+
+out vec4 frag_color;
+
+uniform sampler2D histogram_tex;
+// size of the overlay, in pixels
+uniform vec2 size;
+
+void main() {
+  int bins = textureSize(histogram_tex, 0).x;
+  int bin = min(int(v_uv.x * float(bins)), bins - 1);
+  float height = texelFetch(histogram_tex, ivec2(bin, 0), 0).r;
+
+  // the bins at either end hold the pixels crushed to black and clipped to white
+  vec3 bar = bin == 0 || bin == bins - 1 ? vec3(1., .3, .2) : vec3(.9);
+  vec3 color = v_uv.y < height ? bar : vec3(.05);
+
+  // frame
+  vec2 edge = min(v_uv, 1. - v_uv) * size;
+  if (min(edge.x, edge.y) < 2.) {
+    color = vec3(1.);
+  }
+
+  frag_color = vec4(color, 1.);
+}
@@ -0,0 +1,27 @@
+out float frag_luminance;
+
+uniform sampler2D hdr_tex;
+
+// first halving of the frame: the average of the log luminance of 2×2 pixels; the log keeps the
+// sun from outweighing the rest of the frame
+void main() {
+  ivec2 size = textureSize(hdr_tex, 0);
+  ivec2 base = ivec2(gl_FragCoord.xy) * 2;
+  float sum = 0.;
+  float count = 0.;
+
+  for (int j = 0; j < 2; ++j) {
+    for (int i = 0; i < 2; ++i) {
+      ivec2 p = base + ivec2(i, j);
+
+      // odd sizes leave the last halving with a single row or column
+      if (all(lessThan(p, size))) {
+        vec3 color = texelFetch(hdr_tex, p, 0).rgb;
+        sum += log(dot(color, vec3(.2126, .7152, .0722)) + 1e-4);
+        count += 1.;
+      }
+    }
+  }
+
+  frag_luminance = sum / count;
+}
@@ -0,0 +1,1231 @@
+mod exposure;
+mod frustum;
+mod grass;
+mod histogram;
+mod terrain;
+
+use crate::exposure::Exposure;
+use crate::frustum::Frustum;
+use crate::grass::{Grass, BLADE_HEIGHT, MASK_SIZE};
+use crate::terrain::{Terrain, LOD_LEVELS};
+use cgmath::{ortho, perspective, InnerSpace, Matrix4, Point3, Rad, SquareMatrix, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance::pipeline::TextureBinding;
+use luminance::pixel::{Depth32F, Floating, R32F, RGBA32F};
+use luminance::texture::{Dim2, GenMipmaps, MagFilter, MinFilter, Sampler};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::{BoundTexture, PipelineError, PipelineState};
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::{Program, Uniform};
+use luminance_front::shading_gate::ShadingGate;
+use luminance_front::tess::{Interleaved, Mode, Tess};
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::process::exit;
+use std::time::Instant;
+
+const QUAD_VS_STR: &str = include_str!("quad_vs.glsl");
+const SKY_FS_STR: &str = include_str!("sky_fs.glsl");
+const SCENE_VS_STR: &str = include_str!("scene_vs.glsl");
+const SCENE_FS_STR: &str = include_str!("scene_fs.glsl");
+const SHADOW_FS_STR: &str = include_str!("shadow_fs.glsl");
+const WIREFRAME_FS_STR: &str = include_str!("wireframe_fs.glsl");
+const GRASS_VS_STR: &str = include_str!("grass_vs.glsl");
+const GRASS_FS_STR: &str = include_str!("grass_fs.glsl");
+const WATER_VS_STR: &str = include_str!("water_vs.glsl");
+const WATER_FS_STR: &str = include_str!("water_fs.glsl");
+const OCCLUSION_FS_STR: &str = include_str!("occlusion_fs.glsl");
+const POST_FS_STR: &str = include_str!("post_fs.glsl");
+const LUMINANCE_FS_STR: &str = include_str!("luminance_fs.glsl");
+const REDUCE_FS_STR: &str = include_str!("reduce_fs.glsl");
+const TONEMAP_FS_STR: &str = include_str!("tonemap_fs.glsl");
+const MINIMAP_VS_STR: &str = include_str!("minimap_vs.glsl");
+const MINIMAP_FS_STR: &str = include_str!("minimap_fs.glsl");
+const HISTOGRAM_FS_STR: &str = include_str!("histogram_fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 100.;
+
+/// Half the size of the island and of the water plane around it.
+const EXTENT: f32 = 10.;
+
+/// Duration of a whole day and night cycle, in seconds.
+const DAY_LENGTH: f32 = 60.;
+/// Resolution of the sun’s shadow map.
+const SHADOW_MAP_SIZE: u32 = 1024;
+/// Distance at which the grass has entirely faded out.
+const GRASS_DISTANCE: f32 = 18.;
+
+/// Size of the minimap on the screen, in pixels.
+const MINIMAP_SIZE: u32 = 256;
+/// Distance between the minimap and the corner of the screen, in pixels.
+const MINIMAP_MARGIN: u32 = 16;
+/// Half the size of the area shown by the minimap; it’s larger than the island so that the camera
+/// orbiting it is on the map.
+const MINIMAP_EXTENT: f32 = 16.;
+/// Altitude of the minimap’s camera.
+const MINIMAP_HEIGHT: f32 = 30.;
+/// Size of the histogram overlay on the screen, in pixels; it sits in the bottom left corner, as far
+/// from it as the minimap from its own.
+const HISTOGRAM_SIZE: [u32; 2] = [256, 96];
+/// How much smaller than the screen the copy of the frame the histogram is computed from is.
+const HISTOGRAM_DOWNSCALE: u32 = 4;
+
+/// Color of each level of detail of the terrain in the wireframe view.
+const LOD_COLORS: [[f32; 3]; LOD_LEVELS] =
+  [[1., 1., 1.], [1., 1., 0.], [1., 0.5, 0.], [1., 0., 0.]];
+
+/// Clip plane that doesn’t clip anything.
+const NO_CLIP: [f32; 4] = [0., 0., 0., 1.];
+/// Keep what’s above the water, for the reflection.
+const CLIP_BELOW: [f32; 4] = [0., 1., 0., 0.];
+/// Keep what’s below the water, for the refraction. The plane is slightly above the water so that
+/// the waves don’t reveal a gap on the shore.
+const CLIP_ABOVE: [f32; 4] = [0., -1., 0., 0.1];
+
+#[derive(Debug, UniformInterface)]
+struct SkyInterface {
+  #[uniform(unbound)]
+  inverse_view_projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  sun_dir: Uniform<[f32; 3]>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct GrassInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  clip_plane: Uniform<[f32; 4]>,
+  #[uniform(unbound)]
+  sun_dir: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  time: Uniform<f32>,
+  #[uniform(unbound)]
+  density_mask: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  extent: Uniform<f32>,
+  #[uniform(unbound)]
+  blade_height: Uniform<f32>,
+  #[uniform(unbound)]
+  fade_distance: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct WireframeInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  clip_plane: Uniform<[f32; 4]>,
+  #[uniform(unbound)]
+  color: Uniform<[f32; 3]>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct SceneInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  clip_plane: Uniform<[f32; 4]>,
+  #[uniform(unbound)]
+  sun_dir: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct WaterInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  reflection_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  refraction_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  sun_dir: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  time: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct OcclusionInterface {
+  #[uniform(unbound)]
+  scene_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  sun_uv: Uniform<[f32; 2]>,
+  #[uniform(unbound)]
+  aspect: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct PostInterface {
+  #[uniform(unbound)]
+  scene_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  occlusion_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  shadow_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  inverse_view_projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  light_view_projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  camera_pos: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  sun_dir: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  fog_density: Uniform<f32>,
+  #[uniform(unbound)]
+  fog_anisotropy: Uniform<f32>,
+  #[uniform(unbound)]
+  sun_uv: Uniform<[f32; 2]>,
+  #[uniform(unbound)]
+  sun_visible: Uniform<f32>,
+  #[uniform(unbound)]
+  aspect: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct LuminanceInterface {
+  #[uniform(unbound)]
+  hdr_tex: Uniform<TextureBinding<Dim2, Floating>>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct ReduceInterface {
+  #[uniform(unbound)]
+  luminance_tex: Uniform<TextureBinding<Dim2, Floating>>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct TonemapInterface {
+  #[uniform(unbound)]
+  hdr_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  exposure: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct MinimapInterface {
+  #[uniform(unbound)]
+  minimap_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  rect: Uniform<[f32; 4]>,
+  #[uniform(unbound)]
+  camera_uv: Uniform<[f32; 2]>,
+  #[uniform(unbound)]
+  map_extent: Uniform<f32>,
+  #[uniform(unbound)]
+  map_height: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct HistogramInterface {
+  #[uniform(unbound)]
+  histogram_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  rect: Uniform<[f32; 4]>,
+  #[uniform(unbound)]
+  size: Uniform<[f32; 2]>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+  #[sem(name = "offset", repr = "[f32; 3]", wrapper = "InstanceOffset")]
+  Offset,
+  #[sem(name = "blade", repr = "[f32; 2]", wrapper = "InstanceBlade")]
+  Blade,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  position: VertexPosition,
+  normal: VertexNormal,
+}
+
+pub type VertexIndex = u32;
+
+/// A grass blade: where it grows, and its rotation and random value.
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics", instanced = "true")]
+pub struct GrassInstance {
+  offset: InstanceOffset,
+  blade: InstanceBlade,
+}
+
+/// Point of view the scene is rendered from.
+#[derive(Clone, Copy, Debug)]
+struct Camera {
+  eye: Point3<f32>,
+  view: Matrix4<f32>,
+}
+
+impl Camera {
+  /// Slowly orbit around the island.
+  fn orbit(t: f32) -> Self {
+    let angle = t * 0.15;
+    let eye = Point3::new(14. * angle.cos(), 3.5 + (t * 0.3).sin(), 14. * angle.sin());
+    let view = Matrix4::look_at(eye, Point3::new(0., 0.5, 0.), Vector3::unit_y());
+
+    Camera { eye, view }
+  }
+
+  /// The same camera, seeing the world mirrored by the water plane.
+  ///
+  /// Mirroring the world rather than moving the camera under the water means that a point of the
+  /// water plane projects at the same place in both views, so the water can sample the reflection
+  /// at its own screen position.
+  fn reflected(&self) -> Self {
+    Camera {
+      eye: Point3::new(self.eye.x, -self.eye.y, self.eye.z),
+      view: self.view * Matrix4::from_nonuniform_scale(1., -1., 1.),
+    }
+  }
+
+  /// Look straight down at the island, with -Z pointing up on the screen.
+  fn top_down() -> Self {
+    let eye = Point3::new(0., MINIMAP_HEIGHT, 0.);
+    let view = Matrix4::look_at(eye, Point3::new(0., 0., 0.), -Vector3::unit_z());
+
+    Camera { eye, view }
+  }
+}
+
+/// Everything but the water.
+struct Scene {
+  sky_program: Program<(), (), SkyInterface>,
+  island_program: Program<VertexSemantics, (), SceneInterface>,
+  shadow_program: Program<VertexSemantics, (), SceneInterface>,
+  wireframe_program: Program<VertexSemantics, (), WireframeInterface>,
+  grass_program: Program<VertexSemantics, (), GrassInterface>,
+  sky: Tess<(), (), (), Interleaved>,
+  terrain: Terrain,
+  grass: Grass,
+  sun_dir: Vector3<f32>,
+  time: f32,
+  lod_bias: f32,
+  /// Draw the terrain’s triangles with the color of their level of detail instead of shading them.
+  wireframe: bool,
+}
+
+impl Scene {
+  /// Render the sky and the island as seen by a camera, clipping what lies on the negative side of
+  /// clip_plane.
+  ///
+  /// Only the terrain chunks in the camera’s frustum are drawn, with a level of detail depending on
+  /// their distance to it; visible_chunks is incremented for each of them. The same goes for the
+  /// grass patches, which are also skipped when they’re too far to be seen.
+  fn render(
+    &mut self,
+    shd_gate: &mut ShadingGate,
+    projection: Matrix4<f32>,
+    camera: &Camera,
+    clip_plane: [f32; 4],
+    density_mask: &BoundTexture<Dim2, R32F>,
+    visible_chunks: &mut usize,
+  ) -> Result<(), PipelineError> {
+    let inverse_view_projection = (projection * camera.view)
+      .invert()
+      .unwrap_or_else(Matrix4::identity);
+    let frustum = Frustum::new(projection * camera.view);
+    let Scene {
+      ref mut sky_program,
+      ref mut island_program,
+      ref mut wireframe_program,
+      ref mut grass_program,
+      ref sky,
+      ref terrain,
+      ref grass,
+      sun_dir,
+      time,
+      lod_bias,
+      wireframe,
+      ..
+    } = *self;
+
+    // the sky covers the whole screen at the far plane; it doesn’t need the depth buffer
+    shd_gate
+      .shade(sky_program, |mut iface, uni, mut rdr_gate| {
+        iface.set(&uni.inverse_view_projection, inverse_view_projection.into());
+        iface.set(&uni.camera_pos, camera.eye.into());
+        iface.set(&uni.sun_dir, sun_dir.into());
+
+        rdr_gate.render(
+          &RenderState::default().set_depth_test(None),
+          |mut tess_gate| tess_gate.render(sky),
+        )
+      })
+      .and_then(|_| {
+        if wireframe {
+          // the color changes with the level of detail, so every chunk is rendered on its own
+          shd_gate.shade(wireframe_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, camera.view.into());
+            iface.set(&uni.clip_plane, clip_plane);
+
+            terrain.visible(&frustum).try_for_each(|chunk| {
+              let lod = chunk.lod(camera.eye, lod_bias);
+              *visible_chunks += 1;
+              iface.set(&uni.color, LOD_COLORS[lod]);
+
+              rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+                tess_gate.render(chunk.wireframe(lod))
+              })
+            })
+          })
+        } else {
+          shd_gate.shade(island_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, camera.view.into());
+            iface.set(&uni.clip_plane, clip_plane);
+            iface.set(&uni.sun_dir, sun_dir.into());
+            iface.set(&uni.camera_pos, camera.eye.into());
+
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              terrain.visible(&frustum).try_for_each(|chunk| {
+                *visible_chunks += 1;
+                tess_gate.render(chunk.tess(chunk.lod(camera.eye, lod_bias)))
+              })
+            })
+          })
+        }
+      })
+      .and_then(|_| {
+        // the grass would hide the wireframe
+        if wireframe {
+          return Ok(());
+        }
+
+        shd_gate.shade(grass_program, |mut iface, uni, mut rdr_gate| {
+          iface.set(&uni.projection, projection.into());
+          iface.set(&uni.view, camera.view.into());
+          iface.set(&uni.clip_plane, clip_plane);
+          iface.set(&uni.sun_dir, sun_dir.into());
+          iface.set(&uni.camera_pos, camera.eye.into());
+          iface.set(&uni.time, time);
+          iface.set(&uni.density_mask, density_mask.binding());
+          iface.set(&uni.extent, EXTENT);
+          iface.set(&uni.blade_height, BLADE_HEIGHT);
+          iface.set(&uni.fade_distance, GRASS_DISTANCE);
+
+          rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+            grass
+              .patches()
+              .iter()
+              .filter(|patch| patch.aabb.distance(camera.eye) < GRASS_DISTANCE)
+              .filter(|patch| frustum.intersects(&patch.aabb))
+              .try_for_each(|patch| tess_gate.render(&patch.tess))
+          })
+        })
+      })
+  }
+
+  /// Render the depth of the island as seen from the sun, for the shadow map.
+  ///
+  /// The shadows are always cast by the finest level of detail, so that they don’t change as the
+  /// camera moves.
+  fn render_shadow(
+    &mut self,
+    shd_gate: &mut ShadingGate,
+    light_view_projection: Matrix4<f32>,
+    visible_chunks: &mut usize,
+  ) -> Result<(), PipelineError> {
+    let frustum = Frustum::new(light_view_projection);
+    let terrain = &self.terrain;
+
+    shd_gate.shade(&mut self.shadow_program, |mut iface, uni, mut rdr_gate| {
+      iface.set(&uni.projection, light_view_projection.into());
+      iface.set(&uni.view, Matrix4::<f32>::identity().into());
+
+      rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+        terrain.visible(&frustum).try_for_each(|chunk| {
+          *visible_chunks += 1;
+          tess_gate.render(chunk.tess(0))
+        })
+      })
+    })
+  }
+}
+
+/// Number of terrain chunks drawn in each pass of a frame.
+#[derive(Clone, Copy, Debug, Default)]
+struct ChunkStats {
+  shadow: usize,
+  reflection: usize,
+  refraction: usize,
+  camera: usize,
+  minimap: usize,
+}
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  let mut last_report = start_t;
+
+  let up = VertexNormal::new([0., 1., 0.]);
+  let water_vertices = [
+    Vertex::new(VertexPosition::new([-EXTENT, 0., -EXTENT]), up),
+    Vertex::new(VertexPosition::new([-EXTENT, 0., EXTENT]), up),
+    Vertex::new(VertexPosition::new([EXTENT, 0., EXTENT]), up),
+    Vertex::new(VertexPosition::new([EXTENT, 0., -EXTENT]), up),
+  ];
+  let water = ctxt
+    .new_tess()
+    .set_mode(Mode::TriangleFan)
+    .set_vertices(&water_vertices[..])
+    .build()
+    .unwrap();
+
+  // the sky and the post passes are screen-covering quads whose corners are generated in the
+  // vertex shader
+  let sky_tess = ctxt
+    .new_tess()
+    .set_vertex_nb(4)
+    .set_mode(Mode::TriangleFan)
+    .build()
+    .unwrap();
+  let screen_quad = ctxt
+    .new_tess()
+    .set_vertex_nb(4)
+    .set_mode(Mode::TriangleFan)
+    .build()
+    .unwrap();
+
+  let terrain = Terrain::new(&mut ctxt).unwrap();
+  let grass = Grass::new(&mut ctxt, &terrain).unwrap();
+
+  let mut scene = Scene {
+    sky_program: ctxt
+      .new_shader_program::<(), (), SkyInterface>()
+      .from_strings(QUAD_VS_STR, None, None, SKY_FS_STR)
+      .unwrap()
+      .ignore_warnings(),
+    island_program: ctxt
+      .new_shader_program::<VertexSemantics, (), SceneInterface>()
+      .from_strings(SCENE_VS_STR, None, None, SCENE_FS_STR)
+      .unwrap()
+      .ignore_warnings(),
+    shadow_program: ctxt
+      .new_shader_program::<VertexSemantics, (), SceneInterface>()
+      .from_strings(SCENE_VS_STR, None, None, SHADOW_FS_STR)
+      .unwrap()
+      .ignore_warnings(),
+    wireframe_program: ctxt
+      .new_shader_program::<VertexSemantics, (), WireframeInterface>()
+      .from_strings(SCENE_VS_STR, None, None, WIREFRAME_FS_STR)
+      .unwrap()
+      .ignore_warnings(),
+    grass_program: ctxt
+      .new_shader_program::<VertexSemantics, (), GrassInterface>()
+      .from_strings(GRASS_VS_STR, None, None, GRASS_FS_STR)
+      .unwrap()
+      .ignore_warnings(),
+    sky: sky_tess,
+    terrain,
+    grass,
+    sun_dir: Vector3::unit_y(),
+    time: 0.,
+    lod_bias: 0.,
+    wireframe: false,
+  };
+
+  let mut water_program = ctxt
+    .new_shader_program::<VertexSemantics, (), WaterInterface>()
+    .from_strings(WATER_VS_STR, None, None, WATER_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut occlusion_program = ctxt
+    .new_shader_program::<(), (), OcclusionInterface>()
+    .from_strings(QUAD_VS_STR, None, None, OCCLUSION_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut post_program = ctxt
+    .new_shader_program::<(), (), PostInterface>()
+    .from_strings(QUAD_VS_STR, None, None, POST_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut luminance_program = ctxt
+    .new_shader_program::<(), (), LuminanceInterface>()
+    .from_strings(QUAD_VS_STR, None, None, LUMINANCE_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut reduce_program = ctxt
+    .new_shader_program::<(), (), ReduceInterface>()
+    .from_strings(QUAD_VS_STR, None, None, REDUCE_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut tonemap_program = ctxt
+    .new_shader_program::<(), (), TonemapInterface>()
+    .from_strings(QUAD_VS_STR, None, None, TONEMAP_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  // the reflection and refraction are rendered at the resolution of the screen, so that the water
+  // can sample them with its screen position
+  let [width, height] = back_buffer.size();
+  let sampler = Sampler {
+    min_filter: MinFilter::Linear,
+    mag_filter: MagFilter::Linear,
+    ..Sampler::default()
+  };
+  let mut reflection_fb = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, Depth32F>([width, height], 0, sampler)
+    .expect("reflection framebuffer");
+  let mut refraction_fb = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, Depth32F>([width, height], 0, sampler)
+    .expect("refraction framebuffer");
+
+  // the scene is rendered offscreen and then composited with the post effects
+  let mut scene_fb = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, Depth32F>([width, height], 0, sampler)
+    .expect("scene framebuffer");
+
+  // the post effects are composited in HDR too, and tonemapped with an exposure adapting to the
+  // average luminance of the frame; that average is computed by halving the frame down to a single
+  // texel
+  let mut hdr_fb = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, ()>([width, height], 0, sampler)
+    .expect("HDR framebuffer");
+  let mut luminance_fbs = exposure::reduction_sizes([width, height])
+    .into_iter()
+    .map(|size| ctxt.new_framebuffer::<Dim2, R32F, ()>(size, 0, sampler))
+    .collect::<Result<Vec<_>, _>>()
+    .expect("luminance framebuffers");
+
+  // the light shafts are blurry anyway, so their occlusion pre-pass is done at half resolution
+  let mut occlusion_fb = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, ()>([width / 2, height / 2], 0, sampler)
+    .expect("occlusion framebuffer");
+
+  // the volumetric fog looks the sun up in a shadow map to know where it’s lit
+  let mut shadow_fb = ctxt
+    .new_framebuffer::<Dim2, (), Depth32F>([SHADOW_MAP_SIZE, SHADOW_MAP_SIZE], 0, sampler)
+    .expect("shadow framebuffer");
+
+  // the minimap is rendered with its own camera into a small texture, then drawn in the top right
+  // corner of the screen
+  let mut minimap_fb = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, Depth32F>([MINIMAP_SIZE, MINIMAP_SIZE], 0, sampler)
+    .expect("minimap framebuffer");
+  let minimap_camera = Camera::top_down();
+  let minimap_projection = ortho(
+    -MINIMAP_EXTENT,
+    MINIMAP_EXTENT,
+    -MINIMAP_EXTENT,
+    MINIMAP_EXTENT,
+    1.,
+    2. * MINIMAP_HEIGHT,
+  );
+  let minimap_rect = {
+    let [w, h] = [width as f32, height as f32];
+    let size = MINIMAP_SIZE as f32;
+    let corner = (MINIMAP_SIZE + MINIMAP_MARGIN) as f32;
+
+    [
+      1. - 2. * corner / w,
+      1. - 2. * corner / h,
+      2. * size / w,
+      2. * size / h,
+    ]
+  };
+  let mut minimap_program = ctxt
+    .new_shader_program::<(), (), MinimapInterface>()
+    .from_strings(MINIMAP_VS_STR, None, None, MINIMAP_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  // the histogram is computed from a smaller copy of the tonemapped frame, read back every frame
+  // while it’s shown, and drawn in the bottom left corner of the screen from a texture of its bins
+  let mut histogram_fb = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, ()>(
+      [width / HISTOGRAM_DOWNSCALE, height / HISTOGRAM_DOWNSCALE],
+      0,
+      sampler,
+    )
+    .expect("histogram framebuffer");
+  let mut histogram_tex = ctxt
+    .new_texture_raw::<Dim2, R32F>(
+      [histogram::BINS as u32, 1],
+      0,
+      Sampler {
+        min_filter: MinFilter::Nearest,
+        mag_filter: MagFilter::Nearest,
+        ..Sampler::default()
+      },
+      GenMipmaps::No,
+      &[0.; histogram::BINS],
+    )
+    .expect("histogram texture");
+  let histogram_rect = {
+    let [w, h] = [width as f32, height as f32];
+    let margin = MINIMAP_MARGIN as f32;
+
+    [
+      -1. + 2. * margin / w,
+      -1. + 2. * margin / h,
+      2. * HISTOGRAM_SIZE[0] as f32 / w,
+      2. * HISTOGRAM_SIZE[1] as f32 / h,
+    ]
+  };
+  let mut histogram_program = ctxt
+    .new_shader_program::<(), (), HistogramInterface>()
+    .from_strings(MINIMAP_VS_STR, None, None, HISTOGRAM_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+  let mut show_histogram = false;
+
+  let mut density_mask = ctxt
+    .new_texture_raw(
+      [MASK_SIZE, MASK_SIZE],
+      0,
+      sampler,
+      GenMipmaps::No,
+      &grass::density_mask(),
+    )
+    .expect("grass density mask");
+  let mut fog_density: f32 = 0.02;
+  let mut fog_anisotropy: f32 = 0.6;
+  let mut exposure = Exposure::new();
+  let mut last_t = 0.;
+
+  let aspect = width as f32 / height as f32;
+  let projection = perspective(FOVY, aspect, Z_NEAR, Z_FAR);
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(Key::W, _, Action::Press, _) => {
+          scene.wireframe = !scene.wireframe;
+        }
+
+        WindowEvent::Key(Key::H, _, Action::Press, _) => {
+          show_histogram = !show_histogram;
+          println!("histogram: {}", if show_histogram { "on" } else { "off" });
+        }
+
+        WindowEvent::Key(Key::E, _, Action::Press, _) => {
+          exposure.toggle_auto();
+          print_exposure(&exposure);
+        }
+
+        WindowEvent::Key(key @ Key::Up, _, Action::Press, _)
+        | WindowEvent::Key(key @ Key::Up, _, Action::Repeat, _)
+        | WindowEvent::Key(key @ Key::Down, _, Action::Press, _)
+        | WindowEvent::Key(key @ Key::Down, _, Action::Repeat, _) => {
+          exposure.step(if key == Key::Up { 1. } else { -1. });
+          print_exposure(&exposure);
+        }
+
+        WindowEvent::Key(key, _, Action::Press, _)
+        | WindowEvent::Key(key, _, Action::Repeat, _) => {
+          match key {
+            Key::Minus => fog_density /= 1.25,
+            Key::Equal => fog_density *= 1.25,
+            Key::LeftBracket => fog_anisotropy = (fog_anisotropy - 0.05).max(-0.95),
+            Key::RightBracket => fog_anisotropy = (fog_anisotropy + 0.05).min(0.95),
+            Key::Comma => scene.lod_bias = (scene.lod_bias - 0.5).max(-2.),
+            Key::Period => scene.lod_bias = (scene.lod_bias + 0.5).min(4.),
+            _ => continue,
+          }
+
+          println!(
+            "fog density: {}, anisotropy: {}, LOD bias: {}",
+            fog_density, fog_anisotropy, scene.lod_bias
+          );
+        }
+
+        _ => (),
+      }
+    }
+
+    let t = start_t.elapsed().as_secs_f32();
+    let dt = t - last_t;
+    last_t = t;
+    let camera = Camera::orbit(t);
+    let reflected = camera.reflected();
+    let sun_dir = sun_dir(t);
+    let light_view_projection = light_view_projection(sun_dir);
+    scene.sun_dir = sun_dir;
+    scene.time = t;
+    let mut chunk_stats = ChunkStats::default();
+
+    // render the shadow map…
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(&shadow_fb, &PipelineState::default(), |_, mut shd_gate| {
+        scene.render_shadow(
+          &mut shd_gate,
+          light_view_projection,
+          &mut chunk_stats.shadow,
+        )
+      })
+      .assume();
+
+    if render.is_err() {
+      break 'app;
+    }
+
+    // … the minimap…
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &minimap_fb,
+        &PipelineState::default(),
+        |pipeline, mut shd_gate| {
+          let density_mask = pipeline.bind_texture(&mut density_mask)?;
+
+          scene.render(
+            &mut shd_gate,
+            minimap_projection,
+            &minimap_camera,
+            NO_CLIP,
+            &density_mask,
+            &mut chunk_stats.minimap,
+          )
+        },
+      )
+      .assume();
+
+    if render.is_err() {
+      break 'app;
+    }
+
+    // … what the water reflects…
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &reflection_fb,
+        &PipelineState::default(),
+        |pipeline, mut shd_gate| {
+          let density_mask = pipeline.bind_texture(&mut density_mask)?;
+
+          scene.render(
+            &mut shd_gate,
+            projection,
+            &reflected,
+            CLIP_BELOW,
+            &density_mask,
+            &mut chunk_stats.reflection,
+          )
+        },
+      )
+      .assume();
+
+    if render.is_err() {
+      break 'app;
+    }
+
+    // … what can be seen through it…
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &refraction_fb,
+        &PipelineState::default().set_clear_color([0., 0., 0., Z_FAR]),
+        |pipeline, mut shd_gate| {
+          let density_mask = pipeline.bind_texture(&mut density_mask)?;
+
+          scene.render(
+            &mut shd_gate,
+            projection,
+            &camera,
+            CLIP_ABOVE,
+            &density_mask,
+            &mut chunk_stats.refraction,
+          )
+        },
+      )
+      .assume();
+
+    if render.is_err() {
+      break 'app;
+    }
+
+    // … and then the whole scene, with the water blending both…
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &scene_fb,
+        &PipelineState::default(),
+        |pipeline, mut shd_gate| {
+          let reflection_tex = pipeline.bind_texture(reflection_fb.color_slot())?;
+          let refraction_tex = pipeline.bind_texture(refraction_fb.color_slot())?;
+          let density_mask = pipeline.bind_texture(&mut density_mask)?;
+
+          scene
+            .render(
+              &mut shd_gate,
+              projection,
+              &camera,
+              NO_CLIP,
+              &density_mask,
+              &mut chunk_stats.camera,
+            )
+            .and_then(|_| {
+              shd_gate.shade(&mut water_program, |mut iface, uni, mut rdr_gate| {
+                iface.set(&uni.projection, projection.into());
+                iface.set(&uni.view, camera.view.into());
+                iface.set(&uni.reflection_tex, reflection_tex.binding());
+                iface.set(&uni.refraction_tex, refraction_tex.binding());
+                iface.set(&uni.camera_pos, camera.eye.into());
+                iface.set(&uni.sun_dir, sun_dir.into());
+                iface.set(&uni.time, t);
+
+                rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+                  tess_gate.render(&water)
+                })
+              })
+            })
+        },
+      )
+      .assume();
+
+    if render.is_err() {
+      break 'app;
+    }
+
+    // … before compositing the volumetric fog, the light shafts and the lens flare on top of it; the
+    // shafts are blurred from what of the sky is visible around the sun
+    let inverse_view_projection = (projection * camera.view)
+      .invert()
+      .unwrap_or_else(Matrix4::identity);
+    let (sun_uv, sun_visible) = match sun_screen_pos(projection, &camera, sun_dir) {
+      Some(sun_uv) => (sun_uv, (sun_dir.y * 10.).clamp(0., 1.)),
+      None => ([0., 0.], 0.),
+    };
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &occlusion_fb,
+        &PipelineState::default(),
+        |pipeline, mut shd_gate| {
+          let scene_tex = pipeline.bind_texture(scene_fb.color_slot())?;
+
+          shd_gate.shade(&mut occlusion_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.scene_tex, scene_tex.binding());
+            iface.set(&uni.sun_uv, sun_uv);
+            iface.set(&uni.aspect, aspect);
+
+            rdr_gate.render(
+              &RenderState::default().set_depth_test(None),
+              |mut tess_gate| tess_gate.render(&screen_quad),
+            )
+          })
+        },
+      )
+      .assume();
+
+    if render.is_err() {
+      break 'app;
+    }
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &hdr_fb,
+        &PipelineState::default(),
+        |pipeline, mut shd_gate| {
+          let scene_tex = pipeline.bind_texture(scene_fb.color_slot())?;
+          let occlusion_tex = pipeline.bind_texture(occlusion_fb.color_slot())?;
+          let shadow_tex = pipeline.bind_texture(shadow_fb.depth_stencil_slot())?;
+
+          shd_gate.shade(&mut post_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.scene_tex, scene_tex.binding());
+            iface.set(&uni.occlusion_tex, occlusion_tex.binding());
+            iface.set(&uni.shadow_tex, shadow_tex.binding());
+            iface.set(&uni.inverse_view_projection, inverse_view_projection.into());
+            iface.set(&uni.light_view_projection, light_view_projection.into());
+            iface.set(&uni.camera_pos, camera.eye.into());
+            iface.set(&uni.sun_dir, sun_dir.into());
+            iface.set(&uni.fog_density, fog_density);
+            iface.set(&uni.fog_anisotropy, fog_anisotropy);
+            iface.set(&uni.sun_uv, sun_uv);
+            iface.set(&uni.sun_visible, sun_visible);
+            iface.set(&uni.aspect, aspect);
+
+            rdr_gate.render(
+              &RenderState::default().set_depth_test(None),
+              |mut tess_gate| tess_gate.render(&screen_quad),
+            )
+          })
+        },
+      )
+      .assume();
+
+    if render.is_err() {
+      break 'app;
+    }
+
+    // the average luminance of the frame is computed by halving it down to a single texel…
+    for i in 0..luminance_fbs.len() {
+      let (sources, targets) = luminance_fbs.split_at_mut(i);
+      let target = &targets[0];
+
+      let render = ctxt
+        .new_pipeline_gate()
+        .pipeline(
+          target,
+          &PipelineState::default(),
+          |pipeline, mut shd_gate| match sources.last_mut() {
+            None => {
+              let hdr_tex = pipeline.bind_texture(hdr_fb.color_slot())?;
+
+              shd_gate.shade(&mut luminance_program, |mut iface, uni, mut rdr_gate| {
+                iface.set(&uni.hdr_tex, hdr_tex.binding());
+
+                rdr_gate.render(
+                  &RenderState::default().set_depth_test(None),
+                  |mut tess_gate| tess_gate.render(&screen_quad),
+                )
+              })
+            }
+
+            Some(source) => {
+              let luminance_tex = pipeline.bind_texture(source.color_slot())?;
+
+              shd_gate.shade(&mut reduce_program, |mut iface, uni, mut rdr_gate| {
+                iface.set(&uni.luminance_tex, luminance_tex.binding());
+
+                rdr_gate.render(
+                  &RenderState::default().set_depth_test(None),
+                  |mut tess_gate| tess_gate.render(&screen_quad),
+                )
+              })
+            }
+          },
+        )
+        .assume();
+
+      if render.is_err() {
+        break 'app;
+      }
+    }
+
+    // … which is read back to adapt the exposure; reading it makes the CPU wait for the GPU, but
+    // only for a single texel at the end of the frame
+    if let Some(average) = luminance_fbs
+      .last_mut()
+      .and_then(|fb| fb.color_slot().get_raw_texels().ok())
+      .and_then(|texels| texels.first().copied())
+    {
+      exposure.adapt(average.exp(), dt);
+    }
+
+    // the histogram is computed from the frame as it will be displayed, so after tonemapping
+    if show_histogram {
+      let render = ctxt
+        .new_pipeline_gate()
+        .pipeline(
+          &histogram_fb,
+          &PipelineState::default(),
+          |pipeline, mut shd_gate| {
+            let hdr_tex = pipeline.bind_texture(hdr_fb.color_slot())?;
+
+            shd_gate.shade(&mut tonemap_program, |mut iface, uni, mut rdr_gate| {
+              iface.set(&uni.hdr_tex, hdr_tex.binding());
+              iface.set(&uni.exposure, exposure.value());
+
+              rdr_gate.render(
+                &RenderState::default().set_depth_test(None),
+                |mut tess_gate| tess_gate.render(&screen_quad),
+              )
+            })
+          },
+        )
+        .assume();
+
+      if render.is_err() {
+        break 'app;
+      }
+
+      let bins = histogram_fb
+        .color_slot()
+        .get_raw_texels()
+        .map(|texels| histogram::histogram(&texels))
+        .and_then(|bins| histogram_tex.upload_raw(GenMipmaps::No, &bins));
+
+      if let Err(e) = bins {
+        eprintln!("cannot update the histogram: {}", e);
+        show_histogram = false;
+      }
+    }
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default(),
+        |pipeline, mut shd_gate| {
+          let hdr_tex = pipeline.bind_texture(hdr_fb.color_slot())?;
+          let minimap_tex = pipeline.bind_texture(minimap_fb.color_slot())?;
+          let histogram_tex = pipeline.bind_texture(&mut histogram_tex)?;
+
+          shd_gate
+            .shade(&mut tonemap_program, |mut iface, uni, mut rdr_gate| {
+              iface.set(&uni.hdr_tex, hdr_tex.binding());
+              iface.set(&uni.exposure, exposure.value());
+
+              rdr_gate.render(
+                &RenderState::default().set_depth_test(None),
+                |mut tess_gate| tess_gate.render(&screen_quad),
+              )
+            })
+            .and_then(|_| {
+              shd_gate.shade(&mut minimap_program, |mut iface, uni, mut rdr_gate| {
+                // the map shows -Z up
+                let camera_uv = [
+                  camera.eye.x / MINIMAP_EXTENT * 0.5 + 0.5,
+                  -camera.eye.z / MINIMAP_EXTENT * 0.5 + 0.5,
+                ];
+
+                iface.set(&uni.minimap_tex, minimap_tex.binding());
+                iface.set(&uni.rect, minimap_rect);
+                iface.set(&uni.camera_uv, camera_uv);
+                iface.set(&uni.map_extent, MINIMAP_EXTENT);
+                iface.set(&uni.map_height, MINIMAP_HEIGHT);
+
+                rdr_gate.render(
+                  &RenderState::default().set_depth_test(None),
+                  |mut tess_gate| tess_gate.render(&screen_quad),
+                )
+              })
+            })
+            .and_then(|_| {
+              if !show_histogram {
+                return Ok(());
+              }
+
+              shd_gate.shade(&mut histogram_program, |mut iface, uni, mut rdr_gate| {
+                iface.set(&uni.histogram_tex, histogram_tex.binding());
+                iface.set(&uni.rect, histogram_rect);
+                iface.set(
+                  &uni.size,
+                  [HISTOGRAM_SIZE[0] as f32, HISTOGRAM_SIZE[1] as f32],
+                );
+
+                rdr_gate.render(
+                  &RenderState::default().set_depth_test(None),
+                  |mut tess_gate| tess_gate.render(&screen_quad),
+                )
+              })
+            })
+        },
+      )
+      .assume();
+
+    if last_report.elapsed().as_secs() >= 1 {
+      last_report = Instant::now();
+      println!(
+        "visible chunks out of {}: camera {}, reflection {}, refraction {}, shadow {}, minimap {}",
+        scene.terrain.chunks().len(),
+        chunk_stats.camera,
+        chunk_stats.reflection,
+        chunk_stats.refraction,
+        chunk_stats.shadow,
+        chunk_stats.minimap
+      );
+    }
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+fn print_exposure(exposure: &Exposure) {
+  println!(
+    "exposure: {:.2} ({})",
+    exposure.value(),
+    if exposure.auto { "auto" } else { "manual" }
+  );
+}
+
+/// Direction towards the sun; it rises and sets once every DAY_LENGTH seconds.
+fn sun_dir(t: f32) -> Vector3<f32> {
+  // start the cycle in the morning
+  let angle = (t / DAY_LENGTH + 0.1) * 2. * std::f32::consts::PI;
+
+  Vector3::new(angle.cos(), angle.sin(), 0.4).normalize()
+}
+
+/// Orthographic projection of the island as seen from the sun, for the shadow map.
+fn light_view_projection(sun_dir: Vector3<f32>) -> Matrix4<f32> {
+  // the box must hold the whole island whatever the direction of the sun
+  let radius = EXTENT * std::f32::consts::SQRT_2;
+  let eye = Point3::new(0., 0., 0.) + sun_dir * 2. * radius;
+  let view = Matrix4::look_at(eye, Point3::new(0., 0., 0.), Vector3::unit_y());
+
+  ortho(-radius, radius, -radius, radius, 0., 4. * radius) * view
+}
+
+/// Position of the sun on the screen, in texture coordinates, if it’s in front of the camera.
+fn sun_screen_pos(
+  projection: Matrix4<f32>,
+  camera: &Camera,
+  sun_dir: Vector3<f32>,
+) -> Option<[f32; 2]> {
+  // the sun is infinitely far, so it’s projected as a direction
+  let clip = projection * camera.view * sun_dir.extend(0.);
+
+  if clip.w <= 0. {
+    return None;
+  }
+
+  Some([clip.x / clip.w * 0.5 + 0.5, clip.y / clip.w * 0.5 + 0.5])
+}
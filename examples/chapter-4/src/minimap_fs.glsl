@@ -0,0 +1,34 @@
+in vec2 v_uv;
+
+out vec4 frag_color;
+
+uniform sampler2D minimap_tex;
+uniform vec2 camera_uv;
+// half the size of the area shown by the map, and altitude of the camera it was rendered from
+uniform float map_extent;
+uniform float map_height;
+
+void main() {
+  vec4 map = texture(minimap_tex, v_uv);
+  vec3 color = map.rgb;
+
+  // the water isn’t rendered in the minimap, but what’s under it is further from the camera than
+  // the water plane
+  vec2 xz = (v_uv * 2. - 1.) * vec2(1., -1.) * map_extent;
+  float water_distance = length(vec3(xz.x, map_height, xz.y));
+
+  if (map.a > water_distance) {
+    color = mix(color, vec3(.05, .25, .4), .7);
+  }
+
+  // where the camera is
+  color = mix(color, vec3(1., .2, .1), 1. - smoothstep(.015, .02, length(v_uv - camera_uv)));
+
+  // frame
+  vec2 edge = min(v_uv, 1. - v_uv);
+  if (min(edge.x, edge.y) < .01) {
+    color = vec3(1.);
+  }
+
+  frag_color = vec4(color, 1.);
+}
@@ -0,0 +1,11 @@
+out vec2 v_uv;
+
+// x, y, width and height of the minimap in normalized device coordinates
+uniform vec4 rect;
+
+const vec2 CORNERS[4] = vec2[](vec2(0., 0.), vec2(1., 0.), vec2(1., 1.), vec2(0., 1.));
+
+void main() {
+  v_uv = CORNERS[gl_VertexID];
+  gl_Position = vec4(rect.xy + v_uv * rect.zw, 0., 1.);
+}
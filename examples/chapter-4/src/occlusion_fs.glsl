@@ -0,0 +1,23 @@
+in vec2 v_ndc;
+
+out vec4 frag_color;
+
+uniform sampler2D scene_tex;
+uniform vec2 sun_uv;
+uniform float aspect;
+
+// the sky is drawn with a distance of 1e4; anything closer occludes the light
+const float SKY_DISTANCE = 1e3;
+
+// occluders are black, and the sky glows brighter the closer it is to the sun
+void main() {
+  vec2 uv = v_ndc * .5 + .5;
+
+  if (texture(scene_tex, uv).a < SKY_DISTANCE) {
+    frag_color = vec4(0., 0., 0., 1.);
+    return;
+  }
+
+  float glow = exp(-length((uv - sun_uv) * vec2(aspect, 1.)) * 6.);
+  frag_color = vec4(vec3(1., .9, .7) * glow, 1.);
+}
@@ -0,0 +1,156 @@
+in vec2 v_ndc;
+
+out vec4 frag_color;
+
+uniform sampler2D scene_tex;
+uniform sampler2D occlusion_tex;
+uniform sampler2D shadow_tex;
+uniform mat4 inverse_view_projection;
+uniform mat4 light_view_projection;
+uniform vec3 camera_pos;
+uniform vec3 sun_dir;
+uniform float fog_density;
+uniform float fog_anisotropy;
+uniform vec2 sun_uv;
+uniform float sun_visible;
+uniform float aspect;
+
+// the sky is drawn with a distance of 1e4; anything closer hides the sun
+const float SKY_DISTANCE = 1e3;
+
+// each ghost of the flare sits on the line from the sun to the center of the screen; an offset of 1
+// is the center, and higher offsets are on the other side of it
+const int GHOSTS = 5;
+const float GHOST_OFFSETS[GHOSTS] = float[](.5, .8, 1.2, 1.6, 2.);
+const float GHOST_SIZES[GHOSTS] = float[](.05, .02, .08, .04, .12);
+const vec3 GHOST_COLORS[GHOSTS] = vec3[](
+  vec3(.8, .6, .3),
+  vec3(.4, .8, .5),
+  vec3(.3, .5, .9),
+  vec3(.9, .4, .6),
+  vec3(.5, .4, .9)
+);
+
+const float PI = 3.14159265;
+
+// the fog is ray marched up to the scene, or up to FOG_MAX_DISTANCE for the sky
+const int FOG_STEPS = 32;
+const float FOG_MAX_DISTANCE = 40.;
+
+// the light shafts are a radial blur of the occlusion pre-pass towards the sun
+const int SHAFT_SAMPLES = 64;
+const float SHAFT_DENSITY = .9;
+const float SHAFT_DECAY = .97;
+const float SHAFT_EXPOSURE = .4;
+
+// fraction of the samples around the sun that see the sky
+float sun_occlusion() {
+  vec2 texel = 4. / vec2(textureSize(scene_tex, 0));
+  float visible = 0.;
+
+  for (int j = -2; j <= 2; ++j) {
+    for (int i = -2; i <= 2; ++i) {
+      vec2 p = sun_uv + vec2(i, j) * texel;
+      bool on_screen = all(greaterThanEqual(p, vec2(0.))) && all(lessThanEqual(p, vec2(1.)));
+
+      if (on_screen && texture(scene_tex, p).a >= SKY_DISTANCE) {
+        visible += 1.;
+      }
+    }
+  }
+
+  return visible / 25.;
+}
+
+vec3 lens_flare(vec2 uv) {
+  vec2 axis = vec2(.5) - sun_uv;
+  vec3 flare = vec3(0.);
+
+  for (int i = 0; i < GHOSTS; ++i) {
+    vec2 ghost = sun_uv + axis * GHOST_OFFSETS[i];
+    float r = length((uv - ghost) * vec2(aspect, 1.)) / GHOST_SIZES[i];
+    flare += GHOST_COLORS[i] * (1. - smoothstep(.7, 1., r)) * .2;
+  }
+
+  // glare around the sun itself
+  flare += vec3(1., .9, .7) * .3 * exp(-length((uv - sun_uv) * vec2(aspect, 1.)) * 8.);
+
+  return flare;
+}
+
+// phase function of the fog; an anisotropy above 0 scatters more light forward, towards the sun
+float henyey_greenstein(float cos_theta, float g) {
+  float g2 = g * g;
+  return (1. - g2) / (4. * PI * pow(1. + g2 - 2. * g * cos_theta, 1.5));
+}
+
+// 1 if the sun lights a point, 0 if it’s in the shadow of the island
+float sun_visibility(vec3 p) {
+  vec4 light = light_view_projection * vec4(p, 1.);
+  vec3 coords = light.xyz / light.w * .5 + .5;
+
+  if (any(lessThan(coords, vec3(0.))) || any(greaterThan(coords, vec3(1.)))) {
+    return 1.;
+  }
+
+  return coords.z - .005 <= texture(shadow_tex, coords.xy).r ? 1. : 0.;
+}
+
+// light scattered towards the camera by the fog in rgb, and how much of the scene gets through in a
+vec4 volumetric_fog(vec2 uv, float scene_distance) {
+  vec4 far = inverse_view_projection * vec4(uv * 2. - 1., 1., 1.);
+  vec3 dir = normalize(far.xyz / far.w - camera_pos);
+  float step_length = min(scene_distance, FOG_MAX_DISTANCE) / float(FOG_STEPS);
+
+  // offsetting the samples per pixel trades the banding of the steps for noise
+  float jitter = fract(52.9829189 * fract(dot(gl_FragCoord.xy, vec2(.06711056, .00583715))));
+
+  // the phase is normalized so that an isotropic fog scatters the color of the sun
+  float daylight = smoothstep(-.2, .1, sun_dir.y);
+  vec3 sun_light = vec3(1., .9, .7) * daylight * henyey_greenstein(dot(dir, sun_dir), fog_anisotropy) * 4. * PI;
+  vec3 ambient = mix(vec3(.01, .015, .04), vec3(.3, .4, .5), daylight) * .5;
+
+  float step_transmittance = exp(-fog_density * step_length);
+  float transmittance = 1.;
+  vec3 scattered = vec3(0.);
+
+  for (int i = 0; i < FOG_STEPS; ++i) {
+    vec3 p = camera_pos + dir * (float(i) + jitter) * step_length;
+    vec3 light = sun_light * sun_visibility(p) + ambient;
+
+    scattered += transmittance * (1. - step_transmittance) * light;
+    transmittance *= step_transmittance;
+  }
+
+  return vec4(scattered, transmittance);
+}
+
+vec3 light_shafts(vec2 uv) {
+  vec2 delta = (uv - sun_uv) * SHAFT_DENSITY / float(SHAFT_SAMPLES);
+  vec2 p = uv;
+  float decay = 1.;
+  vec3 shafts = vec3(0.);
+
+  for (int i = 0; i < SHAFT_SAMPLES; ++i) {
+    p -= delta;
+    shafts += texture(occlusion_tex, p).rgb * decay;
+    decay *= SHAFT_DECAY;
+  }
+
+  return shafts * SHAFT_EXPOSURE / float(SHAFT_SAMPLES);
+}
+
+void main() {
+  vec2 uv = v_ndc * .5 + .5;
+  vec4 scene = texture(scene_tex, uv);
+  vec4 fog = volumetric_fog(uv, scene.a);
+  vec3 color = scene.rgb * fog.a + fog.rgb;
+
+  if (sun_visible > 0.) {
+    // the shafts are added over the whole screen; they fade by themselves as the sun gets hidden
+    color += light_shafts(uv) * sun_visible;
+    color += lens_flare(uv) * sun_visible * sun_occlusion();
+  }
+
+  frag_color = vec4(color, 1.);
+}
@@ -0,0 +1,8 @@
+out vec2 v_ndc;
+
+const vec2 CORNERS[4] = vec2[](vec2(-1., -1.), vec2(1., -1.), vec2(1., 1.), vec2(-1., 1.));
+
+void main() {
+  v_ndc = CORNERS[gl_VertexID];
+  gl_Position = vec4(v_ndc, 1., 1.);
+}
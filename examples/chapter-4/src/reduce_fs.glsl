@@ -0,0 +1,24 @@
+out float frag_luminance;
+
+uniform sampler2D luminance_tex;
+
+// next halvings: the average of 2×2 texels of the previous one
+void main() {
+  ivec2 size = textureSize(luminance_tex, 0);
+  ivec2 base = ivec2(gl_FragCoord.xy) * 2;
+  float sum = 0.;
+  float count = 0.;
+
+  for (int j = 0; j < 2; ++j) {
+    for (int i = 0; i < 2; ++i) {
+      ivec2 p = base + ivec2(i, j);
+
+      if (all(lessThan(p, size))) {
+        sum += texelFetch(luminance_tex, p, 0).r;
+        count += 1.;
+      }
+    }
+  }
+
+  frag_luminance = sum / count;
+}
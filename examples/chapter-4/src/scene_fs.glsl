@@ -0,0 +1,29 @@
+in vec3 v_world;
+in vec3 v_normal;
+
+out vec4 frag_color;
+
+// fragments on the negative side of this plane are clipped; that keeps what’s under the water out
+// of the reflection and what’s above it out of the refraction
+uniform vec4 clip_plane;
+uniform vec3 sun_dir;
+uniform vec3 camera_pos;
+
+void main() {
+  if (dot(vec4(v_world, 1.), clip_plane) < 0.) {
+    discard;
+  }
+
+  vec3 n = normalize(v_normal);
+
+  // sand on the shore, grass higher, and rock on steep slopes
+  vec3 albedo = mix(vec3(.76, .7, .5), vec3(.3, .5, .2), smoothstep(.2, .6, v_world.y));
+  albedo = mix(albedo, vec3(.45, .42, .4), smoothstep(.3, .5, 1. - n.y));
+
+  float kd = max(dot(n, sun_dir), 0.);
+  float daylight = smoothstep(-.2, .1, sun_dir.y);
+
+  // the distance to the camera goes in the alpha channel; the water pass uses it to know how deep
+  // the water is in front of the refracted geometry
+  frag_color = vec4(albedo * (.05 + (.2 + .75 * kd) * daylight), distance(camera_pos, v_world));
+}
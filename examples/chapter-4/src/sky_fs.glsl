@@ -0,0 +1,46 @@
+in vec2 v_ndc;
+
+out vec4 frag_color;
+
+uniform mat4 inverse_view_projection;
+uniform vec3 camera_pos;
+uniform vec3 sun_dir;
+
+// Perez’s sky luminance distribution, with the coefficients Preetham et al. fit for a clear sky
+// (turbidity 2.5); theta is the angle from the zenith and gamma the angle from the sun
+float perez(float cos_theta, float gamma, float cos_gamma) {
+  const float T = 2.5;
+  float A = .1787 * T - 1.463;
+  float B = -.3554 * T + .4275;
+  float C = -.0227 * T + 5.3251;
+  float D = .1206 * T - 2.5771;
+  float E = -.067 * T + .3703;
+
+  return (1. + A * exp(B / max(cos_theta, .01))) * (1. + C * exp(D * gamma) + E * cos_gamma * cos_gamma);
+}
+
+void main() {
+  vec4 far = inverse_view_projection * vec4(v_ndc, 1., 1.);
+  vec3 dir = normalize(far.xyz / far.w - camera_pos);
+
+  // below the horizon, show the horizon
+  vec3 sky_dir = normalize(vec3(dir.x, max(dir.y, 0.), dir.z));
+
+  float cos_gamma = clamp(dot(sky_dir, sun_dir), -1., 1.);
+  float sun_cos_theta = max(sun_dir.y, 0.);
+
+  // luminance relative to the zenith’s
+  float luminance = perez(sky_dir.y, acos(cos_gamma), cos_gamma) / perez(1., acos(sun_cos_theta), sun_cos_theta);
+
+  // the sky gets warmer as the sun goes down, and dark once it’s set
+  float daylight = smoothstep(-.2, .1, sun_dir.y);
+  vec3 day_color = mix(vec3(.9, .5, .3), vec3(.3, .5, .9), smoothstep(0., .4, sun_dir.y));
+  vec3 color = mix(vec3(.01, .015, .04), day_color * min(luminance, 4.) * .5, daylight);
+
+  // sun disc
+  color += vec3(1., .9, .7) * smoothstep(.9995, .9998, dot(dir, sun_dir)) * daylight;
+
+  // the sky is infinitely far; the alpha channel holds the distance to the camera for the water and
+  // the post passes
+  frag_color = vec4(color, 1e4);
+}
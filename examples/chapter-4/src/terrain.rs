@@ -0,0 +1,210 @@
+//! The island’s heightfield, split in chunks that can be culled independently.
+//!
+//! Each chunk comes in several levels of detail, from the full resolution grid down to one vertex
+//! out of eight on each side. Neighbouring chunks using different levels don’t share all their border
+//! vertices, which would leave cracks between them; every chunk has skirts, a strip of triangles
+//! hanging down from its borders, to hide them.
+
+use crate::frustum::{Aabb, Frustum};
+use crate::{Vertex, VertexIndex, VertexNormal, VertexPosition, EXTENT};
+use cgmath::{InnerSpace, Point3, Vector3};
+use luminance_front::context::GraphicsContext;
+use luminance_front::tess::{Interleaved, Mode, Tess, TessError};
+use luminance_front::Backend;
+
+/// Number of quads on each side of the island’s grid.
+const GRID_SIZE: u32 = 128;
+/// Number of quads on each side of a chunk.
+const CHUNK_SIZE: u32 = 16;
+/// Number of levels of detail; each one halves the resolution of the previous one.
+pub const LOD_LEVELS: usize = 4;
+/// Distance under which chunks are drawn at full resolution; the level of detail then decreases
+/// every time the distance doubles.
+const LOD_DISTANCE: f32 = 6.;
+/// How far the skirts hang down below the borders of the chunks.
+const SKIRT_DEPTH: f32 = 0.5;
+
+/// Square piece of the heightfield, with a tessellation per level of detail.
+pub struct Chunk {
+  lods: Vec<Tess<Vertex, VertexIndex, (), Interleaved>>,
+  wireframes: Vec<Tess<Vertex, VertexIndex, (), Interleaved>>,
+  pub aabb: Aabb,
+}
+
+impl Chunk {
+  /// Level of detail to draw the chunk with when seen from a point; 0 is the finest.
+  ///
+  /// A positive bias makes chunks switch to coarser levels closer to the point.
+  pub fn lod(&self, eye: Point3<f32>, bias: f32) -> usize {
+    let d = self.aabb.distance(eye);
+    let lod = (d.max(LOD_DISTANCE) / LOD_DISTANCE).log2() + bias;
+
+    (lod.max(0.) as usize).min(LOD_LEVELS - 1)
+  }
+
+  pub fn tess(&self, lod: usize) -> &Tess<Vertex, VertexIndex, (), Interleaved> {
+    &self.lods[lod]
+  }
+
+  /// Edges of the triangles of a level of detail, to be drawn as lines.
+  pub fn wireframe(&self, lod: usize) -> &Tess<Vertex, VertexIndex, (), Interleaved> {
+    &self.wireframes[lod]
+  }
+}
+
+pub struct Terrain {
+  chunks: Vec<Chunk>,
+}
+
+impl Terrain {
+  pub fn new<C>(ctxt: &mut C) -> Result<Self, TessError>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    let chunks_per_side = GRID_SIZE / CHUNK_SIZE;
+    let mut chunks = Vec::new();
+
+    for j in 0..chunks_per_side {
+      for i in 0..chunks_per_side {
+        let mut lods = Vec::new();
+        let mut wireframes = Vec::new();
+        let mut aabb = None;
+
+        for lod in 0..LOD_LEVELS {
+          let (vertices, indices, lod_aabb) = chunk_mesh(i * CHUNK_SIZE, j * CHUNK_SIZE, 1 << lod);
+
+          // every triangle (a, b, c) gives the lines (a, b), (b, c) and (c, a)
+          let lines = indices
+            .chunks(3)
+            .flat_map(|t| vec![t[0], t[1], t[1], t[2], t[2], t[0]])
+            .collect::<Vec<_>>();
+
+          wireframes.push(
+            ctxt
+              .new_tess()
+              .set_mode(Mode::Line)
+              .set_vertices(vertices.clone())
+              .set_indices(lines)
+              .build()?,
+          );
+          lods.push(
+            ctxt
+              .new_tess()
+              .set_mode(Mode::Triangle)
+              .set_vertices(vertices)
+              .set_indices(indices)
+              .build()?,
+          );
+
+          // the finest level is the one that reaches the highest and lowest points
+          aabb = aabb.or(Some(lod_aabb));
+        }
+
+        chunks.push(Chunk {
+          lods,
+          wireframes,
+          aabb: aabb.unwrap(),
+        });
+      }
+    }
+
+    Ok(Terrain { chunks })
+  }
+
+  pub fn chunks(&self) -> &[Chunk] {
+    &self.chunks
+  }
+
+  /// Chunks at least partly inside a frustum.
+  pub fn visible<'a>(&'a self, frustum: &'a Frustum) -> impl Iterator<Item = &'a Chunk> + 'a {
+    self
+      .chunks
+      .iter()
+      .filter(move |chunk| frustum.intersects(&chunk.aabb))
+  }
+}
+
+/// Height of the island at a given point; it dips under the water away from the center.
+pub fn height(x: f32, z: f32) -> f32 {
+  let r2 = x * x + z * z;
+  let hills = 0.3 * (x * 0.9).sin() * (z * 1.1).cos() + 0.15 * (x * 2.3 + z * 1.7).sin();
+
+  3. * (-r2 / 12.).exp() - 1.5 + hills * (-r2 / 30.).exp()
+}
+
+/// Build the chunk starting at a given quad of the grid as an indexed triangle mesh, keeping one
+/// vertex out of stride on each side.
+fn chunk_mesh(i0: u32, j0: u32, stride: u32) -> (Vec<Vertex>, Vec<VertexIndex>, Aabb) {
+  let step = 2. * EXTENT / GRID_SIZE as f32;
+  let n = CHUNK_SIZE / stride;
+  let mut vertices = Vec::new();
+  let mut min = Point3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+  let mut max = Point3::new(-f32::INFINITY, -f32::INFINITY, -f32::INFINITY);
+
+  for j in 0..=n {
+    for i in 0..=n {
+      let x = -EXTENT + (i0 + i * stride) as f32 * step;
+      let z = -EXTENT + (j0 + j * stride) as f32 * step;
+      let y = height(x, z);
+
+      // the normal comes from the central differences of the full resolution heightfield, which
+      // makes it continuous across chunks and keeps the lighting detailed on coarser levels
+      let dx = height(x + step, z) - height(x - step, z);
+      let dz = height(x, z + step) - height(x, z - step);
+      let normal = Vector3::new(-dx, 2. * step, -dz).normalize();
+
+      vertices.push(Vertex::new(
+        VertexPosition::new([x, y, z]),
+        VertexNormal::new(normal.into()),
+      ));
+
+      min = Point3::new(min.x.min(x), min.y.min(y), min.z.min(z));
+      max = Point3::new(max.x.max(x), max.y.max(y), max.z.max(z));
+    }
+  }
+
+  let row = n + 1;
+  let mut indices = Vec::new();
+
+  for j in 0..n {
+    for i in 0..n {
+      let a = j * row + i;
+      let b = a + row;
+
+      indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+    }
+  }
+
+  // the border vertices, going around the chunk
+  let border = (0..n)
+    .chain((0..n).map(|j| j * row + n))
+    .chain((1..=n).rev().map(|i| n * row + i))
+    .chain((1..=n).rev().map(|j| j * row))
+    .collect::<Vec<_>>();
+
+  // each border vertex gets a copy lowered by SKIRT_DEPTH, and each border edge a quad down to
+  // these copies
+  let skirt_start = vertices.len() as VertexIndex;
+
+  for &v in &border {
+    let mut skirt = vertices[v as usize];
+    let [x, y, z] = *skirt.position;
+    skirt.position = VertexPosition::new([x, y - SKIRT_DEPTH, z]);
+    vertices.push(skirt);
+  }
+
+  for k in 0..border.len() {
+    let next = (k + 1) % border.len();
+    let (a, b) = (border[k], border[next]);
+    let (sa, sb) = (
+      skirt_start + k as VertexIndex,
+      skirt_start + next as VertexIndex,
+    );
+
+    indices.extend_from_slice(&[a, b, sa, b, sb, sa]);
+  }
+
+  min.y -= SKIRT_DEPTH;
+
+  (vertices, indices, Aabb { min, max })
+}
@@ -0,0 +1,17 @@
+in vec2 v_ndc;
+
+out vec4 frag_color;
+
+uniform sampler2D hdr_tex;
+uniform float exposure;
+
+// filmic curve fitted to the ACES one by Krzysztof Narkowicz; it rolls highlights off smoothly
+// instead of clipping them
+vec3 aces(vec3 x) {
+  return clamp((x * (2.51 * x + .03)) / (x * (2.43 * x + .59) + .14), 0., 1.);
+}
+
+void main() {
+  vec3 color = texture(hdr_tex, v_ndc * .5 + .5).rgb;
+  frag_color = vec4(aces(color * exposure), 1.);
+}
@@ -0,0 +1,58 @@
+in vec4 v_clip;
+in vec3 v_world;
+
+out vec4 frag_color;
+
+uniform sampler2D reflection_tex;
+uniform sampler2D refraction_tex;
+uniform vec3 camera_pos;
+uniform vec3 sun_dir;
+uniform float time;
+
+// add a directional sine wave’s slope to grad; only the slope is needed to perturb the normal
+void add_wave(inout vec2 grad, vec2 p, vec2 dir, float amplitude, float frequency, float speed) {
+  grad += amplitude * frequency * cos(dot(p, dir) * frequency + time * speed) * dir;
+}
+
+vec3 water_normal(vec2 p) {
+  vec2 grad = vec2(0.);
+  add_wave(grad, p, normalize(vec2(1., .4)), .04, 2.1, 1.3);
+  add_wave(grad, p, normalize(vec2(-.6, 1.)), .03, 3.3, 1.9);
+  add_wave(grad, p, normalize(vec2(.2, -1.)), .015, 7.1, 2.7);
+  add_wave(grad, p, normalize(vec2(-1., -.3)), .01, 11.3, 3.1);
+  return normalize(vec3(-grad.x, 1., -grad.y));
+}
+
+void main() {
+  vec3 n = water_normal(v_world.xz);
+  vec3 view_dir = normalize(camera_pos - v_world);
+
+  // the reflection and refraction textures were rendered from the same point of view as the water,
+  // so the screen position of the fragment is the texture coordinate to sample them at; the normal
+  // distorts it to make the surface look wavy
+  vec2 uv = v_clip.xy / v_clip.w * .5 + .5;
+  vec2 distortion = n.xz * .05;
+  vec3 reflection = texture(reflection_tex, clamp(uv + distortion, .001, .999)).rgb;
+  vec4 refraction = texture(refraction_tex, clamp(uv + distortion, .001, .999));
+  vec3 still_refraction = texture(refraction_tex, uv).rgb;
+
+  // how much water there is between the surface and the refracted geometry
+  float water_depth = refraction.a - distance(camera_pos, v_world);
+
+  // deep water absorbs light
+  float daylight = smoothstep(-.2, .1, sun_dir.y);
+  vec3 deep_color = vec3(.02, .12, .18) * (.1 + .9 * daylight);
+  vec3 refracted = mix(refraction.rgb, deep_color, clamp(water_depth * .25, 0., 1.));
+
+  // Schlick’s approximation of the Fresnel term, with the reflectance of water at normal incidence
+  float fresnel = .02 + .98 * pow(1. - max(dot(view_dir, n), 0.), 5.);
+  vec3 color = mix(refracted, reflection, fresnel);
+
+  // sun glints
+  color += vec3(1., .95, .8) * pow(max(dot(reflect(-view_dir, n), sun_dir), 0.), 200.) * daylight;
+
+  // fade to the undistorted refraction on the shoreline, where the water gets very shallow
+  float shore = clamp(water_depth * 2., 0., 1.);
+  // like the rest of the scene, the alpha channel holds the distance to the camera
+  frag_color = vec4(mix(still_refraction, color, shore), distance(camera_pos, v_world));
+}
@@ -0,0 +1,13 @@
+in vec3 position;
+
+out vec4 v_clip;
+out vec3 v_world;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+void main() {
+  v_world = position;
+  v_clip = projection * view * vec4(position, 1.);
+  gl_Position = v_clip;
+}
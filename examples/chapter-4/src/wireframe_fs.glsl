@@ -0,0 +1,16 @@
+in vec3 v_world;
+
+out vec4 frag_color;
+
+uniform vec4 clip_plane;
+uniform vec3 color;
+
+void main() {
+  if (dot(vec4(v_world, 1.), clip_plane) < 0.) {
+    discard;
+  }
+
+  // a zero distance in the alpha channel keeps the lines out of the fog, and shows them as they are
+  // through the water
+  frag_color = vec4(color, 0.);
+}
@@ -0,0 +1,174 @@
+//! Node animations.
+//!
+//! A glTF animation is made of channels, each one moving the translation, rotation or scale of a
+//! node along keys. Animations loop: once past their last key, they start over from time 0.
+
+use crate::graph::SceneGraph;
+use cgmath::{InnerSpace, Quaternion, Vector4, VectorSpace};
+
+/// Part of the local transform of a node a channel moves.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Property {
+  Translation,
+  Rotation,
+  Scale,
+}
+
+/// How values are interpolated between keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Interpolation {
+  /// The value of a key is held until the next one.
+  Step,
+  /// Rotations are interpolated spherically.
+  Linear,
+  /// Hermite spline, with tangents given along the keys.
+  Cubic,
+}
+
+/// Keys of one property of one node.
+#[derive(Clone, Debug)]
+pub struct Channel {
+  node: usize,
+  property: Property,
+  interpolation: Interpolation,
+  /// Times of the keys, in seconds, in time order.
+  times: Vec<f32>,
+  /// Values at the keys; rotations are quaternions as X, Y, Z and W, and vectors have a W of 0.
+  /// Cubic channels have an in-tangent, a value and an out-tangent per key.
+  values: Vec<Vector4<f32>>,
+}
+
+impl Channel {
+  pub fn new(
+    node: usize,
+    property: Property,
+    interpolation: Interpolation,
+    times: Vec<f32>,
+    values: Vec<Vector4<f32>>,
+  ) -> Result<Self, String> {
+    let values_per_key = if interpolation == Interpolation::Cubic {
+      3
+    } else {
+      1
+    };
+
+    if times.is_empty() {
+      return Err("channel without keys".to_owned());
+    }
+
+    if values.len() != times.len() * values_per_key {
+      return Err(format!("{} values for {} keys", values.len(), times.len()));
+    }
+
+    if times.windows(2).any(|pair| pair[1] <= pair[0]) {
+      return Err("keys not in time order".to_owned());
+    }
+
+    Ok(Channel {
+      node,
+      property,
+      interpolation,
+      times,
+      values,
+    })
+  }
+
+  /// Value of the channel at time `t`.
+  ///
+  /// Before the first key and after the last one, the channel holds its value.
+  fn sample(&self, t: f32) -> Vector4<f32> {
+    let cubic = self.interpolation == Interpolation::Cubic;
+    let value = |i: usize| {
+      if cubic {
+        self.values[3 * i + 1]
+      } else {
+        self.values[i]
+      }
+    };
+
+    // index of the first key after t
+    let next = match self.times.iter().position(|&key_t| key_t > t) {
+      Some(0) => return value(0),
+      Some(next) => next,
+      None => return value(self.times.len() - 1),
+    };
+
+    let (t0, t1) = (self.times[next - 1], self.times[next]);
+    let (p0, p1) = (value(next - 1), value(next));
+    let s = (t - t0) / (t1 - t0);
+
+    match self.interpolation {
+      Interpolation::Step => p0,
+
+      Interpolation::Linear if self.property == Property::Rotation => {
+        let q = quaternion(p0).slerp(quaternion(p1), s);
+        Vector4::new(q.v.x, q.v.y, q.v.z, q.s)
+      }
+
+      Interpolation::Linear => p0.lerp(p1, s),
+
+      Interpolation::Cubic => {
+        // tangents are given per second; the out-tangent of the previous key and the in-tangent
+        // of the next one shape the segment
+        let m0 = self.values[3 * (next - 1) + 2] * (t1 - t0);
+        let m1 = self.values[3 * next] * (t1 - t0);
+        let s2 = s * s;
+        let s3 = s2 * s;
+
+        p0 * (2. * s3 - 3. * s2 + 1.)
+          + m0 * (s3 - 2. * s2 + s)
+          + p1 * (-2. * s3 + 3. * s2)
+          + m1 * (s3 - s2)
+      }
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct Animation {
+  pub name: Option<String>,
+  channels: Vec<Channel>,
+  /// Time of the last key of all the channels.
+  duration: f32,
+}
+
+impl Animation {
+  pub fn new(name: Option<String>, channels: Vec<Channel>) -> Self {
+    let duration = channels
+      .iter()
+      .filter_map(|channel| channel.times.last().copied())
+      .fold(0., f32::max);
+
+    Animation {
+      name,
+      channels,
+      duration,
+    }
+  }
+
+  /// Move the nodes of `graph` to where they are at time `t`; nodes and properties without a
+  /// channel are left untouched.
+  pub fn apply(&self, graph: &mut SceneGraph, t: f32) {
+    let t = if self.duration > 0. {
+      t.rem_euclid(self.duration)
+    } else {
+      0.
+    };
+
+    for channel in &self.channels {
+      let value = channel.sample(t);
+      let node = &mut graph.nodes[channel.node];
+
+      match channel.property {
+        Property::Translation => node.translation = value.truncate(),
+        // splines don’t keep quaternions at unit length
+        Property::Rotation => node.rotation = quaternion(value).normalize(),
+        Property::Scale => node.scale = value.truncate(),
+      }
+    }
+  }
+}
+
+fn quaternion(v: Vector4<f32>) -> Quaternion<f32> {
+  Quaternion::new(v.w, v.x, v.y, v.z)
+}
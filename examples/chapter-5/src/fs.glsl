@@ -0,0 +1,30 @@
+in vec3 v_position;
+in vec3 v_normal;
+in vec2 v_uv;
+
+out vec3 frag_color;
+
+// 0: directional, 1: point, 2: spot
+uniform int light_kind;
+uniform vec3 light_position;
+uniform vec3 light_direction;
+uniform vec3 light_color;
+// cosines of the inner and outer angles of spot lights
+uniform vec2 light_cone;
+
+void main() {
+  // a faint checkerboard shows how the texture coordinates are laid out on the model
+  vec2 cell = floor(v_uv * 8.);
+  float checker = mod(cell.x + cell.y, 2.);
+  vec3 obj_color = mix(vec3(.55, .55, .55), vec3(.65, .65, .65), checker);
+
+  vec3 to_light = light_kind == 0 ? -light_direction : normalize(light_position - v_position);
+  float kd = max(dot(normalize(v_normal), to_light), 0.);
+
+  // spot lights fade out from their inner cone to their outer one
+  if (light_kind == 2) {
+    kd *= smoothstep(light_cone.y, light_cone.x, dot(-to_light, light_direction));
+  }
+
+  frag_color = obj_color * (.2 + .8 * kd * light_color);
+}
@@ -0,0 +1,127 @@
+//! Scene graph.
+//!
+//! Meshes are flattened into world space when loading, but cameras and lights stay attached to
+//! their nodes: their world transform is found by walking up the hierarchy, composing the local
+//! transforms of their node and of all its ancestors. A light parented to an animated node orbits
+//! with it, and a camera parented to a node moving along a path follows that path.
+
+use cgmath::{ortho, perspective, InnerSpace, Matrix4, Quaternion, Rad, Vector3, Vector4};
+
+/// Far plane of cameras with an infinite projection; cgmath only builds finite ones.
+const INFINITE_Z_FAR: f32 = 1000.;
+
+/// Node of the hierarchy.
+#[derive(Clone, Debug)]
+pub struct SceneNode {
+  pub name: Option<String>,
+  /// Index of the parent node; root nodes have none.
+  pub parent: Option<usize>,
+  /// Local transform, relative to the parent: scaled, then rotated, then translated.
+  pub translation: Vector3<f32>,
+  pub rotation: Quaternion<f32>,
+  pub scale: Vector3<f32>,
+}
+
+impl SceneNode {
+  /// Transform from the space of the node to the space of its parent.
+  pub fn local_transform(&self) -> Matrix4<f32> {
+    Matrix4::from_translation(self.translation)
+      * Matrix4::from(self.rotation)
+      * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+  }
+}
+
+/// All the nodes of a file, indexed like in the file.
+#[derive(Clone, Debug, Default)]
+pub struct SceneGraph {
+  pub nodes: Vec<SceneNode>,
+}
+
+impl SceneGraph {
+  /// Transform from the space of node `index` to the world.
+  pub fn world_transform(&self, index: usize) -> Matrix4<f32> {
+    let node = &self.nodes[index];
+    let local = node.local_transform();
+
+    match node.parent {
+      Some(parent) => self.world_transform(parent) * local,
+      None => local,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+  Perspective {
+    fovy: Rad<f32>,
+    z_near: f32,
+    /// Infinite projections have none.
+    z_far: Option<f32>,
+  },
+  Orthographic {
+    /// Half the width and height of the view.
+    xmag: f32,
+    ymag: f32,
+    z_near: f32,
+    z_far: f32,
+  },
+}
+
+/// Camera attached to a node; it looks down the local -Z axis of the node, Y being up.
+#[derive(Clone, Debug)]
+pub struct SceneCamera {
+  pub node: usize,
+  pub name: Option<String>,
+  pub projection: Projection,
+}
+
+impl SceneCamera {
+  /// Projection matrix for a viewport of the given aspect ratio; the ratio the camera was authored
+  /// for is ignored, so that the view isn’t stretched.
+  pub fn projection(&self, aspect: f32) -> Matrix4<f32> {
+    match self.projection {
+      Projection::Perspective {
+        fovy,
+        z_near,
+        z_far,
+      } => perspective(fovy, aspect, z_near, z_far.unwrap_or(INFINITE_Z_FAR)),
+
+      Projection::Orthographic {
+        xmag,
+        ymag,
+        z_near,
+        z_far,
+      } => ortho(-xmag, xmag, -ymag, ymag, z_near, z_far),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightKind {
+  /// Lights everything from the local -Z axis of its node.
+  Directional,
+  /// Lights all around the origin of its node.
+  Point,
+  /// Lights a cone around the local -Z axis of its node; the light fades between the inner and
+  /// outer angles.
+  Spot { inner: Rad<f32>, outer: Rad<f32> },
+}
+
+/// Light attached to a node.
+#[derive(Clone, Debug)]
+pub struct SceneLight {
+  pub node: usize,
+  pub name: Option<String>,
+  pub kind: LightKind,
+  pub color: [f32; 3],
+}
+
+impl SceneLight {
+  /// Position and direction of the light, given the world transform of its node.
+  pub fn place(&self, transform: Matrix4<f32>) -> (Vector3<f32>, Vector3<f32>) {
+    let position = (transform * Vector4::new(0., 0., 0., 1.)).truncate();
+    let direction = (transform * Vector4::new(0., 0., -1., 0.)).truncate();
+
+    (position, direction.normalize())
+  }
+}
@@ -0,0 +1,604 @@
+//! glTF 2.0 loading.
+//!
+//! Both `.gltf` files (JSON with external or embedded buffers) and `.glb` binaries are supported.
+//! glTF is already Y-up, right-handed and in meters, so unlike OBJ files no basis conversion is
+//! needed.
+//!
+//! A glTF scene is a hierarchy of nodes, each with its own transform; meshes are attached to nodes
+//! and made of several primitives, usually one per material. All the primitives of all the meshes
+//! of the scene are flattened into a single list of vertices, in world space, so that they’re
+//! drawn with a single tessellation.
+//!
+//! The hierarchy itself is kept as well, along with the cameras and lights attached to its nodes
+//! (the latter from the `KHR_lights_punctual` extension) and the animations moving them. Meshes stay
+//! where they are in the file, unless they’re skinned: skinned vertices follow the joints of their
+//! skeleton.
+
+use crate::animation::{Animation, Channel, Interpolation, Property};
+use crate::graph::{LightKind, Projection, SceneCamera, SceneGraph, SceneLight, SceneNode};
+use crate::skin::{Skin, SkinnedVertex, Skinning};
+use crate::{Vertex, VertexIndex, VertexNormal, VertexPosition, VertexUV};
+use cgmath::{
+  InnerSpace, Matrix, Matrix3, Matrix4, Point3, Quaternion, Rad, SquareMatrix, Transform, Vector3,
+  Vector4,
+};
+use gltf::animation::util::ReadOutputs;
+use gltf::animation::{Interpolation as GltfInterpolation, Property as GltfProperty};
+use gltf::camera::Projection as GltfProjection;
+use gltf::khr_lights_punctual::Kind;
+use gltf::mesh::Mode as PrimitiveMode;
+use gltf::{buffer, Document, Node};
+use luminance_front::context::GraphicsContext;
+use luminance_front::tess::{Interleaved, Mode, Tess, TessError};
+use luminance_front::Backend;
+use std::path::Path;
+
+/// Statistics about the source file, gathered while loading.
+#[derive(Clone, Debug, Default)]
+pub struct GltfStats {
+  pub nodes: usize,
+  pub meshes: usize,
+  pub primitives: usize,
+  /// Primitives without normals, which got flat normals.
+  pub flat_primitives: usize,
+}
+
+pub struct Gltf {
+  pub vertices: Vec<Vertex>,
+  pub indices: Vec<VertexIndex>,
+  pub graph: SceneGraph,
+  pub cameras: Vec<SceneCamera>,
+  pub lights: Vec<SceneLight>,
+  pub animations: Vec<Animation>,
+  pub skinning: Skinning,
+  pub stats: GltfStats,
+}
+
+impl Gltf {
+  pub fn to_tess<C>(
+    &self,
+    ctxt: &mut C,
+  ) -> Result<Tess<Vertex, VertexIndex, (), Interleaved>, TessError>
+  where
+    C: GraphicsContext<Backend = Backend>,
+  {
+    ctxt
+      .new_tess()
+      .set_mode(Mode::Triangle)
+      .set_vertices(self.vertices.clone())
+      .set_indices(self.indices.clone())
+      .build()
+  }
+
+  /// Axis-aligned bounding box of the model, as its minimum and maximum corners.
+  pub fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+
+    for vertex in &self.vertices {
+      for (i, &x) in vertex.position.iter().enumerate() {
+        min[i] = min[i].min(x);
+        max[i] = max[i].max(x);
+      }
+    }
+
+    (min, max)
+  }
+
+  /// Move the model to the origin and scale it so that its largest extent is 1.
+  ///
+  /// Only the vertices are moved; the returned transform is the one applied to them, for nodes to
+  /// follow.
+  pub fn fit_unit(&mut self) -> Matrix4<f32> {
+    let (min, max) = self.bounds();
+    let extent = (0..3).map(|i| max[i] - min[i]).fold(0., f32::max);
+
+    if extent <= 0. {
+      return Matrix4::identity();
+    }
+
+    let center = [
+      (min[0] + max[0]) * 0.5,
+      (min[1] + max[1]) * 0.5,
+      (min[2] + max[2]) * 0.5,
+    ];
+
+    for vertex in &mut self.vertices {
+      let [x, y, z] = *vertex.position;
+      vertex.position = VertexPosition::new([
+        (x - center[0]) / extent,
+        (y - center[1]) / extent,
+        (z - center[2]) / extent,
+      ]);
+    }
+
+    Matrix4::from_scale(1. / extent)
+      * Matrix4::from_translation(-Vector3::new(center[0], center[1], center[2]))
+  }
+
+  /// Node of a joint of one of the skins, by name.
+  pub fn find_joint(&self, name: &str) -> Option<usize> {
+    self
+      .skinning
+      .skins
+      .iter()
+      .flat_map(|skin| skin.joints.iter().copied())
+      .find(|&joint| self.graph.nodes[joint].name.as_deref() == Some(name))
+  }
+
+  /// Names of the joints of all the skins.
+  pub fn joint_names(&self) -> Vec<&str> {
+    let mut names = self
+      .skinning
+      .skins
+      .iter()
+      .flat_map(|skin| skin.joints.iter())
+      .filter_map(|&joint| self.graph.nodes[joint].name.as_deref())
+      .collect::<Vec<_>>();
+
+    // skins often share joints
+    names.sort_unstable();
+    names.dedup();
+    names
+  }
+
+  pub fn load<P>(path: P) -> Result<Self, String>
+  where
+    P: AsRef<Path>,
+  {
+    let (document, buffers, _) =
+      gltf::import(path).map_err(|e| format!("cannot load file: {}", e))?;
+
+    // files without a default scene are expected to show their first one
+    let scene = document
+      .default_scene()
+      .or_else(|| document.scenes().next())
+      .ok_or_else(|| "no scene to show".to_owned())?;
+
+    let mut gltf = Gltf {
+      vertices: Vec::new(),
+      indices: Vec::new(),
+      graph: read_graph(&document),
+      cameras: Vec::new(),
+      lights: Vec::new(),
+      animations: Vec::new(),
+      skinning: Skinning::default(),
+      stats: GltfStats::default(),
+    };
+
+    for skin in document.skins() {
+      let skin = read_skin(&skin, &buffers).map_err(|e| format!("skin {}: {}", skin.index(), e))?;
+      gltf.skinning.skins.push(skin);
+    }
+
+    for node in scene.nodes() {
+      gltf.add_node(&node, Matrix4::identity(), &buffers)?;
+    }
+
+    // skinned vertices were added in their bind pose; put them where the skeleton stands
+    gltf
+      .skinning
+      .pose(&gltf.graph, Matrix4::identity(), &mut gltf.vertices);
+
+    if gltf.indices.is_empty() {
+      return Err("no triangles in the scene".to_owned());
+    }
+
+    for animation in document.animations() {
+      let animation = read_animation(&animation, &buffers)
+        .map_err(|e| format!("animation {}: {}", animation.index(), e))?;
+      gltf.animations.push(animation);
+    }
+
+    Ok(gltf)
+  }
+
+  /// Add the meshes of a node and of its children, `parent` being the transform of its parent.
+  fn add_node(
+    &mut self,
+    node: &Node,
+    parent: Matrix4<f32>,
+    buffers: &[buffer::Data],
+  ) -> Result<(), String> {
+    let transform = parent * Matrix4::from(node.transform().matrix());
+    self.stats.nodes += 1;
+
+    if let Some(mesh) = node.mesh() {
+      self.stats.meshes += 1;
+      let skin = node.skin();
+
+      for primitive in mesh.primitives() {
+        if primitive.mode() != PrimitiveMode::Triangles {
+          return Err(format!(
+            "unsupported non-triangle primitive in mesh {}",
+            mesh.index()
+          ));
+        }
+
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        let positions: Vec<[f32; 3]> = reader
+          .read_positions()
+          .ok_or_else(|| format!("missing positions in mesh {}", mesh.index()))?
+          .collect();
+        let normals: Option<Vec<[f32; 3]>> = reader.read_normals().map(Iterator::collect);
+        let uvs: Option<Vec<[f32; 2]>> = reader
+          .read_tex_coords(0)
+          .map(|uvs| uvs.into_f32().collect());
+        // non-indexed primitives use their vertices in order
+        let indices: Vec<u32> = match reader.read_indices() {
+          Some(indices) => indices.into_u32().collect(),
+          None => (0..positions.len() as u32).collect(),
+        };
+
+        let counts_match = normals.iter().all(|n| n.len() == positions.len())
+          && uvs.iter().all(|uv| uv.len() == positions.len());
+
+        if !counts_match {
+          return Err(format!(
+            "attributes of different lengths in mesh {}",
+            mesh.index()
+          ));
+        }
+
+        if let Some(&index) = indices.iter().find(|&&i| i as usize >= positions.len()) {
+          return Err(format!(
+            "index {} out of bounds in mesh {}",
+            index,
+            mesh.index()
+          ));
+        }
+
+        let skin = match skin {
+          Some(ref skin) => {
+            let joint_count = self.skinning.skins[skin.index()].joints.len();
+            let skin = read_primitive_skin(&reader, skin.index(), positions.len(), joint_count)
+              .map_err(|e| format!("{} in mesh {}", e, mesh.index()))?;
+            Some(skin)
+          }
+
+          None => None,
+        };
+
+        // skinned meshes are placed by their joints only, whatever the transform of their node
+        let transform = if skin.is_some() {
+          Matrix4::identity()
+        } else {
+          transform
+        };
+
+        let primitive = Primitive {
+          positions,
+          normals,
+          uvs,
+          indices,
+          skin,
+        };
+
+        self.add_primitive(primitive, transform);
+      }
+    }
+
+    if let Some(camera) = node.camera() {
+      let projection = match camera.projection() {
+        GltfProjection::Perspective(p) => Projection::Perspective {
+          fovy: Rad(p.yfov()),
+          z_near: p.znear(),
+          z_far: p.zfar(),
+        },
+        GltfProjection::Orthographic(o) => Projection::Orthographic {
+          xmag: o.xmag(),
+          ymag: o.ymag(),
+          z_near: o.znear(),
+          z_far: o.zfar(),
+        },
+      };
+
+      self.cameras.push(SceneCamera {
+        node: node.index(),
+        name: camera.name().map(str::to_owned),
+        projection,
+      });
+    }
+
+    // intensities are left out: the chapter shades without exposure, and physical light units
+    // would blow everything out
+    if let Some(light) = node.light() {
+      let kind = match light.kind() {
+        Kind::Directional => LightKind::Directional,
+        Kind::Point => LightKind::Point,
+        Kind::Spot {
+          inner_cone_angle,
+          outer_cone_angle,
+        } => LightKind::Spot {
+          inner: Rad(inner_cone_angle),
+          outer: Rad(outer_cone_angle),
+        },
+      };
+
+      self.lights.push(SceneLight {
+        node: node.index(),
+        name: light.name().map(str::to_owned),
+        kind,
+        color: light.color(),
+      });
+    }
+
+    for child in node.children() {
+      self.add_node(&child, transform, buffers)?;
+    }
+
+    Ok(())
+  }
+
+  /// Add a primitive, moving it to world space with `transform`.
+  fn add_primitive(&mut self, primitive: Primitive, transform: Matrix4<f32>) {
+    // normals are transformed by the inverse transpose, so that non-uniform scales keep them
+    // perpendicular to the surface
+    let linear = Matrix3::from_cols(
+      transform.x.truncate(),
+      transform.y.truncate(),
+      transform.z.truncate(),
+    );
+    let normal_matrix = linear
+      .invert()
+      .map_or(linear, |inverse| inverse.transpose());
+    // a transform mirroring the model turns its triangles inside out
+    let mirrored = linear.determinant() < 0.;
+
+    let position = |i: u32| {
+      let [x, y, z] = primitive.positions[i as usize];
+      transform.transform_point(Point3::new(x, y, z))
+    };
+    let uv = |i: u32| {
+      primitive
+        .uvs
+        .as_ref()
+        .map_or([0., 0.], |uvs| uvs[i as usize])
+    };
+
+    let mut triangles: Vec<[u32; 3]> = primitive
+      .indices
+      .chunks_exact(3)
+      .map(|t| [t[0], t[1], t[2]])
+      .collect();
+
+    if mirrored {
+      for triangle in &mut triangles {
+        triangle.swap(1, 2);
+      }
+    }
+
+    self.stats.primitives += 1;
+
+    match primitive.normals {
+      // indices of the primitive are offset past the vertices already added
+      Some(ref normals) => {
+        let base = self.vertices.len() as VertexIndex;
+
+        for (i, &[x, y, z]) in normals.iter().enumerate() {
+          let normal = normal_matrix * Vector3::new(x, y, z);
+          let normal = if normal.magnitude2() > 0. {
+            normal.normalize()
+          } else {
+            normal
+          };
+          let vertex = Vertex {
+            position: VertexPosition::new(position(i as u32).into()),
+            normal: VertexNormal::new(normal.into()),
+            uv: VertexUV::new(uv(i as u32)),
+          };
+
+          self.push_vertex(&primitive, i as u32, vertex);
+        }
+
+        for triangle in triangles {
+          self
+            .indices
+            .extend(triangle.iter().map(|&i| base + i as VertexIndex));
+        }
+      }
+
+      // glTF asks for flat normals when there are none; vertices can’t be shared between
+      // triangles then
+      None => {
+        self.stats.flat_primitives += 1;
+
+        for triangle in triangles {
+          let [a, b, c] = [
+            position(triangle[0]),
+            position(triangle[1]),
+            position(triangle[2]),
+          ];
+          let normal = (b - a).cross(c - a);
+          let normal = if normal.magnitude2() > 0. {
+            normal.normalize()
+          } else {
+            normal
+          };
+
+          for &i in &triangle {
+            let vertex = Vertex {
+              position: VertexPosition::new(position(i).into()),
+              normal: VertexNormal::new(normal.into()),
+              uv: VertexUV::new(uv(i)),
+            };
+
+            self.indices.push(self.vertices.len() as VertexIndex);
+            self.push_vertex(&primitive, i, vertex);
+          }
+        }
+      }
+    }
+  }
+
+  /// Add a vertex made from vertex `i` of `primitive`, keeping track of its joints if it has any.
+  fn push_vertex(&mut self, primitive: &Primitive, i: u32, vertex: Vertex) {
+    if let Some(ref skin) = primitive.skin {
+      self.skinning.vertices.push(SkinnedVertex {
+        vertex: self.vertices.len(),
+        skin: skin.skin,
+        joints: skin.joints[i as usize],
+        weights: skin.weights[i as usize],
+        position: Point3::from(*vertex.position),
+        normal: Vector3::from(*vertex.normal),
+      });
+    }
+
+    self.vertices.push(vertex);
+  }
+}
+
+/// Read the joints of a skin and their inverse bind matrices.
+fn read_skin(skin: &gltf::Skin, buffers: &[buffer::Data]) -> Result<Skin, String> {
+  let joints = skin.joints().map(|joint| joint.index()).collect::<Vec<_>>();
+  let reader = skin.reader(|buffer| Some(&buffers[buffer.index()]));
+  // without inverse bind matrices, joints are bound where they stand
+  let inverse_binds = match reader.read_inverse_bind_matrices() {
+    Some(matrices) => matrices.map(Matrix4::from).collect::<Vec<_>>(),
+    None => vec![Matrix4::identity(); joints.len()],
+  };
+
+  if inverse_binds.len() != joints.len() {
+    return Err(format!(
+      "{} inverse bind matrices for {} joints",
+      inverse_binds.len(),
+      joints.len()
+    ));
+  }
+
+  Ok(Skin {
+    joints,
+    inverse_binds,
+  })
+}
+
+fn read_primitive_skin<'a, 's, F>(
+  reader: &gltf::mesh::Reader<'a, 's, F>,
+  skin: usize,
+  vertex_count: usize,
+  joint_count: usize,
+) -> Result<PrimitiveSkin, String>
+where
+  F: Clone + Fn(gltf::Buffer<'a>) -> Option<&'s [u8]>,
+{
+  let joints: Vec<[u16; 4]> = reader
+    .read_joints(0)
+    .ok_or_else(|| "missing joints".to_owned())?
+    .into_u16()
+    .collect();
+  let weights: Vec<[f32; 4]> = reader
+    .read_weights(0)
+    .ok_or_else(|| "missing joint weights".to_owned())?
+    .into_f32()
+    .collect();
+
+  if joints.len() != vertex_count || weights.len() != vertex_count {
+    return Err("joints or weights of different lengths than positions".to_owned());
+  }
+
+  if let Some(&joint) = joints
+    .iter()
+    .flatten()
+    .find(|&&j| j as usize >= joint_count)
+  {
+    return Err(format!("joint {} out of bounds", joint));
+  }
+
+  Ok(PrimitiveSkin {
+    skin,
+    joints,
+    weights,
+  })
+}
+
+/// Read the local transforms of all the nodes of the file and link them to their parents.
+fn read_graph(document: &Document) -> SceneGraph {
+  let mut nodes = document
+    .nodes()
+    .map(|node| {
+      let (translation, [x, y, z, w], scale) = node.transform().decomposed();
+
+      SceneNode {
+        name: node.name().map(str::to_owned),
+        parent: None,
+        translation: translation.into(),
+        rotation: Quaternion::new(w, x, y, z),
+        scale: scale.into(),
+      }
+    })
+    .collect::<Vec<_>>();
+
+  for node in document.nodes() {
+    for child in node.children() {
+      nodes[child.index()].parent = Some(node.index());
+    }
+  }
+
+  SceneGraph { nodes }
+}
+
+fn read_animation(
+  animation: &gltf::Animation,
+  buffers: &[buffer::Data],
+) -> Result<Animation, String> {
+  let mut channels = Vec::new();
+
+  for (i, channel) in animation.channels().enumerate() {
+    // meshes are baked, so there’s nothing to morph
+    let property = match channel.target().property() {
+      GltfProperty::Translation => Property::Translation,
+      GltfProperty::Rotation => Property::Rotation,
+      GltfProperty::Scale => Property::Scale,
+      GltfProperty::MorphTargetWeights => continue,
+    };
+    let interpolation = match channel.sampler().interpolation() {
+      GltfInterpolation::Step => Interpolation::Step,
+      GltfInterpolation::Linear => Interpolation::Linear,
+      GltfInterpolation::CubicSpline => Interpolation::Cubic,
+    };
+    let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+    let times = reader
+      .read_inputs()
+      .ok_or_else(|| "missing key times".to_owned())?
+      .collect();
+    let vector = |[x, y, z]: [f32; 3]| Vector4::new(x, y, z, 0.);
+    let values = match reader.read_outputs() {
+      Some(ReadOutputs::Translations(values)) => values.map(vector).collect(),
+      Some(ReadOutputs::Rotations(values)) => values.into_f32().map(Vector4::from).collect(),
+      Some(ReadOutputs::Scales(values)) => values.map(vector).collect(),
+      Some(ReadOutputs::MorphTargetWeights(_)) => continue,
+      None => return Err("missing key values".to_owned()),
+    };
+
+    let channel = Channel::new(
+      channel.target().node().index(),
+      property,
+      interpolation,
+      times,
+      values,
+    )
+    .map_err(|e| format!("channel {}: {}", i, e))?;
+    channels.push(channel);
+  }
+
+  Ok(Animation::new(
+    animation.name().map(str::to_owned),
+    channels,
+  ))
+}
+
+/// Attributes of a primitive, as read from the buffers.
+struct Primitive {
+  positions: Vec<[f32; 3]>,
+  normals: Option<Vec<[f32; 3]>>,
+  uvs: Option<Vec<[f32; 2]>>,
+  indices: Vec<u32>,
+  skin: Option<PrimitiveSkin>,
+}
+
+/// Joints and weights of the vertices of a skinned primitive.
+struct PrimitiveSkin {
+  skin: usize,
+  joints: Vec<[u16; 4]>,
+  weights: Vec<[f32; 4]>,
+}
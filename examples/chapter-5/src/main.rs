@@ -0,0 +1,339 @@
+mod animation;
+mod graph;
+mod loader;
+mod skin;
+
+use crate::graph::LightKind;
+use crate::loader::Gltf;
+use cgmath::{
+  perspective, EuclideanSpace, InnerSpace, Matrix4, Point3, Rad, SquareMatrix, Vector3,
+};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::env;
+use std::process::exit;
+use std::time::Instant;
+
+const VS_STR: &str = include_str!("vs.glsl");
+const FS_STR: &str = include_str!("fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_2);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 10.;
+
+/// Light used when the file has none, or when its lights are cycled past.
+const DEFAULT_LIGHT_DIR: [f32; 3] = [0., -1., -0.5];
+
+#[derive(Debug, UniformInterface)]
+struct ShaderInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  model: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  light_kind: Uniform<i32>,
+  #[uniform(unbound)]
+  light_position: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  light_direction: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  light_color: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  light_cone: Uniform<[f32; 2]>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+  #[sem(name = "uv", repr = "[f32; 2]", wrapper = "VertexUV")]
+  UV,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  pub position: VertexPosition,
+  pub normal: VertexNormal,
+  pub uv: VertexUV,
+}
+
+pub type VertexIndex = u32;
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut args = env::args().skip(1);
+  let path = args.next().unwrap_or_else(|| usage());
+  // a model attached to a joint of the skeleton, following it when animated
+  let attachment = match (args.next(), args.next()) {
+    (Some(attachment_path), Some(joint_name)) => Some((attachment_path, joint_name)),
+    (None, _) => None,
+    _ => usage(),
+  };
+  println!("loading {}", path);
+
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+
+  let mut gltf = match Gltf::load(&path) {
+    Ok(gltf) => gltf,
+    Err(e) => {
+      eprintln!("cannot load {}: {}", path, e);
+      exit(1);
+    }
+  };
+
+  let stats = &gltf.stats;
+  println!(
+    "{} nodes, {} meshes, {} primitives",
+    stats.nodes, stats.meshes, stats.primitives
+  );
+  println!(
+    "{} vertices, {} triangles",
+    gltf.vertices.len(),
+    gltf.indices.len() / 3
+  );
+
+  if stats.flat_primitives > 0 {
+    println!(
+      "{} primitives without normals, shaded flat",
+      stats.flat_primitives
+    );
+  }
+
+  println!(
+    "{} cameras, {} lights, {} animations, {} skins",
+    gltf.cameras.len(),
+    gltf.lights.len(),
+    gltf.animations.len(),
+    gltf.skinning.skins.len()
+  );
+
+  // the camera is set for a model of about a unit, whatever the size of the scene; nodes are moved
+  // along with the vertices
+  let fit = gltf.fit_unit();
+  let mut mesh = gltf.to_tess(&mut ctxt).unwrap();
+
+  // the attachment keeps its size: it’s made for the model, so it’s scaled along with it
+  let attachment = attachment.map(|(attachment_path, joint_name)| {
+    let joint = gltf.find_joint(&joint_name).unwrap_or_else(|| {
+      eprintln!(
+        "no joint named {}; joints: {}",
+        joint_name,
+        gltf.joint_names().join(", ")
+      );
+      exit(1);
+    });
+    let attachment = Gltf::load(&attachment_path).unwrap_or_else(|e| {
+      eprintln!("cannot load {}: {}", attachment_path, e);
+      exit(1);
+    });
+    println!("attaching {} to {}", attachment_path, joint_name);
+
+    (attachment.to_tess(&mut ctxt).unwrap(), joint)
+  });
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), ShaderInterface>()
+    .from_strings(VS_STR, None, None, FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let [width, height] = back_buffer.size();
+  let aspect = width as f32 / height as f32;
+  let default_projection = perspective(FOVY, aspect, Z_NEAR, Z_FAR);
+
+  let default_view =
+    Matrix4::<f32>::look_at(Point3::new(2., 2., 2.), Point3::origin(), Vector3::unit_y());
+
+  // the default camera and light are used until the ones of the file are cycled through
+  let mut camera = None;
+  let mut light = None;
+  let mut animation = if gltf.animations.is_empty() {
+    None
+  } else {
+    Some(0)
+  };
+  // local transforms of the nodes, moved by the animation being played
+  let mut graph = gltf.graph.clone();
+  let start_t = Instant::now();
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(Key::C, _, Action::Press, _) => {
+          camera = cycle(camera, gltf.cameras.len());
+          let name = camera.map(|i| label(&gltf.cameras[i].name, i));
+          println!("camera: {}", name.as_deref().unwrap_or("default"));
+        }
+
+        WindowEvent::Key(Key::L, _, Action::Press, _) => {
+          light = cycle(light, gltf.lights.len());
+          let name = light.map(|i| label(&gltf.lights[i].name, i));
+          println!("light: {}", name.as_deref().unwrap_or("default"));
+        }
+
+        // nodes left untouched by the next animation go back to where they were
+        WindowEvent::Key(Key::A, _, Action::Press, _) => {
+          animation = cycle(animation, gltf.animations.len());
+          graph = gltf.graph.clone();
+          let name = animation.map(|i| label(&gltf.animations[i].name, i));
+          println!("animation: {}", name.as_deref().unwrap_or("none"));
+        }
+
+        _ => (),
+      }
+    }
+
+    let t = start_t.elapsed().as_secs_f32();
+
+    if let Some(i) = animation {
+      gltf.animations[i].apply(&mut graph, t);
+    }
+
+    if !gltf.skinning.is_empty() {
+      gltf.skinning.pose(&graph, fit, &mut gltf.vertices);
+
+      match mesh.vertices_mut() {
+        Ok(mut mesh_vertices) => mesh_vertices.copy_from_slice(&gltf.vertices),
+        Err(e) => eprintln!("cannot update vertices: {}", e),
+      }
+    }
+
+    let attachment_model = attachment
+      .as_ref()
+      .map(|(tess, joint)| (tess, fit * graph.world_transform(*joint)));
+
+    // cameras and lights follow their nodes, moved like the vertices were
+    let (projection, view) = match camera {
+      Some(i) => {
+        let camera = &gltf.cameras[i];
+        let transform = fit * graph.world_transform(camera.node);
+
+        // a node scaled down to nothing leaves no way to look through it
+        let view = transform.invert().unwrap_or(default_view);
+        (camera.projection(aspect), view)
+      }
+
+      None => (default_projection, default_view),
+    };
+
+    let (light_kind, light_position, light_direction, light_color, light_cone) = match light {
+      Some(i) => {
+        let light = &gltf.lights[i];
+        let (position, direction) = light.place(fit * graph.world_transform(light.node));
+        let (kind, cone) = match light.kind {
+          LightKind::Directional => (0, [0., 0.]),
+          LightKind::Point => (1, [0., 0.]),
+          LightKind::Spot { inner, outer } => (2, [inner.0.cos(), outer.0.cos()]),
+        };
+
+        (kind, position.into(), direction.into(), light.color, cone)
+      }
+
+      None => (
+        0,
+        [0., 0., 0.],
+        Vector3::from(DEFAULT_LIGHT_DIR).normalize().into(),
+        [1., 1., 1.],
+        [0., 0.],
+      ),
+    };
+
+    // rendering code goes here
+    let color = [0.3, 0.3, 0.3, 1.];
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color(color),
+        |_, mut shd_gate| {
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.light_kind, light_kind);
+            iface.set(&uni.light_position, light_position);
+            iface.set(&uni.light_direction, light_direction);
+            iface.set(&uni.light_color, light_color);
+            iface.set(&uni.light_cone, light_cone);
+
+            iface.set(&uni.model, Matrix4::<f32>::identity().into());
+            rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+              tess_gate.render(&mesh)
+            })?;
+
+            if let Some((tess, model)) = attachment_model {
+              iface.set(&uni.model, model.into());
+              rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+                tess_gate.render(tess)
+              })?;
+            }
+
+            Ok(())
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+fn usage() -> ! {
+  eprintln!("usage: chapter-5 <model.gltf|model.glb> [<attachment.gltf|attachment.glb> <joint>]");
+  exit(1);
+}
+
+/// Next of `len` items, going back to none past the last one.
+fn cycle(current: Option<usize>, len: usize) -> Option<usize> {
+  match current {
+    None if len > 0 => Some(0),
+    Some(i) if i + 1 < len => Some(i + 1),
+    _ => None,
+  }
+}
+
+/// Name of an item of the file, or its index for unnamed ones.
+fn label(name: &Option<String>, index: usize) -> String {
+  name.clone().unwrap_or_else(|| format!("#{}", index))
+}
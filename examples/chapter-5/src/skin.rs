@@ -0,0 +1,87 @@
+//! Skinning.
+//!
+//! Skinned vertices aren’t attached to a single node but to up to four joints of a skeleton, each
+//! with a weight. Posing them means moving each vertex with the weighted sum of the transforms of
+//! its joints, from the bind pose the mesh was modelled in to wherever the joints stand now. That’s
+//! done on the CPU: skinned models are usually small, and the vertices are uploaded again after
+//! each pose.
+
+use crate::graph::SceneGraph;
+use crate::{Vertex, VertexNormal, VertexPosition};
+use cgmath::{InnerSpace, Matrix4, Point3, Transform, Vector3, Zero};
+
+/// Joints of a skeleton.
+#[derive(Clone, Debug)]
+pub struct Skin {
+  /// Nodes acting as joints.
+  pub joints: Vec<usize>,
+  /// Transforms from the bind pose to the space of each joint.
+  pub inverse_binds: Vec<Matrix4<f32>>,
+}
+
+/// Vertex moved by the joints of a skin.
+#[derive(Clone, Debug)]
+pub struct SkinnedVertex {
+  /// Index of the vertex in the mesh.
+  pub vertex: usize,
+  pub skin: usize,
+  /// Joints, as indices in the skin, and their weights.
+  pub joints: [u16; 4],
+  pub weights: [f32; 4],
+  /// Position and normal in the bind pose.
+  pub position: Point3<f32>,
+  pub normal: Vector3<f32>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Skinning {
+  pub skins: Vec<Skin>,
+  pub vertices: Vec<SkinnedVertex>,
+}
+
+impl Skinning {
+  pub fn is_empty(&self) -> bool {
+    self.vertices.is_empty()
+  }
+
+  /// Pose the skinned vertices of `vertices` after the joints in `graph`, `fit` being applied to
+  /// the world afterwards.
+  pub fn pose(&self, graph: &SceneGraph, fit: Matrix4<f32>, vertices: &mut [Vertex]) {
+    let joint_transforms = self
+      .skins
+      .iter()
+      .map(|skin| {
+        skin
+          .joints
+          .iter()
+          .zip(&skin.inverse_binds)
+          .map(|(&joint, inverse_bind)| fit * graph.world_transform(joint) * inverse_bind)
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>();
+
+    for skinned in &self.vertices {
+      let joints = &joint_transforms[skinned.skin];
+      let transform = skinned
+        .joints
+        .iter()
+        .zip(&skinned.weights)
+        .fold(Matrix4::zero(), |transform, (&joint, &weight)| {
+          transform + joints[joint as usize] * weight
+        });
+      let position = transform.transform_point(skinned.position);
+      // joints seldom scale, so the normals are moved like directions rather than with the inverse
+      // transpose
+      let normal = transform.transform_vector(skinned.normal);
+      let normal = if normal.magnitude2() > 0. {
+        normal.normalize()
+      } else {
+        normal
+      };
+
+      let vertex = &mut vertices[skinned.vertex];
+      vertex.position = VertexPosition::new(position.into());
+      vertex.normal = VertexNormal::new(normal.into());
+    }
+  }
+}
@@ -0,0 +1,18 @@
+in vec3 position;
+in vec3 normal;
+in vec2 uv;
+
+out vec3 v_position;
+out vec3 v_normal;
+out vec2 v_uv;
+
+uniform mat4 projection;
+uniform mat4 view;
+uniform mat4 model;
+
+void main() {
+  v_position = (model * vec4(position, 1.)).xyz;
+  v_normal = mat3(model) * normal;
+  v_uv = uv;
+  gl_Position = projection * view * vec4(v_position, 1.);
+}
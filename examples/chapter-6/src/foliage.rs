@@ -0,0 +1,140 @@
+//! Leaf cards.
+//!
+//! Foliage is rarely modeled leaf by leaf: bushes and tree crowns are clouds of cards, quads
+//! textured with a few leaves whose outline lies in the alpha channel. The cards are drawn opaque;
+//! the fragments outside of the leaves are thrown away.
+//!
+//! Alpha testing discards fragments whose alpha is under a threshold. It’s cheap and order
+//! independent, but the outline of the leaves is decided per pixel, so it aliases and shimmers as
+//! the camera moves. With multisampling, alpha-to-coverage turns the alpha of a fragment into the
+//! fraction of its samples that get covered instead, which antialiases the outline for free.
+//! luminance doesn’t expose it, so it’s toggled with raw GL calls.
+
+use crate::{Vertex, VertexIndex, VertexNormal, VertexPosition, VertexUV};
+use cgmath::{InnerSpace, Point3, Vector3};
+
+/// Size of the leaf texture, in texels.
+pub const LEAF_SIZE: u32 = 256;
+/// Number of cards per bush.
+pub const CARDS_PER_BUSH: u32 = 80;
+/// Size of a card.
+const CARD_SIZE: f32 = 0.45;
+
+pub fn set_alpha_to_coverage(enabled: bool) {
+  unsafe {
+    if enabled {
+      gl::Enable(gl::SAMPLE_ALPHA_TO_COVERAGE);
+    } else {
+      gl::Disable(gl::SAMPLE_ALPHA_TO_COVERAGE);
+    }
+  }
+}
+
+/// RGBA texels of a leaf, from the bottom row up.
+///
+/// The leaf points up, its stem at the bottom. Its outline fades over about a texel, so that the
+/// alpha channel keeps a gradient across the edge once filtered.
+pub fn leaf_texels() -> Vec<u8> {
+  let size = LEAF_SIZE as f32;
+  let mut texels = Vec::with_capacity((LEAF_SIZE * LEAF_SIZE * 4) as usize);
+
+  for y in 0..LEAF_SIZE {
+    for x in 0..LEAF_SIZE {
+      let u = (x as f32 + 0.5) / size;
+      let v = (y as f32 + 0.5) / size;
+
+      // half the width of the leaf along its length, pointed at both ends
+      let along = ((v - 0.05) / 0.9).clamp(0., 1.);
+      let half_width = 0.32 * (along * std::f32::consts::PI).sin().powf(0.8);
+      let across = (u - 0.5).abs();
+      let alpha = ((half_width - across) * size + 0.5).clamp(0., 1.);
+
+      // lighter along the midrib, darker towards the edges and the stem
+      let rib = (1. - across / 0.015).max(0.);
+      let shade = 0.7 + 0.3 * along - 0.3 * (across / 0.32).min(1.);
+      let green = [0.18 * shade, 0.45 * shade, 0.12 * shade];
+      let color = [
+        green[0] + rib * 0.2,
+        green[1] + rib * 0.2,
+        green[2] + rib * 0.1,
+      ];
+
+      texels.extend_from_slice(&[
+        (color[0].min(1.) * 255.) as u8,
+        (color[1].min(1.) * 255.) as u8,
+        (color[2].min(1.) * 255.) as u8,
+        (alpha * 255.) as u8,
+      ]);
+    }
+  }
+
+  texels
+}
+
+/// Cards of a bush, scattered in a sphere.
+///
+/// Cards get the normal of the sphere rather than their own, which shades the bush as a whole: lit
+/// on one side, in the shade on the other, instead of as a mess of flat cards facing every way.
+pub fn bush(
+  center: Point3<f32>,
+  radius: f32,
+  seed: u32,
+  vertices: &mut Vec<Vertex>,
+  indices: &mut Vec<VertexIndex>,
+) {
+  for card in 0..CARDS_PER_BUSH {
+    let n = seed * CARDS_PER_BUSH + card;
+    let random = |i: u32| random(n * 8 + i);
+
+    // a point in the sphere, spread over its volume
+    let direction = Vector3::new(
+      random(0) * 2. - 1.,
+      random(1) * 2. - 1.,
+      random(2) * 2. - 1.,
+    );
+    let direction = if direction.magnitude2() > 1e-6 {
+      direction.normalize()
+    } else {
+      Vector3::unit_y()
+    };
+    let position = center + direction * radius * random(3).cbrt();
+
+    // a random orientation, leaves pointing upwards more often than not
+    let yaw = random(4) * std::f32::consts::PI * 2.;
+    let tilt = (random(5) - 0.3) * std::f32::consts::FRAC_PI_2;
+    let right = Vector3::new(yaw.cos(), 0., yaw.sin());
+    let up = Vector3::new(-yaw.sin() * tilt.sin(), tilt.cos(), yaw.cos() * tilt.sin());
+    let right = right * CARD_SIZE * 0.5;
+    let up = up * CARD_SIZE * 0.5;
+
+    let normal = VertexNormal::new(direction.into());
+    let base = vertices.len() as VertexIndex;
+    let corners = [
+      (position - right - up, [0., 0.]),
+      (position + right - up, [1., 0.]),
+      (position + right + up, [1., 1.]),
+      (position - right + up, [0., 1.]),
+    ];
+
+    for &(corner, uv) in &corners {
+      vertices.push(Vertex {
+        position: VertexPosition::new(corner.into()),
+        normal,
+        uv: VertexUV::new(uv),
+      });
+    }
+
+    indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+  }
+}
+
+/// Pseudo-random number in [0; 1).
+fn random(n: u32) -> f32 {
+  // integer hash from Hugo Elias
+  let n = (n << 13) ^ n;
+  let n = n
+    .wrapping_mul(n.wrapping_mul(n).wrapping_mul(15731).wrapping_add(789221))
+    .wrapping_add(1376312589);
+
+  (n & 0x7fffffff) as f32 / 0x80000000u32 as f32
+}
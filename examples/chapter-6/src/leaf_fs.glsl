@@ -0,0 +1,30 @@
+in vec3 v_normal;
+in vec2 v_uv;
+
+out vec4 frag_color;
+
+uniform sampler2D leaf;
+uniform bool alpha_to_coverage;
+
+void main() {
+  vec4 texel = texture(leaf, v_uv);
+  float alpha = texel.a;
+
+  if (alpha_to_coverage) {
+    // sharpen the alpha over a pixel, so that the outline is as crisp as with alpha testing but
+    // covers its samples smoothly; mipmapped alpha also stays around 0.5 on the outline, so that
+    // distant leaves don’t shrink away
+    alpha = clamp((alpha - .5) / max(fwidth(alpha), 1e-4) + .5, 0., 1.);
+  } else {
+    if (alpha < .5) {
+      discard;
+    }
+
+    alpha = 1.;
+  }
+
+  vec3 light_dir = normalize(vec3(-.5, -1., -.3));
+  float kd = max(dot(normalize(v_normal), -light_dir), 0.) * .7 + .3;
+
+  frag_color = vec4(texel.rgb * kd, alpha);
+}
@@ -0,0 +1,15 @@
+in vec3 position;
+in vec3 normal;
+in vec2 uv;
+
+out vec3 v_normal;
+out vec2 v_uv;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+void main() {
+  v_normal = normal;
+  v_uv = uv;
+  gl_Position = projection * view * vec4(position, 1.);
+}
@@ -0,0 +1,213 @@
+mod foliage;
+
+use crate::foliage::LEAF_SIZE;
+use cgmath::{perspective, Matrix4, Point3, Rad, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance::pipeline::TextureBinding;
+use luminance::pixel::{Floating, NormRGBA8UI};
+use luminance::texture::{Dim2, GenMipmaps, MagFilter, MinFilter, Sampler};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_front::tess::Mode;
+use luminance_front::texture::Texture;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::ffi::c_void;
+use std::process::exit;
+use std::time::Instant;
+
+const LEAF_VS_STR: &str = include_str!("leaf_vs.glsl");
+const LEAF_FS_STR: &str = include_str!("leaf_fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 50.;
+
+/// Samples per pixel; alpha-to-coverage has as many levels of transparency, plus one.
+const SAMPLES: u32 = 4;
+
+#[derive(Debug, UniformInterface)]
+struct LeafInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  leaf: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  alpha_to_coverage: Uniform<bool>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+  #[sem(name = "uv", repr = "[f32; 2]", wrapper = "VertexUV")]
+  UV,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  pub position: VertexPosition,
+  pub normal: VertexNormal,
+  pub uv: VertexUV,
+}
+
+pub type VertexIndex = u32;
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let opt = WindowOpt::default()
+    .set_dim(dim)
+    .set_num_samples(Some(SAMPLES));
+  let surface = GlfwSurface::new_gl33("Hello, world!", opt);
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  // alpha-to-coverage is toggled with raw GL calls; the functions are loaded once, now that the
+  // context is current
+  let window = &mut ctxt.window;
+  gl::load_with(|name| window.get_proc_address(name) as *const c_void);
+
+  // a few bushes of different sizes, side by side
+  let mut vertices = Vec::new();
+  let mut indices = Vec::new();
+  let bushes = [
+    (Point3::new(0., 1., 0.), 1.),
+    (Point3::new(-2.2, 0.7, -0.8), 0.7),
+    (Point3::new(1.9, 0.8, -1.2), 0.8),
+    (Point3::new(1.2, 0.5, 1.6), 0.5),
+  ];
+
+  for (seed, &(center, radius)) in bushes.iter().enumerate() {
+    foliage::bush(center, radius, seed as u32, &mut vertices, &mut indices);
+  }
+
+  let cards = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(vertices)
+    .set_indices(indices)
+    .build()
+    .unwrap();
+
+  // mipmaps keep distant leaves from shimmering even with alpha testing
+  let sampler = Sampler {
+    min_filter: MinFilter::LinearMipmapLinear,
+    mag_filter: MagFilter::Linear,
+    ..Sampler::default()
+  };
+  let mipmaps = (LEAF_SIZE as f32).log2() as usize;
+  let mut leaf: Texture<Dim2, NormRGBA8UI> = ctxt
+    .new_texture_raw(
+      [LEAF_SIZE, LEAF_SIZE],
+      mipmaps,
+      sampler,
+      GenMipmaps::Yes,
+      &foliage::leaf_texels(),
+    )
+    .expect("leaf texture");
+
+  let mut program = ctxt
+    .new_shader_program::<VertexSemantics, (), LeafInterface>()
+    .from_strings(LEAF_VS_STR, None, None, LEAF_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  // cards are seen from both sides
+  let render_state = RenderState::default().set_face_culling(None);
+
+  let [width, height] = back_buffer.size();
+  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+
+  let mut alpha_to_coverage = true;
+  println!("alpha-to-coverage: on (press C to compare with alpha testing)");
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(Key::C, _, Action::Press, _) => {
+          alpha_to_coverage = !alpha_to_coverage;
+          println!(
+            "{}",
+            if alpha_to_coverage {
+              "alpha-to-coverage: on"
+            } else {
+              "alpha-to-coverage: off, alpha testing"
+            }
+          );
+        }
+
+        _ => (),
+      }
+    }
+
+    // slowly orbit around the bushes, so that aliased outlines shimmer
+    let t = start_t.elapsed().as_secs_f32();
+    let angle = t * 0.2;
+    let eye = Point3::new(5. * angle.cos(), 1.8, 5. * angle.sin());
+    let view = Matrix4::look_at(eye, Point3::new(0., 0.7, 0.), Vector3::unit_y());
+
+    // luminance doesn’t know about alpha-to-coverage, so it’s only on while drawing the cards
+    foliage::set_alpha_to_coverage(alpha_to_coverage);
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default().set_clear_color([0.55, 0.7, 0.85, 1.]),
+        |pipeline, mut shd_gate| {
+          let leaf = pipeline.bind_texture(&mut leaf)?;
+
+          shd_gate.shade(&mut program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.projection, projection.into());
+            iface.set(&uni.view, view.into());
+            iface.set(&uni.leaf, leaf.binding());
+            iface.set(&uni.alpha_to_coverage, alpha_to_coverage);
+
+            rdr_gate.render(&render_state, |mut tess_gate| tess_gate.render(&cards))
+          })
+        },
+      )
+      .assume();
+
+    foliage::set_alpha_to_coverage(false);
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
@@ -0,0 +1,21 @@
+in vec2 v_uv;
+
+out vec4 frag_color;
+
+// standard depth at the top, reversed depth at the bottom
+uniform sampler2D top_tex;
+uniform sampler2D bottom_tex;
+// height of the screen, in pixels
+uniform float height;
+
+void main() {
+  float y = v_uv.y * 2.;
+
+  if (abs(gl_FragCoord.y - height * .5) < 1.) {
+    frag_color = vec4(1.);
+  } else if (y >= 1.) {
+    frag_color = texture(top_tex, vec2(v_uv.x, y - 1.));
+  } else {
+    frag_color = texture(bottom_tex, vec2(v_uv.x, y));
+  }
+}
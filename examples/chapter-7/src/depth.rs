@@ -0,0 +1,93 @@
+//! Reversed depth.
+//!
+//! A perspective projection maps the distance to the camera hyperbolically: half of the depth
+//! range covers the distances between the near plane and twice as far, and everything in the
+//! distance gets crammed right under 1. Floating-point depth doesn’t help much there: floats are
+//! dense around 0 and sparse around 1, so the precision is lost exactly where it was already
+//! lacking, and far surfaces z-fight.
+//!
+//! Reversing the depth range maps the near plane to 1 and the far plane to 0, so that the density
+//! of floats around 0 makes up for the hyperbolic mapping; the precision is then about the same
+//! relative to the distance, near or far. It takes three things:
+//!
+//! - A projection mapping depths from 1 to 0, with the nearest fragments winning the `Greater`
+//!   comparison and the depth buffer cleared to 0.
+//! - A float depth buffer; an integer one has the same precision everywhere and gains nothing.
+//! - A [0; 1] clip space depth. OpenGL’s default is [-1; 1], remapped to [0; 1] with a
+//!   `0.5 + 0.5 z` that rounds away the small values reverse-Z relies on. `glClipControl`
+//!   (OpenGL 4.5 or ARB_clip_control) removes that remapping.
+//!
+//! luminance exposes neither the clip control nor the depth the buffers get cleared to, so they’re
+//! set with raw GL calls.
+
+use cgmath::{perspective, Matrix4, Rad};
+use luminance_front::depth_test::DepthComparison;
+
+/// How depth is mapped to the depth buffer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DepthMode {
+  /// Near plane at -1 in clip space, far plane at 1.
+  Standard,
+  /// Near plane at 1 in clip space, far plane at 0.
+  Reversed,
+}
+
+impl DepthMode {
+  pub fn name(self) -> &'static str {
+    match self {
+      DepthMode::Standard => "standard depth",
+      DepthMode::Reversed => "reversed depth",
+    }
+  }
+
+  /// Perspective projection for this depth mode.
+  pub fn projection(self, fovy: Rad<f32>, aspect: f32, z_near: f32, z_far: f32) -> Matrix4<f32> {
+    let mut projection = perspective(fovy, aspect, z_near, z_far);
+
+    // with w = -z, the depth (a z + b) / -z goes from 1 at -z_near to 0 at -z_far
+    if self == DepthMode::Reversed {
+      projection.z.z = z_near / (z_far - z_near);
+      projection.w.z = z_near * z_far / (z_far - z_near);
+    }
+
+    projection
+  }
+
+  /// Depth comparison letting the nearest fragments through.
+  pub fn comparison(self) -> DepthComparison {
+    match self {
+      DepthMode::Standard => DepthComparison::Less,
+      DepthMode::Reversed => DepthComparison::Greater,
+    }
+  }
+}
+
+/// Whether the clip space depth can be set to [0; 1]; without it, reversed depth works but gains
+/// little precision.
+pub fn has_clip_control() -> bool {
+  gl::ClipControl::is_loaded()
+}
+
+/// Set the clip space depth range, and the depth that depth buffers get cleared to, for the
+/// passes to come.
+pub fn set_depth_mode(mode: DepthMode) {
+  unsafe {
+    match mode {
+      DepthMode::Standard => {
+        if has_clip_control() {
+          gl::ClipControl(gl::LOWER_LEFT, gl::NEGATIVE_ONE_TO_ONE);
+        }
+
+        gl::ClearDepth(1.);
+      }
+
+      DepthMode::Reversed => {
+        if has_clip_control() {
+          gl::ClipControl(gl::LOWER_LEFT, gl::ZERO_TO_ONE);
+        }
+
+        gl::ClearDepth(0.);
+      }
+    }
+  }
+}
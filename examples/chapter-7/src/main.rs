@@ -0,0 +1,282 @@
+mod depth;
+
+use crate::depth::DepthMode;
+use cgmath::{Deg, InnerSpace, Matrix3, Matrix4, Point3, Rad, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance::pipeline::TextureBinding;
+use luminance::pixel::{Depth32F, Floating, RGBA32F};
+use luminance::texture::{Dim2, MagFilter, MinFilter, Sampler};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_front::tess::Mode;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::ffi::c_void;
+use std::process::exit;
+use std::time::Instant;
+
+const SCENE_VS_STR: &str = include_str!("scene_vs.glsl");
+const SCENE_FS_STR: &str = include_str!("scene_fs.glsl");
+const QUAD_VS_STR: &str = include_str!("quad_vs.glsl");
+const COMPOSE_FS_STR: &str = include_str!("compose_fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+/// The near plane is very close and the far plane very far, as in a large outdoor scene with the
+/// camera close to the ground.
+const Z_NEAR: f32 = 0.01;
+const Z_FAR: f32 = 10_000.;
+
+/// Number of panels, each twice as far as the previous one.
+const PANELS: i32 = 10;
+/// Distance of the nearest panel.
+const FIRST_DISTANCE: f32 = 10.;
+/// Distance between a panel and the plate in front of it, relative to their distance to the
+/// camera; the same on screen for all panels.
+const GAP: f32 = 1e-3;
+
+#[derive(Debug, UniformInterface)]
+struct SceneInterface {
+  #[uniform(unbound)]
+  projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view: Uniform<[[f32; 4]; 4]>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct ComposeInterface {
+  #[uniform(unbound)]
+  top_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  bottom_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  height: Uniform<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "color", repr = "[f32; 3]", wrapper = "VertexColor")]
+  Color,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  pub position: VertexPosition,
+  pub color: VertexColor,
+}
+
+pub type VertexIndex = u32;
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+  let start_t = Instant::now();
+
+  // depth modes are switched with raw GL calls; the functions are loaded once, now that the
+  // context is current
+  let window = &mut ctxt.window;
+  gl::load_with(|name| window.get_proc_address(name) as *const c_void);
+
+  if !depth::has_clip_control() {
+    eprintln!("glClipControl isn’t available; reversed depth loses most of its precision");
+  }
+
+  let (vertices, indices) = panels();
+  let scene = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(vertices)
+    .set_indices(indices)
+    .build()
+    .unwrap();
+
+  // the two halves of the screen are composited from offscreen renders, whose corners are
+  // generated in the vertex shader
+  let screen_quad = ctxt
+    .new_tess()
+    .set_vertex_nb(4)
+    .set_mode(Mode::TriangleFan)
+    .build()
+    .unwrap();
+
+  let mut scene_program = ctxt
+    .new_shader_program::<VertexSemantics, (), SceneInterface>()
+    .from_strings(SCENE_VS_STR, None, None, SCENE_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut compose_program = ctxt
+    .new_shader_program::<(), (), ComposeInterface>()
+    .from_strings(QUAD_VS_STR, None, None, COMPOSE_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  // both halves get a float depth buffer, so that only the depth mapping differs
+  let [width, height] = back_buffer.size();
+  let half = [width, height / 2];
+  let sampler = Sampler {
+    min_filter: MinFilter::Nearest,
+    mag_filter: MagFilter::Nearest,
+    ..Sampler::default()
+  };
+  let mut standard_fb = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, Depth32F>(half, 0, sampler)
+    .expect("standard depth framebuffer");
+  let mut reversed_fb = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, Depth32F>(half, 0, sampler)
+    .expect("reversed depth framebuffer");
+
+  let aspect = half[0] as f32 / half[1] as f32;
+
+  println!(
+    "top: {}, bottom: {}",
+    DepthMode::Standard.name(),
+    DepthMode::Reversed.name()
+  );
+  println!("the blue panels behind the orange plates show through where depth lacks precision");
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+        _ => (),
+      }
+    }
+
+    // the camera sways a little, so that z-fighting shimmers
+    let t = start_t.elapsed().as_secs_f32();
+    let eye = Point3::new(0.05 * (t * 0.7).sin(), 0.02 * (t * 0.5).cos(), 0.);
+    let view = Matrix4::look_at(eye, Point3::new(0., 0., -1.), Vector3::unit_y());
+
+    let halves = [
+      (DepthMode::Standard, &standard_fb),
+      (DepthMode::Reversed, &reversed_fb),
+    ];
+
+    for &(mode, framebuffer) in &halves {
+      let projection = mode.projection(FOVY, aspect, Z_NEAR, Z_FAR);
+      let render_state = RenderState::default().set_depth_test(Some(mode.comparison()));
+
+      // the depth mode must be set before the framebuffer gets cleared
+      depth::set_depth_mode(mode);
+
+      let render = ctxt
+        .new_pipeline_gate()
+        .pipeline(
+          framebuffer,
+          &PipelineState::default().set_clear_color([0.1, 0.1, 0.1, 1.]),
+          |_, mut shd_gate| {
+            shd_gate.shade(&mut scene_program, |mut iface, uni, mut rdr_gate| {
+              iface.set(&uni.projection, projection.into());
+              iface.set(&uni.view, view.into());
+
+              rdr_gate.render(&render_state, |mut tess_gate| tess_gate.render(&scene))
+            })
+          },
+        )
+        .assume();
+
+      if render.is_err() {
+        break 'app;
+      }
+    }
+
+    depth::set_depth_mode(DepthMode::Standard);
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default(),
+        |pipeline, mut shd_gate| {
+          let top_tex = pipeline.bind_texture(standard_fb.color_slot())?;
+          let bottom_tex = pipeline.bind_texture(reversed_fb.color_slot())?;
+
+          shd_gate.shade(&mut compose_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.top_tex, top_tex.binding());
+            iface.set(&uni.bottom_tex, bottom_tex.binding());
+            iface.set(&uni.height, height as f32);
+
+            rdr_gate.render(
+              &RenderState::default().set_depth_test(None),
+              |mut tess_gate| tess_gate.render(&screen_quad),
+            )
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+/// A row of panels going away from the camera, each with an orange plate right in front of it.
+///
+/// Panels are scaled with their distance, so that they all look the same size; they’re turned a
+/// bit so that depth varies across them, like on the ground or a wall seen at an angle.
+fn panels() -> (Vec<Vertex>, Vec<VertexIndex>) {
+  let mut vertices = Vec::new();
+  let mut indices = Vec::new();
+
+  let blue = VertexColor::new([0.2, 0.4, 0.9]);
+  let orange = VertexColor::new([1., 0.6, 0.2]);
+  let turn = Matrix3::from_angle_y(Deg(30.));
+
+  for i in 0..PANELS {
+    let distance = FIRST_DISTANCE * 2f32.powi(i);
+    let center = Vector3::new((i as f32 - (PANELS - 1) as f32 * 0.5) * 0.35, 0., -1.) * distance;
+    let right = turn * Vector3::unit_x() * distance * 0.15;
+    let up = Vector3::unit_y() * distance * 0.15;
+    let front = right.cross(up).normalize() * distance * GAP;
+
+    // the plate is smaller than the panel, which frames it in blue
+    let quads = [(center, 1., blue), (center + front, 0.8, orange)];
+
+    for &(center, size, color) in &quads {
+      let base = vertices.len() as VertexIndex;
+      let corners = [-right - up, right - up, right + up, -right + up];
+
+      for &corner in &corners {
+        let position = VertexPosition::new((center + corner * size).into());
+        vertices.push(Vertex::new(position, color));
+      }
+
+      indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+  }
+
+  (vertices, indices)
+}
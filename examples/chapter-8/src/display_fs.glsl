@@ -0,0 +1,9 @@
+in vec2 v_uv;
+
+out vec4 frag_color;
+
+uniform sampler2D source_tex;
+
+void main() {
+  frag_color = texture(source_tex, v_uv);
+}
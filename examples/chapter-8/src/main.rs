@@ -0,0 +1,417 @@
+mod taa;
+
+use cgmath::{perspective, Matrix4, Point3, Rad, Vector3};
+use glfw::{Action, Context as _, Key, WindowEvent};
+use luminance::pipeline::TextureBinding;
+use luminance::pixel::{Depth32F, Floating, RG32F, RGBA32F};
+use luminance::texture::{Dim2, MagFilter, MinFilter, Sampler};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_front::tess::Mode;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::process::exit;
+use std::time::Instant;
+
+const QUAD_VS_STR: &str = include_str!("quad_vs.glsl");
+const SKY_FS_STR: &str = include_str!("sky_fs.glsl");
+const SCENE_VS_STR: &str = include_str!("scene_vs.glsl");
+const SCENE_FS_STR: &str = include_str!("scene_fs.glsl");
+const RESOLVE_FS_STR: &str = include_str!("resolve_fs.glsl");
+const DISPLAY_FS_STR: &str = include_str!("display_fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 100.;
+
+/// Number of thin poles standing behind the cube; they alias badly without anti-aliasing.
+const POLES: u32 = 12;
+
+#[derive(Debug, UniformInterface)]
+struct SceneInterface {
+  #[uniform(unbound)]
+  model: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  prev_model: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view_projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  prev_view_projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  jitter: Uniform<[f32; 2]>,
+  #[uniform(unbound)]
+  color: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  checker: Uniform<f32>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct ResolveInterface {
+  #[uniform(unbound)]
+  current_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  velocity_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  history_tex: Uniform<TextureBinding<Dim2, Floating>>,
+  #[uniform(unbound)]
+  reset: Uniform<bool>,
+}
+
+#[derive(Debug, UniformInterface)]
+struct DisplayInterface {
+  #[uniform(unbound)]
+  source_tex: Uniform<TextureBinding<Dim2, Floating>>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  pub position: VertexPosition,
+  pub normal: VertexNormal,
+}
+
+pub type VertexIndex = u32;
+
+/// Object of the scene: its model matrix, color and number of checker cells per unit.
+type Object = (Matrix4<f32>, [f32; 3], f32);
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+
+  let (vertices, indices) = unit_box();
+  let mesh = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(vertices)
+    .set_indices(indices)
+    .build()
+    .unwrap();
+
+  // the sky and the TAA passes are screen-covering quads whose corners are generated in the vertex
+  // shader
+  let screen_quad = ctxt
+    .new_tess()
+    .set_vertex_nb(4)
+    .set_mode(Mode::TriangleFan)
+    .build()
+    .unwrap();
+
+  let mut sky_program = ctxt
+    .new_shader_program::<(), (), ()>()
+    .from_strings(QUAD_VS_STR, None, None, SKY_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut scene_program = ctxt
+    .new_shader_program::<VertexSemantics, (), SceneInterface>()
+    .from_strings(SCENE_VS_STR, None, None, SCENE_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut resolve_program = ctxt
+    .new_shader_program::<(), (), ResolveInterface>()
+    .from_strings(QUAD_VS_STR, None, None, RESOLVE_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut display_program = ctxt
+    .new_shader_program::<(), (), DisplayInterface>()
+    .from_strings(QUAD_VS_STR, None, None, DISPLAY_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  // the scene is rendered offscreen with its velocity, read texel by texel
+  let size = back_buffer.size();
+  let nearest = Sampler {
+    min_filter: MinFilter::Nearest,
+    mag_filter: MagFilter::Nearest,
+    ..Sampler::default()
+  };
+  let mut scene_fb = ctxt
+    .new_framebuffer::<Dim2, (RGBA32F, RG32F), Depth32F>(size, 0, nearest)
+    .expect("scene framebuffer");
+
+  // the history is read where things were last frame, between texels, and written to the other
+  // buffer; they’re swapped every frame
+  let linear = Sampler {
+    min_filter: MinFilter::Linear,
+    mag_filter: MagFilter::Linear,
+    ..Sampler::default()
+  };
+  let mut history_read = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, ()>(size, 0, linear)
+    .expect("history framebuffer");
+  let mut history_write = ctxt
+    .new_framebuffer::<Dim2, RGBA32F, ()>(size, 0, linear)
+    .expect("history framebuffer");
+
+  let projection = perspective(FOVY, size[0] as f32 / size[1] as f32, Z_NEAR, Z_FAR);
+
+  let mut taa = true;
+  let mut reset_history = true;
+  let mut paused = false;
+  let mut frame = 0;
+  let mut t = 0.;
+  let mut last_frame_t = Instant::now();
+  let mut prev_t = t;
+  let mut prev_view_projection = projection * view(t);
+
+  println!("TAA: on (press T to compare with no anti-aliasing, space to pause)");
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(Key::T, _, Action::Press, _) => {
+          taa = !taa;
+          reset_history = true;
+          println!("TAA: {}", if taa { "on" } else { "off" });
+        }
+
+        WindowEvent::Key(Key::Space, _, Action::Press, _) => {
+          paused = !paused;
+          println!("animation: {}", if paused { "paused" } else { "playing" });
+        }
+
+        _ => (),
+      }
+    }
+
+    // a paused scene doesn’t move, which shows how the history converges on still images
+    let now = Instant::now();
+    if !paused {
+      t += (now - last_frame_t).as_secs_f32();
+    }
+    last_frame_t = now;
+
+    let view_projection = projection * view(t);
+    let jitter = if taa {
+      taa::jitter_ndc(frame, size)
+    } else {
+      [0., 0.]
+    };
+    let scene = objects(t);
+    let prev_scene = objects(prev_t);
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(&scene_fb, &PipelineState::default(), |_, mut shd_gate| {
+        shd_gate.shade(&mut sky_program, |_, _, mut rdr_gate| {
+          rdr_gate.render(
+            &RenderState::default().set_depth_test(None),
+            |mut tess_gate| tess_gate.render(&screen_quad),
+          )
+        })?;
+
+        shd_gate.shade(&mut scene_program, |mut iface, uni, mut rdr_gate| {
+          iface.set(&uni.view_projection, view_projection.into());
+          iface.set(&uni.prev_view_projection, prev_view_projection.into());
+          iface.set(&uni.jitter, jitter);
+
+          scene.iter().zip(&prev_scene).try_for_each(
+            |(&(model, color, checker), &(prev_model, _, _))| {
+              iface.set(&uni.model, model.into());
+              iface.set(&uni.prev_model, prev_model.into());
+              iface.set(&uni.color, color);
+              iface.set(&uni.checker, checker);
+
+              rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+                tess_gate.render(&mesh)
+              })
+            },
+          )
+        })
+      })
+      .assume();
+
+    if render.is_err() {
+      break 'app;
+    }
+
+    // the current frame is blended into the history, which is then displayed
+    if taa {
+      let render = ctxt
+        .new_pipeline_gate()
+        .pipeline(
+          &history_write,
+          &PipelineState::default(),
+          |pipeline, mut shd_gate| {
+            let (current, velocity) = scene_fb.color_slot();
+            let current_tex = pipeline.bind_texture(current)?;
+            let velocity_tex = pipeline.bind_texture(velocity)?;
+            let history_tex = pipeline.bind_texture(history_read.color_slot())?;
+
+            shd_gate.shade(&mut resolve_program, |mut iface, uni, mut rdr_gate| {
+              iface.set(&uni.current_tex, current_tex.binding());
+              iface.set(&uni.velocity_tex, velocity_tex.binding());
+              iface.set(&uni.history_tex, history_tex.binding());
+              iface.set(&uni.reset, reset_history);
+
+              rdr_gate.render(
+                &RenderState::default().set_depth_test(None),
+                |mut tess_gate| tess_gate.render(&screen_quad),
+              )
+            })
+          },
+        )
+        .assume();
+
+      if render.is_err() {
+        break 'app;
+      }
+
+      reset_history = false;
+    }
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default(),
+        |pipeline, mut shd_gate| {
+          let source = if taa {
+            history_write.color_slot()
+          } else {
+            &mut scene_fb.color_slot().0
+          };
+          let source_tex = pipeline.bind_texture(source)?;
+
+          shd_gate.shade(&mut display_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.source_tex, source_tex.binding());
+
+            rdr_gate.render(
+              &RenderState::default().set_depth_test(None),
+              |mut tess_gate| tess_gate.render(&screen_quad),
+            )
+          })
+        },
+      )
+      .assume();
+
+    // what was just written is read as the history of the next frame
+    std::mem::swap(&mut history_read, &mut history_write);
+    frame += 1;
+    prev_t = t;
+    prev_view_projection = view_projection;
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+/// View of the camera, slowly orbiting around the scene.
+fn view(t: f32) -> Matrix4<f32> {
+  let angle = t * 0.2;
+  let eye = Point3::new(6. * angle.cos(), 2.5, 6. * angle.sin());
+  Matrix4::look_at(eye, Point3::new(0., 0.8, 0.), Vector3::unit_y())
+}
+
+/// Objects of the scene at time `t`, all made out of the unit box.
+///
+/// A checkered floor, whose cells get thinner than pixels in the distance, a spinning cube and a
+/// row of poles a few pixels wide.
+fn objects(t: f32) -> Vec<Object> {
+  let mut objects = vec![
+    (
+      Matrix4::from_translation(Vector3::new(0., -0.05, 0.))
+        * Matrix4::from_nonuniform_scale(40., 0.1, 40.),
+      [0.9, 0.9, 0.85],
+      2.,
+    ),
+    (
+      Matrix4::from_translation(Vector3::new(0., 1., 0.))
+        * Matrix4::from_angle_y(Rad(t))
+        * Matrix4::from_angle_x(Rad(t * 0.5))
+        * Matrix4::from_scale(1.2),
+      [0.9, 0.4, 0.2],
+      0.,
+    ),
+  ];
+
+  for i in 0..POLES {
+    let x = (i as f32 / (POLES - 1) as f32 - 0.5) * 6.;
+    objects.push((
+      Matrix4::from_translation(Vector3::new(x, 1., -2.5))
+        * Matrix4::from_nonuniform_scale(0.03, 2., 0.03),
+      [0.2, 0.3, 0.4],
+      0.,
+    ));
+  }
+
+  objects
+}
+
+/// Box going from -0.5 to 0.5 along all axes, with a normal per face.
+fn unit_box() -> (Vec<Vertex>, Vec<VertexIndex>) {
+  let mut vertices = Vec::new();
+  let mut indices = Vec::new();
+
+  // each face is given by its normal and two axes spanning it, in counter-clockwise order
+  let faces = [
+    ([1., 0., 0.], [0., 0., -1.], [0., 1., 0.]),
+    ([-1., 0., 0.], [0., 0., 1.], [0., 1., 0.]),
+    ([0., 1., 0.], [1., 0., 0.], [0., 0., -1.]),
+    ([0., -1., 0.], [1., 0., 0.], [0., 0., 1.]),
+    ([0., 0., 1.], [1., 0., 0.], [0., 1., 0.]),
+    ([0., 0., -1.], [-1., 0., 0.], [0., 1., 0.]),
+  ];
+
+  for &(normal, u, v) in &faces {
+    let normal = Vector3::from(normal);
+    let u = Vector3::from(u);
+    let v = Vector3::from(v);
+    let base = vertices.len() as VertexIndex;
+
+    for &(su, sv) in &[(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)] {
+      let position = (normal + u * su + v * sv) * 0.5;
+      vertices.push(Vertex::new(
+        VertexPosition::new(position.into()),
+        VertexNormal::new(normal.into()),
+      ));
+    }
+
+    indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+  }
+
+  (vertices, indices)
+}
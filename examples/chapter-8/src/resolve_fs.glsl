@@ -0,0 +1,42 @@
+in vec2 v_uv;
+
+out vec4 frag_color;
+
+uniform sampler2D current_tex;
+uniform sampler2D velocity_tex;
+uniform sampler2D history_tex;
+// whether the history is meaningless, on the first frame
+uniform bool reset;
+
+// weight of the current frame; the history holds the exponential moving average of the others
+const float BLEND = .1;
+
+void main() {
+  ivec2 size = textureSize(current_tex, 0);
+  ivec2 p = ivec2(gl_FragCoord.xy);
+  vec3 current = texelFetch(current_tex, p, 0).rgb;
+
+  // where the fragment was last frame
+  vec2 prev_uv = v_uv - texelFetch(velocity_tex, p, 0).xy;
+
+  if (reset || any(lessThan(prev_uv, vec2(0.))) || any(greaterThan(prev_uv, vec2(1.)))) {
+    frag_color = vec4(current, 1.);
+    return;
+  }
+
+  // the history is clamped to the colors around the fragment in the current frame: what was
+  // uncovered, or changed color, would otherwise leave ghosts behind moving objects
+  vec3 lo = current;
+  vec3 hi = current;
+
+  for (int y = -1; y <= 1; ++y) {
+    for (int x = -1; x <= 1; ++x) {
+      vec3 c = texelFetch(current_tex, clamp(p + ivec2(x, y), ivec2(0), size - 1), 0).rgb;
+      lo = min(lo, c);
+      hi = max(hi, c);
+    }
+  }
+
+  vec3 history = clamp(texture(history_tex, prev_uv).rgb, lo, hi);
+  frag_color = vec4(mix(history, current, BLEND), 1.);
+}
@@ -0,0 +1,27 @@
+in vec3 v_position;
+in vec3 v_normal;
+in vec4 v_clip;
+in vec4 v_prev_clip;
+
+layout (location = 0) out vec4 frag_color;
+layout (location = 1) out vec2 frag_velocity;
+
+uniform vec3 color;
+// number of checker cells per unit on the XZ plane, or 0 for a plain color
+uniform float checker;
+
+void main() {
+  vec3 n = normalize(v_normal);
+  vec3 albedo = color;
+
+  if (checker > 0.) {
+    ivec2 cell = ivec2(floor(v_position.xz * checker));
+    albedo *= ((cell.x + cell.y) & 1) == 0 ? 1. : .3;
+  }
+
+  float kd = max(dot(n, normalize(vec3(.4, 1., .3))), 0.);
+  frag_color = vec4(albedo * (.25 + .75 * kd), 1.);
+
+  // motion of the fragment on screen since the last frame, in texture coordinates
+  frag_velocity = (v_clip.xy / v_clip.w - v_prev_clip.xy / v_prev_clip.w) * .5;
+}
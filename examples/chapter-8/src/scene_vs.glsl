@@ -0,0 +1,29 @@
+in vec3 position;
+in vec3 normal;
+
+out vec3 v_position;
+out vec3 v_normal;
+out vec4 v_clip;
+out vec4 v_prev_clip;
+
+uniform mat4 model;
+uniform mat4 prev_model;
+uniform mat4 view_projection;
+uniform mat4 prev_view_projection;
+// sub-pixel offset of the frame, in normalized device coordinates
+uniform vec2 jitter;
+
+void main() {
+  vec4 world = model * vec4(position, 1.);
+
+  v_position = world.xyz;
+  // models are only scaled along their own axes, which keeps the normals of boxes perpendicular
+  v_normal = mat3(model) * normal;
+  v_clip = view_projection * world;
+  v_prev_clip = prev_view_projection * prev_model * vec4(position, 1.);
+
+  // the jitter moves the whole image on screen; it’s left out of the velocity, which is the motion
+  // of the scene only
+  gl_Position = v_clip;
+  gl_Position.xy += jitter * v_clip.w;
+}
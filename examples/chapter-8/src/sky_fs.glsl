@@ -0,0 +1,10 @@
+in vec2 v_uv;
+
+layout (location = 0) out vec4 frag_color;
+layout (location = 1) out vec2 frag_velocity;
+
+void main() {
+  frag_color = vec4(mix(vec3(.75, .8, .85), vec3(.35, .5, .75), v_uv.y), 1.);
+  // the sky is the same color everywhere it shows, so it doesn’t need to be reprojected
+  frag_velocity = vec2(0.);
+}
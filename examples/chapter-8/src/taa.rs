@@ -0,0 +1,43 @@
+//! Temporal anti-aliasing.
+//!
+//! Instead of shading several samples per pixel in a single frame, like multisampling does, TAA
+//! spreads them over time: every frame is rendered with the projection moved by a different
+//! sub-pixel offset, and blended into a history of the previous frames. Still images converge to
+//! an average over the whole pixel after a few frames.
+//!
+//! Things move, though, so the history is reprojected: a velocity buffer written with the scene
+//! tells where each fragment was on screen in the previous frame, which is where its history is
+//! looked up. What the history can’t know about, like surfaces that just got uncovered, is handled
+//! by clamping the history to the range of colors around the fragment in the current frame.
+
+/// Number of offsets the jitter cycles through.
+pub const JITTER_SAMPLES: u32 = 8;
+
+/// Sub-pixel offset of a frame, in pixels, within [-0.5; 0.5].
+///
+/// The offsets follow a Halton sequence in base 2 and 3, which covers the pixel evenly whatever the
+/// number of frames blended.
+pub fn jitter(frame: u32) -> [f32; 2] {
+  let index = frame % JITTER_SAMPLES + 1;
+  [halton(index, 2) - 0.5, halton(index, 3) - 0.5]
+}
+
+/// Jitter of a frame in normalized device coordinates, for a framebuffer of the given size.
+pub fn jitter_ndc(frame: u32, [width, height]: [u32; 2]) -> [f32; 2] {
+  let [x, y] = jitter(frame);
+  [x * 2. / width as f32, y * 2. / height as f32]
+}
+
+/// Element of the Halton sequence: the digits of `index` in `base` mirrored around the point.
+fn halton(mut index: u32, base: u32) -> f32 {
+  let mut result = 0.;
+  let mut fraction = 1.;
+
+  while index > 0 {
+    fraction /= base as f32;
+    result += fraction * (index % base) as f32;
+    index /= base;
+  }
+
+  result
+}
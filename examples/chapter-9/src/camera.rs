@@ -0,0 +1,71 @@
+//! First-person camera.
+//!
+//! The camera flies freely: it looks around with its yaw (around the vertical axis) and its pitch
+//! (above or below the horizon), and moves along where it looks. There’s no roll, so the horizon
+//! always stays level, and the pitch stops short of the vertical, where the up vector of the view
+//! would be aligned with the direction of the camera.
+
+use cgmath::{InnerSpace, Matrix4, Point3, Rad, Vector3};
+use std::f32::consts::FRAC_PI_2;
+
+/// Rotation per pixel the mouse moves, in radians.
+const SENSITIVITY: f32 = 0.002;
+/// Highest pitch, in radians, just under the vertical.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FlyCamera {
+  pub position: Point3<f32>,
+  /// Angle around the vertical axis, 0 looking towards -Z.
+  pub yaw: Rad<f32>,
+  /// Angle above the horizon.
+  pub pitch: Rad<f32>,
+}
+
+impl FlyCamera {
+  pub fn new(position: Point3<f32>, yaw: Rad<f32>, pitch: Rad<f32>) -> Self {
+    FlyCamera {
+      position,
+      yaw,
+      pitch,
+    }
+  }
+
+  /// Direction the camera looks at.
+  pub fn forward(&self) -> Vector3<f32> {
+    let (sin_yaw, cos_yaw) = self.yaw.0.sin_cos();
+    let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
+
+    Vector3::new(cos_pitch * sin_yaw, sin_pitch, -cos_pitch * cos_yaw)
+  }
+
+  /// Direction to the right of the camera, always horizontal.
+  pub fn right(&self) -> Vector3<f32> {
+    let (sin_yaw, cos_yaw) = self.yaw.0.sin_cos();
+    Vector3::new(cos_yaw, 0., sin_yaw)
+  }
+
+  pub fn view(&self) -> Matrix4<f32> {
+    Matrix4::look_at_dir(self.position, self.forward(), Vector3::unit_y())
+  }
+
+  /// Look around after the mouse moved by `dx` and `dy` pixels.
+  ///
+  /// Moving the mouse right turns right, and moving it up looks up.
+  pub fn look(&mut self, dx: f32, dy: f32) {
+    self.yaw += Rad(dx * SENSITIVITY);
+    self.pitch = Rad((self.pitch.0 - dy * SENSITIVITY).clamp(-MAX_PITCH, MAX_PITCH));
+  }
+
+  /// Move by `distance` along a direction given relative to the camera: right, up (the world’s up,
+  /// not the camera’s) and forward.
+  ///
+  /// The direction is normalized, so that moving diagonally isn’t faster.
+  pub fn fly(&mut self, [right, up, forward]: [f32; 3], distance: f32) {
+    let direction = self.right() * right + Vector3::unit_y() * up + self.forward() * forward;
+
+    if direction.magnitude2() > 0. {
+      self.position += direction.normalize() * distance;
+    }
+  }
+}
@@ -0,0 +1,85 @@
+//! Input state.
+//!
+//! GLFW reports what changes: a key got pressed, released, the cursor moved. A camera moving
+//! smoothly needs what is: which keys are held right now, and how far the mouse moved since the
+//! last frame. The tracker folds the events of a frame into that state.
+
+use glfw::{Action, Key, WindowEvent};
+use std::collections::HashSet;
+
+#[derive(Debug, Default)]
+pub struct Input {
+  /// Keys currently held down.
+  held: HashSet<Key>,
+  /// Last position of the cursor, if known.
+  cursor: Option<[f64; 2]>,
+  /// Distance the cursor moved since the delta was last taken, in pixels.
+  mouse_delta: [f64; 2],
+}
+
+impl Input {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Update the state with an event.
+  pub fn event(&mut self, event: &WindowEvent) {
+    match *event {
+      WindowEvent::Key(key, _, Action::Press, _) => {
+        self.held.insert(key);
+      }
+
+      WindowEvent::Key(key, _, Action::Release, _) => {
+        self.held.remove(&key);
+      }
+
+      WindowEvent::CursorPos(x, y) => {
+        if let Some([last_x, last_y]) = self.cursor {
+          self.mouse_delta[0] += x - last_x;
+          self.mouse_delta[1] += y - last_y;
+        }
+
+        self.cursor = Some([x, y]);
+      }
+
+      // keys released while the window is in the background are never reported, so they would
+      // stay held forever
+      WindowEvent::Focus(false) => {
+        self.held.clear();
+        self.forget_cursor();
+      }
+
+      _ => (),
+    }
+  }
+
+  pub fn is_held(&self, key: Key) -> bool {
+    self.held.contains(&key)
+  }
+
+  /// -1 if one of the `negative` keys is held, 1 if one of the `positive` ones is, and 0 if both or
+  /// none are.
+  pub fn axis(&self, negative: &[Key], positive: &[Key]) -> f32 {
+    let any_held = |keys: &[Key]| keys.iter().any(|&key| self.is_held(key));
+
+    match (any_held(negative), any_held(positive)) {
+      (true, false) => -1.,
+      (false, true) => 1.,
+      _ => 0.,
+    }
+  }
+
+  /// Distance the cursor moved since the last call, in pixels.
+  pub fn take_mouse_delta(&mut self) -> [f32; 2] {
+    let [dx, dy] = std::mem::take(&mut self.mouse_delta);
+    [dx as f32, dy as f32]
+  }
+
+  /// Forget where the cursor was, so that the next position isn’t taken as a move.
+  ///
+  /// The cursor jumps when it gets captured or released; that jump isn’t the user moving the mouse.
+  pub fn forget_cursor(&mut self) {
+    self.cursor = None;
+    self.mouse_delta = [0., 0.];
+  }
+}
@@ -0,0 +1,296 @@
+mod camera;
+mod input;
+
+use crate::camera::FlyCamera;
+use crate::input::Input;
+use cgmath::{perspective, Matrix4, Point3, Rad, Vector3};
+use glfw::{Action, Context as _, CursorMode, Key, MouseButton, WindowEvent};
+use luminance_derive::{Semantics, UniformInterface, Vertex};
+use luminance_front::context::GraphicsContext;
+use luminance_front::pipeline::PipelineState;
+use luminance_front::render_state::RenderState;
+use luminance_front::shader::Uniform;
+use luminance_front::tess::Mode;
+use luminance_glfw::GlfwSurface;
+use luminance_windowing::{WindowDim, WindowOpt};
+use std::process::exit;
+use std::time::Instant;
+
+const QUAD_VS_STR: &str = include_str!("quad_vs.glsl");
+const SKY_FS_STR: &str = include_str!("sky_fs.glsl");
+const SCENE_VS_STR: &str = include_str!("scene_vs.glsl");
+const SCENE_FS_STR: &str = include_str!("scene_fs.glsl");
+
+const FOVY: Rad<f32> = Rad(std::f32::consts::FRAC_PI_3);
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 200.;
+
+/// Flying speed, in units per second.
+const SPEED: f32 = 4.;
+/// Speed multiplier while Shift is held.
+const SPRINT: f32 = 4.;
+/// Longest time a frame is allowed to move the camera for, in seconds; after a hiccup, like the
+/// window being dragged, the camera would otherwise jump.
+const MAX_FRAME_TIME: f32 = 0.1;
+
+/// Number of pillars on each side of the grid.
+const PILLARS: i32 = 9;
+/// Distance between two pillars.
+const PILLAR_SPACING: f32 = 4.;
+
+#[derive(Debug, UniformInterface)]
+struct SceneInterface {
+  #[uniform(unbound)]
+  model: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  view_projection: Uniform<[[f32; 4]; 4]>,
+  #[uniform(unbound)]
+  color: Uniform<[f32; 3]>,
+  #[uniform(unbound)]
+  checker: Uniform<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum VertexSemantics {
+  #[sem(name = "position", repr = "[f32; 3]", wrapper = "VertexPosition")]
+  Position,
+  #[sem(name = "normal", repr = "[f32; 3]", wrapper = "VertexNormal")]
+  Normal,
+}
+
+#[derive(Clone, Copy, Debug, Vertex)]
+#[vertex(sem = "VertexSemantics")]
+pub struct Vertex {
+  pub position: VertexPosition,
+  pub normal: VertexNormal,
+}
+
+pub type VertexIndex = u32;
+
+/// Object of the scene: its model matrix, color and number of checker cells per unit.
+type Object = (Matrix4<f32>, [f32; 3], f32);
+
+fn main() {
+  let dim = WindowDim::Windowed {
+    width: 960,
+    height: 540,
+  };
+  let surface = GlfwSurface::new_gl33("Hello, world!", WindowOpt::default().set_dim(dim));
+
+  match surface {
+    Ok(surface) => {
+      eprintln!("graphics surface created");
+      main_loop(surface);
+    }
+
+    Err(e) => {
+      eprintln!("cannot create graphics surface:\n{}", e);
+      exit(1);
+    }
+  }
+}
+
+fn main_loop(surface: GlfwSurface) {
+  let mut ctxt = surface.context;
+  let events = surface.events_rx;
+  let back_buffer = ctxt.back_buffer().expect("back buffer");
+
+  let (vertices, indices) = unit_box();
+  let mesh = ctxt
+    .new_tess()
+    .set_mode(Mode::Triangle)
+    .set_vertices(vertices)
+    .set_indices(indices)
+    .build()
+    .unwrap();
+
+  // the sky is a screen-covering quad whose corners are generated in the vertex shader
+  let screen_quad = ctxt
+    .new_tess()
+    .set_vertex_nb(4)
+    .set_mode(Mode::TriangleFan)
+    .build()
+    .unwrap();
+
+  let mut sky_program = ctxt
+    .new_shader_program::<(), (), ()>()
+    .from_strings(QUAD_VS_STR, None, None, SKY_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let mut scene_program = ctxt
+    .new_shader_program::<VertexSemantics, (), SceneInterface>()
+    .from_strings(SCENE_VS_STR, None, None, SCENE_FS_STR)
+    .unwrap()
+    .ignore_warnings();
+
+  let [width, height] = back_buffer.size();
+  let projection = perspective(FOVY, width as f32 / height as f32, Z_NEAR, Z_FAR);
+  let scene = objects();
+
+  let mut camera = FlyCamera::new(Point3::new(0., 1.7, 20.), Rad(0.), Rad(0.));
+  let mut input = Input::new();
+  let mut last_frame_t = Instant::now();
+
+  // the cursor is hidden and held in the window, so that the mouse can turn the camera forever
+  // without the cursor hitting the edges of the screen
+  let mut captured = true;
+  ctxt.window.set_cursor_mode(CursorMode::Disabled);
+
+  println!("WASD or arrows to move, space and C to go up and down, shift to go faster");
+  println!("the mouse looks around; tab releases the cursor, and clicking captures it again");
+
+  'app: loop {
+    // handle events
+    ctxt.window.glfw.poll_events();
+    for (_, event) in glfw::flush_messages(&events) {
+      input.event(&event);
+
+      match event {
+        WindowEvent::Close | WindowEvent::Key(Key::Escape, _, Action::Release, _) => break 'app,
+
+        WindowEvent::Key(Key::Tab, _, Action::Press, _) if captured => {
+          captured = false;
+          ctxt.window.set_cursor_mode(CursorMode::Normal);
+          input.forget_cursor();
+        }
+
+        WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) if !captured => {
+          captured = true;
+          ctxt.window.set_cursor_mode(CursorMode::Disabled);
+          input.forget_cursor();
+        }
+
+        _ => (),
+      }
+    }
+
+    // the camera moves by the time the frame took, so that it goes as fast whatever the frame rate
+    let now = Instant::now();
+    let dt = (now - last_frame_t).as_secs_f32().min(MAX_FRAME_TIME);
+    last_frame_t = now;
+
+    let [dx, dy] = input.take_mouse_delta();
+    if captured {
+      camera.look(dx, dy);
+    }
+
+    let direction = [
+      input.axis(&[Key::A, Key::Left], &[Key::D, Key::Right]),
+      input.axis(&[Key::C], &[Key::Space]),
+      input.axis(&[Key::S, Key::Down], &[Key::W, Key::Up]),
+    ];
+    let speed = if input.is_held(Key::LeftShift) || input.is_held(Key::RightShift) {
+      SPEED * SPRINT
+    } else {
+      SPEED
+    };
+    camera.fly(direction, speed * dt);
+
+    let view_projection = projection * camera.view();
+
+    let render = ctxt
+      .new_pipeline_gate()
+      .pipeline(
+        &back_buffer,
+        &PipelineState::default(),
+        |_, mut shd_gate| {
+          shd_gate.shade(&mut sky_program, |_, _, mut rdr_gate| {
+            rdr_gate.render(
+              &RenderState::default().set_depth_test(None),
+              |mut tess_gate| tess_gate.render(&screen_quad),
+            )
+          })?;
+
+          shd_gate.shade(&mut scene_program, |mut iface, uni, mut rdr_gate| {
+            iface.set(&uni.view_projection, view_projection.into());
+
+            scene.iter().try_for_each(|&(model, color, checker)| {
+              iface.set(&uni.model, model.into());
+              iface.set(&uni.color, color);
+              iface.set(&uni.checker, checker);
+
+              rdr_gate.render(&RenderState::default(), |mut tess_gate| {
+                tess_gate.render(&mesh)
+              })
+            })
+          })
+        },
+      )
+      .assume();
+
+    // swap buffer chains
+    if render.is_ok() {
+      ctxt.window.swap_buffers();
+    } else {
+      break 'app;
+    }
+  }
+}
+
+/// Objects of the scene, all made out of the unit box.
+///
+/// A checkered floor with a grid of pillars of various heights to fly around and between.
+fn objects() -> Vec<Object> {
+  let extent = PILLARS as f32 * PILLAR_SPACING * 2.;
+  let mut objects = vec![(
+    Matrix4::from_translation(Vector3::new(0., -0.05, 0.))
+      * Matrix4::from_nonuniform_scale(extent, 0.1, extent),
+    [0.9, 0.9, 0.85],
+    0.5,
+  )];
+
+  for j in 0..PILLARS {
+    for i in 0..PILLARS {
+      // heights and colors vary from pillar to pillar, but are the same from a run to another
+      let hash = ((i * 73 + j * 151) % 97) as f32 / 97.;
+      let height = 1. + 7. * hash;
+      let x = (i as f32 - (PILLARS - 1) as f32 * 0.5) * PILLAR_SPACING;
+      let z = (j as f32 - (PILLARS - 1) as f32 * 0.5) * PILLAR_SPACING;
+
+      objects.push((
+        Matrix4::from_translation(Vector3::new(x, height * 0.5, z))
+          * Matrix4::from_nonuniform_scale(0.8, height, 0.8),
+        [0.3 + 0.6 * hash, 0.4, 0.9 - 0.6 * hash],
+        0.,
+      ));
+    }
+  }
+
+  objects
+}
+
+/// Box going from -0.5 to 0.5 along all axes, with a normal per face.
+fn unit_box() -> (Vec<Vertex>, Vec<VertexIndex>) {
+  let mut vertices = Vec::new();
+  let mut indices = Vec::new();
+
+  // each face is given by its normal and two axes spanning it, in counter-clockwise order
+  let faces = [
+    ([1., 0., 0.], [0., 0., -1.], [0., 1., 0.]),
+    ([-1., 0., 0.], [0., 0., 1.], [0., 1., 0.]),
+    ([0., 1., 0.], [1., 0., 0.], [0., 0., -1.]),
+    ([0., -1., 0.], [1., 0., 0.], [0., 0., 1.]),
+    ([0., 0., 1.], [1., 0., 0.], [0., 1., 0.]),
+    ([0., 0., -1.], [-1., 0., 0.], [0., 1., 0.]),
+  ];
+
+  for &(normal, u, v) in &faces {
+    let normal = Vector3::from(normal);
+    let u = Vector3::from(u);
+    let v = Vector3::from(v);
+    let base = vertices.len() as VertexIndex;
+
+    for &(su, sv) in &[(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)] {
+      let position = (normal + u * su + v * sv) * 0.5;
+      vertices.push(Vertex::new(
+        VertexPosition::new(position.into()),
+        VertexNormal::new(normal.into()),
+      ));
+    }
+
+    indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+  }
+
+  (vertices, indices)
+}
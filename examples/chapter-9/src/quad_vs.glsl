@@ -0,0 +1,8 @@
+out vec2 v_uv;
+
+const vec2 CORNERS[4] = vec2[](vec2(-1., -1.), vec2(1., -1.), vec2(1., 1.), vec2(-1., 1.));
+
+void main() {
+  v_uv = CORNERS[gl_VertexID] * .5 + .5;
+  gl_Position = vec4(CORNERS[gl_VertexID], 0., 1.);
+}
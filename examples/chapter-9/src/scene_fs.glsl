@@ -0,0 +1,21 @@
+in vec3 v_position;
+in vec3 v_normal;
+
+out vec4 frag_color;
+
+uniform vec3 color;
+// number of checker cells per unit on the XZ plane, or 0 for a plain color
+uniform float checker;
+
+void main() {
+  vec3 n = normalize(v_normal);
+  vec3 albedo = color;
+
+  if (checker > 0.) {
+    ivec2 cell = ivec2(floor(v_position.xz * checker));
+    albedo *= ((cell.x + cell.y) & 1) == 0 ? 1. : .3;
+  }
+
+  float kd = max(dot(n, normalize(vec3(.4, 1., .3))), 0.);
+  frag_color = vec4(albedo * (.25 + .75 * kd), 1.);
+}
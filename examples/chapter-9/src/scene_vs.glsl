@@ -0,0 +1,17 @@
+in vec3 position;
+in vec3 normal;
+
+out vec3 v_position;
+out vec3 v_normal;
+
+uniform mat4 model;
+uniform mat4 view_projection;
+
+void main() {
+  vec4 world = model * vec4(position, 1.);
+
+  v_position = world.xyz;
+  // models are only scaled along their own axes, which keeps the normals of boxes perpendicular
+  v_normal = mat3(model) * normal;
+  gl_Position = view_projection * world;
+}
@@ -0,0 +1,7 @@
+in vec2 v_uv;
+
+out vec4 frag_color;
+
+void main() {
+  frag_color = vec4(mix(vec3(.75, .8, .85), vec3(.35, .5, .75), v_uv.y), 1.);
+}